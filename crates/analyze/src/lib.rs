@@ -18,9 +18,11 @@
 use anyhow::{
     anyhow,
     bail,
+    Context,
     Result,
 };
 pub use contract_metadata::Language;
+use semver::Version;
 use std::collections::HashMap;
 use wasmparser::{
     FuncType,
@@ -30,6 +32,7 @@ use wasmparser::{
     Operator,
     Parser,
     Payload,
+    ProducersSectionReader,
     TypeRef,
     ValType,
 };
@@ -225,7 +228,7 @@ fn is_ink_function_present(module: &Module) -> bool {
 /// the contract's source language. It currently supports detection for Ink!, Solidity,
 /// and AssemblyScript languages.
 pub fn determine_language(code: &[u8]) -> Result<Language> {
-    let module = Module::new(code)?;
+    let module = Module::new(code).context("failed to parse Wasm blob")?;
     let start_section = module.start_section.is_some();
 
     if !start_section && module.custom_sections.keys().any(|e| e == &"producers") {
@@ -247,6 +250,35 @@ pub fn determine_language(code: &[u8]) -> Result<Language> {
     bail!("Language unsupported or unrecognized.")
 }
 
+/// Detects the version of the compiler which produced a smart contract from its
+/// WebAssembly (Wasm) binary code.
+///
+/// The version is read from the tools recorded in the `producers` custom section.
+/// Currently only `solang` records itself there, ink! contracts have the section
+/// stripped during optimization.
+///
+/// Returns `Ok(None)` if the version can not be determined with confidence.
+pub fn detect_compiler_version(code: &[u8]) -> Result<Option<Version>> {
+    let module = Module::new(code).context("failed to parse Wasm blob")?;
+    let Some(producers) = module.custom_sections.get("producers") else {
+        return Ok(None)
+    };
+    let reader = ProducersSectionReader::new(producers, 0)?;
+    for field in reader {
+        let field = field?;
+        if field.name != "processed-by" {
+            continue
+        }
+        for value in field.values {
+            let value = value?;
+            if value.name == "solang" {
+                return Ok(Version::parse(value.version).ok())
+            }
+        }
+    }
+    Ok(None)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +304,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fails_with_malformed_blob() {
+        let random_bytes = [0x13, 0x37, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x42, 0x99];
+        for code in [&random_bytes[..], &[]] {
+            let lang = determine_language(code);
+            assert!(lang.is_err());
+            assert!(lang
+                .unwrap_err()
+                .to_string()
+                .starts_with("failed to parse Wasm blob"));
+        }
+    }
+
     #[test]
     fn determines_ink_language() {
         let contract = r#"
@@ -350,6 +395,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detects_solang_version() {
+        let contract = r#"
+        (module
+            (type (;0;) (func (param i32 i32 i32)))
+            (import "env" "memory" (memory (;0;) 16 16))
+            (func (;0;) (type 0))
+            (@producers
+                (language "Solidity" "")
+                (processed-by "solang" "0.3.3")
+            )
+        )
+        "#;
+        let code = &wat::parse_str(contract).expect("Invalid wat.");
+        let version = detect_compiler_version(code).expect("Failed to parse code.");
+        assert_eq!(version, Some(Version::new(0, 3, 3)));
+    }
+
+    #[test]
+    fn compiler_version_is_none_if_unknown() {
+        let contract = r#"
+        (module
+            (type (;0;) (func (param i32 i32 i32)))
+            (import "seal0" "value_transferred" (func (;0;) (type 0)))
+            (import "env" "memory" (memory (;0;) 2 16))
+            (func (;1;) (type 0))
+            (@producers
+                (processed-by "rustc" "1.78.0")
+            )
+        )
+        "#;
+        let code = &wat::parse_str(contract).expect("Invalid wat.");
+        let version = detect_compiler_version(code).expect("Failed to parse code.");
+        assert_eq!(version, None);
+    }
+
     #[test]
     fn determines_assembly_script_language() {
         let contract = r#"
@@ -265,6 +265,11 @@ impl Features {
         self.features.push(feature.to_owned())
     }
 
+    /// Returns `true` if no features are activated.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
     /// Appends the raw features args to pass through to the `cargo` invocation.
     pub fn append_to_args(&self, args: &mut Vec<String>) {
         if !self.features.is_empty() {
@@ -15,6 +15,7 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
+    metadata::COMPRESSED_BUNDLE_EXTENSION,
     ManifestPath,
     Target,
 };
@@ -34,13 +35,16 @@ use serde_json::{
 };
 use std::{
     fs,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 use toml::value;
 use url::Url;
 
 /// Relevant metadata obtained from Cargo.toml.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CrateMetadata {
     pub manifest_path: ManifestPath,
     pub cargo_meta: cargo_metadata::Metadata,
@@ -68,8 +72,25 @@ impl CrateMetadata {
 
     /// Parses the contract manifest and returns relevant metadata.
     pub fn collect(manifest_path: &ManifestPath, target: Target) -> Result<Self> {
+        Self::collect_with_target_dir(manifest_path, target, None)
+    }
+
+    /// Parses the contract manifest and returns relevant metadata, placing all
+    /// artifacts in `target_dir`.
+    ///
+    /// If no `target_dir` is given, the target directory reported by `cargo metadata`
+    /// is used, which honors the `CARGO_TARGET_DIR` environment variable.
+    pub fn collect_with_target_dir(
+        manifest_path: &ManifestPath,
+        target: Target,
+        target_dir: Option<&Path>,
+    ) -> Result<Self> {
         let (metadata, root_package) = get_cargo_metadata(manifest_path)?;
-        let mut target_directory = metadata.target_directory.as_path().join("ink");
+        let mut target_directory = match target_dir {
+            // the build runs from a temporary directory, so the path must be absolute
+            Some(target_dir) => std::env::current_dir()?.join(target_dir).join("ink"),
+            None => metadata.target_directory.as_std_path().join("ink"),
+        };
 
         // Normalize the final contract artifact name.
         let contract_artifact_name = root_package.name.replace('-', "_");
@@ -139,14 +160,14 @@ impl CrateMetadata {
             cargo_meta: metadata,
             root_package,
             contract_artifact_name,
-            original_code: original_code.into(),
-            dest_code: dest_code.into(),
+            original_code,
+            dest_code,
             ink_version,
             documentation,
             homepage,
             user,
-            target_file_path: target_directory.join(".target").into(),
-            target_directory: target_directory.into(),
+            target_file_path: target_directory.join(".target"),
+            target_directory,
         };
         Ok(crate_metadata)
     }
@@ -163,6 +184,18 @@ impl CrateMetadata {
         let fname_bundle = format!("{}.contract", self.contract_artifact_name);
         target_directory.join(fname_bundle)
     }
+
+    /// Get the path of the contract bundle compressed with zstd.
+    pub fn compressed_contract_bundle_path(&self) -> PathBuf {
+        self.contract_bundle_path()
+            .with_extension(format!("contract.{COMPRESSED_BUNDLE_EXTENSION}"))
+    }
+
+    /// Get the path of the CycloneDX software bill of materials.
+    pub fn sbom_path(&self) -> PathBuf {
+        let sbom_file = format!("{}.cdx.json", self.contract_artifact_name);
+        self.target_directory.join(sbom_file)
+    }
 }
 
 /// Get the result of `cargo metadata`, together with the root package id.
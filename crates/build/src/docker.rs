@@ -39,6 +39,7 @@ use std::{
         hash_map::DefaultHasher,
         HashMap,
     },
+    fs,
     hash::{
         Hash,
         Hasher,
@@ -48,7 +49,10 @@ use std::{
         Write,
     },
     marker::Unpin,
-    path::Path,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 
 use anyhow::{
@@ -79,7 +83,6 @@ use bollard::{
     },
     Docker,
 };
-use contract_metadata::ContractMetadata;
 use tokio_stream::{
     Stream,
     StreamExt,
@@ -90,6 +93,7 @@ use crate::{
     BuildResult,
     CrateMetadata,
     ExecuteArgs,
+    OutputType,
     Verbosity,
 };
 
@@ -100,6 +104,12 @@ const IMAGE: &str = "useink/contracts-verifiable";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 /// The default directory to be mounted in the container.
 const MOUNT_DIR: &str = "/contract";
+/// The `CARGO_HOME` of the image, containing the caches of downloaded crates.
+const CARGO_HOME: &str = "/usr/local/cargo";
+/// The directories in `CARGO_HOME` which are cached between builds.
+const CACHED_CARGO_DIRS: [&str; 2] = ["registry", "git"];
+/// The image label containing the Rust toolchain of the image.
+const RUST_VERSION_LABEL: &str = "ink.use.version.rust";
 
 /// The image to be used.
 #[derive(Clone, Debug, Default)]
@@ -107,7 +117,7 @@ pub enum ImageVariant {
     /// The default image is used, specified in the `IMAGE` constant.
     #[default]
     Default,
-    /// Custom image is used.
+    /// Custom image is used, either referenced by a tag or pinned by a digest.
     Custom(String),
 }
 
@@ -121,6 +131,43 @@ impl From<Option<String>> for ImageVariant {
     }
 }
 
+/// The cache of downloaded crates shared between verifiable builds.
+///
+/// Only the cargo registry and git checkouts are cached. Their contents are verified
+/// against the checksums in the `Cargo.lock`, so the cache does not affect the
+/// resulting contract. The build artifacts are not cached, they live in the target
+/// directory of the mounted project.
+#[derive(Clone, Debug, Default)]
+pub enum DockerCache {
+    /// The cache is stored in the `docker-cache` folder of the target directory.
+    #[default]
+    Default,
+    /// The cache is stored in a custom directory.
+    Custom(PathBuf),
+    /// No cache is used, all crates are downloaded for every build.
+    Disabled,
+}
+
+/// Returns the `sha256:` digest an image reference is pinned to, if any.
+///
+/// Both `<repository>@sha256:<digest>` and a plain `sha256:<digest>` image id are
+/// accepted, references by tag return `None`.
+pub fn image_digest(image: &str) -> Result<Option<&str>> {
+    let digest = match image.split_once('@') {
+        Some((_, digest)) => digest,
+        None if image.starts_with("sha256:") => image,
+        None => return Ok(None),
+    };
+    let is_valid = digest
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    anyhow::ensure!(
+        is_valid,
+        "Invalid image digest `{digest}`, expected `sha256:` followed by 64 hex characters"
+    );
+    Ok(Some(digest))
+}
+
 /// Launches the docker container to execute verifiable build.
 pub fn docker_build(args: ExecuteArgs) -> Result<BuildResult> {
     let ExecuteArgs {
@@ -129,6 +176,7 @@ pub fn docker_build(args: ExecuteArgs) -> Result<BuildResult> {
         output_type,
         target,
         image,
+        docker_cache,
         ..
     } = args;
     tokio::runtime::Builder::new_multi_thread()
@@ -147,16 +195,42 @@ pub fn docker_build(args: ExecuteArgs) -> Result<BuildResult> {
             })?;
 
             let image = match image {
-                ImageVariant::Custom(i) => i.clone(),
+                ImageVariant::Custom(i) => {
+                    image_digest(&i)?;
+                    i.clone()
+                }
                 ImageVariant::Default => {
                     format!("{}:{}", IMAGE, VERSION)
                 }
             };
 
+            if find_local_image(&client, image.clone()).await?.is_none() {
+                pull_image(&client, image.clone(), &verbosity).await?;
+            }
+            let build_image = find_local_image(&client, image.clone())
+                .await?
+                .context("Image summary does not exist")?;
+            let pinned_image = pinned_image(&build_image, &image);
+
+            let cache_dir = match docker_cache {
+                DockerCache::Default => {
+                    Some(crate_metadata.target_directory.join("docker-cache"))
+                }
+                DockerCache::Custom(dir) => Some(dir),
+                DockerCache::Disabled => None,
+            };
+            let cache_mounts = match cache_dir {
+                Some(cache_dir) => {
+                    cargo_cache_mounts(&cache_dir, &pinned_image, &build_image)?
+                }
+                None => Vec::new(),
+            };
+
             let container = create_container(
                 &client,
                 args.clone(),
                 &image,
+                cache_mounts,
                 &crate_metadata.contract_artifact_name,
                 &host_folder,
                 &verbosity,
@@ -164,9 +238,15 @@ pub fn docker_build(args: ExecuteArgs) -> Result<BuildResult> {
             .await?;
 
             let build_result = async {
-                let mut build_result = run_build(&client, &container, &verbosity).await?;
+                // the build output is shown as it happens, unless the build result is
+                // requested as JSON
+                let stream_output =
+                    verbosity.is_verbose() && !matches!(output_type, OutputType::Json);
+                let mut build_result =
+                    run_build(&client, &container, &verbosity, stream_output).await?;
                 update_build_result(&host_folder, &mut build_result)?;
-                update_metadata(&build_result, &verbosity, &image, &client).await?;
+                update_metadata(&build_result, &verbosity, &pinned_image)?;
+                build_result.image = Some(pinned_image);
                 Ok::<BuildResult, anyhow::Error>(build_result)
             }
             .await;
@@ -219,6 +299,16 @@ fn update_build_result(host_folder: &Path, build_result: &mut BuildResult) -> Re
     });
     build_result.dest_wasm = new_path;
 
+    if let Some(sbom_result) = build_result.sbom_result.as_mut() {
+        sbom_result.dest_sbom = host_folder.join(
+            sbom_result
+                .dest_sbom
+                .as_path()
+                .strip_prefix(MOUNT_DIR)
+                .expect("cannot strip prefix"),
+        );
+    }
+
     build_result.metadata_result.as_mut().map(|m| {
         m.dest_bundle = host_folder.join(
             m.dest_bundle
@@ -237,37 +327,88 @@ fn update_build_result(host_folder: &Path, build_result: &mut BuildResult) -> Re
     Ok(())
 }
 
-/// Overwrites `build_result` and `image` fields in the metadata.
-async fn update_metadata(
+/// Overwrites the `image` field in the metadata with the image pinned by its digest.
+fn update_metadata(
     build_result: &BuildResult,
     verbosity: &Verbosity,
-    build_image: &str,
-    client: &Docker,
+    pinned_image: &str,
 ) -> Result<()> {
     if let Some(metadata_artifacts) = &build_result.metadata_result {
-        let mut metadata = ContractMetadata::load(&metadata_artifacts.dest_bundle)?;
-
-        let build_image = find_local_image(client, build_image.to_string())
-            .await?
-            .context("Image summary does not exist")?;
-        // find alternative unique identifier of the image, otherwise grab the digest
-        let image_tag = match build_image
-            .repo_tags
-            .iter()
-            .find(|t| !t.ends_with("latest"))
-        {
-            Some(tag) => tag.to_owned(),
-            None => build_image.id.clone(),
-        };
-
-        metadata.image = Some(image_tag);
-
+        let mut metadata =
+            crate::metadata::load_contract_bundle(&metadata_artifacts.dest_bundle)?;
+        metadata.image = Some(pinned_image.to_string());
         crate::metadata::write_metadata(metadata_artifacts, metadata, verbosity, true)?;
     }
     Ok(())
 }
 
-/// Searches for the local copy of the docker image.
+/// Resolves the `build_image`, referenced as `image`, to a reference pinned by its
+/// digest.
+///
+/// The registry digest is preferred, since it is the same on every machine. Images
+/// which were never pushed to a registry only have their local id.
+fn pinned_image(build_image: &ImageSummary, image: &str) -> String {
+    let repository = image_repository(image);
+    let repo_digest = build_image
+        .repo_digests
+        .iter()
+        .find(|d| d.split_once('@').map(|(repo, _)| repo) == Some(repository))
+        .or_else(|| build_image.repo_digests.first());
+    repo_digest.unwrap_or(&build_image.id).clone()
+}
+
+/// Returns the mounts of the cargo caches in `cache_dir` for the `build_image`.
+///
+/// The cache is keyed by the image digest and its Rust toolchain, so that a change
+/// of either never reuses crates prepared by a different toolchain.
+fn cargo_cache_mounts(
+    cache_dir: &Path,
+    pinned_image: &str,
+    build_image: &ImageSummary,
+) -> Result<Vec<Mount>> {
+    let toolchain = build_image
+        .labels
+        .get(RUST_VERSION_LABEL)
+        .map(String::as_str)
+        .unwrap_or_default();
+    let cache_dir = cache_dir.join(cache_key(pinned_image, toolchain));
+    CACHED_CARGO_DIRS
+        .iter()
+        .map(|dir| {
+            let source = cache_dir.join(dir);
+            fs::create_dir_all(&source)?;
+            Ok(Mount {
+                target: Some(format!("{CARGO_HOME}/{dir}")),
+                source: Some(
+                    source
+                        .to_str()
+                        .context("Cannot convert path to string.")?
+                        .to_string(),
+                ),
+                typ: Some(MountTypeEnum::BIND),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Returns the name of the cache directory for the image and toolchain.
+fn cache_key(pinned_image: &str, toolchain: &str) -> String {
+    let hash = crate::code_hash(format!("{pinned_image}\n{toolchain}").as_bytes());
+    hex::encode(&hash[..8])
+}
+
+/// Returns the repository of an image reference, i.e. without its tag or digest.
+fn image_repository(image: &str) -> &str {
+    let image = image.split_once('@').map_or(image, |(repo, _)| repo);
+    match image.rsplit_once(':') {
+        // a colon before the last slash separates the registry port
+        Some((repo, tag)) if !tag.contains('/') => repo,
+        _ => image,
+    }
+}
+
+/// Searches for the local copy of the docker image, by tag or by digest.
 async fn find_local_image(
     client: &Docker,
     image: String,
@@ -278,7 +419,9 @@ async fn find_local_image(
             ..Default::default()
         }))
         .await?;
-    let build_image = images.iter().find(|i| i.repo_tags.contains(&image));
+    let build_image = images.iter().find(|i| {
+        i.repo_tags.contains(&image) || i.repo_digests.contains(&image) || i.id == image
+    });
 
     Ok(build_image.cloned())
 }
@@ -290,6 +433,7 @@ async fn create_container(
     client: &Docker,
     mut build_args: Vec<String>,
     build_image: &str,
+    cache_mounts: Vec<Mount>,
     contract_name: &str,
     host_folder: &Path,
     verbosity: &Verbosity,
@@ -304,7 +448,13 @@ async fn create_container(
 
     cmd.append(&mut build_args);
 
-    let digest_code = container_digest(cmd.clone(), build_image.to_string());
+    // containers are only reused if they mount the same caches
+    let cache_sources = cache_mounts
+        .iter()
+        .filter_map(|m| m.source.clone())
+        .collect();
+    let digest_code =
+        container_digest(cmd.clone(), build_image.to_string(), cache_sources);
     let container_name =
         format!("ink-verified-{}-{}", contract_name, digest_code.clone());
 
@@ -336,8 +486,10 @@ async fn create_container(
         typ: Some(MountTypeEnum::BIND),
         ..Default::default()
     };
+    let mut mounts = vec![mount];
+    mounts.extend(cache_mounts);
     let host_cfg = Some(HostConfig {
-        mounts: Some(vec![mount]),
+        mounts: Some(mounts),
         ..Default::default()
     });
 
@@ -397,10 +549,14 @@ async fn create_container(
 }
 
 /// Starts the container and executed the build inside it.
+///
+/// If `stream_output` is set the output of the build is written to stderr as it
+/// happens, otherwise it is only shown if the build fails.
 async fn run_build(
     client: &Docker,
     container_name: &str,
     verbosity: &Verbosity,
+    stream_output: bool,
 ) -> Result<BuildResult> {
     client
         .start_container::<String>(container_name, None)
@@ -427,19 +583,47 @@ async fn run_build(
             .bold(),
     );
 
-    // pipe docker attach output into stdout
     let stderr = std::io::stderr();
     let mut stderr = stderr.lock();
-    let mut build_result = None;
-    let mut message_bytes: Vec<u8> = vec![];
-    while let Some(Ok(output)) = output.next().await {
-        match output {
+    let live_output = stream_output.then_some(&mut stderr as &mut dyn Write);
+    let (message_bytes, log) = collect_output(&mut output, live_output).await?;
+
+    if message_bytes.is_empty() {
+        // the log was not shown yet, so the cause of the failure would be lost
+        if !stream_output {
+            stderr.write_all(&log)?;
+            stderr.flush()?;
+        }
+        anyhow::bail!("Failed to read build result from docker build")
+    }
+
+    serde_json::from_reader(BufReader::new(message_bytes.as_slice())).context(format!(
+        "Error decoding BuildResult:\n {}",
+        String::from_utf8_lossy(&message_bytes)
+    ))
+}
+
+/// Collects the stdout and stderr of the container from its `output`.
+///
+/// The stderr, which contains the progress of the build, is written to `live_output`
+/// as it arrives, if given.
+async fn collect_output(
+    output: &mut (impl Stream<Item = Result<LogOutput, Error>> + Unpin),
+    mut live_output: Option<&mut dyn Write>,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    while let Some(output) = output.next().await {
+        match output? {
             LogOutput::StdOut { message } => {
-                message_bytes.extend(&message);
+                stdout.extend(&message);
             }
             LogOutput::StdErr { message } => {
-                stderr.write_all(message.as_ref())?;
-                stderr.flush()?;
+                if let Some(live_output) = live_output.as_mut() {
+                    live_output.write_all(message.as_ref())?;
+                    live_output.flush()?;
+                }
+                stderr.extend(&message);
             }
             LogOutput::Console { message: _ } => {
                 panic!("LogOutput::Console")
@@ -447,35 +631,29 @@ async fn run_build(
             LogOutput::StdIn { message: _ } => panic!("LogOutput::StdIn"),
         };
     }
-
-    if !message_bytes.is_empty() {
-        build_result = Some(
-            serde_json::from_reader(BufReader::new(message_bytes.as_slice())).context(
-                format!(
-                    "Error decoding BuildResult:\n {}",
-                    std::str::from_utf8(&message_bytes).unwrap()
-                ),
-            ),
-        )
-    };
-
-    if let Some(build_result) = build_result {
-        build_result
-    } else {
-        Err(anyhow::anyhow!(
-            "Failed to read build result from docker build"
-        ))
-    }
+    Ok((stdout, stderr))
 }
 
 /// Takes CLI args from the host and appends them to the build command inside the docker.
 fn compose_build_args() -> Result<Vec<String>> {
     use regex::Regex;
     let mut args: Vec<String> = Vec::new();
-    // match `--image` or `verify` with arg with 1 or more white spaces surrounded
-    let rex = Regex::new(r#"(--image|verify)[ ]*[^ ]*[ ]*"#)?;
+    // match `--image`, `--docker-cache-dir` or `verify` with arg with 1 or more white
+    // spaces surrounded
+    let rex = Regex::new(r#"(--image|--docker-cache-dir|verify)[ ]*[^ ]*[ ]*"#)?;
+    // the post-build command runs on the host after the build, and may contain spaces,
+    // so we remove it before joining the args together
+    let mut env_args = std::env::args();
+    let mut host_args = Vec::new();
+    while let Some(arg) = env_args.next() {
+        if arg == "--post-build" {
+            env_args.next();
+        } else if !arg.starts_with("--post-build=") {
+            host_args.push(arg);
+        }
+    }
     // we join the args together, so we can remove `--image <arg>`
-    let args_string: String = std::env::args().collect::<Vec<String>>().join(" ");
+    let args_string: String = host_args.join(" ");
     let args_string = rex.replace_all(&args_string, "").to_string();
 
     // and then we turn it back to the vec, filtering out commands and arguments
@@ -484,6 +662,7 @@ fn compose_build_args() -> Result<Vec<String>> {
         .split_ascii_whitespace()
         .filter(|a| {
             a != &"--verifiable"
+                && a != &"--no-docker-cache"
                 && !a.contains("cargo-contract")
                 && a != &"cargo"
                 && a != &"contract"
@@ -594,16 +773,124 @@ async fn show_pull_progress(
 }
 
 /// Calculates the unique container's code.
-fn container_digest(entrypoint: Vec<String>, image_digest: String) -> String {
+fn container_digest(
+    entrypoint: Vec<String>,
+    image_digest: String,
+    cache_sources: Vec<String>,
+) -> String {
     // in order to optimise the container usage
     // we are hashing the inputted command
     // in order to reuse the container for the same permutation of arguments
     let mut s = DefaultHasher::new();
-    // the data is set of commands and args and the image digest
-    let data = (entrypoint, image_digest);
+    // the data is set of commands and args, the image digest and the mounted caches
+    let data = (entrypoint, image_digest, cache_sources);
     data.hash(&mut s);
     let digest = s.finish();
     // taking the first 5 digits to be a unique identifier
     let digest_code: String = digest.to_string().chars().take(5).collect();
     digest_code
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str =
+        "sha256:7b6f1c2d0e5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c";
+
+    fn container_output() -> Vec<Result<LogOutput, Error>> {
+        vec![
+            Ok(LogOutput::StdErr {
+                message: " [==] Building cargo project\n".into(),
+            }),
+            Ok(LogOutput::StdOut {
+                message: "{\"dest_wasm\": null}".into(),
+            }),
+            Ok(LogOutput::StdErr {
+                message: " [==] Post processing code\n".into(),
+            }),
+        ]
+    }
+
+    #[tokio::test]
+    async fn build_progress_is_streamed() {
+        let mut live_output = Vec::new();
+
+        let (stdout, stderr) = collect_output(
+            &mut tokio_stream::iter(container_output()),
+            Some(&mut live_output),
+        )
+        .await
+        .unwrap();
+
+        let progress = " [==] Building cargo project\n [==] Post processing code\n";
+        assert_eq!(String::from_utf8(live_output).unwrap(), progress);
+        assert_eq!(String::from_utf8(stderr).unwrap(), progress);
+        assert_eq!(String::from_utf8(stdout).unwrap(), "{\"dest_wasm\": null}");
+    }
+
+    #[tokio::test]
+    async fn build_progress_is_captured_without_streaming() {
+        let (stdout, stderr) =
+            collect_output(&mut tokio_stream::iter(container_output()), None)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            " [==] Building cargo project\n [==] Post processing code\n"
+        );
+        assert!(!stdout.is_empty());
+    }
+
+    #[test]
+    fn image_digest_is_parsed() {
+        let image = format!("useink/contracts-verifiable@{DIGEST}");
+
+        assert_eq!(image_digest(&image).unwrap(), Some(DIGEST));
+        assert_eq!(image_digest(DIGEST).unwrap(), Some(DIGEST));
+        assert_eq!(
+            image_digest("useink/contracts-verifiable:4.1.1").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_image_digest_is_rejected() {
+        let result = image_digest("useink/contracts-verifiable@sha256:abcd");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid image digest `sha256:abcd`, expected `sha256:` followed by 64 hex \
+             characters"
+        );
+    }
+
+    #[test]
+    fn cache_key_depends_on_image_and_toolchain() {
+        let image = format!("useink/contracts-verifiable@{DIGEST}");
+
+        let key = cache_key(&image, "1.78.0");
+
+        assert_eq!(key.len(), 16);
+        assert_eq!(key, cache_key(&image, "1.78.0"));
+        assert_ne!(key, cache_key(&image, "1.79.0"));
+        assert_ne!(key, cache_key(DIGEST, "1.78.0"));
+    }
+
+    #[test]
+    fn image_repository_strips_tag_and_digest() {
+        assert_eq!(
+            image_repository("useink/contracts-verifiable:4.1.1"),
+            "useink/contracts-verifiable"
+        );
+        assert_eq!(
+            image_repository(&format!("useink/contracts-verifiable@{DIGEST}")),
+            "useink/contracts-verifiable"
+        );
+        assert_eq!(
+            image_repository("localhost:5000/contracts-verifiable"),
+            "localhost:5000/contracts-verifiable"
+        );
+    }
+}
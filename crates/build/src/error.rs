@@ -0,0 +1,149 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    validate_wasm::WasmValidationError,
+    CargoError,
+};
+use std::fmt;
+
+/// The stage of the build in which an error occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildStage {
+    /// Invoking cargo to lint and compile the contract.
+    Cargo,
+    /// Optimizing and validating the compiled contract code.
+    PostProcess,
+    /// Generating the contract metadata.
+    Metadata,
+}
+
+/// An error which occurred in a specific [`BuildStage`] of the build.
+///
+/// Displays the same as the underlying error, use [`anyhow::Error::downcast_ref`] on
+/// the error returned by [`crate::execute`] to get the stage.
+#[derive(Debug)]
+pub struct BuildError {
+    stage: BuildStage,
+    hints: Vec<String>,
+    error: anyhow::Error,
+}
+
+impl BuildError {
+    /// Return the stage of the build which failed.
+    pub fn stage(&self) -> BuildStage {
+        self.stage
+    }
+
+    /// Return the hints to the cause of the error, e.g. the first compiler error or
+    /// the explanation of an ink! error marker found in the contract code.
+    pub fn hints(&self) -> &[String] {
+        &self.hints
+    }
+
+    /// Return the underlying error.
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // includes the causes of the underlying error
+        write!(f, "{:?}", self.error)
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Attributes the error of a result to a [`BuildStage`].
+pub(crate) trait WithStage<T> {
+    /// Wraps the error in a [`BuildError`] for the `stage`, unless it already is one.
+    fn stage(self, stage: BuildStage) -> anyhow::Result<T>;
+}
+
+impl<T> WithStage<T> for anyhow::Result<T> {
+    fn stage(self, stage: BuildStage) -> anyhow::Result<T> {
+        self.map_err(|error| {
+            if error.is::<BuildError>() {
+                return error
+            }
+            let hints = hints(&error);
+            BuildError {
+                stage,
+                hints,
+                error,
+            }
+            .into()
+        })
+    }
+}
+
+/// Extracts the hints to the cause of the `error`.
+fn hints(error: &anyhow::Error) -> Vec<String> {
+    if let Some(err) = error.downcast_ref::<WasmValidationError>() {
+        return err.errors.clone()
+    }
+    if let Some(err) = error.downcast_ref::<CargoError>() {
+        return err.first_error.iter().cloned().collect()
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_error_keeps_stage_and_hints() {
+        let result: anyhow::Result<()> = Err(WasmValidationError {
+            errors: vec!["An unexpected import function was found".to_string()],
+        }
+        .into());
+
+        let err = result.stage(BuildStage::PostProcess).unwrap_err();
+
+        let build_error = err
+            .downcast_ref::<BuildError>()
+            .expect("must be a BuildError");
+        assert_eq!(build_error.stage(), BuildStage::PostProcess);
+        assert_eq!(
+            build_error.hints(),
+            ["An unexpected import function was found".to_string()]
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Validation of the Wasm failed."));
+    }
+
+    #[test]
+    fn build_error_is_not_wrapped_twice() {
+        let result: anyhow::Result<()> = Err(anyhow::anyhow!("cargo failed"));
+
+        let err = result
+            .stage(BuildStage::Cargo)
+            .stage(BuildStage::Metadata)
+            .unwrap_err();
+
+        let build_error = err
+            .downcast_ref::<BuildError>()
+            .expect("must be a BuildError");
+        assert_eq!(build_error.stage(), BuildStage::Cargo);
+        assert!(build_error.hints().is_empty());
+        assert_eq!(err.to_string(), "cargo failed");
+    }
+}
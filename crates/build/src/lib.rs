@@ -26,9 +26,13 @@ use which as _;
 mod args;
 mod crate_metadata;
 mod docker;
+mod error;
+mod lint;
 pub mod metadata;
 mod new;
 mod post_process_wasm;
+mod sbom;
+mod size_breakdown;
 #[cfg(test)]
 mod tests;
 pub mod util;
@@ -53,16 +57,42 @@ pub use self::{
         VerbosityFlags,
     },
     crate_metadata::CrateMetadata,
+    error::{
+        BuildError,
+        BuildStage,
+    },
+    lint::{
+        lint_contract,
+        LintFinding,
+        LintLevels,
+        LintReport,
+        LintSeverity,
+        LintSpan,
+    },
     metadata::{
+        is_compressed_bundle,
+        load_contract_bundle,
         BuildInfo,
         MetadataArtifacts,
         WasmOptSettings,
+        COMPRESSED_BUNDLE_EXTENSION,
+    },
+    new::{
+        add_workspace_member,
+        find_enclosing_workspace,
+        new_contract_project,
+        new_contract_project_from_git,
+        new_contract_project_with_defines,
     },
-    new::new_contract_project,
     post_process_wasm::{
         load_module,
         post_process_wasm,
     },
+    sbom::SbomArtifact,
+    size_breakdown::{
+        SizeBreakdown,
+        SizeEntry,
+    },
     util::DEFAULT_KEY_COL_WIDTH,
     wasm_opt::{
         OptimizationPasses,
@@ -79,9 +109,14 @@ pub use self::{
     },
 };
 
-use crate::wasm_opt::WasmOptHandler;
+use crate::{
+    error::WithStage as _,
+    wasm_opt::WasmOptHandler,
+};
 pub use docker::{
     docker_build,
+    image_digest,
+    DockerCache,
     ImageVariant,
 };
 
@@ -93,18 +128,27 @@ use colored::Colorize;
 use semver::Version;
 use std::{
     fs,
+    io::{
+        BufRead,
+        BufReader,
+    },
     path::{
         Path,
         PathBuf,
     },
     process::Command,
     str,
+    thread,
 };
 use strum::IntoEnumIterator;
 
 /// This is the default maximum number of pages available for a contract to allocate.
 pub const DEFAULT_MAX_MEMORY_PAGES: u64 = 16;
 
+/// This is the default maximum size of the contract code in bytes, the `MaxCodeLen`
+/// of `pallet-contracts` as configured by the common runtimes.
+pub const DEFAULT_MAX_CODE_SIZE: u64 = 123 * 1024;
+
 /// Version of the currently executing `cargo-contract` binary.
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -139,7 +183,34 @@ pub struct ExecuteArgs {
     pub skip_wasm_validation: bool,
     pub target: Target,
     pub max_memory_pages: u64,
+    /// The maximum size of the contract code in bytes, the build fails if the
+    /// post-processed code is larger. Not enforced if not set.
+    pub max_code_size: Option<u64>,
+    /// Additional flags passed to `rustc` when building the contract for the on-chain
+    /// target.
+    pub rustflags: Vec<String>,
+    /// Overrides of the levels of individual ink! lints.
+    pub lint_levels: LintLevels,
+    /// Write the contract bundle compressed with zstd, as `<name>.contract.zst`.
+    pub compress: bool,
     pub image: ImageVariant,
+    /// The cache of downloaded crates for the verifiable build.
+    pub docker_cache: DockerCache,
+    /// Generate the metadata concurrently with post processing the contract code.
+    pub parallel: bool,
+    /// Compute a breakdown of the size of the optimized Wasm.
+    pub size_breakdown: bool,
+    /// Write a CycloneDX software bill of materials next to the contract bundle.
+    pub sbom: bool,
+    /// The version of the ink! metadata ABI to generate.
+    ///
+    /// Must be the version produced by the ink! version of the contract, if not set
+    /// that version is generated.
+    pub metadata_version: Option<u64>,
+    /// The directory to write all build artifacts to.
+    ///
+    /// Falls back to the `CARGO_TARGET_DIR` environment variable if not set.
+    pub target_dir: Option<PathBuf>,
 }
 
 impl Default for ExecuteArgs {
@@ -159,7 +230,17 @@ impl Default for ExecuteArgs {
             skip_wasm_validation: Default::default(),
             target: Default::default(),
             max_memory_pages: DEFAULT_MAX_MEMORY_PAGES,
+            max_code_size: Default::default(),
+            rustflags: Default::default(),
+            lint_levels: Default::default(),
+            compress: Default::default(),
             image: Default::default(),
+            docker_cache: Default::default(),
+            parallel: Default::default(),
+            size_breakdown: Default::default(),
+            sbom: Default::default(),
+            metadata_version: Default::default(),
+            target_dir: Default::default(),
         }
     }
 }
@@ -171,6 +252,8 @@ pub struct BuildResult {
     pub dest_wasm: Option<PathBuf>,
     /// Result of the metadata generation.
     pub metadata_result: Option<MetadataArtifacts>,
+    /// Result of the software bill of materials generation.
+    pub sbom_result: Option<SbomArtifact>,
     /// Path to the directory where output files are written to.
     pub target_directory: PathBuf,
     /// If existent the result of the optimization.
@@ -181,21 +264,39 @@ pub struct BuildResult {
     pub build_artifact: BuildArtifacts,
     /// The verbosity flags.
     pub verbosity: Verbosity,
-    /// Image used for the verifiable build
+    /// Image used for the verifiable build, pinned by its digest.
     pub image: Option<String>,
+    /// The maximum size of the contract code in bytes enforced by the build.
+    pub max_code_size: Option<u64>,
     /// The type of formatting to use for the build output.
     #[serde(skip_serializing, skip_deserializing)]
     pub output_type: OutputType,
 }
 
+/// Result of a contract build whose artifacts are kept in memory.
+pub struct InMemoryBuildResult {
+    /// The final contract code.
+    pub code: Vec<u8>,
+    /// The contract metadata, as written to the `<name>.json` file by [`execute`].
+    ///
+    /// Only generated if all build artifacts were requested.
+    pub metadata: Option<String>,
+    /// The result of the optimization.
+    pub optimization_result: OptimizationResult,
+}
+
 impl BuildResult {
     pub fn display(&self) -> String {
         let opt_size_diff = if let Some(ref opt_result) = self.optimization_result {
-            let size_diff = format!(
+            let mut size_diff = format!(
                 "\nOriginal wasm size: {}, Optimized: {}\n\n",
                 format!("{:.1}K", opt_result.original_size).bold(),
                 format!("{:.1}K", opt_result.optimized_size).bold(),
             );
+            if let Some(size_breakdown) = &opt_result.size_breakdown {
+                size_diff.push_str(&size_breakdown.display());
+                size_diff.push('\n');
+            }
             debug_assert!(
                 opt_result.optimized_size > 0.0,
                 "optimized file size must be greater 0"
@@ -222,7 +323,7 @@ impl BuildResult {
                     .to_string()
                     .bold()
             );
-            return out
+            return out + &self.display_sbom()
         };
 
         let mut out = format!(
@@ -252,6 +353,26 @@ impl BuildResult {
             );
             out.push_str(&metadata);
         }
+        out.push_str(&self.display_sbom());
+        out
+    }
+
+    /// Display the generated software bill of materials, if any.
+    fn display_sbom(&self) -> String {
+        let Some(sbom_result) = self.sbom_result.as_ref() else {
+            return String::new()
+        };
+        let mut out = format!(
+            "\n  - {} (the software bill of materials)",
+            util::base_name(&sbom_result.dest_sbom).bold()
+        );
+        if !sbom_result.missing_licenses.is_empty() {
+            out.push_str(&format!(
+                "\n\n{} crates without a license: {}",
+                "warning:".yellow().bold(),
+                sbom_result.missing_licenses.join(", ")
+            ));
+        }
         out
     }
 
@@ -261,6 +382,23 @@ impl BuildResult {
     }
 }
 
+/// Returns the `CARGO_ENCODED_RUSTFLAGS` for the on-chain build, with the additional
+/// `rustflags` appended to the common and the target specific flags.
+fn encoded_rustflags(target: &Target, rustflags: &[String]) -> String {
+    // merge target specific flags with the common flags (defined here)
+    // We want to disable warnings here as they will be duplicates of the clippy pass.
+    // However, if we want to do so with either `--cap-lints allow` or  `-A
+    // warnings` the build will fail. It seems that the cross compilation
+    // depends on some warning to be enabled. Until we figure that out we need
+    // to live with duplicated warnings. For the metadata build we can disable
+    // warnings.
+    let common_flags = "-Clinker-plugin-lto";
+    let mut flags = vec![common_flags];
+    flags.extend(target.rustflags());
+    flags.extend(rustflags.iter().map(String::as_str));
+    flags.join("\x1f")
+}
+
 /// Executes the supplied cargo command on the project in the specified directory,
 /// defaults to the current directory.
 ///
@@ -282,6 +420,9 @@ impl BuildResult {
 ///
 /// To disable this and use the original `Cargo.toml` as is then pass the `-Z
 /// original_manifest` flag.
+///
+/// Returns the blake2 hash of the `Cargo.lock` the dependencies were resolved with, if
+/// it exists.
 #[allow(clippy::too_many_arguments)]
 fn exec_cargo_for_onchain_target(
     crate_metadata: &CrateMetadata,
@@ -292,7 +433,8 @@ fn exec_cargo_for_onchain_target(
     verbosity: &Verbosity,
     unstable_flags: &UnstableFlags,
     target: &Target,
-) -> Result<()> {
+    rustflags: &[String],
+) -> Result<Option<[u8; 32]>> {
     let cargo_build = |manifest_path: &ManifestPath| {
         let target_dir = format!(
             "--target-dir={}",
@@ -315,21 +457,7 @@ fn exec_cargo_for_onchain_target(
             env.push(("RUSTC_BOOTSTRAP", Some("1".to_string())))
         }
 
-        // merge target specific flags with the common flags (defined here)
-        // We want to disable warnings here as they will be duplicates of the clippy pass.
-        // However, if we want to do so with either `--cap-lints allow` or  `-A
-        // warnings` the build will fail. It seems that the cross compilation
-        // depends on some warning to be enabled. Until we figure that out we need
-        // to live with duplicated warnings. For the metadata build we can disable
-        // warnings.
-        let rustflags = {
-            let common_flags = "-Clinker-plugin-lto";
-            if let Some(target_flags) = target.rustflags() {
-                format!("{}\x1f{}", common_flags, target_flags)
-            } else {
-                common_flags.to_string()
-            }
-        };
+        let rustflags = encoded_rustflags(target, rustflags);
 
         // the linker needs our linker script as file
         if matches!(target, Target::RiscV) {
@@ -347,13 +475,10 @@ fn exec_cargo_for_onchain_target(
             env.push(("CARGO_ENCODED_RUSTFLAGS", Some(rustflags)));
         };
 
-        execute_cargo(util::cargo_cmd(
-            command,
-            &args,
-            manifest_path.directory(),
-            *verbosity,
-            env,
-        ))
+        execute_cargo_summarized(
+            util::cargo_cmd(command, &args, manifest_path.directory(), *verbosity, env),
+            verbosity,
+        )
     };
 
     if unstable_flags.original_manifest {
@@ -365,6 +490,7 @@ fn exec_cargo_for_onchain_target(
                 .bold()
         );
         cargo_build(&crate_metadata.manifest_path)?;
+        cargo_lock_hash(crate_metadata.cargo_meta.workspace_root.as_std_path())
     } else {
         Workspace::new(&crate_metadata.cargo_meta, &crate_metadata.root_package.id)?
             .with_root_package_manifest(|manifest| {
@@ -375,17 +501,24 @@ fn exec_cargo_for_onchain_target(
                     .with_empty_workspace();
                 Ok(())
             })?
-            .using_temp(cargo_build)?;
+            .using_temp(|manifest_path| {
+                cargo_build(manifest_path)?;
+                // The amended manifest is the root of its own workspace, so the
+                // `Cargo.lock` copied from the original workspace is resolved next to it.
+                cargo_lock_hash(manifest_path.directory().unwrap_or(Path::new("")))
+            })
     }
-
-    Ok(())
 }
 
 /// Check if the `INK_STATIC_BUFFER_SIZE` is set.
 /// If so, then checks if the current contract has already been compiled with a new value.
 /// If not, or metadata is not present, we need to clean binaries and rebuild.
+///
+/// Only the packages which depend on the buffer size are cleaned, so that the other
+/// dependencies don't need to be rebuilt.
 fn check_buffer_size_invoke_cargo_clean(
     crate_metadata: &CrateMetadata,
+    target: &Target,
     verbosity: &Verbosity,
 ) -> Result<()> {
     if let Ok(buffer_size) = std::env::var("INK_STATIC_BUFFER_SIZE") {
@@ -413,13 +546,41 @@ fn check_buffer_size_invoke_cargo_clean(
             Ok(size)
         };
 
-        let cargo = util::cargo_cmd(
-            "clean",
-            Vec::<&str>::new(),
-            crate_metadata.manifest_path.directory(),
-            *verbosity,
-            vec![],
-        );
+        let cargo_clean = || {
+            let package_names = crate_metadata
+                .cargo_meta
+                .packages
+                .iter()
+                .map(|package| package.name.as_str());
+            let clean_packages = package_clean_args(
+                &crate_metadata.root_package.name,
+                package_names,
+                &crate_metadata.target_directory,
+                target,
+            )
+            .and_then(|args| {
+                let cargo = util::cargo_cmd(
+                    "clean",
+                    args,
+                    crate_metadata.manifest_path.directory(),
+                    *verbosity,
+                    vec![],
+                );
+                execute_cargo(cargo).ok()
+            });
+            if clean_packages.is_none() {
+                tracing::debug!("Falling back to cleaning the whole target directory");
+                let cargo = util::cargo_cmd(
+                    "clean",
+                    Vec::<&str>::new(),
+                    crate_metadata.manifest_path.directory(),
+                    *verbosity,
+                    vec![],
+                );
+                execute_cargo(cargo)?;
+            }
+            anyhow::Ok(())
+        };
 
         match extract_buffer_size(crate_metadata.metadata_path()) {
             Ok(contract_buffer_size) if contract_buffer_size == buffer_size_value => {
@@ -439,7 +600,7 @@ fn check_buffer_size_invoke_cargo_clean(
                     "Detected a change in the configured buffer size. Rebuilding the project."
                         .bold()
                 );
-                execute_cargo(cargo)?;
+                cargo_clean()?;
             }
             Err(_) => {
                 verbose_eprintln!(
@@ -449,13 +610,42 @@ fn check_buffer_size_invoke_cargo_clean(
                     "Cannot find the previous size of the static buffer. Rebuilding the project."
                         .bold()
                 );
-                execute_cargo(cargo)?;
+                cargo_clean()?;
             }
         }
     }
     Ok(())
 }
 
+/// Returns the arguments for `cargo clean` to only clean the `contract` package and
+/// `ink_env`, which reads `INK_STATIC_BUFFER_SIZE` at compile time.
+///
+/// The artifacts are cleaned in the `target_dir` of the on-chain build, for the
+/// on-chain `target` and the release profile the contract is built with.
+///
+/// Returns `None` if `ink_env` is not among the `package_names` of the dependency
+/// graph, in which case the whole target directory needs to be cleaned.
+fn package_clean_args<'a>(
+    contract: &str,
+    mut package_names: impl Iterator<Item = &'a str>,
+    target_dir: &Path,
+    target: &Target,
+) -> Option<Vec<String>> {
+    const INK_ENV: &str = "ink_env";
+    if !package_names.any(|name| name == INK_ENV) {
+        return None
+    }
+    Some(vec![
+        format!("--target-dir={}", target_dir.to_string_lossy()),
+        format!("--target={}", target.llvm_target()),
+        "--release".to_owned(),
+        "-p".to_owned(),
+        contract.to_owned(),
+        "-p".to_owned(),
+        INK_ENV.to_owned(),
+    ])
+}
+
 /// Executes the supplied cargo command, reading the output and scanning for known errors.
 /// Writes the captured stderr back to stderr and maintains the cargo tty progress bar.
 fn execute_cargo(cargo: duct::Expression) -> Result<()> {
@@ -466,21 +656,161 @@ fn execute_cargo(cargo: duct::Expression) -> Result<()> {
     }
 }
 
+/// Executes the supplied cargo command, capturing its diagnostics unless the verbosity
+/// is [`Verbosity::Verbose`].
+///
+/// The progress lines of cargo, e.g. `Compiling ...`, are forwarded as they come in
+/// unless the verbosity is [`Verbosity::Quiet`]. On success only the number of warnings
+/// is printed afterwards, as they are usually duplicates of the ones reported by the
+/// clippy pass. On failure the first error is highlighted above the captured output,
+/// so the root cause isn't buried.
+fn execute_cargo_summarized(
+    cargo: duct::Expression,
+    verbosity: &Verbosity,
+) -> Result<()> {
+    if matches!(verbosity, Verbosity::Verbose) {
+        return execute_cargo(cargo)
+    }
+    let reader = match cargo.stderr_to_stdout().unchecked().reader() {
+        Ok(reader) => reader,
+        Err(e) => anyhow::bail!("Cannot run `cargo` command: {:?}", e),
+    };
+    let progress = CargoProgress::new();
+    let mut stderr = String::new();
+    for line in BufReader::new(&reader).lines() {
+        let line = line?;
+        if !matches!(verbosity, Verbosity::Quiet) && progress.is_match(&line) {
+            eprintln!("{line}");
+        }
+        stderr.push_str(&line);
+        stderr.push('\n');
+    }
+    let status = reader
+        .try_wait()?
+        .expect("cargo exited, its output was read to the end")
+        .status;
+    let diagnostics = CargoDiagnostics::scan(&stderr);
+    if !status.success() {
+        return Err(CargoError {
+            first_error: diagnostics.first_error,
+            stderr,
+        }
+        .into())
+    }
+    if diagnostics.warnings > 0 {
+        verbose_eprintln!(
+            verbosity,
+            "{} {} warning{}, run with --verbose to see {}",
+            "warning:".yellow().bold(),
+            diagnostics.warnings,
+            if diagnostics.warnings == 1 { "" } else { "s" },
+            if diagnostics.warnings == 1 {
+                "it"
+            } else {
+                "them"
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Matches the lines of cargo's output which report its progress, e.g.
+/// `   Compiling contract v0.1.0`, rather than being part of a diagnostic.
+struct CargoProgress {
+    ansi_escapes: regex::Regex,
+    progress: regex::Regex,
+}
+
+impl CargoProgress {
+    fn new() -> Self {
+        Self {
+            ansi_escapes: regex::Regex::new(r"\x1b\[[0-9;]*m").expect("regex is valid"),
+            progress: regex::Regex::new(r"^\s*[A-Z][a-z]+ ").expect("regex is valid"),
+        }
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        self.progress
+            .is_match(&self.ansi_escapes.replace_all(line, ""))
+    }
+}
+
+/// A failed cargo invocation, with the first error highlighted above its stderr.
+#[derive(Debug)]
+pub(crate) struct CargoError {
+    /// The first error cargo reported, see [`CargoDiagnostics::first_error`].
+    pub first_error: Option<String>,
+    /// The captured stderr of cargo.
+    pub stderr: String,
+}
+
+impl std::fmt::Display for CargoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.first_error {
+            Some(error) => write!(f, "{}\n\n{}", error.bright_red().bold(), self.stderr),
+            None => write!(f, "{}", self.stderr),
+        }
+    }
+}
+
+impl std::error::Error for CargoError {}
+
+/// Summary of the diagnostics cargo wrote to stderr.
+#[derive(Debug, Default, PartialEq)]
+struct CargoDiagnostics {
+    /// The number of warnings, excluding cargo's own summary lines.
+    warnings: usize,
+    /// The first error, preferring errors with a code like `error[E0425]`.
+    first_error: Option<String>,
+}
+
+impl CargoDiagnostics {
+    fn scan(stderr: &str) -> Self {
+        let ansi_escapes = regex::Regex::new(r"\x1b\[[0-9;]*m").expect("regex is valid");
+        // e.g. "warning: `contract` (lib) generated 2 warnings"
+        let warnings_summary = regex::Regex::new(r"^warning: .+ generated \d+ warnings?")
+            .expect("regex is valid");
+        let stderr = ansi_escapes.replace_all(stderr, "");
+        let mut diagnostics = Self::default();
+        let mut first_plain_error = None;
+        for line in stderr.lines().map(str::trim_end) {
+            if line.starts_with("warning:") {
+                if !warnings_summary.is_match(line) {
+                    diagnostics.warnings += 1;
+                }
+            } else if line.starts_with("error[") && diagnostics.first_error.is_none() {
+                diagnostics.first_error = Some(line.to_string());
+            } else if line.starts_with("error:") && first_plain_error.is_none() {
+                first_plain_error = Some(line.to_string());
+            }
+        }
+        diagnostics.first_error = diagnostics.first_error.or(first_plain_error);
+        diagnostics
+    }
+}
+
 /// Run linting that involves two steps: `clippy` and `dylint`. Both are mandatory as
 /// they're part of the compilation process and implement security-critical features.
+///
+/// `clippy` checks the given `features`, or all features if none are given. Builds
+/// always lint all features, only `cargo contract check` narrows them down.
 fn lint(
     extra_lints: bool,
+    lint_levels: &LintLevels,
     crate_metadata: &CrateMetadata,
+    features: &Features,
     target: &Target,
     verbosity: &Verbosity,
 ) -> Result<()> {
+    lint_levels.validate(extra_lints, target)?;
+
     verbose_eprintln!(
         verbosity,
         " {} {}",
         "[==]".bold(),
         "Checking clippy linting rules".bright_green().bold()
     );
-    exec_cargo_clippy(crate_metadata, *verbosity)?;
+    exec_cargo_clippy(crate_metadata, features, *verbosity)?;
 
     // TODO (jubnzv): Dylint needs a custom toolchain installed by the user. Currently,
     // it's required only for RiscV target. We're working on the toolchain integration
@@ -492,32 +822,47 @@ fn lint(
             "[==]".bold(),
             "Checking ink! linting rules".bright_green().bold()
         );
-        exec_cargo_dylint(extra_lints, crate_metadata, target, *verbosity)?;
+        exec_cargo_dylint(extra_lints, lint_levels, crate_metadata, target, *verbosity)?;
     }
 
     Ok(())
 }
 
 /// Run cargo clippy on the unmodified manifest.
-fn exec_cargo_clippy(crate_metadata: &CrateMetadata, verbosity: Verbosity) -> Result<()> {
-    let args = [
-        "--all-features",
-        // customize clippy lints after the "--"
-        "--",
-        // this is a hard error because we want to guarantee that implicit overflows
-        // never happen
-        "-Dclippy::arithmetic_side_effects",
-    ];
+fn exec_cargo_clippy(
+    crate_metadata: &CrateMetadata,
+    features: &Features,
+    verbosity: Verbosity,
+) -> Result<()> {
     // we execute clippy with the plain manifest no temp dir required
     execute_cargo(util::cargo_cmd(
         "clippy",
-        args,
+        clippy_args(features),
         crate_metadata.manifest_path.directory(),
         verbosity,
         vec![],
     ))
 }
 
+/// Returns the args for cargo clippy, which checks the given `features` or all features
+/// if none are given.
+fn clippy_args(features: &Features) -> Vec<String> {
+    let mut args = Vec::new();
+    if features.is_empty() {
+        args.push("--all-features".to_owned());
+    } else {
+        features.append_to_args(&mut args);
+    }
+    args.extend([
+        // customize clippy lints after the "--"
+        "--".to_owned(),
+        // this is a hard error because we want to guarantee that implicit overflows
+        // never happen
+        "-Dclippy::arithmetic_side_effects".to_owned(),
+    ]);
+    args
+}
+
 /// Returns a list of cargo options used for on-chain builds
 fn onchain_cargo_options(target: &Target) -> Vec<String> {
     vec![
@@ -533,10 +878,38 @@ fn onchain_cargo_options(target: &Target) -> Vec<String> {
 /// `cargo dylint` with it.
 fn exec_cargo_dylint(
     extra_lints: bool,
+    lint_levels: &LintLevels,
     crate_metadata: &CrateMetadata,
     target: &Target,
     verbosity: Verbosity,
 ) -> Result<()> {
+    cargo_dylint(
+        extra_lints,
+        lint_levels,
+        crate_metadata,
+        target,
+        verbosity,
+        &[],
+        |cargo| {
+            cargo.run()?;
+            Ok(())
+        },
+    )
+}
+
+/// Runs `cargo dylint` with the ink! linting libraries on the contract, passing the
+/// additional `cargo_args` to `cargo check`.
+///
+/// The `cargo dylint` command is passed to `run`, which is responsible for executing it.
+pub(crate) fn cargo_dylint<T>(
+    extra_lints: bool,
+    lint_levels: &LintLevels,
+    crate_metadata: &CrateMetadata,
+    target: &Target,
+    verbosity: Verbosity,
+    cargo_args: &[&str],
+    run: impl FnOnce(duct::Expression) -> Result<T>,
+) -> Result<T> {
     check_dylint_requirements(crate_metadata.manifest_path.directory())?;
 
     // `dylint` is verbose by default, it doesn't have a `--verbose` argument,
@@ -557,9 +930,10 @@ fn exec_cargo_dylint(
     // Pass on-chain build options to ensure the linter expands all conditional `cfg_attr`
     // macros, as it does for the release build.
     args.extend(onchain_cargo_options(target));
+    args.extend(cargo_args.iter().map(|arg| arg.to_string()));
 
     let target_dir = &crate_metadata.target_directory.to_string_lossy();
-    let env = vec![
+    let mut env = vec![
         // We need to set the `CARGO_TARGET_DIR` environment variable in
         // case `cargo dylint` is invoked.
         //
@@ -573,6 +947,11 @@ fn exec_cargo_dylint(
         // Until we have a justification for leaving the wrapper we should unset it.
         ("RUSTC_WRAPPER", None),
     ];
+    let driver_flags = lint_levels.driver_flags();
+    if !driver_flags.is_empty() {
+        // The lint levels are passed to the driver running the lints.
+        env.push(("DYLINT_RUSTFLAGS", Some(driver_flags.join(" "))));
+    }
 
     Workspace::new(&crate_metadata.cargo_meta, &crate_metadata.root_package.id)?
         .with_root_package_manifest(|manifest| {
@@ -587,13 +966,82 @@ fn exec_cargo_dylint(
                 verbosity,
                 env,
             );
-            cargo.run()?;
-            Ok(())
-        })?;
+            run(cargo)
+        })
+}
 
+/// Checks if the toolchain is able to build the standard library for the on-chain
+/// target, i.e. if the `rust-src` component is installed and unstable features are
+/// available.
+///
+/// The components are listed in the `working_dir`, so that a toolchain override of the
+/// contract, e.g. a `rust-toolchain.toml`, is taken into account.
+fn check_onchain_build_requirements(working_dir: Option<&Path>) -> Result<()> {
+    let channel = rustc_version::version_meta()?.channel;
+    let rustc_bootstrap = std::env::var("RUSTC_BOOTSTRAP").ok();
+    check_unstable_features_available(channel, rustc_bootstrap.as_deref())?;
+
+    let mut rustup = Command::new("rustup");
+    rustup.args(["component", "list", "--installed"]);
+    if let Some(dir) = working_dir {
+        rustup.current_dir(dir);
+    }
+    match rustup.output() {
+        Ok(output) if output.status.success() => {
+            anyhow::ensure!(
+                rust_src_installed(&String::from_utf8_lossy(&output.stdout)),
+                "The `rust-src` component was not found!\n\
+                It is required to build the standard library for the contract.\n\n\
+                You can install it by executing `rustup component add rust-src`."
+                    .to_string()
+                    .bright_yellow()
+            );
+        }
+        _ => {
+            // without `rustup` the toolchain might still provide the sources, e.g. if
+            // installed by a package manager, so we let cargo report the error.
+            tracing::debug!(
+                "Unable to list the installed components with `rustup`, skipping the \
+                `rust-src` check"
+            );
+        }
+    }
     Ok(())
 }
 
+/// Checks if the unstable `build-std` feature is available with the given toolchain
+/// `channel`.
+///
+/// On a stable toolchain we enable it with `RUSTC_BOOTSTRAP`, a beta toolchain
+/// requires it to be set by the user.
+fn check_unstable_features_available(
+    channel: rustc_version::Channel,
+    rustc_bootstrap: Option<&str>,
+) -> Result<()> {
+    use rustc_version::Channel;
+    match channel {
+        Channel::Nightly | Channel::Dev | Channel::Stable => Ok(()),
+        Channel::Beta if rustc_bootstrap == Some("1") => Ok(()),
+        Channel::Beta => {
+            anyhow::bail!(
+                "Building a contract requires unstable cargo features, which are not \
+                available on the beta toolchain.\n\n\
+                Use a stable or nightly toolchain, or set `RUSTC_BOOTSTRAP=1`."
+                    .to_string()
+                    .bright_yellow()
+            )
+        }
+    }
+}
+
+/// Returns `true` if the output of `rustup component list --installed` contains the
+/// `rust-src` component.
+fn rust_src_installed(installed_components: &str) -> bool {
+    installed_components
+        .lines()
+        .any(|line| line.split_whitespace().next() == Some("rust-src"))
+}
+
 /// Checks if all requirements for `dylint` are installed.
 ///
 /// We require both `cargo-dylint` and `dylint-link` because the driver is being
@@ -696,30 +1144,18 @@ pub fn assert_debug_mode_supported(ink_version: &Version) -> Result<()> {
     Ok(())
 }
 
-/// Executes build of the smart contract which produces a Wasm binary that is ready for
-/// deploying.
-///
-/// It does so by invoking `cargo build` and then post processing the final binary.
-pub fn execute(args: ExecuteArgs) -> Result<BuildResult> {
+/// Resolves the optimization passes and collects the metadata of the contract crate,
+/// checking that the contract can be built with the given arguments.
+fn prepare_build(args: &ExecuteArgs) -> Result<(CrateMetadata, OptimizationPasses)> {
     let ExecuteArgs {
         manifest_path,
-        verbosity,
-        features,
         build_mode,
-        network,
-        build_artifact,
-        unstable_flags,
         optimization_passes,
-        extra_lints,
-        output_type,
         target,
+        metadata_version,
+        target_dir,
         ..
-    } = &args;
-
-    // if image exists, then --verifiable was called and we need to build inside docker.
-    if build_mode == &BuildMode::Verifiable {
-        return docker_build(args)
-    }
+    } = args;
 
     // The CLI flag `optimization-passes` overwrites optimization passes which are
     // potentially defined in the `Cargo.toml` profile.
@@ -737,7 +1173,11 @@ pub fn execute(args: ExecuteArgs) -> Result<BuildResult> {
         }
     };
 
-    let crate_metadata = CrateMetadata::collect(manifest_path, *target)?;
+    let crate_metadata = CrateMetadata::collect_with_target_dir(
+        manifest_path,
+        *target,
+        target_dir.as_deref(),
+    )?;
 
     if build_mode == &BuildMode::Debug {
         assert_debug_mode_supported(&crate_metadata.ink_version)?;
@@ -747,37 +1187,119 @@ pub fn execute(args: ExecuteArgs) -> Result<BuildResult> {
         eprintln!("{} {}", "warning:".yellow().bold(), e.to_string().bold());
     }
 
+    if let Some(metadata_version) = metadata_version {
+        metadata::check_metadata_version(*metadata_version, &crate_metadata.ink_version)
+            .stage(BuildStage::Metadata)?;
+    }
+
+    Ok((crate_metadata, optimization_passes))
+}
+
+/// Executes build of the smart contract which produces a Wasm binary that is ready for
+/// deploying.
+///
+/// It does so by invoking `cargo build` and then post processing the final binary.
+pub fn execute(args: ExecuteArgs) -> Result<BuildResult> {
+    let ExecuteArgs {
+        verbosity,
+        features,
+        build_mode,
+        network,
+        build_artifact,
+        unstable_flags,
+        extra_lints,
+        lint_levels,
+        output_type,
+        target,
+        parallel,
+        sbom,
+        metadata_version,
+        ..
+    } = &args;
+
+    // if image exists, then --verifiable was called and we need to build inside docker.
+    if build_mode == &BuildMode::Verifiable {
+        return docker_build(args)
+    }
+
+    let (crate_metadata, optimization_passes) = prepare_build(&args)?;
+
     let clean_metadata = || {
         fs::remove_file(crate_metadata.metadata_path()).ok();
         fs::remove_file(crate_metadata.contract_bundle_path()).ok();
+        fs::remove_file(crate_metadata.compressed_contract_bundle_path()).ok();
     };
 
     let (opt_result, metadata_result, dest_wasm) = match build_artifact {
         BuildArtifacts::CheckOnly => {
             // Check basically means only running our linter without building.
-            lint(*extra_lints, &crate_metadata, target, verbosity)?;
+            lint(
+                *extra_lints,
+                lint_levels,
+                &crate_metadata,
+                features,
+                target,
+                verbosity,
+            )
+            .stage(BuildStage::Cargo)?;
             (None, None, None)
         }
         BuildArtifacts::CodeOnly => {
             // when building only the code metadata will become stale
             clean_metadata();
-            let (opt_result, _, dest_wasm) =
-                local_build(&crate_metadata, &optimization_passes, &args)?;
+            let (opt_result, _, dest_wasm, _) =
+                local_build(&crate_metadata, &optimization_passes, &args, || ())?;
             (opt_result, None, Some(dest_wasm))
         }
         BuildArtifacts::All => {
-            let (opt_result, build_info, dest_wasm) =
-                local_build(&crate_metadata, &optimization_passes, &args).map_err(
-                    |e| {
+            // if the code changed, generate the ink! metadata while the code is being
+            // post processed. The scope joins the metadata generation before returning,
+            // also when the post processing of the code fails.
+            let (opt_result, build_info, dest_wasm, ink_metadata) =
+                thread::scope(|scope| {
+                    let spawn_metadata = || {
+                        parallel.then(|| {
+                            scope.spawn(|| {
+                                metadata::generate_ink_metadata(
+                                    &crate_metadata,
+                                    features,
+                                    *network,
+                                    *verbosity,
+                                    unstable_flags,
+                                    *metadata_version,
+                                )
+                            })
+                        })
+                    };
+                    let (opt_result, build_info, dest_wasm, metadata_task) = local_build(
+                        &crate_metadata,
+                        &optimization_passes,
+                        &args,
+                        spawn_metadata,
+                    )
+                    .inspect_err(|_| {
                         // build error -> bundle is stale
                         clean_metadata();
-                        e
-                    },
-                )?;
+                    })?;
+                    let ink_metadata = metadata_task
+                        .flatten()
+                        .map(|task| {
+                            task.join().map_err(|_| {
+                                anyhow::anyhow!("Metadata generation panicked")
+                            })?
+                        })
+                        .transpose()
+                        .stage(BuildStage::Metadata)?;
+                    anyhow::Ok((opt_result, build_info, dest_wasm, ink_metadata))
+                })?;
 
             let metadata_result = MetadataArtifacts {
                 dest_metadata: crate_metadata.metadata_path(),
-                dest_bundle: crate_metadata.contract_bundle_path(),
+                dest_bundle: if args.compress {
+                    crate_metadata.compressed_contract_bundle_path()
+                } else {
+                    crate_metadata.contract_bundle_path()
+                },
             };
 
             // skip metadata generation if contract unchanged and all metadata artifacts
@@ -788,40 +1310,250 @@ pub fn execute(args: ExecuteArgs) -> Result<BuildResult> {
             {
                 // if metadata build fails after a code build it might become stale
                 clean_metadata();
-                metadata::execute(
-                    &crate_metadata,
-                    dest_wasm.as_path(),
-                    &metadata_result,
-                    features,
-                    *network,
-                    *verbosity,
-                    unstable_flags,
-                    build_info,
-                )?;
+                match ink_metadata {
+                    Some(ink_metadata) => {
+                        metadata::write_contract_metadata(
+                            &crate_metadata,
+                            dest_wasm.as_path(),
+                            &metadata_result,
+                            *verbosity,
+                            build_info,
+                            ink_metadata,
+                        )
+                        .stage(BuildStage::Metadata)?
+                    }
+                    None => {
+                        metadata::execute(
+                            &crate_metadata,
+                            dest_wasm.as_path(),
+                            &metadata_result,
+                            features,
+                            *network,
+                            *verbosity,
+                            unstable_flags,
+                            *metadata_version,
+                            build_info,
+                        )
+                        .stage(BuildStage::Metadata)?
+                    }
+                }
             }
             (opt_result, Some(metadata_result), Some(dest_wasm))
         }
     };
 
+    let sbom_result = if *sbom && dest_wasm.is_some() {
+        Some(sbom::write_sbom(&crate_metadata)?)
+    } else {
+        None
+    };
+
     Ok(BuildResult {
         dest_wasm,
         metadata_result,
+        sbom_result,
         target_directory: crate_metadata.target_directory,
         optimization_result: opt_result,
         build_mode: *build_mode,
         build_artifact: *build_artifact,
         verbosity: *verbosity,
         image: None,
+        max_code_size: args.max_code_size,
         output_type: output_type.clone(),
     })
 }
 
+/// Builds the smart contract like [`execute`], but returns the final code and the
+/// metadata instead of writing them to the target directory.
+///
+/// The code is post processed in a temporary directory, and no fingerprint of it is
+/// kept, so it is post processed on every invocation. Verifiable builds are not
+/// supported.
+pub fn execute_in_memory(args: ExecuteArgs) -> Result<InMemoryBuildResult> {
+    let ExecuteArgs {
+        verbosity,
+        features,
+        build_mode,
+        network,
+        build_artifact,
+        unstable_flags,
+        extra_lints,
+        lint_levels,
+        target,
+        metadata_version,
+        ..
+    } = &args;
+
+    anyhow::ensure!(
+        build_mode != &BuildMode::Verifiable,
+        "Verifiable builds can not be executed in memory"
+    );
+    anyhow::ensure!(
+        build_artifact != &BuildArtifacts::CheckOnly,
+        "Checking a contract does not produce any artifacts to return"
+    );
+
+    let (crate_metadata, optimization_passes) = prepare_build(&args)?;
+
+    check_onchain_build_requirements(crate_metadata.manifest_path.directory())
+        .stage(BuildStage::Cargo)?;
+    // the features of the build do not narrow down the linted features
+    lint(
+        *extra_lints,
+        lint_levels,
+        &crate_metadata,
+        &Features::default(),
+        target,
+        verbosity,
+    )
+    .stage(BuildStage::Cargo)?;
+    let build_info = compile_code(&crate_metadata, &optimization_passes, &args)?;
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("cargo-contract-build.")
+        .tempdir()?;
+    let dest_code = tmp_dir.path().join(
+        crate_metadata
+            .dest_code
+            .file_name()
+            .expect("the code path always has a file name"),
+    );
+    let optimization_result = post_process_code(
+        &crate_metadata,
+        &optimization_passes,
+        &args,
+        dest_code.clone(),
+    )?;
+
+    let metadata = if build_artifact == &BuildArtifacts::All {
+        let ink_meta = metadata::generate_ink_metadata(
+            &crate_metadata,
+            features,
+            *network,
+            *verbosity,
+            unstable_flags,
+            *metadata_version,
+        )
+        .stage(BuildStage::Metadata)?;
+        let mut metadata = metadata::contract_metadata(
+            &crate_metadata,
+            &dest_code,
+            build_info,
+            ink_meta,
+        )
+        .stage(BuildStage::Metadata)?;
+        metadata.remove_source_wasm_attribute();
+        Some(serde_json::to_string_pretty(&metadata)?)
+    } else {
+        None
+    };
+
+    Ok(InMemoryBuildResult {
+        code: fs::read(&dest_code)?,
+        metadata,
+        optimization_result,
+    })
+}
+
 /// Build the contract on host locally
-fn local_build(
+///
+/// If the code changed, `on_code_changed` is invoked right before the code is post
+/// processed and its result is returned alongside the build result.
+fn local_build<T>(
+    crate_metadata: &CrateMetadata,
+    optimization_passes: &OptimizationPasses,
+    args: &ExecuteArgs,
+    on_code_changed: impl FnOnce() -> T,
+) -> Result<(Option<OptimizationResult>, BuildInfo, PathBuf, Option<T>)> {
+    let ExecuteArgs {
+        verbosity,
+        extra_lints,
+        lint_levels,
+        target,
+        max_code_size,
+        ..
+    } = args;
+
+    // Both the linting and the build compile the standard library for the on-chain
+    // target, which fails with an opaque error if the toolchain is not able to.
+    check_onchain_build_requirements(crate_metadata.manifest_path.directory())
+        .stage(BuildStage::Cargo)?;
+
+    // We always want to lint first so we don't suppress any warnings when a build is
+    // skipped because of a matching fingerprint. The features of the build do not
+    // narrow down the linted features.
+    lint(
+        *extra_lints,
+        lint_levels,
+        crate_metadata,
+        &Features::default(),
+        target,
+        verbosity,
+    )
+    .stage(BuildStage::Cargo)?;
+
+    let pre_fingerprint = Fingerprint::new(crate_metadata)?;
+
+    let build_info = compile_code(crate_metadata, optimization_passes, args)?;
+
+    let post_fingerprint = Fingerprint::new(crate_metadata)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Expected '{}' to be generated by build",
+            crate_metadata.original_code.display()
+        )
+    })?;
+
+    tracing::debug!(
+        "Fingerprint before build: {:?}, after build: {:?}",
+        pre_fingerprint,
+        post_fingerprint
+    );
+
+    let dest_code_path = crate_metadata.dest_code.clone();
+
+    if pre_fingerprint == Some(post_fingerprint) && crate_metadata.dest_code.exists() {
+        tracing::info!(
+            "No changes in the original wasm at {}, fingerprint {:?}. \
+                Skipping Wasm optimization and metadata generation.",
+            crate_metadata.original_code.display(),
+            pre_fingerprint
+        );
+        if let Some(max_code_size) = max_code_size {
+            check_code_size(&dest_code_path, *max_code_size)
+                .stage(BuildStage::PostProcess)?;
+        }
+        return Ok((None, build_info, dest_code_path, None))
+    }
+
+    let on_code_changed = on_code_changed();
+
+    // remove build artifacts so we don't have anything stale lingering around
+    for t in Target::iter() {
+        fs::remove_file(crate_metadata.dest_code.with_extension(t.dest_extension())).ok();
+    }
+
+    let optimization_result = post_process_code(
+        crate_metadata,
+        optimization_passes,
+        args,
+        dest_code_path.clone(),
+    )?;
+
+    Ok((
+        Some(optimization_result),
+        build_info,
+        dest_code_path,
+        Some(on_code_changed),
+    ))
+}
+
+/// Invokes `cargo build` for the on-chain target, producing the original code of the
+/// contract.
+fn compile_code(
     crate_metadata: &CrateMetadata,
     optimization_passes: &OptimizationPasses,
     args: &ExecuteArgs,
-) -> Result<(Option<OptimizationResult>, BuildInfo, PathBuf)> {
+) -> Result<BuildInfo> {
     let ExecuteArgs {
         verbosity,
         features,
@@ -829,18 +1561,20 @@ fn local_build(
         network,
         unstable_flags,
         keep_debug_symbols,
-        extra_lints,
-        skip_wasm_validation,
         target,
-        max_memory_pages,
+        rustflags,
         ..
     } = args;
 
-    // We always want to lint first so we don't suppress any warnings when a build is
-    // skipped because of a matching fingerprint.
-    lint(*extra_lints, crate_metadata, target, verbosity)?;
-
-    let pre_fingerprint = Fingerprint::new(crate_metadata)?;
+    if !rustflags.is_empty() {
+        eprintln!(
+            "{} {}",
+            "warning:".yellow().bold(),
+            "Additional rustflags may break the cross compilation of the contract or \
+            the reproducibility of the build."
+                .bold()
+        );
+    }
 
     verbose_eprintln!(
         verbosity,
@@ -848,8 +1582,9 @@ fn local_build(
         "[==]".bold(),
         "Building cargo project".bright_green().bold()
     );
-    check_buffer_size_invoke_cargo_clean(crate_metadata, verbosity)?;
-    exec_cargo_for_onchain_target(
+    check_buffer_size_invoke_cargo_clean(crate_metadata, target, verbosity)
+        .stage(BuildStage::Cargo)?;
+    let cargo_lock_hash = exec_cargo_for_onchain_target(
         crate_metadata,
         "build",
         features,
@@ -858,7 +1593,9 @@ fn local_build(
         verbosity,
         unstable_flags,
         target,
-    )?;
+        rustflags,
+    )
+    .stage(BuildStage::Cargo)?;
 
     // We persist the latest target we used so we trigger a rebuild when we switch
     fs::write(&crate_metadata.target_file_path, target.llvm_target())?;
@@ -872,7 +1609,7 @@ fn local_build(
         );
     };
 
-    let build_info = BuildInfo {
+    Ok(BuildInfo {
         rust_toolchain: util::rust_toolchain()?,
         cargo_contract_version,
         build_mode: *build_mode,
@@ -880,32 +1617,29 @@ fn local_build(
             optimization_passes: *optimization_passes,
             keep_debug_symbols: *keep_debug_symbols,
         },
-    };
-
-    let post_fingerprint = Fingerprint::new(crate_metadata)?.ok_or_else(|| {
-        anyhow::anyhow!(
-            "Expected '{}' to be generated by build",
-            crate_metadata.original_code.display()
-        )
-    })?;
-
-    tracing::debug!(
-        "Fingerprint before build: {:?}, after build: {:?}",
-        pre_fingerprint,
-        post_fingerprint
-    );
-
-    let dest_code_path = crate_metadata.dest_code.clone();
+        cargo_lock_hash,
+        rustflags: rustflags.clone(),
+    })
+}
 
-    if pre_fingerprint == Some(post_fingerprint) && crate_metadata.dest_code.exists() {
-        tracing::info!(
-            "No changes in the original wasm at {}, fingerprint {:?}. \
-                Skipping Wasm optimization and metadata generation.",
-            crate_metadata.original_code.display(),
-            pre_fingerprint
-        );
-        return Ok((None, build_info, dest_code_path))
-    }
+/// Post processes the original code of the contract, writing the final code to
+/// `dest_code`.
+fn post_process_code(
+    crate_metadata: &CrateMetadata,
+    optimization_passes: &OptimizationPasses,
+    args: &ExecuteArgs,
+    dest_code: PathBuf,
+) -> Result<OptimizationResult> {
+    let ExecuteArgs {
+        verbosity,
+        keep_debug_symbols,
+        skip_wasm_validation,
+        target,
+        max_memory_pages,
+        max_code_size,
+        size_breakdown,
+        ..
+    } = args;
 
     verbose_eprintln!(
         verbosity,
@@ -914,42 +1648,58 @@ fn local_build(
         "Post processing code".bright_green().bold()
     );
 
-    // remove build artifacts so we don't have anything stale lingering around
-    for t in Target::iter() {
-        fs::remove_file(crate_metadata.dest_code.with_extension(t.dest_extension())).ok();
-    }
-
     let original_size =
         fs::metadata(&crate_metadata.original_code)?.len() as f64 / 1000.0;
 
     match target {
         Target::Wasm => {
-            let handler = WasmOptHandler::new(*optimization_passes, *keep_debug_symbols)?;
-            handler.optimize(&crate_metadata.original_code, &crate_metadata.dest_code)?;
+            let handler = WasmOptHandler::new(*optimization_passes, *keep_debug_symbols)
+                .stage(BuildStage::PostProcess)?;
+            handler
+                .optimize(&crate_metadata.original_code, &dest_code)
+                .stage(BuildStage::PostProcess)?;
             post_process_wasm(
-                &crate_metadata.dest_code,
+                &dest_code,
                 *skip_wasm_validation,
                 verbosity,
                 *max_memory_pages,
-            )?;
+            )
+            .stage(BuildStage::PostProcess)?;
         }
         Target::RiscV => {
-            fs::copy(&crate_metadata.original_code, &crate_metadata.dest_code)?;
+            fs::copy(&crate_metadata.original_code, &dest_code)?;
         }
     }
 
-    let optimized_size = fs::metadata(&dest_code_path)?.len() as f64 / 1000.0;
+    if let Some(max_code_size) = max_code_size {
+        check_code_size(&dest_code, *max_code_size).stage(BuildStage::PostProcess)?;
+    }
+
+    let optimized_size = fs::metadata(&dest_code)?.len() as f64 / 1000.0;
 
-    let optimization_result = OptimizationResult {
+    let size_breakdown = if *size_breakdown && matches!(target, Target::Wasm) {
+        Some(SizeBreakdown::new(&fs::read(&dest_code)?)?)
+    } else {
+        None
+    };
+
+    Ok(OptimizationResult {
         original_size,
         optimized_size,
-    };
+        size_breakdown,
+    })
+}
 
-    Ok((
-        Some(optimization_result),
-        build_info,
-        crate_metadata.dest_code.clone(),
-    ))
+/// Fails if the contract code at `code_path` is larger than `max_code_size` bytes.
+fn check_code_size(code_path: &Path, max_code_size: u64) -> Result<()> {
+    let code_size = fs::metadata(code_path)?.len();
+    anyhow::ensure!(
+        code_size <= max_code_size,
+        "The contract code is {code_size} bytes, which exceeds the maximum code size of \
+        {max_code_size} bytes. Use `--size-breakdown` to see what the code size is \
+        made up of."
+    );
+    Ok(())
 }
 
 /// Unique fingerprint for a file to detect whether it has changed.
@@ -986,6 +1736,16 @@ impl Fingerprint {
     }
 }
 
+/// Returns the blake2 hash of the `Cargo.lock` in the workspace root `dir`, if it
+/// exists.
+fn cargo_lock_hash(dir: &Path) -> Result<Option<[u8; 32]>> {
+    let lockfile = dir.join("Cargo.lock");
+    if !lockfile.exists() {
+        return Ok(None)
+    }
+    Ok(Some(blake2_hash(&fs::read(lockfile)?)))
+}
+
 /// Returns the blake2 hash of the code slice.
 pub fn code_hash(code: &[u8]) -> [u8; 32] {
     blake2_hash(code)
@@ -1037,6 +1797,181 @@ mod unit_tests {
         );
     }
 
+    #[test]
+    fn package_clean_args_clean_contract_and_ink_env() {
+        let packages = ["ink", "ink_env", "parity-scale-codec", "flipper"];
+
+        let args = package_clean_args(
+            "flipper",
+            packages.into_iter(),
+            Path::new("/flipper/target/ink"),
+            &Target::Wasm,
+        );
+
+        assert_eq!(
+            args,
+            Some(vec![
+                "--target-dir=/flipper/target/ink".to_string(),
+                format!("--target={}", Target::Wasm.llvm_target()),
+                "--release".to_string(),
+                "-p".to_string(),
+                "flipper".to_string(),
+                "-p".to_string(),
+                "ink_env".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn package_clean_args_require_ink_env() {
+        let packages = ["parity-scale-codec", "flipper"];
+
+        assert_eq!(
+            package_clean_args(
+                "flipper",
+                packages.into_iter(),
+                Path::new("/flipper/target/ink"),
+                &Target::Wasm,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn cargo_diagnostics_count_warnings_without_summaries() {
+        let stderr = "\x1b[1m\x1b[33mwarning\x1b[0m: unused variable: `x`\n  \
+                      --> lib.rs:1:1\n\
+                      warning: unused import: `Foo`\n\
+                      warning: `contract` (lib) generated 2 warnings \
+                      (run `cargo fix --lib -p contract` to apply 1 suggestion)\n\
+                      warning: `contract` (lib) generated 1 warning\n\
+                      \x20   Finished release [optimized] target(s) in 1.00s\n";
+
+        let diagnostics = CargoDiagnostics::scan(stderr);
+
+        assert_eq!(
+            diagnostics,
+            CargoDiagnostics {
+                warnings: 2,
+                first_error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn only_cargo_progress_lines_are_forwarded() {
+        let progress = CargoProgress::new();
+        assert!(progress.is_match("\x1b[1m\x1b[32m   Compiling\x1b[0m contract v0.1.0"));
+        assert!(progress.is_match("    Finished release [optimized] target(s) in 1.00s"));
+        assert!(!progress.is_match("warning: unused variable: `x`"));
+        assert!(!progress.is_match("  --> lib.rs:1:1"));
+        assert!(!progress.is_match("   = note: `#[warn(unused)]` on by default"));
+        assert!(!progress.is_match("1 | let x = 1;"));
+    }
+
+    #[test]
+    fn cargo_diagnostics_prefer_errors_with_code() {
+        let stderr = "warning: unused import: `Foo`\n\
+                      error: could not find `Foo` in `bar`\n\
+                      error[E0425]: cannot find value `x` in this scope\n\
+                      error[E0308]: mismatched types\n\
+                      error: could not compile `contract`\n";
+
+        let diagnostics = CargoDiagnostics::scan(stderr);
+
+        assert_eq!(
+            diagnostics.first_error.as_deref(),
+            Some("error[E0425]: cannot find value `x` in this scope")
+        );
+    }
+
+    #[test]
+    fn cargo_diagnostics_fall_back_to_first_error() {
+        let stderr = "error: failed to parse manifest\n\
+                      error: could not compile `contract`\n";
+
+        let diagnostics = CargoDiagnostics::scan(stderr);
+
+        assert_eq!(
+            diagnostics.first_error.as_deref(),
+            Some("error: failed to parse manifest")
+        );
+    }
+
+    #[test]
+    fn rust_src_component_is_detected() {
+        let installed = "cargo-x86_64-unknown-linux-gnu\n\
+                         rust-src\n\
+                         rust-std-x86_64-unknown-linux-gnu\n\
+                         rustc-x86_64-unknown-linux-gnu\n";
+        assert!(rust_src_installed(installed));
+    }
+
+    #[test]
+    fn missing_rust_src_component_is_detected() {
+        let installed = "cargo-x86_64-unknown-linux-gnu\n\
+                         rust-std-x86_64-unknown-linux-gnu\n\
+                         rust-src-extra-x86_64-unknown-linux-gnu\n";
+        assert!(!rust_src_installed(installed));
+        assert!(!rust_src_installed(""));
+    }
+
+    #[test]
+    fn unstable_features_require_rustc_bootstrap_on_beta() {
+        use rustc_version::Channel;
+        assert!(check_unstable_features_available(Channel::Nightly, None).is_ok());
+        assert!(check_unstable_features_available(Channel::Stable, None).is_ok());
+        assert!(check_unstable_features_available(Channel::Beta, Some("1")).is_ok());
+        assert!(check_unstable_features_available(Channel::Beta, None).is_err());
+    }
+
+    #[test]
+    fn clippy_args_pass_through_features() {
+        let mut features = Features::default();
+        assert_eq!(clippy_args(&features)[0], "--all-features");
+
+        features.push("foo");
+        features.push("e2e-tests");
+        assert_eq!(
+            clippy_args(&features),
+            vec![
+                "--features",
+                "foo,e2e-tests",
+                "--",
+                "-Dclippy::arithmetic_side_effects"
+            ]
+        );
+    }
+
+    #[test]
+    fn encoded_rustflags_include_additional_flags() {
+        let rustflags = vec!["-Copt-level=s".to_string(), "-Cdebuginfo=1".to_string()];
+
+        let encoded = encoded_rustflags(&Target::Wasm, &rustflags);
+
+        assert!(encoded.starts_with("-Clinker-plugin-lto\x1f"));
+        assert!(encoded.ends_with("\x1f-Copt-level=s\x1f-Cdebuginfo=1"));
+        assert_eq!(
+            encoded_rustflags(&Target::Wasm, &[]),
+            format!(
+                "-Clinker-plugin-lto\x1f{}",
+                Target::Wasm.rustflags().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn check_code_size_fails_above_limit() {
+        let code = tempfile::NamedTempFile::new().unwrap();
+        fs::write(code.path(), [0u8; 64]).unwrap();
+
+        assert!(check_code_size(code.path(), 64).is_ok());
+        let err = check_code_size(code.path(), 63).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("The contract code is 64 bytes, which exceeds the maximum"));
+    }
+
     #[test]
     fn build_result_seralization_sanity_check() {
         // given
@@ -1046,6 +1981,7 @@ mod unit_tests {
     "dest_metadata": "/path/to/contract.json",
     "dest_bundle": "/path/to/contract.contract"
   },
+  "sbom_result": null,
   "target_directory": "/path/to/target",
   "optimization_result": {
     "original_size": 64.0,
@@ -1054,7 +1990,8 @@ mod unit_tests {
   "build_mode": "Debug",
   "build_artifact": "All",
   "verbosity": "Quiet",
-  "image": null
+  "image": null,
+  "max_code_size": 125952
 }"#;
 
         let build_result = BuildResult {
@@ -1063,14 +2000,17 @@ mod unit_tests {
                 dest_metadata: PathBuf::from("/path/to/contract.json"),
                 dest_bundle: PathBuf::from("/path/to/contract.contract"),
             }),
+            sbom_result: None,
             target_directory: PathBuf::from("/path/to/target"),
             optimization_result: Some(OptimizationResult {
                 original_size: 64.0,
                 optimized_size: 32.0,
+                size_breakdown: None,
             }),
             build_mode: Default::default(),
             build_artifact: Default::default(),
             image: None,
+            max_code_size: Some(DEFAULT_MAX_CODE_SIZE),
             verbosity: Verbosity::Quiet,
             output_type: OutputType::Json,
         };
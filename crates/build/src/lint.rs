@@ -0,0 +1,458 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    cargo_dylint,
+    CrateMetadata,
+    ManifestPath,
+    Target,
+    Verbosity,
+};
+use anyhow::Result;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The lints of the mandatory ink! linting library, which are always run.
+const MANDATORY_LINTS: [&str; 1] = ["no_main"];
+
+/// The lints of the extra ink! linting library, which are only run if enabled.
+const EXTRA_LINTS: [&str; 4] = [
+    "non_fallible_api",
+    "primitive_topic",
+    "storage_never_freed",
+    "strict_balance_equality",
+];
+
+/// Overrides of the levels of individual ink! lints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LintLevels {
+    /// The lints to allow.
+    pub allow: Vec<String>,
+    /// The lints to deny.
+    pub deny: Vec<String>,
+}
+
+impl LintLevels {
+    /// Checks that all overridden lints exist, and are run with the given `extra_lints`
+    /// setting for the `target`.
+    ///
+    /// The mandatory lints are only run during a build for RISC-V contracts or if the
+    /// extra lints are enabled.
+    pub(crate) fn validate(&self, extra_lints: bool, target: &Target) -> Result<()> {
+        self.validate_lints(extra_lints, extra_lints || matches!(target, Target::RiscV))
+    }
+
+    /// Checks that all overridden lints exist, and are run with the given `extra_lints`
+    /// and `mandatory_lints` settings.
+    fn validate_lints(&self, extra_lints: bool, mandatory_lints: bool) -> Result<()> {
+        if let Some(lint) = self
+            .allow
+            .iter()
+            .find(|lint| MANDATORY_LINTS.contains(&lint.as_str()))
+        {
+            anyhow::bail!("The mandatory ink! lint `{lint}` can not be allowed");
+        }
+        for lint in self.allow.iter().chain(&self.deny) {
+            if MANDATORY_LINTS.contains(&lint.as_str()) {
+                if !mandatory_lints {
+                    anyhow::bail!(
+                        "The ink! lint `{lint}` is only run for RISC-V contracts or if \
+                        the extra lints are enabled"
+                    );
+                }
+                continue
+            }
+            if !EXTRA_LINTS.contains(&lint.as_str()) {
+                anyhow::bail!(
+                    "Unknown ink! lint `{lint}`, the available lints are: {}",
+                    MANDATORY_LINTS
+                        .iter()
+                        .chain(&EXTRA_LINTS)
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            if !extra_lints {
+                anyhow::bail!(
+                    "The ink! lint `{lint}` is only run if the extra lints are enabled"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the flags setting the lint levels for the dylint driver.
+    pub(crate) fn driver_flags(&self) -> Vec<String> {
+        let allow = self
+            .allow
+            .iter()
+            .flat_map(|lint| ["-A".to_owned(), lint.clone()]);
+        let deny = self
+            .deny
+            .iter()
+            .flat_map(|lint| ["-D".to_owned(), lint.clone()]);
+        allow.chain(deny).collect()
+    }
+}
+
+/// The severity of a lint finding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl LintSeverity {
+    /// Maps the level of a compiler diagnostic to the severity.
+    fn from_level(level: &str) -> Self {
+        match level {
+            "error" | "error: internal compiler error" => Self::Error,
+            "warning" => Self::Warning,
+            "help" => Self::Help,
+            _ => Self::Note,
+        }
+    }
+}
+
+/// The location of a lint finding in the contract source code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintSpan {
+    /// The path of the source file, relative to the contract manifest directory.
+    pub file: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+/// A finding of the linter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LintFinding {
+    /// The name of the lint, not present for plain compiler errors.
+    pub lint: Option<String>,
+    pub severity: LintSeverity,
+    /// The primary location of the finding, if any.
+    pub span: Option<LintSpan>,
+    pub message: String,
+    /// The finding as rendered by the compiler.
+    #[serde(skip)]
+    pub rendered: Option<String>,
+}
+
+/// The findings of running the ink! lints on a contract.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    /// Collects the findings from the JSON messages of `cargo check`.
+    fn from_cargo_messages(messages: &str) -> Self {
+        let mut findings: Vec<LintFinding> = Vec::new();
+        for line in messages.lines() {
+            let Ok(message) = serde_json::from_str::<CargoMessage>(line) else {
+                continue
+            };
+            if message.reason != "compiler-message" {
+                continue
+            }
+            let Some(diagnostic) = message.message else {
+                continue
+            };
+            // skip the summaries like `1 warning emitted`
+            if diagnostic.code.is_none() && diagnostic.spans.is_empty() {
+                continue
+            }
+            let finding = LintFinding::from(diagnostic);
+            // the same finding is reported for every target the contract is checked for
+            if !findings.iter().any(|f| {
+                f.lint == finding.lint
+                    && f.span == finding.span
+                    && f.message == finding.message
+            }) {
+                findings.push(finding);
+            }
+        }
+        Self { findings }
+    }
+
+    /// Returns `true` if any of the findings is an error.
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|f| f.severity == LintSeverity::Error)
+    }
+
+    /// Fails if any of the findings is an error.
+    pub fn check(&self) -> Result<()> {
+        let errors = self
+            .findings
+            .iter()
+            .filter(|f| f.severity == LintSeverity::Error)
+            .count();
+        anyhow::ensure!(errors == 0, "The linter reported {errors} error(s)");
+        Ok(())
+    }
+
+    /// Display the findings as rendered by the compiler.
+    pub fn display(&self) -> String {
+        if self.findings.is_empty() {
+            return "No lint findings".to_string()
+        }
+        self.findings
+            .iter()
+            .map(|f| {
+                f.rendered
+                    .clone()
+                    .unwrap_or_else(|| format!("{}\n", f.message))
+            })
+            .collect()
+    }
+
+    /// Returns the findings as JSON.
+    pub fn serialize_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Runs the ink! lints on the contract at `manifest_path` and returns the findings,
+/// instead of printing them.
+///
+/// The extra lints are only run if `extra_lints` is set, the mandatory ones are always
+/// run.
+pub fn lint_contract(
+    manifest_path: &ManifestPath,
+    extra_lints: bool,
+    lint_levels: &LintLevels,
+    target: Target,
+    verbosity: Verbosity,
+) -> Result<LintReport> {
+    // the mandatory lints are always run by the standalone linting, whatever the target
+    lint_levels.validate_lints(extra_lints, true)?;
+    let crate_metadata = CrateMetadata::collect(manifest_path, target)?;
+    let output = cargo_dylint(
+        extra_lints,
+        lint_levels,
+        &crate_metadata,
+        &target,
+        verbosity,
+        &["--message-format=json"],
+        |cargo| Ok(cargo.stdout_capture().stderr_capture().unchecked().run()?),
+    )?;
+    let report =
+        LintReport::from_cargo_messages(&String::from_utf8_lossy(&output.stdout));
+    // the lints failing the check are reported as errors
+    if !output.status.success() && !report.has_errors() {
+        anyhow::bail!(
+            "`cargo dylint` failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(report)
+}
+
+/// A JSON message of `cargo check`, only the fields of compiler messages are parsed.
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    code: Option<DiagnosticCode>,
+    level: String,
+    spans: Vec<DiagnosticSpan>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+}
+
+impl From<Diagnostic> for LintFinding {
+    fn from(diagnostic: Diagnostic) -> Self {
+        let span = diagnostic
+            .spans
+            .into_iter()
+            .find(|span| span.is_primary)
+            .map(|span| {
+                LintSpan {
+                    file: span.file_name,
+                    line_start: span.line_start,
+                    line_end: span.line_end,
+                    column_start: span.column_start,
+                    column_end: span.column_end,
+                }
+            });
+        Self {
+            lint: diagnostic.code.map(|code| code.code),
+            severity: LintSeverity::from_level(&diagnostic.level),
+            span,
+            message: diagnostic.message,
+            rendered: diagnostic.rendered,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const PRIMITIVE_TOPIC: &str = r#"{"reason":"compiler-message","package_id":"flipper 0.1.0 (path+file:///flipper)","manifest_path":"/flipper/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"flipper","src_path":"/flipper/lib.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: using `#[ink(topic)]` for a field with a primitive number type\n  --> lib.rs:12:9\n","children":[],"code":{"code":"primitive_topic","explanation":null},"level":"warning","message":"using `#[ink(topic)]` for a field with a primitive number type","spans":[{"byte_end":320,"byte_start":291,"column_end":38,"column_start":9,"expansion":null,"file_name":"lib.rs","is_primary":true,"label":null,"line_end":12,"line_start":12,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}"#;
+
+    const SUMMARY: &str = r#"{"reason":"compiler-message","package_id":"flipper 0.1.0 (path+file:///flipper)","manifest_path":"/flipper/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"flipper","src_path":"/flipper/lib.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: 1 warning emitted\n\n","children":[],"code":null,"level":"warning","message":"1 warning emitted","spans":[]}}"#;
+
+    const BUILD_FINISHED: &str = r#"{"reason":"build-finished","success":true}"#;
+
+    #[test]
+    fn lint_report_json_schema() {
+        let messages =
+            [PRIMITIVE_TOPIC, PRIMITIVE_TOPIC, SUMMARY, BUILD_FINISHED].join("\n");
+
+        let report = LintReport::from_cargo_messages(&messages);
+
+        assert!(!report.has_errors());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&report.serialize_json().unwrap())
+                .unwrap(),
+            serde_json::json!({
+                "findings": [{
+                    "lint": "primitive_topic",
+                    "severity": "warning",
+                    "span": {
+                        "file": "lib.rs",
+                        "line_start": 12,
+                        "line_end": 12,
+                        "column_start": 9,
+                        "column_end": 38
+                    },
+                    "message": "using `#[ink(topic)]` for a field with a primitive number type"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn lint_levels_translate_to_driver_flags() {
+        let lint_levels = LintLevels {
+            allow: vec!["primitive_topic".to_string()],
+            deny: vec!["storage_never_freed".to_string(), "no_main".to_string()],
+        };
+
+        assert!(lint_levels.validate(true, &Target::Wasm).is_ok());
+        assert_eq!(
+            lint_levels.driver_flags(),
+            vec![
+                "-A",
+                "primitive_topic",
+                "-D",
+                "storage_never_freed",
+                "-D",
+                "no_main"
+            ]
+        );
+        assert!(LintLevels::default().driver_flags().is_empty());
+    }
+
+    #[test]
+    fn lint_levels_reject_unknown_and_disabled_lints() {
+        let lint_levels = |allow: &str| {
+            LintLevels {
+                allow: vec![allow.to_string()],
+                deny: Vec::new(),
+            }
+        };
+
+        assert_eq!(
+            lint_levels("primitive_topics")
+                .validate(true, &Target::Wasm)
+                .unwrap_err()
+                .to_string(),
+            "Unknown ink! lint `primitive_topics`, the available lints are: no_main, \
+            non_fallible_api, primitive_topic, storage_never_freed, \
+            strict_balance_equality"
+        );
+        assert_eq!(
+            lint_levels("primitive_topic")
+                .validate(false, &Target::Wasm)
+                .unwrap_err()
+                .to_string(),
+            "The ink! lint `primitive_topic` is only run if the extra lints are enabled"
+        );
+        assert_eq!(
+            lint_levels("no_main")
+                .validate(true, &Target::Wasm)
+                .unwrap_err()
+                .to_string(),
+            "The mandatory ink! lint `no_main` can not be allowed"
+        );
+    }
+
+    #[test]
+    fn lint_levels_reject_mandatory_lints_that_are_not_run() {
+        let lint_levels = LintLevels {
+            allow: Vec::new(),
+            deny: vec!["no_main".to_string()],
+        };
+
+        assert!(lint_levels.validate(true, &Target::Wasm).is_ok());
+        assert!(lint_levels.validate(false, &Target::RiscV).is_ok());
+        // the standalone linting always runs the mandatory lints
+        assert!(lint_levels.validate_lints(false, true).is_ok());
+        assert_eq!(
+            lint_levels
+                .validate(false, &Target::Wasm)
+                .unwrap_err()
+                .to_string(),
+            "The ink! lint `no_main` is only run for RISC-V contracts or if the extra \
+            lints are enabled"
+        );
+    }
+
+    #[test]
+    fn lint_report_with_errors_fails_check() {
+        let messages =
+            PRIMITIVE_TOPIC.replace(r#""level":"warning""#, r#""level":"error""#);
+
+        let report = LintReport::from_cargo_messages(&messages);
+
+        assert!(report.has_errors());
+        assert_eq!(
+            report.check().unwrap_err().to_string(),
+            "The linter reported 1 error(s)"
+        );
+    }
+}
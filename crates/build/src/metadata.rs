@@ -33,7 +33,10 @@ use crate::{
     Verbosity,
 };
 
-use anyhow::Result;
+use anyhow::{
+    Context,
+    Result,
+};
 use colored::Colorize;
 use contract_metadata::{
     Compiler,
@@ -48,7 +51,10 @@ use contract_metadata::{
 };
 use semver::Version;
 use std::{
-    fs,
+    fs::{
+        self,
+        File,
+    },
     path::{
         Path,
         PathBuf,
@@ -56,6 +62,9 @@ use std::{
 };
 use url::Url;
 
+/// The extension of contract bundles compressed with zstd, `<name>.contract.zst`.
+pub const COMPRESSED_BUNDLE_EXTENSION: &str = "zst";
+
 /// Artifacts resulting from metadata generation.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct MetadataArtifacts {
@@ -86,6 +95,57 @@ pub struct BuildInfo {
     pub build_mode: BuildMode,
     /// Information about the `wasm-opt` optimization settings.
     pub wasm_opt_settings: WasmOptSettings,
+    /// The blake2 hash of the workspace `Cargo.lock` the contract was built with.
+    ///
+    /// Not present for contracts built without a `Cargo.lock` or by older versions of
+    /// `cargo-contract`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "cargo_lock_hash"
+    )]
+    pub cargo_lock_hash: Option<[u8; 32]>,
+    /// The additional flags passed to `rustc` when building the contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rustflags: Vec<String>,
+}
+
+/// (De)serializes the `Cargo.lock` hash as a hex string.
+mod cargo_lock_hash {
+    use impl_serde::serialize as serde_hex;
+    use serde::{
+        de::Error as _,
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+
+    pub fn serialize<S>(hash: &Option<[u8; 32]>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match hash {
+            Some(hash) => serializer.serialize_str(&serde_hex::to_hex(hash, false)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<[u8; 32]>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|hex| {
+                let bytes = serde_hex::from_hex(&hex).map_err(D::Error::custom)?;
+                <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| {
+                    D::Error::custom(format!(
+                        "Expected a 32 byte hash, got {} bytes",
+                        bytes.len()
+                    ))
+                })
+            })
+            .transpose()
+    }
 }
 
 impl TryFrom<BuildInfo> for serde_json::Map<String, serde_json::Value> {
@@ -118,16 +178,42 @@ pub fn execute(
     network: Network,
     verbosity: Verbosity,
     unstable_options: &UnstableFlags,
+    metadata_version: Option<u64>,
     build_info: BuildInfo,
 ) -> Result<()> {
-    // build the extended contract project metadata
-    let ExtendedMetadataResult {
-        source,
-        contract,
-        user,
-    } = extended_metadata(crate_metadata, final_contract_wasm, build_info)?;
+    let ink_meta = generate_ink_metadata(
+        crate_metadata,
+        features,
+        network,
+        verbosity,
+        unstable_options,
+        metadata_version,
+    )?;
+    write_contract_metadata(
+        crate_metadata,
+        final_contract_wasm,
+        metadata_artifacts,
+        verbosity,
+        build_info,
+        ink_meta,
+    )
+}
 
-    let generate_metadata = |manifest_path: &ManifestPath| -> Result<()> {
+/// Generates the ink! metadata describing the ABI of the smart contract, by generating
+/// and invoking a temporary workspace member.
+///
+/// This does not depend on the final contract Wasm, so it can run while the Wasm is
+/// still being post processed.
+pub(crate) fn generate_ink_metadata(
+    crate_metadata: &CrateMetadata,
+    features: &Features,
+    network: Network,
+    verbosity: Verbosity,
+    unstable_options: &UnstableFlags,
+    metadata_version: Option<u64>,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let generate_metadata =
+        |manifest_path: &ManifestPath| -> Result<serde_json::Map<String, serde_json::Value>> {
         verbose_eprintln!(
             verbosity,
             " {} {}",
@@ -167,17 +253,11 @@ pub fn execute(
         );
         let output = cmd.stdout_capture().run()?;
 
-        let ink_meta: serde_json::Map<String, serde_json::Value> =
-            serde_json::from_slice(&output.stdout)?;
-        let metadata = ContractMetadata::new(source, contract, None, user, ink_meta);
-
-        write_metadata(metadata_artifacts, metadata, &verbosity, false)?;
-
-        Ok(())
+        Ok(serde_json::from_slice(&output.stdout)?)
     };
 
-    if unstable_options.original_manifest {
-        generate_metadata(&crate_metadata.manifest_path)?;
+    let ink_meta = if unstable_options.original_manifest {
+        generate_metadata(&crate_metadata.manifest_path)?
     } else {
         Workspace::new(&crate_metadata.cargo_meta, &crate_metadata.root_package.id)?
             .with_root_package_manifest(|manifest| {
@@ -192,12 +272,84 @@ pub fn execute(
                 Ok(())
             })?
             .with_metadata_gen_package()?
-            .using_temp(generate_metadata)?;
+            .using_temp(generate_metadata)?
+    };
+    if let Some(metadata_version) = metadata_version {
+        ensure_metadata_version(&ink_meta, metadata_version)?;
     }
+    Ok(ink_meta)
+}
 
+/// Checks that the requested version of the ink! metadata ABI can be produced by the
+/// ink! version of the contract, which only produces the metadata version matching its
+/// major version.
+pub(crate) fn check_metadata_version(
+    requested: u64,
+    ink_version: &Version,
+) -> Result<()> {
+    let produced = ink_version.major;
+    anyhow::ensure!(
+        requested == produced,
+        "Metadata version {requested} is not supported by ink! {ink_version}, which \
+        produces metadata version {produced}"
+    );
+    Ok(())
+}
+
+/// Checks that the generated ink! metadata has the requested version.
+fn ensure_metadata_version(
+    ink_meta: &serde_json::Map<String, serde_json::Value>,
+    requested: u64,
+) -> Result<()> {
+    // ink! 4 writes the version as a string, later versions as a number
+    let version = match ink_meta.get("version") {
+        Some(serde_json::Value::Number(version)) => version.as_u64(),
+        Some(serde_json::Value::String(version)) => version.parse().ok(),
+        _ => None,
+    };
+    anyhow::ensure!(
+        version == Some(requested),
+        "The generated metadata has version {}, but version {requested} was requested",
+        version.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+    );
     Ok(())
 }
 
+/// Combines the ink! metadata with the extended contract project metadata of the final
+/// contract Wasm, and writes the metadata file and the contract bundle.
+pub(crate) fn write_contract_metadata(
+    crate_metadata: &CrateMetadata,
+    final_contract_wasm: &Path,
+    metadata_artifacts: &MetadataArtifacts,
+    verbosity: Verbosity,
+    build_info: BuildInfo,
+    ink_meta: serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    let metadata =
+        contract_metadata(crate_metadata, final_contract_wasm, build_info, ink_meta)?;
+    write_metadata(metadata_artifacts, metadata, &verbosity, false)
+}
+
+/// Combines the ink! metadata with the extended contract project metadata of the final
+/// contract Wasm.
+pub(crate) fn contract_metadata(
+    crate_metadata: &CrateMetadata,
+    final_contract_wasm: &Path,
+    build_info: BuildInfo,
+    ink_meta: serde_json::Map<String, serde_json::Value>,
+) -> Result<ContractMetadata> {
+    // build the extended contract project metadata
+    let ExtendedMetadataResult {
+        source,
+        contract,
+        user,
+    } = extended_metadata(crate_metadata, final_contract_wasm, build_info)?;
+
+    Ok(ContractMetadata::new(
+        source, contract, None, user, ink_meta,
+    ))
+}
+
 pub fn write_metadata(
     metadata_artifacts: &MetadataArtifacts,
     metadata: ContractMetadata,
@@ -227,11 +379,36 @@ pub fn write_metadata(
         );
     }
     let contents = serde_json::to_string(&metadata)?;
-    fs::write(&metadata_artifacts.dest_bundle, contents)?;
+    if is_compressed_bundle(&metadata_artifacts.dest_bundle) {
+        let compressed = zstd::encode_all(contents.as_bytes(), 0)?;
+        fs::write(&metadata_artifacts.dest_bundle, compressed)?;
+    } else {
+        fs::write(&metadata_artifacts.dest_bundle, contents)?;
+    }
 
     Ok(())
 }
 
+/// Returns `true` if the contract bundle at `path` is compressed with zstd.
+pub fn is_compressed_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == COMPRESSED_BUNDLE_EXTENSION)
+}
+
+/// Loads the contract bundle at `path`, decompressing it if it is compressed with zstd.
+pub fn load_contract_bundle(path: &Path) -> Result<ContractMetadata> {
+    if !is_compressed_bundle(path) {
+        return ContractMetadata::load(path)
+    }
+    let file = File::open(path)
+        .context(format!("Failed to open contract bundle {}", path.display()))?;
+    let decoder = zstd::Decoder::new(file)?;
+    serde_json::from_reader(decoder).context(format!(
+        "Failed to deserialize contract bundle {}",
+        path.display()
+    ))
+}
+
 /// Generate the extended contract project metadata
 fn extended_metadata(
     crate_metadata: &CrateMetadata,
@@ -308,3 +485,129 @@ fn extended_metadata(
         user,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn check_metadata_version_rejects_unsupported_version() {
+        let ink_version = Version::new(5, 0, 0);
+
+        assert!(check_metadata_version(5, &ink_version).is_ok());
+        let err = check_metadata_version(4, &ink_version).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Metadata version 4 is not supported by ink! 5.0.0, which produces metadata \
+            version 5"
+        );
+    }
+
+    #[test]
+    fn ensure_metadata_version_works() {
+        let ink_meta = |version: serde_json::Value| {
+            serde_json::json!({ "version": version })
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+
+        assert!(ensure_metadata_version(&ink_meta(serde_json::json!(5)), 5).is_ok());
+        assert!(ensure_metadata_version(&ink_meta(serde_json::json!("4")), 4).is_ok());
+        assert!(ensure_metadata_version(&ink_meta(serde_json::json!(5)), 4).is_err());
+    }
+
+    fn contract_metadata() -> ContractMetadata {
+        let source = Source::new(
+            Some(SourceWasm::new(vec![0, 97, 115, 109])),
+            [0x11; 32].into(),
+            SourceLanguage::new(Language::Ink, Version::new(5, 0, 0)),
+            SourceCompiler::new(Compiler::RustC, Version::new(1, 78, 0)),
+            None,
+        );
+        let contract = Contract::builder()
+            .name("flipper")
+            .version(Version::new(1, 0, 0))
+            .authors(vec!["Use Ink <ink@use.ink>"])
+            .build()
+            .unwrap();
+        ContractMetadata::new(source, contract, None, None, Default::default())
+    }
+
+    #[test]
+    fn compressed_bundle_roundtrip() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let write = |dest_bundle: PathBuf| {
+            let metadata_artifacts = MetadataArtifacts {
+                dest_metadata: tmp_dir.path().join("flipper.json"),
+                dest_bundle,
+            };
+            write_metadata(
+                &metadata_artifacts,
+                contract_metadata(),
+                &Verbosity::Quiet,
+                false,
+            )
+            .unwrap();
+            metadata_artifacts.dest_bundle
+        };
+        let bundle = write(tmp_dir.path().join("flipper.contract"));
+        let compressed_bundle = write(tmp_dir.path().join("flipper.contract.zst"));
+
+        assert!(is_compressed_bundle(&compressed_bundle));
+        assert!(
+            fs::metadata(&compressed_bundle).unwrap().len()
+                != fs::metadata(&bundle).unwrap().len()
+        );
+        let loaded = load_contract_bundle(&bundle).unwrap();
+        let loaded_compressed = load_contract_bundle(&compressed_bundle).unwrap();
+        assert_eq!(
+            serde_json::to_value(loaded_compressed).unwrap(),
+            serde_json::to_value(loaded).unwrap()
+        );
+    }
+
+    fn build_info(cargo_lock_hash: Option<[u8; 32]>) -> BuildInfo {
+        BuildInfo {
+            rust_toolchain: "stable-x86_64-unknown-linux-gnu".to_string(),
+            cargo_contract_version: Version::new(4, 1, 1),
+            build_mode: BuildMode::Release,
+            wasm_opt_settings: WasmOptSettings {
+                optimization_passes: OptimizationPasses::Z,
+                keep_debug_symbols: false,
+            },
+            cargo_lock_hash,
+            rustflags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn build_info_with_cargo_lock_hash_roundtrip() {
+        let build_info = build_info(Some([0xab; 32]));
+
+        let json = serde_json::to_value(&build_info).unwrap();
+        assert_eq!(json["cargo_lock_hash"], format!("0x{}", "ab".repeat(32)));
+
+        let decoded: BuildInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.cargo_lock_hash, Some([0xab; 32]));
+    }
+
+    #[test]
+    fn build_info_without_cargo_lock_hash_is_backwards_compatible() {
+        let json = serde_json::to_value(build_info(None)).unwrap();
+        assert!(json.get("cargo_lock_hash").is_none());
+
+        let decoded: BuildInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.cargo_lock_hash, None);
+    }
+
+    #[test]
+    fn build_info_rejects_invalid_cargo_lock_hash() {
+        let mut json = serde_json::to_value(build_info(None)).unwrap();
+        json["cargo_lock_hash"] = "0xabcd".into();
+
+        let err = serde_json::from_value::<BuildInfo>(json).unwrap_err();
+        assert_eq!(err.to_string(), "Expected a 32 byte hash, got 2 bytes");
+    }
+}
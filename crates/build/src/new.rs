@@ -14,9 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
-use anyhow::Result;
-use heck::ToUpperCamelCase as _;
+use anyhow::{
+    Context,
+    Result,
+};
+use cargo_metadata::MetadataCommand;
+use heck::{
+    ToSnakeCase as _,
+    ToUpperCamelCase as _,
+};
+use regex::{
+    Captures,
+    Regex,
+};
 use std::{
+    collections::BTreeMap,
     env,
     fs,
     io::{
@@ -29,10 +41,210 @@ use std::{
         Path,
         PathBuf,
     },
+    process::Command,
 };
 
+/// The strict and reserved keywords of Rust 2021, which can not be used as a contract
+/// name since it is used verbatim as the name of the contract module.
+///
+/// Most of them could be used as raw identifiers, but `crate`, `self`, `super` and
+/// `Self` can not, and a raw identifier as the name of a package is not valid either.
+const RUST_KEYWORDS: [&str; 51] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+    "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become",
+    "box", "do", "final", "macro", "override", "priv", "try", "typeof", "unsized",
+    "virtual", "yield",
+];
+
+/// The variables which are always available to templates.
+const BUILTIN_VARIABLES: [&str; 4] = ["name", "camel_name", "snake_name", "year"];
+
 /// Creates a new contract project from the template.
 pub fn new_contract_project<P>(name: &str, dir: Option<P>) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    new_contract_project_with_defines(name, dir, &[])
+}
+
+/// Creates a new contract project from the template, replacing the `{{key}}`
+/// placeholders for the user defined variables in `defines` in addition to the
+/// built-in ones.
+pub fn new_contract_project_with_defines<P>(
+    name: &str,
+    dir: Option<P>,
+    defines: &[(String, String)],
+) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let variables = template_variables(name, defines)?;
+    let out_dir = project_dir(name, dir)?;
+    let template = include_bytes!(concat!(env!("OUT_DIR"), "/template.zip"));
+
+    unzip(template, out_dir, Some(&variables))?;
+
+    Ok(())
+}
+
+/// Creates a new contract project from a template in a remote git repository.
+///
+/// The template is given as `<git-url>[#rev]`, where `rev` is an optional branch, tag
+/// or commit to check out instead of the default branch. The placeholders in the
+/// files of the template are replaced the same way as for the embedded template.
+pub fn new_contract_project_from_git<P>(
+    name: &str,
+    dir: Option<P>,
+    template: &str,
+    defines: &[(String, String)],
+) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let (url, rev) = match template.split_once('#') {
+        Some((url, rev)) => (url, Some(rev)),
+        None => (template, None),
+    };
+    let variables = template_variables(name, defines)?;
+    // the template is fetched before the project directory is created, so that no
+    // empty directory is left behind if the url or the revision is invalid
+    let checkout = tempfile::tempdir()?;
+    fetch_git_template(url, rev, checkout.path())?;
+    let out_dir = project_dir(name, dir)?;
+
+    copy_template(checkout.path(), checkout.path(), &out_dir, &variables)
+}
+
+/// Returns the built-in template variables for the contract `name`, extended by the
+/// user defined ones.
+fn template_variables(
+    name: &str,
+    defines: &[(String, String)],
+) -> Result<BTreeMap<String, String>> {
+    let mut variables = BTreeMap::from([
+        ("name".to_string(), name.to_string()),
+        ("camel_name".to_string(), name.to_upper_camel_case()),
+        ("snake_name".to_string(), name.to_snake_case()),
+        ("year".to_string(), current_year().to_string()),
+    ]);
+    for (key, value) in defines {
+        if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            anyhow::bail!(
+                "Template variable '{key}' can only contain alphanumeric characters \
+                 and underscores"
+            );
+        }
+        if BUILTIN_VARIABLES.contains(&key.as_str()) {
+            anyhow::bail!(
+                "Template variable '{key}' collides with a built-in variable, the \
+                 built-in variables are: {}",
+                BUILTIN_VARIABLES.join(", ")
+            );
+        }
+        if variables.insert(key.clone(), value.clone()).is_some() {
+            anyhow::bail!("Template variable '{key}' is defined more than once");
+        }
+    }
+    Ok(variables)
+}
+
+/// Returns the current year in UTC.
+fn current_year() -> i32 {
+    time::OffsetDateTime::now_utc().year()
+}
+
+/// Returns the path of the root manifest of the cargo workspace enclosing `dir`, which
+/// defaults to the current directory.
+pub fn find_enclosing_workspace<P>(dir: Option<P>) -> Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.map_or(env::current_dir()?, |p| p.as_ref().to_path_buf());
+    let metadata = MetadataCommand::new()
+        .no_deps()
+        .current_dir(&dir)
+        .exec()
+        .with_context(|| {
+            format!("No cargo workspace found enclosing {}", dir.display())
+        })?;
+    let manifest_path = metadata
+        .workspace_root
+        .into_std_path_buf()
+        .join("Cargo.toml");
+    workspace_manifest(&manifest_path)?;
+    Ok(manifest_path)
+}
+
+/// Adds the contract project `name` in `dir`, which defaults to the current directory,
+/// to the `[workspace].members` of the workspace manifest at `workspace_manifest_path`.
+///
+/// The formatting and comments of the workspace manifest are preserved.
+pub fn add_workspace_member<P>(
+    workspace_manifest_path: &Path,
+    name: &str,
+    dir: Option<P>,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let project_dir = dir
+        .map_or(env::current_dir()?, |p| p.as_ref().to_path_buf())
+        .join(name)
+        .canonicalize()?;
+    let workspace_root = workspace_manifest_path
+        .parent()
+        .context("The workspace manifest has no parent directory")?
+        .canonicalize()?;
+    let member = project_dir.strip_prefix(&workspace_root).with_context(|| {
+        format!(
+            "The contract project {} is not inside the workspace {}",
+            project_dir.display(),
+            workspace_root.display()
+        )
+    })?;
+    let member = member
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut manifest = workspace_manifest(workspace_manifest_path)?;
+    let members = manifest["workspace"]
+        .as_table_like_mut()
+        .expect("the `[workspace]` section is checked to be a table")
+        .entry("members")
+        .or_insert(toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .context("`workspace.members` must be an array")?;
+    if !members.iter().any(|m| m.as_str() == Some(member.as_str())) {
+        members.push(member);
+    }
+    fs::write(workspace_manifest_path, manifest.to_string())?;
+    Ok(())
+}
+
+/// Parses the workspace manifest at `manifest_path`, which must have a `[workspace]`
+/// section.
+fn workspace_manifest(manifest_path: &Path) -> Result<toml_edit::DocumentMut> {
+    let manifest: toml_edit::DocumentMut = fs::read_to_string(manifest_path)?
+        .parse()
+        .with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+    if !manifest
+        .get("workspace")
+        .is_some_and(toml_edit::Item::is_table_like)
+    {
+        anyhow::bail!(
+            "No cargo workspace found: {} does not contain a `[workspace]` section",
+            manifest_path.display()
+        );
+    }
+    Ok(manifest)
+}
+
+/// Validates the contract name and creates the project directory for it.
+fn project_dir<P>(name: &str, dir: Option<P>) -> Result<PathBuf>
 where
     P: AsRef<Path>,
 {
@@ -51,6 +263,12 @@ where
         anyhow::bail!("Contract names must begin with an alphabetic character");
     }
 
+    if RUST_KEYWORDS.contains(&name) {
+        anyhow::bail!(
+            "Contract names must not be a Rust keyword, `{name}` is reserved by the language"
+        );
+    }
+
     let out_dir = dir
         .map_or(env::current_dir()?, |p| p.as_ref().to_path_buf())
         .join(name);
@@ -60,20 +278,113 @@ where
     if !out_dir.exists() {
         fs::create_dir(&out_dir)?;
     }
+    Ok(out_dir)
+}
 
-    let template = include_bytes!(concat!(env!("OUT_DIR"), "/template.zip"));
-
-    unzip(template, out_dir, Some(name))?;
+/// Shallow clones the `rev` of the git repository at `url` into `checkout_dir`.
+fn fetch_git_template(url: &str, rev: Option<&str>, checkout_dir: &Path) -> Result<()> {
+    let git = |args: &[&str]| -> Result<()> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(checkout_dir)
+            .output()
+            .context("Failed to run git, is it installed?")?;
+        if !output.status.success() {
+            anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim())
+        }
+        Ok(())
+    };
+    git(&["init", "--quiet"])?;
+    git(&[
+        "fetch",
+        "--quiet",
+        "--depth",
+        "1",
+        // separates the user supplied url from the options
+        "--",
+        url,
+        rev.unwrap_or("HEAD"),
+    ])
+    .map_err(|err| {
+        match rev {
+            Some(rev) => {
+                anyhow::anyhow!("Revision {rev} not found in template {url}: {err}")
+            }
+            None => anyhow::anyhow!("Failed to fetch template {url}: {err}"),
+        }
+    })?;
+    git(&["checkout", "--quiet", "FETCH_HEAD"])
+}
 
+/// Copies the files in `dir` of the template checked out at `checkout_dir` to
+/// `out_dir`, replacing the placeholders for the template `variables`.
+fn copy_template(
+    checkout_dir: &Path,
+    dir: &Path,
+    out_dir: &Path,
+    variables: &BTreeMap<String, String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name() == Some(".git".as_ref()) {
+            continue
+        }
+        let relative_path = path.strip_prefix(checkout_dir)?;
+        let outpath = out_dir.join(relative_path);
+        if path.is_dir() {
+            fs::create_dir_all(&outpath)?;
+            copy_template(checkout_dir, &path, out_dir, variables)?;
+            continue
+        }
+        let mut outfile = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&outpath)
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    anyhow::anyhow!("File {} already exists", relative_path.display())
+                } else {
+                    anyhow::anyhow!(e)
+                }
+            })?;
+        let contents = fs::read(&path)?;
+        match String::from_utf8(contents) {
+            Ok(contents) => {
+                let contents = replace_placeholders(&contents, variables);
+                outfile.write_all(contents.as_bytes())?
+            }
+            // binary files are copied as they are
+            Err(err) => outfile.write_all(err.as_bytes())?,
+        }
+        fs::set_permissions(&outpath, fs::metadata(&path)?.permissions())?;
+    }
     Ok(())
 }
 
+/// Replaces the `{{key}}` placeholders in a template file with the value of the
+/// template variable `key`. Placeholders for unknown variables are left as they are.
+fn replace_placeholders(contents: &str, variables: &BTreeMap<String, String>) -> String {
+    let placeholder = Regex::new(r"\{\{(\w+)\}\}").expect("placeholder regex is valid");
+    placeholder
+        .replace_all(contents, |caps: &Captures| {
+            variables
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
 // Unzips the file at `template` to `out_dir`.
 //
-// In case `name` is set the zip file is treated as if it were a template for a new
-// contract. Replacements in `Cargo.toml` for `name`-placeholders are attempted in
+// In case `variables` are set the zip file is treated as if it were a template for a
+// new contract. Replacements of the placeholders for the variables are attempted in
 // that case.
-fn unzip(template: &[u8], out_dir: PathBuf, name: Option<&str>) -> Result<()> {
+fn unzip(
+    template: &[u8],
+    out_dir: PathBuf,
+    variables: Option<&BTreeMap<String, String>>,
+) -> Result<()> {
     let mut cursor = Cursor::new(Vec::new());
     cursor.write_all(template)?;
     cursor.rewind()?;
@@ -104,12 +415,10 @@ fn unzip(template: &[u8], out_dir: PathBuf, name: Option<&str>) -> Result<()> {
                     }
                 })?;
 
-            if let Some(name) = name {
+            if let Some(variables) = variables {
                 let mut contents = String::new();
                 file.read_to_string(&mut contents)?;
-                let contents = contents.replace("{{name}}", name);
-                let contents =
-                    contents.replace("{{camel_name}}", &name.to_upper_camel_case());
+                let contents = replace_placeholders(&contents, variables);
                 outfile.write_all(contents.as_bytes())?;
             } else {
                 let mut v = Vec::new();
@@ -176,6 +485,33 @@ mod tests {
         })
     }
 
+    #[test]
+    fn rejects_rust_keywords() {
+        with_tmp_dir(|path| {
+            for keyword in ["match", "async", "crate", "self", "Self", "yield"] {
+                let result = new_contract_project(keyword, Some(path));
+                assert_eq!(
+                    result.err().unwrap().to_string(),
+                    format!(
+                        "Contract names must not be a Rust keyword, `{keyword}` is \
+                        reserved by the language"
+                    )
+                );
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn accepts_names_containing_rust_keywords() {
+        with_tmp_dir(|path| {
+            for name in ["matcher", "async_flipper", "crates", "my_self", "Typed"] {
+                new_contract_project(name, Some(path))?;
+            }
+            Ok(())
+        })
+    }
+
     #[test]
     fn contract_cargo_project_already_exists() {
         with_tmp_dir(|path| {
@@ -192,6 +528,192 @@ mod tests {
         })
     }
 
+    #[test]
+    fn adds_contract_to_workspace_members() {
+        with_tmp_dir(|path| {
+            let manifest_path = path.join("Cargo.toml");
+            fs::write(
+                &manifest_path,
+                "# the contracts\n[workspace]\nmembers = [\"flipper\"]\n",
+            )?;
+            let contracts_dir = path.join("contracts");
+            fs::create_dir(&contracts_dir)?;
+            new_contract_project("incrementer", Some(&contracts_dir))?;
+
+            add_workspace_member(&manifest_path, "incrementer", Some(&contracts_dir))?;
+            // adding the same member again is a no-op
+            add_workspace_member(&manifest_path, "incrementer", Some(&contracts_dir))?;
+
+            assert_eq!(
+                fs::read_to_string(&manifest_path)?,
+                "# the contracts\n[workspace]\nmembers = [\"flipper\", \
+                \"contracts/incrementer\"]\n"
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn missing_workspace_section_is_rejected() {
+        with_tmp_dir(|path| {
+            let manifest_path = path.join("Cargo.toml");
+            fs::write(&manifest_path, "[package]\nname = \"flipper\"\n")?;
+
+            let result = workspace_manifest(&manifest_path);
+
+            assert_eq!(
+                result.err().unwrap().to_string(),
+                format!(
+                    "No cargo workspace found: {} does not contain a `[workspace]` \
+                    section",
+                    manifest_path.display()
+                )
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn replaces_builtin_and_user_defined_variables() {
+        let variables = template_variables(
+            "my_contract",
+            &[("author".to_string(), "Alice".to_string())],
+        )
+        .unwrap();
+
+        let contents = replace_placeholders(
+            "{{name}} {{camel_name}} {{snake_name}} {{author}} {{unknown}}",
+            &variables,
+        );
+
+        assert_eq!(
+            contents,
+            "my_contract MyContract my_contract Alice {{unknown}}"
+        );
+        assert!(current_year() >= 2024);
+    }
+
+    #[test]
+    fn user_defined_variables_must_not_collide_with_builtins() {
+        let result = template_variables(
+            "my_contract",
+            &[("year".to_string(), "1970".to_string())],
+        );
+
+        assert!(result
+            .err()
+            .expect("Should fail")
+            .to_string()
+            .starts_with("Template variable 'year' collides with a built-in variable"));
+    }
+
+    #[test]
+    fn user_defined_variables_must_be_unique() {
+        let defines = [
+            ("author".to_string(), "Alice".to_string()),
+            ("author".to_string(), "Bob".to_string()),
+        ];
+
+        let result = template_variables("my_contract", &defines);
+
+        assert_eq!(
+            result.err().expect("Should fail").to_string(),
+            "Template variable 'author' is defined more than once"
+        );
+    }
+
+    /// Creates a git repository with a template in `dir`, returning its url.
+    fn git_template(dir: &Path) -> String {
+        let repo = dir.join("template_repo");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join("Cargo.toml"), "[package]\nname = \"{{name}}\"\n").unwrap();
+        fs::write(
+            repo.join("src").join("lib.rs"),
+            "pub struct {{camel_name}};\n",
+        )
+        .unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "--quiet",
+            "-m",
+            "template",
+        ]);
+        format!("file://{}", repo.display())
+    }
+
+    #[test]
+    fn new_project_from_git_template() {
+        with_tmp_dir(|path| {
+            let template = git_template(path);
+
+            new_contract_project_from_git("my_contract", Some(path), &template, &[])?;
+
+            let project = path.join("my_contract");
+            assert_eq!(
+                fs::read_to_string(project.join("Cargo.toml"))?,
+                "[package]\nname = \"my_contract\"\n"
+            );
+            assert_eq!(
+                fs::read_to_string(project.join("src").join("lib.rs"))?,
+                "pub struct MyContract;\n"
+            );
+            assert!(!project.join(".git").exists());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn new_project_from_git_template_rejects_unknown_revision() {
+        with_tmp_dir(|path| {
+            let template = format!("{}#does-not-exist", git_template(path));
+
+            let result =
+                new_contract_project_from_git("my_contract", Some(path), &template, &[]);
+
+            let err = result.err().expect("Should fail").to_string();
+            assert!(
+                err.starts_with("Revision does-not-exist not found in template"),
+                "unexpected error: {err}"
+            );
+            assert!(!path.join("my_contract").exists());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn new_project_from_git_template_does_not_overwrite_files() {
+        with_tmp_dir(|path| {
+            let template = git_template(path);
+            let dir = path.join("my_contract").join("src");
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("lib.rs"), "")?;
+
+            let result =
+                new_contract_project_from_git("my_contract", Some(path), &template, &[]);
+
+            let expected = format!(
+                "File {} already exists",
+                Path::new("src").join("lib.rs").display()
+            );
+            assert_eq!(result.err().expect("Should fail").to_string(), expected);
+            Ok(())
+        })
+    }
+
     #[test]
     fn dont_overwrite_existing_files_not_in_cargo_project() {
         with_tmp_dir(|path| {
@@ -0,0 +1,395 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::CrateMetadata;
+use anyhow::{
+    Context,
+    Result,
+};
+use cargo_metadata::{
+    DependencyKind,
+    Metadata,
+    Package,
+    PackageId,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        VecDeque,
+    },
+    fs,
+    path::PathBuf,
+};
+
+/// The CycloneDX specification version of the generated SBOM.
+const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+
+/// Result of the software bill of materials generation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SbomArtifact {
+    /// Path to the resulting CycloneDX JSON file.
+    pub dest_sbom: PathBuf,
+    /// The crates without a `license` or `license-file` field, as `name@version`.
+    pub missing_licenses: Vec<String>,
+}
+
+/// Writes a CycloneDX software bill of materials of all crates the contract code is
+/// built from next to the contract bundle.
+pub(crate) fn write_sbom(crate_metadata: &CrateMetadata) -> Result<SbomArtifact> {
+    let bom = Bom::new(&crate_metadata.cargo_meta, &crate_metadata.root_package)?;
+    let missing_licenses = bom.missing_licenses();
+    let dest_sbom = crate_metadata.sbom_path();
+    fs::write(&dest_sbom, serde_json::to_string_pretty(&bom)?)?;
+    Ok(SbomArtifact {
+        dest_sbom,
+        missing_licenses,
+    })
+}
+
+/// A CycloneDX bill of materials.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Bom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: BomMetadata,
+    components: Vec<Component>,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Serialize)]
+struct BomMetadata {
+    tools: Vec<Tool>,
+    component: Component,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Component {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    version: String,
+    purl: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<LicenseChoice>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    properties: Vec<Property>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum LicenseChoice {
+    Expression { expression: String },
+    License { license: License },
+}
+
+#[derive(Debug, Serialize)]
+struct License {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Property {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Dependency {
+    #[serde(rename = "ref")]
+    reference: String,
+    depends_on: Vec<String>,
+}
+
+impl Bom {
+    /// Creates the bill of materials for the `root` package from its resolved
+    /// dependency graph, including all transitive normal dependencies.
+    fn new(metadata: &Metadata, root: &Package) -> Result<Self> {
+        let resolve = metadata
+            .resolve
+            .as_ref()
+            .context("Cargo metadata does not contain the resolved dependencies")?;
+        let nodes: BTreeMap<_, _> =
+            resolve.nodes.iter().map(|node| (&node.id, node)).collect();
+        let packages: BTreeMap<_, _> = metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package))
+            .collect();
+
+        // Build and dev dependencies do not end up in the contract code.
+        let normal_deps = |id: &PackageId| -> Result<Vec<&PackageId>> {
+            let node = nodes.get(id).with_context(|| {
+                format!("Package {id} is missing in the resolve graph")
+            })?;
+            Ok(node
+                .deps
+                .iter()
+                .filter(|dep| {
+                    dep.dep_kinds.is_empty()
+                        || dep
+                            .dep_kinds
+                            .iter()
+                            .any(|info| info.kind == DependencyKind::Normal)
+                })
+                .map(|dep| &dep.pkg)
+                .collect())
+        };
+
+        let mut dependencies = Vec::new();
+        let mut visited = BTreeSet::from([&root.id]);
+        let mut queue = VecDeque::from([&root.id]);
+        while let Some(id) = queue.pop_front() {
+            let deps = normal_deps(id)?;
+            for dep in &deps {
+                if visited.insert(*dep) {
+                    queue.push_back(*dep);
+                }
+            }
+            dependencies.push(Dependency {
+                reference: id.repr.clone(),
+                depends_on: deps.iter().map(|dep| dep.repr.clone()).collect(),
+            });
+        }
+        dependencies.sort_by(|a, b| a.reference.cmp(&b.reference));
+
+        let mut components = visited
+            .into_iter()
+            .filter(|id| *id != &root.id)
+            .map(|id| {
+                packages
+                    .get(id)
+                    .map(|package| Component::new(package, "library"))
+                    .with_context(|| format!("Package {id} is missing in cargo metadata"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        components.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+        Ok(Self {
+            bom_format: "CycloneDX",
+            spec_version: CYCLONEDX_SPEC_VERSION,
+            version: 1,
+            metadata: BomMetadata {
+                tools: vec![Tool {
+                    name: "cargo-contract",
+                    version: crate::VERSION,
+                }],
+                component: Component::new(root, "application"),
+            },
+            components,
+            dependencies,
+        })
+    }
+
+    /// Returns the components without any license, as `name@version`.
+    fn missing_licenses(&self) -> Vec<String> {
+        std::iter::once(&self.metadata.component)
+            .chain(&self.components)
+            .filter(|component| component.licenses.is_empty())
+            .map(|component| format!("{}@{}", component.name, component.version))
+            .collect()
+    }
+}
+
+impl Component {
+    fn new(package: &Package, kind: &'static str) -> Self {
+        let licenses = match (&package.license, &package.license_file) {
+            (Some(license), _) => {
+                vec![LicenseChoice::Expression {
+                    expression: license.clone(),
+                }]
+            }
+            (None, Some(license_file)) => {
+                vec![LicenseChoice::License {
+                    license: License {
+                        name: license_file.to_string(),
+                    },
+                }]
+            }
+            (None, None) => Vec::new(),
+        };
+        let mut properties = Vec::new();
+        if let Some(source) = &package.source {
+            properties.push(Property {
+                name: "cargo:source",
+                value: source.repr.clone(),
+            });
+        }
+        if licenses.is_empty() {
+            properties.push(Property {
+                name: "cargo-contract:license-missing",
+                value: "true".to_string(),
+            });
+        }
+        Self {
+            kind,
+            bom_ref: package.id.repr.clone(),
+            name: package.name.clone(),
+            version: package.version.to_string(),
+            purl: format!("pkg:cargo/{}@{}", package.name, package.version),
+            licenses,
+            properties,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn package(name: &str, license: Option<&str>) -> serde_json::Value {
+        json!({
+            "name": name,
+            "version": "1.0.0",
+            "id": format!("{name} 1.0.0"),
+            "source": (name != "contract")
+                .then_some("registry+https://github.com/rust-lang/crates.io-index"),
+            "license": license,
+            "license_file": null,
+            "description": null,
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": format!("/{name}/Cargo.toml"),
+            "authors": [],
+            "categories": [],
+            "keywords": [],
+            "readme": null,
+            "repository": null,
+            "homepage": null,
+            "documentation": null,
+            "edition": "2021",
+            "metadata": null,
+            "links": null,
+            "publish": null,
+            "default_run": null,
+            "rust_version": null
+        })
+    }
+
+    fn node(name: &str, deps: &[(&str, Option<&str>)]) -> serde_json::Value {
+        let deps: Vec<_> = deps
+            .iter()
+            .map(|(dep, kind)| {
+                json!({
+                    "name": dep,
+                    "pkg": format!("{dep} 1.0.0"),
+                    "dep_kinds": [{ "kind": kind, "target": null }]
+                })
+            })
+            .collect();
+        let dependencies: Vec<_> = deps.iter().map(|dep| dep["pkg"].clone()).collect();
+        json!({
+            "id": format!("{name} 1.0.0"),
+            "dependencies": dependencies,
+            "deps": deps,
+            "features": []
+        })
+    }
+
+    fn metadata() -> Metadata {
+        serde_json::from_value(json!({
+            "packages": [
+                package("contract", Some("Apache-2.0")),
+                package("ink", Some("Apache-2.0")),
+                package("scale", None),
+                package("build_helper", Some("MIT")),
+                package("test_helper", Some("MIT")),
+            ],
+            "workspace_members": ["contract 1.0.0"],
+            "workspace_default_members": ["contract 1.0.0"],
+            "resolve": {
+                "nodes": [
+                    node("contract", &[
+                        ("ink", None),
+                        ("build_helper", Some("build")),
+                        ("test_helper", Some("dev")),
+                    ]),
+                    node("ink", &[("scale", None)]),
+                    node("scale", &[]),
+                    node("build_helper", &[]),
+                    node("test_helper", &[]),
+                ],
+                "root": "contract 1.0.0"
+            },
+            "workspace_root": "/contract",
+            "target_directory": "/contract/target",
+            "version": 1,
+            "metadata": null
+        }))
+        .expect("metadata must be valid")
+    }
+
+    #[test]
+    fn sbom_contains_transitive_normal_dependencies() {
+        let metadata = metadata();
+        let root = &metadata.packages[0];
+
+        let bom = Bom::new(&metadata, root).unwrap();
+
+        let components: Vec<_> = bom.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(components, ["ink", "scale"]);
+        let dependencies: Vec<_> = bom
+            .dependencies
+            .iter()
+            .map(|d| (d.reference.as_str(), d.depends_on.clone()))
+            .collect();
+        assert_eq!(
+            dependencies,
+            [
+                ("contract 1.0.0", vec!["ink 1.0.0".to_string()]),
+                ("ink 1.0.0", vec!["scale 1.0.0".to_string()]),
+                ("scale 1.0.0", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn sbom_flags_missing_licenses() {
+        let metadata = metadata();
+        let root = &metadata.packages[0];
+
+        let bom = Bom::new(&metadata, root).unwrap();
+
+        assert_eq!(bom.missing_licenses(), ["scale@1.0.0"]);
+        let json = serde_json::to_value(&bom).unwrap();
+        assert_eq!(json["bomFormat"], "CycloneDX");
+        assert_eq!(
+            json["metadata"]["component"]["licenses"][0]["expression"],
+            "Apache-2.0"
+        );
+        assert_eq!(
+            json["components"][1]["properties"][1]["name"],
+            "cargo-contract:license-missing"
+        );
+    }
+}
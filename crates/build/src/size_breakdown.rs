@@ -0,0 +1,210 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::Write,
+};
+use wasmparser::{
+    Name,
+    NameSectionReader,
+    Parser,
+    Payload,
+    TypeRef,
+};
+
+/// The size of a single section or function of a Wasm binary.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SizeEntry {
+    /// The name of the section or function.
+    pub name: String,
+    /// The size in bytes.
+    pub size: usize,
+}
+
+/// Breakdown of the size of a Wasm binary by section and by function.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SizeBreakdown {
+    /// The sections of the binary, largest first.
+    pub sections: Vec<SizeEntry>,
+    /// The function bodies of the binary, largest first.
+    ///
+    /// Empty if the binary does not contain a name section, e.g. because the debug
+    /// symbols were stripped.
+    pub functions: Vec<SizeEntry>,
+}
+
+impl SizeBreakdown {
+    /// Computes the size breakdown of the given Wasm binary.
+    pub fn new(code: &[u8]) -> Result<Self> {
+        let mut sections = Vec::new();
+        let mut imported_functions = 0;
+        let mut body_sizes = Vec::new();
+        let mut function_names = HashMap::new();
+
+        for payload in Parser::new(0).parse_all(code) {
+            let payload = payload?;
+            if let Some((id, range)) = payload.as_section() {
+                let name = match &payload {
+                    Payload::CustomSection(reader) => {
+                        format!("custom \"{}\"", reader.name())
+                    }
+                    _ => section_name(id).to_string(),
+                };
+                sections.push(SizeEntry {
+                    name,
+                    size: range.len(),
+                });
+            }
+            match payload {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let TypeRef::Func(_) = import?.ty {
+                            imported_functions += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    body_sizes.push(body.range().len());
+                }
+                Payload::CustomSection(reader) if reader.name() == "name" => {
+                    let names =
+                        NameSectionReader::new(reader.data(), reader.data_offset());
+                    for name in names {
+                        if let Name::Function(map) = name? {
+                            for naming in map {
+                                let naming = naming?;
+                                function_names
+                                    .insert(naming.index, naming.name.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut functions: Vec<_> = body_sizes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, size)| {
+                let index = imported_functions + i as u32;
+                let name = function_names.remove(&index)?;
+                Some(SizeEntry { name, size })
+            })
+            .collect();
+
+        sections.sort_by_key(|s| Reverse(s.size));
+        functions.sort_by_key(|f| Reverse(f.size));
+        Ok(Self {
+            sections,
+            functions,
+        })
+    }
+
+    /// Displays the size breakdown as a table.
+    pub fn display(&self) -> String {
+        let mut out = format!("{}\n", "Size breakdown:".bold());
+        let _ = writeln!(out, "  {}", "Sections".bold());
+        for entry in &self.sections {
+            let _ = writeln!(out, "    {:>8}  {}", entry.size, entry.name);
+        }
+        if !self.functions.is_empty() {
+            let _ = writeln!(out, "  {}", "Functions".bold());
+            for entry in &self.functions {
+                let _ = writeln!(out, "    {:>8}  {}", entry.size, entry.name);
+            }
+        }
+        out
+    }
+}
+
+/// Returns the name of a known Wasm section.
+fn section_name(id: u8) -> &'static str {
+    match id {
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "data count",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn size_breakdown_lists_named_functions_by_size() {
+        // given
+        let contract = r#"
+            (module
+                (type (;0;) (func))
+                (import "seal" "foo" (func $foo (type 0)))
+                (func $small (type 0))
+                (func $large (type 0)
+                    call $foo
+                    call $foo
+                    call $foo)
+            )"#;
+        let module = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(contract)
+            .expect("Invalid wabt");
+
+        // when
+        let breakdown = SizeBreakdown::new(module.as_ref()).unwrap();
+
+        // then
+        let names: Vec<_> = breakdown.functions.iter().map(|f| &f.name).collect();
+        assert_eq!(names, ["large", "small"]);
+        assert!(breakdown.functions[0].size > breakdown.functions[1].size);
+        assert!(breakdown.sections.iter().any(|s| s.name == "code"));
+        assert!(breakdown
+            .sections
+            .iter()
+            .any(|s| s.name == "custom \"name\""));
+    }
+
+    #[test]
+    fn size_breakdown_without_names_has_no_functions() {
+        // given
+        let contract = r#"
+            (module
+                (type (;0;) (func))
+                (func (;0;) (type 0))
+            )"#;
+        let module = wabt::wat2wasm(contract).expect("Invalid wabt");
+
+        // when
+        let breakdown = SizeBreakdown::new(&module).unwrap();
+
+        // then
+        assert!(breakdown.functions.is_empty());
+        assert!(breakdown.sections.iter().any(|s| s.name == "code"));
+    }
+}
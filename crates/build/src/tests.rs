@@ -78,12 +78,15 @@ build_tests!(
     keep_debug_symbols_in_debug_mode,
     keep_debug_symbols_in_release_mode,
     build_with_json_output_works,
+    building_with_parallel_metadata_generation_works,
+    building_with_different_target_dirs_does_not_share_fingerprints,
     building_contract_with_source_file_in_subfolder_must_work,
     building_contract_with_build_rs_must_work,
     missing_linting_toolchain_installation_must_be_detected,
     generates_metadata,
     unchanged_contract_skips_optimization_and_metadata_steps,
-    unchanged_contract_no_metadata_artifacts_generates_metadata
+    unchanged_contract_no_metadata_artifacts_generates_metadata,
+    building_in_memory_matches_artifacts_on_disk
 );
 
 fn build_code_only(manifest_path: &ManifestPath) -> Result<()> {
@@ -381,6 +384,36 @@ fn build_with_json_output_works(manifest_path: &ManifestPath) -> Result<()> {
     Ok(())
 }
 
+fn building_with_parallel_metadata_generation_works(
+    manifest_path: &ManifestPath,
+) -> Result<()> {
+    // given
+    let args = ExecuteArgs {
+        manifest_path: manifest_path.clone(),
+        parallel: true,
+        extra_lints: false,
+        ..Default::default()
+    };
+
+    // when
+    let res = super::execute(args).expect("build failed");
+
+    // then
+    let dest_wasm = res.dest_wasm.expect("Wasm should be generated");
+    let dest_bundle = res
+        .metadata_result
+        .expect("Metadata should be generated")
+        .dest_bundle;
+    let metadata: ContractMetadata = serde_json::from_slice(&fs::read(dest_bundle)?)?;
+    let wasm = fs::read(dest_wasm)?;
+    assert_eq!(
+        metadata.source.hash,
+        CodeHash(crate::code_hash(&wasm)),
+        "The metadata should describe the optimized Wasm"
+    );
+    Ok(())
+}
+
 #[cfg(unix)]
 fn missing_linting_toolchain_installation_must_be_detected(
     manifest_path: &ManifestPath,
@@ -586,6 +619,41 @@ fn unchanged_contract_skips_optimization_and_metadata_steps(
     Ok(())
 }
 
+fn building_with_different_target_dirs_does_not_share_fingerprints(
+    manifest_path: &ManifestPath,
+) -> Result<()> {
+    // given
+    let target_dir1 = tempfile::tempdir()?;
+    let target_dir2 = tempfile::tempdir()?;
+    let build = |target_dir: &Path| {
+        super::execute(ExecuteArgs {
+            manifest_path: manifest_path.clone(),
+            build_mode: BuildMode::Release,
+            build_artifact: BuildArtifacts::CodeOnly,
+            target_dir: Some(target_dir.to_path_buf()),
+            ..Default::default()
+        })
+    };
+
+    // when
+    let res1 = build(target_dir1.path()).expect("build failed");
+    let res2 = build(target_dir2.path()).expect("build failed");
+
+    // then
+    assert!(res1.target_directory.starts_with(target_dir1.path()));
+    assert!(res2.target_directory.starts_with(target_dir2.path()));
+    assert!(res1.dest_wasm.unwrap().starts_with(target_dir1.path()));
+    assert!(res2.dest_wasm.unwrap().starts_with(target_dir2.path()));
+    assert!(
+        res2.optimization_result.is_some(),
+        "Build in a fresh target directory must not reuse the fingerprint of another"
+    );
+    assert!(res1.target_directory.join(".target").exists());
+    assert!(res2.target_directory.join(".target").exists());
+
+    Ok(())
+}
+
 fn unchanged_contract_no_metadata_artifacts_generates_metadata(
     manifest_path: &ManifestPath,
 ) -> Result<()> {
@@ -629,6 +697,34 @@ fn unchanged_contract_no_metadata_artifacts_generates_metadata(
     Ok(())
 }
 
+fn building_in_memory_matches_artifacts_on_disk(
+    manifest_path: &ManifestPath,
+) -> Result<()> {
+    // given
+    let args = || {
+        ExecuteArgs {
+            manifest_path: manifest_path.clone(),
+            build_artifact: BuildArtifacts::All,
+            ..Default::default()
+        }
+    };
+    let res = super::execute(args()).expect("build failed");
+
+    // when
+    let in_memory = super::execute_in_memory(args()).expect("in-memory build failed");
+
+    // then
+    assert_eq!(in_memory.code, fs::read(res.dest_wasm.unwrap())?);
+    assert_eq!(
+        in_memory.metadata,
+        Some(fs::read_to_string(
+            res.metadata_result.unwrap().dest_metadata
+        )?)
+    );
+
+    Ok(())
+}
+
 /// Get the last modified date of the given file.
 /// Panics if the file does not exist.
 fn file_last_modified(path: &Path) -> SystemTime {
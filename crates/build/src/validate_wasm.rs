@@ -17,6 +17,7 @@
 use anyhow::Result;
 use colored::Colorize;
 use impl_serde::serialize as serde_hex;
+use std::fmt;
 use wasmparser::{
     Parser,
     Payload,
@@ -72,6 +73,29 @@ pub enum EnforcedErrors {
     },
 }
 
+/// The errors found when validating the import section of the Wasm.
+#[derive(Debug)]
+pub(crate) struct WasmValidationError {
+    /// The human readable explanation of each error.
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for WasmValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Validation of the Wasm failed.\n\n\n{}\n\nIgnore with `--skip-wasm-validation`",
+            self.errors
+                .iter()
+                .map(|err| format!("{} {}", "ERROR:".to_string().bold(), err))
+                .collect::<Vec<String>>()
+                .join("\n\n\n")
+        )
+    }
+}
+
+impl std::error::Error for WasmValidationError {}
+
 /// Validates the import section in the Wasm.
 ///
 /// The checks currently fall into two categories:
@@ -123,13 +147,7 @@ pub fn validate_import_section(module: &[u8]) -> Result<()> {
         });
 
     if original_imports_len as usize != filtered_imports.count() {
-        anyhow::bail!(format!(
-            "Validation of the Wasm failed.\n\n\n{}\n\nIgnore with `--skip-wasm-validation`",
-            errs.into_iter()
-                .map(|err| format!("{} {}", "ERROR:".to_string().bold(), err))
-                .collect::<Vec<String>>()
-                .join("\n\n\n")
-        ));
+        return Err(WasmValidationError { errors: errs }.into())
     }
     Ok(())
 }
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::SizeBreakdown;
 use anyhow::Result;
 use wasm_opt::{
     Feature,
@@ -165,4 +166,7 @@ pub struct OptimizationResult {
     pub original_size: f64,
     /// The Wasm size after optimizations have been applied.
     pub optimized_size: f64,
+    /// The breakdown of the optimized Wasm size, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_breakdown: Option<SizeBreakdown>,
 }
@@ -124,9 +124,9 @@ impl Workspace {
     /// Write the amended manifest file to a temporary directory, then execute the
     /// supplied function with the temporary manifest path before the directory is
     /// cleaned up.
-    pub fn using_temp<F>(&mut self, f: F) -> Result<()>
+    pub fn using_temp<F, T>(&mut self, f: F) -> Result<T>
     where
-        F: FnOnce(&ManifestPath) -> Result<()>,
+        F: FnOnce(&ManifestPath) -> Result<T>,
     {
         let tmp_dir = tempfile::Builder::new()
             .prefix("cargo-contract_")
@@ -0,0 +1,464 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    call_with_config,
+    ErrorVariant,
+};
+
+use contract_build::util::DEFAULT_KEY_COL_WIDTH;
+use ink_env::Environment;
+use serde::Serialize;
+use std::{
+    fmt::{
+        Debug,
+        Display,
+    },
+    path::PathBuf,
+    str::FromStr,
+};
+
+use super::{
+    call::CallDryRunResult,
+    config::SignerConfig,
+    display_contract_exec_result,
+    display_dry_run_result_warning,
+    parse_account,
+    parse_balance,
+    print_dry_running_status,
+    print_gas_required_success,
+    prompt_confirm_tx,
+    write_signed_extrinsic,
+    CLIExtrinsicOpts,
+    MAX_KEY_COL_WIDTH,
+};
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use colored::Colorize;
+use contract_build::name_value_println;
+use contract_extrinsics::{
+    sign_batch_offline,
+    BalanceVariant,
+    BatchCommandBuilder,
+    BatchExec,
+    CallCommandBuilder,
+    DisplayEvents,
+    ExtrinsicOptsBuilder,
+};
+use sp_weights::Weight;
+use subxt::{
+    config::{
+        DefaultExtrinsicParams,
+        ExtrinsicParams,
+    },
+    ext::{
+        scale_decode::IntoVisitor,
+        scale_encode::EncodeAsType,
+    },
+    Config,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "batch",
+    about = "Call multiple contracts in a single extrinsic"
+)]
+pub struct BatchCommand {
+    /// Path to a JSON file listing the calls, e.g.
+    /// `[{ "contract": "5G...", "message": "flip", "args": [], "value": "0" }]`.
+    ///
+    /// Each call may set a `file` with the contract artifacts, otherwise the artifacts
+    /// of the command's contract are used. The `gas` and `proof_size` of a call are
+    /// estimated via a dry-run if not set, they are required for `--offline-output`.
+    #[clap(value_parser)]
+    calls: PathBuf,
+    #[clap(flatten)]
+    extrinsic_cli_opts: CLIExtrinsicOpts,
+    /// Revert all calls if any of them fails, using `utility.batch_all` instead of
+    /// `utility.batch`.
+    #[clap(long)]
+    atomic: bool,
+    /// Export the batch output in JSON format.
+    #[clap(long, conflicts_with = "verbose")]
+    output_json: bool,
+}
+
+/// A contract call as listed in the batch file.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchEntry {
+    contract: String,
+    message: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    file: Option<PathBuf>,
+    #[serde(default)]
+    gas: Option<u64>,
+    #[serde(default)]
+    proof_size: Option<u64>,
+}
+
+impl BatchCommand {
+    /// Returns whether to export the batch output in JSON format.
+    pub fn output_json(&self) -> bool {
+        self.output_json
+    }
+
+    pub async fn handle(&self) -> Result<(), ErrorVariant> {
+        call_with_config!(self, run, self.extrinsic_cli_opts.chain()?.config())
+    }
+
+    async fn run<C: Config + Environment + SignerConfig<C>>(
+        &self,
+    ) -> Result<(), ErrorVariant>
+    where
+        <C as Config>::AccountId: IntoVisitor + FromStr + EncodeAsType,
+        <<C as Config>::AccountId as FromStr>::Err: Display,
+        C::Balance:
+            From<u128> + Display + Default + FromStr + Serialize + Debug + EncodeAsType,
+        <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+            From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+        <C as Config>::Hash: From<[u8; 32]>,
+    {
+        let entries: Vec<BatchEntry> = serde_json::from_str(
+            &std::fs::read_to_string(&self.calls)
+                .with_context(|| format!("Failed to read {}", self.calls.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", self.calls.display()))?;
+
+        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri()?)
+            .map_err(|_| anyhow::anyhow!("Failed to parse suri option"))?;
+        let chain = self.extrinsic_cli_opts.chain()?;
+        let token_metadata = self
+            .extrinsic_cli_opts
+            .token_metadata::<C>(&chain.url())
+            .await?;
+        let storage_deposit_limit = self
+            .extrinsic_cli_opts
+            .storage_deposit_limit(&token_metadata)?;
+        let tip = self.extrinsic_cli_opts.tip(&token_metadata)?;
+        let mortal_checkpoint = self.extrinsic_cli_opts.mortal_checkpoint()?;
+
+        let mut call_builders = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.into_iter().enumerate() {
+            let contract = parse_account(&entry.contract).map_err(|e| {
+                anyhow::anyhow!("Failed to parse contract of call #{index}: {}", e)
+            })?;
+            let value = entry
+                .value
+                .map(|value| parse_balance(&value, &token_metadata))
+                .transpose()
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to parse value of call #{index}: {}", e)
+                })?
+                .unwrap_or_default();
+            // a call's own artifacts take precedence over the command's contract
+            let (file, manifest_path) = match entry.file {
+                Some(file) => (Some(file), None),
+                None => {
+                    (
+                        self.extrinsic_cli_opts.file.clone(),
+                        self.extrinsic_cli_opts.manifest_path.clone(),
+                    )
+                }
+            };
+            let extrinsic_opts = ExtrinsicOptsBuilder::new(signer.clone())
+                .file(file)
+                .manifest_path(manifest_path)
+                .url(chain.url())
+                .storage_deposit_limit(storage_deposit_limit)
+                .mortal(self.extrinsic_cli_opts.mortal)
+                .mortal_checkpoint(mortal_checkpoint)
+                .nonce(self.extrinsic_cli_opts.nonce)
+                .tip(tip)
+                .ss58_prefix(self.extrinsic_cli_opts.ss58_prefix())
+                .pallet_name(self.extrinsic_cli_opts.chain_cli_opts.pallet_name.clone())
+                .rpc_retries(self.extrinsic_cli_opts.rpc_retries)
+                .rpc_retry_delay(self.extrinsic_cli_opts.rpc_retry_delay())
+                .tx_timeout(self.extrinsic_cli_opts.tx_timeout())
+                .ws_keepalive(self.extrinsic_cli_opts.ws_keepalive())
+                .verbosity(self.extrinsic_cli_opts.verbosity()?)
+                .done();
+            let call_builder =
+                CallCommandBuilder::new(contract, &entry.message, extrinsic_opts)
+                    .args(entry.args)
+                    .value(value)
+                    .gas_limit(entry.gas)
+                    .proof_size(entry.proof_size);
+            call_builders.push(call_builder);
+        }
+
+        if let Some(offline_output) = &self.extrinsic_cli_opts.offline_output {
+            if !self.extrinsic_cli_opts.skip_dry_run {
+                return Err(anyhow!(
+                    "--offline-output requires --skip-dry-run and the `gas` and \
+                    `proof_size` of every call, the gas limits can not be estimated \
+                    without a connection to the node"
+                )
+                .into())
+            }
+            let client = self
+                .extrinsic_cli_opts
+                .offline_client::<C>(false, None, None)?;
+            let extrinsic = sign_batch_offline(&call_builders, self.atomic, &client)?;
+            write_signed_extrinsic(offline_output, &extrinsic)?;
+            return Ok(())
+        }
+        let mut calls = Vec::with_capacity(call_builders.len());
+        for call_builder in call_builders {
+            calls.push(call_builder.done().await?);
+        }
+        let batch_exec = BatchCommandBuilder::new(calls).atomic(self.atomic).done()?;
+        let metadata = batch_exec.calls()[0].client().metadata();
+
+        if !self.extrinsic_cli_opts.execute {
+            let results = batch_exec.calls_dry_run().await?;
+            let mut dry_run_results = Vec::with_capacity(results.len());
+            let mut gas_required = Weight::zero();
+            for (call_exec, result) in batch_exec.calls().iter().zip(&results) {
+                match result.result {
+                    Ok(ref ret_val) => {
+                        let value = call_exec
+                            .transcoder()
+                            .decode_message_return(
+                                call_exec.message(),
+                                &mut &ret_val.data[..],
+                            )
+                            .context(format!(
+                                "Failed to decode return value {:?}",
+                                &ret_val
+                            ))?;
+                        gas_required = gas_required.saturating_add(result.gas_required);
+                        dry_run_results.push(CallDryRunResult {
+                            reverted: ret_val.did_revert(),
+                            data: value,
+                            gas_consumed: result.gas_consumed,
+                            gas_required: result.gas_required,
+                            storage_deposit: result.storage_deposit.clone(),
+                        });
+                    }
+                    Err(ref err) => {
+                        let object = ErrorVariant::from_dispatch_error(
+                            err,
+                            &metadata,
+                            call_exec.opts().pallet_name(),
+                        )?;
+                        if self.output_json() {
+                            return Err(object)
+                        } else {
+                            name_value_println!(
+                                "Message",
+                                call_exec.message(),
+                                MAX_KEY_COL_WIDTH
+                            );
+                            name_value_println!("Result", object, MAX_KEY_COL_WIDTH);
+                            display_contract_exec_result::<_, MAX_KEY_COL_WIDTH, _>(
+                                result,
+                            )?;
+                            return Err(anyhow!("Dry-run of the batch failed").into())
+                        }
+                    }
+                }
+            }
+            let dry_run_result = BatchDryRunResult {
+                calls: dry_run_results,
+                gas_required,
+            };
+            if self.output_json() {
+                println!("{}", dry_run_result.to_json()?);
+            } else {
+                for (call_exec, result) in
+                    batch_exec.calls().iter().zip(&dry_run_result.calls)
+                {
+                    name_value_println!(
+                        "Message",
+                        call_exec.message(),
+                        DEFAULT_KEY_COL_WIDTH
+                    );
+                    result.print(false);
+                }
+                name_value_println!(
+                    "Gas required",
+                    gas_required.to_string(),
+                    DEFAULT_KEY_COL_WIDTH
+                );
+                eprintln!(
+                    "{} Calls are dry-run independently of each other, a call \
+                    depending on a previous call of the batch might yield a different \
+                    result.",
+                    "warning:".yellow().bold()
+                );
+                display_dry_run_result_warning("batch");
+            }
+        } else {
+            let gas_limits = self.pre_submit_dry_run_gas_estimate(&batch_exec).await?;
+            if !self.extrinsic_cli_opts.skip_confirm {
+                let tip_display =
+                    BalanceVariant::<u128>::from(tip, Some(&token_metadata))?;
+                prompt_confirm_tx(|| {
+                    for (call_exec, gas_limit) in
+                        batch_exec.calls().iter().zip(&gas_limits)
+                    {
+                        name_value_println!(
+                            "Message",
+                            call_exec.message(),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                        name_value_println!(
+                            "Args",
+                            call_exec.args().join(" "),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                        name_value_println!(
+                            "Gas limit",
+                            gas_limit.to_string(),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                    }
+                    name_value_println!(
+                        "Atomic",
+                        batch_exec.atomic().to_string(),
+                        DEFAULT_KEY_COL_WIDTH
+                    );
+                    if tip > 0 {
+                        name_value_println!(
+                            "Tip",
+                            tip_display.to_string(),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                    }
+                })?;
+            }
+            let events = batch_exec.batch(Some(gas_limits)).await?;
+            let display_events = DisplayEvents::from_events_with_transcoders::<C, C, _>(
+                &events,
+                |contract| batch_exec.transcoder_for(contract),
+                self.extrinsic_cli_opts.ss58_prefix(),
+                &metadata,
+                self.extrinsic_cli_opts.chain_cli_opts.pallet_name(),
+            )?;
+            let mut items = display_events.split_batch_items();
+            // events which are not emitted by any of the calls, e.g. the fee payment
+            let remaining = items.pop().expect("split always returns the remainder");
+
+            if self.output_json() {
+                let output = BatchEvents {
+                    calls: items,
+                    events: remaining,
+                };
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                let verbosity = self.extrinsic_cli_opts.verbosity()?;
+                for (call_exec, item) in batch_exec.calls().iter().zip(&items) {
+                    name_value_println!(
+                        "Message",
+                        call_exec.message(),
+                        DEFAULT_KEY_COL_WIDTH
+                    );
+                    println!("{}", item.display_events::<C>(verbosity, &token_metadata)?);
+                }
+                println!(
+                    "{}",
+                    remaining.display_events::<C>(verbosity, &token_metadata)?
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Estimates the gas required for each call of the batch.
+    async fn pre_submit_dry_run_gas_estimate<C: Config + Environment, Signer>(
+        &self,
+        batch_exec: &BatchExec<C, C, Signer>,
+    ) -> Result<Vec<Weight>>
+    where
+        Signer: subxt::tx::Signer<C> + Clone,
+        <C as Config>::AccountId: IntoVisitor + EncodeAsType,
+        C::Balance: EncodeAsType,
+        <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+            From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+    {
+        if self.extrinsic_cli_opts.skip_dry_run {
+            return Err(anyhow!(
+                "`--skip-dry-run` is not supported for batches, the gas limits of the calls are estimated via a dry-run"
+            ))
+        }
+        if !self.output_json() {
+            print_dry_running_status("batch");
+        }
+        let gas_limits = batch_exec.estimate_gas().await?;
+        if !self.output_json() {
+            let gas_required = gas_limits
+                .iter()
+                .fold(Weight::zero(), |total, gas| total.saturating_add(*gas));
+            print_gas_required_success(gas_required);
+        }
+        Ok(gas_limits)
+    }
+}
+
+/// Result of dry-running the calls of a batch.
+#[derive(serde::Serialize)]
+pub struct BatchDryRunResult<Balance> {
+    /// The results of the calls in the order of the batch.
+    pub calls: Vec<CallDryRunResult<Balance>>,
+    /// The sum of the gas required by all calls.
+    pub gas_required: Weight,
+}
+
+impl<Balance: Serialize> BatchDryRunResult<Balance> {
+    /// Returns a result in json format
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Events emitted by a submitted batch.
+#[derive(serde::Serialize)]
+struct BatchEvents {
+    /// The events of each completed call in the order of the batch.
+    calls: Vec<DisplayEvents>,
+    /// The events not belonging to any of the calls.
+    events: DisplayEvents,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_entry_weights_are_optional() {
+        let entries: Vec<BatchEntry> = serde_json::from_str(
+            r#"[
+                { "contract": "5G", "message": "flip" },
+                { "contract": "5G", "message": "flip", "gas": 5000, "proof_size": 6000 }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!((entries[0].gas, entries[0].proof_size), (None, None));
+        assert_eq!(
+            (entries[1].gas, entries[1].proof_size),
+            (Some(5000), Some(6000))
+        );
+    }
+}
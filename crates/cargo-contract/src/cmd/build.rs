@@ -14,14 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
-use anyhow::Result;
+use anyhow::{
+    Context,
+    Result,
+};
 use contract_build::{
     BuildArtifacts,
+    BuildError,
     BuildMode,
     BuildResult,
+    BuildStage,
+    DockerCache,
     ExecuteArgs,
     Features,
     ImageVariant,
+    LintLevels,
     ManifestPath,
     Network,
     OptimizationPasses,
@@ -34,7 +41,11 @@ use contract_build::{
 };
 use std::{
     convert::TryFrom,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::Command,
 };
 
 /// Executes build of the smart contract which produces a Wasm binary that is ready for
@@ -63,6 +74,8 @@ pub struct BuildCommand {
     /// Basic clippy lints are deemed important and run anyways.
     #[clap(long)]
     lint: bool,
+    #[clap(flatten)]
+    lint_levels: LintLevelFlags,
     /// Which build artifacts to generate.
     ///
     /// - `all`: Generate the Wasm, the metadata and a bundled `<name>.contract` file.
@@ -115,6 +128,14 @@ pub struct BuildCommand {
     /// Export the build output in JSON format.
     #[clap(long, conflicts_with = "verbose")]
     output_json: bool,
+    /// The format of the messages on a build failure.
+    ///
+    /// - `human`: Print the error to stderr.
+    ///
+    /// - `json`: Additionally print a JSON object with the failed stage of the build,
+    ///   the error and hints to its cause to stdout.
+    #[clap(long, value_enum, default_value = "human")]
+    message_format: MessageFormat,
     /// Don't perform wasm validation checks e.g. for permitted imports.
     #[clap(long)]
     skip_wasm_validation: bool,
@@ -124,17 +145,131 @@ pub struct BuildCommand {
     /// The maximum number of pages available for a wasm contract to allocate.
     #[clap(long, default_value_t = contract_build::DEFAULT_MAX_MEMORY_PAGES)]
     max_memory_pages: u64,
+    /// Fail the build if the optimized contract code is larger than this many bytes.
+    ///
+    /// If passed without a value, the limit of common `pallet-contracts` runtimes
+    /// (125952 bytes) is used. Not enforced if not passed.
+    #[clap(
+        long,
+        value_name = "BYTES",
+        num_args = 0..=1,
+        default_missing_value = "125952"
+    )]
+    max_code_size: Option<u64>,
+    /// Pass an additional flag to `rustc` when building the contract, e.g.
+    /// `--rustflag=-Cdebuginfo=1`. Can be repeated.
+    ///
+    /// This may break the cross compilation of the contract or the reproducibility of
+    /// the build.
+    #[clap(long = "rustflag", value_name = "FLAG", allow_hyphen_values = true)]
+    rustflags: Vec<String>,
+    /// Write the contract bundle compressed with zstd, as `<name>.contract.zst`.
+    #[clap(long)]
+    compress: bool,
     /// Executes the build inside a docker container to produce a verifiable bundle.
     /// Requires docker daemon running.
     #[clap(long, default_value_t = false)]
     verifiable: bool,
-    /// Specify a custom image for the verifiable build
+    /// Specify a custom image for the verifiable build, either by tag or pinned by its
+    /// digest, e.g. `useink/contracts-verifiable@sha256:<digest>`.
+    ///
+    /// The image is recorded in the metadata by its digest in both cases.
     #[clap(long, default_value = None)]
     image: Option<String>,
+    /// Directory to cache the crates downloaded by verifiable builds in, defaults to
+    /// the `docker-cache` folder of the target directory.
+    ///
+    /// The cache is keyed by the image digest and its Rust toolchain. Downloaded crates
+    /// are checked against the `Cargo.lock`, so the cache does not affect the result of
+    /// the build.
+    #[clap(long, value_parser)]
+    docker_cache_dir: Option<PathBuf>,
+    /// Download all crates again for the verifiable build instead of using the cache.
+    #[clap(long, conflicts_with = "docker_cache_dir")]
+    no_docker_cache: bool,
+    /// Generate the metadata concurrently with optimizing the Wasm, instead of after
+    /// it. Only has an effect if the contract code changed.
+    #[clap(long)]
+    parallel: bool,
+    /// Print a breakdown of the optimized Wasm size by section and by function. Only
+    /// available for Wasm builds.
+    ///
+    /// Function sizes are only available if the debug symbols are kept, see
+    /// `--keep-debug-symbols`.
+    #[clap(long)]
+    size_breakdown: bool,
+    /// Write a CycloneDX software bill of materials (`<name>.cdx.json`) of all crates
+    /// the contract is built from next to the contract bundle.
+    #[clap(long)]
+    sbom: bool,
+    /// The version of the ink! metadata ABI to generate, must match the version
+    /// produced by the ink! version of the contract.
+    #[clap(long)]
+    metadata_version: Option<u64>,
+    /// Directory for all generated artifacts.
+    ///
+    /// Takes precedence over the `CARGO_TARGET_DIR` environment variable.
+    #[clap(long, value_parser)]
+    target_dir: Option<PathBuf>,
+    /// A shell command to run after a successful build, the build fails if the command
+    /// fails.
+    ///
+    /// The paths of the build artifacts are passed to the command in the
+    /// `CONTRACT_BINARY`, `CONTRACT_METADATA`, `CONTRACT_BUNDLE` and `TARGET_DIR`
+    /// environment variables, unset if the artifact was not generated.
+    #[clap(long, value_name = "COMMAND")]
+    post_build: Option<String>,
+}
+
+/// The format of the messages on a build failure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// The machine readable description of a failed build.
+#[derive(Debug, serde::Serialize)]
+struct BuildFailure {
+    success: bool,
+    /// The stage of the build which failed, if known.
+    stage: Option<BuildStage>,
+    error: String,
+    hints: Vec<String>,
+}
+
+impl BuildFailure {
+    fn new(err: &anyhow::Error) -> Self {
+        let build_error = err.downcast_ref::<BuildError>();
+        Self {
+            success: false,
+            stage: build_error.map(BuildError::stage),
+            error: format!("{err:#}"),
+            hints: build_error.map(|e| e.hints().to_vec()).unwrap_or_default(),
+        }
+    }
 }
 
 impl BuildCommand {
     pub fn exec(&self) -> Result<BuildResult> {
+        if self.output_json || self.message_format == MessageFormat::Json {
+            // the JSON output must not contain any colour codes
+            colored::control::set_override(false);
+        }
+        let result = self.build().and_then(|build_result| {
+            if let Some(command) = &self.post_build {
+                run_post_build(command, &build_result)?;
+            }
+            Ok(build_result)
+        });
+        if let (Err(err), MessageFormat::Json) = (&result, self.message_format) {
+            println!("{}", serde_json::to_string_pretty(&BuildFailure::new(err))?);
+        }
+        result
+    }
+
+    fn build(&self) -> Result<BuildResult> {
         let manifest_path = ManifestPath::try_from(self.manifest_path.as_ref())?;
         let unstable_flags: UnstableFlags =
             TryFrom::<&UnstableOptions>::try_from(&self.unstable_options)?;
@@ -163,11 +298,34 @@ impl BuildCommand {
             anyhow::bail!("--image flag can only be used with verifiable builds!");
         }
 
+        if self.target_dir.is_some() && build_mode == BuildMode::Verifiable {
+            anyhow::bail!("--target-dir flag cannot be used with verifiable builds!");
+        }
+
+        if self.size_breakdown && !matches!(self.target, Target::Wasm) {
+            anyhow::bail!("--size-breakdown flag can only be used with Wasm builds!");
+        }
+
+        if (self.docker_cache_dir.is_some() || self.no_docker_cache)
+            && build_mode != BuildMode::Verifiable
+        {
+            anyhow::bail!(
+                "--docker-cache-dir and --no-docker-cache flags can only be used with \
+                verifiable builds!"
+            );
+        }
+
         let image = match &self.image {
             Some(i) => ImageVariant::Custom(i.clone()),
             None => ImageVariant::Default,
         };
 
+        let docker_cache = match (&self.docker_cache_dir, self.no_docker_cache) {
+            (_, true) => DockerCache::Disabled,
+            (Some(dir), false) => DockerCache::Custom(dir.clone()),
+            (None, false) => DockerCache::Default,
+        };
+
         let args = ExecuteArgs {
             manifest_path,
             verbosity,
@@ -179,11 +337,21 @@ impl BuildCommand {
             optimization_passes: self.optimization_passes,
             keep_debug_symbols: self.keep_debug_symbols,
             extra_lints: self.lint,
+            lint_levels: self.lint_levels.to_lint_levels(),
             output_type,
             skip_wasm_validation: self.skip_wasm_validation,
             target: self.target,
             max_memory_pages: self.max_memory_pages,
+            max_code_size: self.max_code_size,
+            rustflags: self.rustflags.clone(),
+            compress: self.compress,
             image,
+            docker_cache,
+            parallel: self.parallel,
+            size_breakdown: self.size_breakdown,
+            sbom: self.sbom,
+            metadata_version: self.metadata_version,
+            target_dir: self.target_dir.clone(),
         };
         contract_build::execute(args)
     }
@@ -195,10 +363,38 @@ pub struct CheckCommand {
     /// Path to the `Cargo.toml` of the contract to build
     #[clap(long, value_parser)]
     manifest_path: Option<PathBuf>,
+    /// Performs extra linting checks for ink! specific issues.
+    #[clap(long)]
+    lint: bool,
+    #[clap(flatten)]
+    lint_levels: LintLevelFlags,
+    #[clap(flatten)]
+    features: Features,
     #[clap(flatten)]
     verbosity: VerbosityFlags,
 }
 
+/// Overrides of the levels of individual ink! lints.
+#[derive(Debug, clap::Args)]
+pub struct LintLevelFlags {
+    /// Allow the ink! lint with the given name. Can be repeated.
+    #[clap(long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+    /// Deny the ink! lint with the given name, failing if it is triggered. Can be
+    /// repeated.
+    #[clap(long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+}
+
+impl LintLevelFlags {
+    pub fn to_lint_levels(&self) -> LintLevels {
+        LintLevels {
+            allow: self.allow.clone(),
+            deny: self.deny.clone(),
+        }
+    }
+}
+
 impl CheckCommand {
     pub fn exec(&self) -> Result<BuildResult> {
         let manifest_path = ManifestPath::try_from(self.manifest_path.as_ref())?;
@@ -208,20 +404,160 @@ impl CheckCommand {
             manifest_path,
             verbosity,
             build_mode: BuildMode::Debug,
-            features: Default::default(),
+            features: self.features.clone(),
             network: Network::default(),
             build_artifact: BuildArtifacts::CheckOnly,
             unstable_flags: Default::default(),
             optimization_passes: Some(OptimizationPasses::Zero),
             keep_debug_symbols: false,
-            extra_lints: false,
+            extra_lints: self.lint,
+            lint_levels: self.lint_levels.to_lint_levels(),
             output_type: OutputType::default(),
             skip_wasm_validation: false,
             target: Default::default(),
             max_memory_pages: 0,
+            max_code_size: None,
+            rustflags: Vec::new(),
+            compress: false,
             image: ImageVariant::Default,
+            docker_cache: DockerCache::Disabled,
+            parallel: false,
+            size_breakdown: false,
+            sbom: false,
+            metadata_version: None,
+            target_dir: None,
         };
 
         contract_build::execute(args)
     }
 }
+
+/// Runs the `--post-build` shell command with the paths of the build artifacts in its
+/// environment.
+///
+/// With JSON output the stdout of the command is redirected to stderr.
+fn run_post_build(command: &str, build_result: &BuildResult) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    if matches!(build_result.output_type, OutputType::Json) {
+        // keep stdout free for the JSON build result
+        cmd.stdout(std::io::stderr());
+    }
+    let status = cmd
+        .arg(command)
+        .envs(post_build_env(build_result))
+        .status()
+        .context(format!("Failed to run the post-build command `{command}`"))?;
+    anyhow::ensure!(
+        status.success(),
+        "The post-build command `{command}` failed with {status}"
+    );
+    Ok(())
+}
+
+/// The environment variables with the paths of the generated build artifacts.
+fn post_build_env(build_result: &BuildResult) -> Vec<(&'static str, &Path)> {
+    let metadata = build_result.metadata_result.as_ref();
+    [
+        ("CONTRACT_BINARY", build_result.dest_wasm.as_deref()),
+        (
+            "CONTRACT_METADATA",
+            metadata.map(|m| m.dest_metadata.as_path()),
+        ),
+        ("CONTRACT_BUNDLE", metadata.map(|m| m.dest_bundle.as_path())),
+        ("TARGET_DIR", Some(build_result.target_directory.as_path())),
+    ]
+    .into_iter()
+    .filter_map(|(name, path)| path.map(|path| (name, path)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_failure_without_stage() {
+        let err = anyhow::anyhow!("Manifest path does not exist");
+
+        let failure = serde_json::to_value(BuildFailure::new(&err)).unwrap();
+
+        assert_eq!(
+            failure,
+            serde_json::json!({
+                "success": false,
+                "stage": null,
+                "error": "Manifest path does not exist",
+                "hints": [],
+            })
+        );
+    }
+
+    #[test]
+    fn max_code_size_defaults_to_pallet_limit() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            build: BuildCommand,
+        }
+
+        let cli = <Cli as clap::Parser>::parse_from(["build", "--max-code-size"]);
+        assert_eq!(
+            cli.build.max_code_size,
+            Some(contract_build::DEFAULT_MAX_CODE_SIZE)
+        );
+
+        let cli = <Cli as clap::Parser>::parse_from(["build"]);
+        assert_eq!(cli.build.max_code_size, None);
+    }
+
+    fn build_result() -> BuildResult {
+        BuildResult {
+            dest_wasm: Some(PathBuf::from("/target/ink/flipper.wasm")),
+            metadata_result: None,
+            sbom_result: None,
+            target_directory: PathBuf::from("/target/ink"),
+            optimization_result: None,
+            build_mode: Default::default(),
+            build_artifact: BuildArtifacts::CodeOnly,
+            verbosity: Verbosity::Quiet,
+            image: None,
+            max_code_size: None,
+            output_type: OutputType::Json,
+        }
+    }
+
+    #[test]
+    fn post_build_env_contains_generated_artifacts() {
+        let build_result = build_result();
+
+        let env = post_build_env(&build_result);
+
+        assert_eq!(
+            env,
+            vec![
+                ("CONTRACT_BINARY", Path::new("/target/ink/flipper.wasm")),
+                ("TARGET_DIR", Path::new("/target/ink")),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn post_build_fails_on_non_zero_exit() {
+        let build_result = build_result();
+
+        assert!(run_post_build("test -n \"$CONTRACT_BINARY\"", &build_result).is_ok());
+        let err = run_post_build("exit 3", &build_result).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("The post-build command `exit 3` failed with"));
+    }
+}
@@ -27,19 +27,29 @@ use std::{
         Debug,
         Display,
     },
+    path::PathBuf,
     str::FromStr,
+    time::Duration,
 };
 
 use super::{
     config::SignerConfig,
+    deployment::contract_or_last_deployment,
     display_contract_exec_result,
     display_contract_exec_result_debug,
     display_dry_run_result_warning,
+    display_gas_limit,
+    fiat::FiatCliOpts,
+    load_json_args,
     parse_account,
     parse_balance,
     print_dry_running_status,
     print_gas_required_success,
     prompt_confirm_tx,
+    prompt_missing_args,
+    weight_with_overrides,
+    write_dry_run_json,
+    write_signed_extrinsic,
     CLIExtrinsicOpts,
     MAX_KEY_COL_WIDTH,
 };
@@ -50,12 +60,13 @@ use anyhow::{
 };
 use contract_build::name_value_println;
 use contract_extrinsics::{
+    csv_row,
     pallet_contracts_primitives::StorageDeposit,
+    BalanceVariant,
     CallCommandBuilder,
     CallExec,
     DisplayEvents,
     ExtrinsicOptsBuilder,
-    TokenMetadata,
 };
 use contract_transcode::Value;
 use sp_weights::Weight;
@@ -74,35 +85,84 @@ use subxt::{
 #[derive(Debug, clap::Args)]
 #[clap(name = "call", about = "Call a contract")]
 pub struct CallCommand {
-    /// The address of the the contract to call.
+    /// The address of the the contract to call. Defaults to the contract last
+    /// instantiated from this project on the same node.
     #[clap(name = "contract", long, env = "CONTRACT")]
-    contract: String,
+    contract: Option<String>,
     /// The name of the contract message to call.
     #[clap(long, short)]
     message: String,
     /// The arguments of the contract message to call.
     #[clap(long, num_args = 0..)]
     args: Vec<String>,
+    /// Path to a JSON file with the arguments of the contract message to call, either
+    /// as an array or as an object keyed by the argument names.
+    #[clap(long, value_parser, conflicts_with = "args")]
+    args_json: Option<PathBuf>,
+    /// Allow omitting fields of struct arguments, which are then set to the default
+    /// value of their type, e.g. `0` for numbers or `None` for options.
+    #[clap(long, conflicts_with = "args_json")]
+    allow_default_fields: bool,
+    /// Prompt for the arguments which are not given via `--args`, validating each of
+    /// them against the type of its parameter.
+    #[clap(long, conflicts_with = "args_json")]
+    interactive: bool,
     #[clap(flatten)]
     extrinsic_cli_opts: CLIExtrinsicOpts,
+    #[clap(flatten)]
+    fiat_cli_opts: FiatCliOpts,
     /// Maximum amount of gas (execution time) to be used for this command.
     /// If not specified will perform a dry-run to estimate the gas consumed for the
     /// call.
-    #[clap(name = "gas", long)]
+    #[clap(name = "gas", long, alias = "gas-limit")]
     gas_limit: Option<u64>,
     /// Maximum proof size for this call.
     /// If not specified will perform a dry-run to estimate the proof size required for
     /// the call.
     #[clap(long)]
     proof_size: Option<u64>,
-    /// The value to be transferred as part of the call.
+    /// The value to be transferred as part of the call, e.g. `1.5DOT` or `100mUNIT`.
     #[clap(name = "value", long, default_value = "0")]
     value: String,
     /// Export the call output in JSON format.
     #[clap(long, conflicts_with = "verbose")]
     output_json: bool,
+    /// Export the events emitted by the call in CSV format, one row per event.
+    #[clap(long, conflicts_with_all = ["verbose", "output_json"])]
+    output_csv: bool,
+    /// After the call has been executed, keep watching new finalized blocks and print
+    /// the events emitted by the contract until interrupted.
+    #[clap(long, requires = "execute")]
+    subscribe: bool,
+    /// Stop watching for events after the given number of seconds.
+    #[clap(long, requires = "subscribe")]
+    subscribe_timeout: Option<u64>,
+    /// Only query the message with a dry-run via RPC, without requiring a signing key.
+    ///
+    /// The dry-run is performed with a well-known development account as the caller.
+    #[clap(
+        long,
+        conflicts_with_all = ["execute", "offline_output", "suri", "suri_file"]
+    )]
+    rpc_only: bool,
+    /// Display the decoded return value of a dry-run indented over multiple lines.
+    #[clap(long, conflicts_with = "output_json")]
+    pretty: bool,
+    /// Write the complete result of the dry-run, including the debug message, the
+    /// decoded return value and the emitted events, as JSON to the given file.
+    #[clap(long, value_parser, conflicts_with_all = ["execute", "offline_output"])]
+    dry_run_json: Option<PathBuf>,
+    /// Print the debug message of a successful dry-run, e.g. the output of
+    /// `ink::env::debug_println!` in contracts built in debug mode. It is always printed
+    /// if the dry-run failed or reverted.
+    #[clap(long, conflicts_with = "execute")]
+    show_debug: bool,
 }
 
+/// The secret key URI of the caller for `--rpc-only` dry-runs. It is never used to
+/// sign an extrinsic.
+const RPC_ONLY_CALLER_SURI: &str = "//Alice";
+
 impl CallCommand {
     /// Returns whether to export the call output in JSON format.
     pub fn output_json(&self) -> bool {
@@ -110,11 +170,7 @@ impl CallCommand {
     }
 
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(
-            self,
-            run,
-            self.extrinsic_cli_opts.chain_cli_opts.chain().config()
-        )
+        call_with_config!(self, run, self.extrinsic_cli_opts.chain()?.config())
     }
 
     async fn run<C: Config + Environment + SignerConfig<C>>(
@@ -127,22 +183,30 @@ impl CallCommand {
             From<u128> + Display + Default + FromStr + Serialize + Debug + EncodeAsType,
         <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
             From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+        <C as Config>::Hash: From<[u8; 32]>,
     {
-        let contract = parse_account(&self.contract)
+        let chain = self.extrinsic_cli_opts.chain()?;
+        let contract = contract_or_last_deployment(
+            self.contract.as_ref(),
+            self.extrinsic_cli_opts.manifest_path.as_ref(),
+            &chain.url(),
+        )?;
+        let contract = parse_account(&contract)
             .map_err(|e| anyhow::anyhow!("Failed to parse contract option: {}", e))?;
-        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri)
+        let suri = if self.rpc_only {
+            RPC_ONLY_CALLER_SURI.to_string()
+        } else {
+            self.extrinsic_cli_opts.suri()?
+        };
+        let signer = C::Signer::from_str(&suri)
             .map_err(|_| anyhow::anyhow!("Failed to parse suri option"))?;
-        let chain = self.extrinsic_cli_opts.chain_cli_opts.chain();
-        let token_metadata = TokenMetadata::query::<C>(&chain.url()).await?;
+        let token_metadata = self
+            .extrinsic_cli_opts
+            .token_metadata::<C>(&chain.url())
+            .await?;
         let storage_deposit_limit = self
             .extrinsic_cli_opts
-            .storage_deposit_limit
-            .clone()
-            .map(|b| parse_balance(&b, &token_metadata))
-            .transpose()
-            .map_err(|e| {
-                anyhow::anyhow!("Failed to parse storage_deposit_limit option: {}", e)
-            })?;
+            .storage_deposit_limit(&token_metadata)?;
         let value = parse_balance(&self.value, &token_metadata)
             .map_err(|e| anyhow::anyhow!("Failed to parse value option: {}", e))?;
         let extrinsic_opts = ExtrinsicOptsBuilder::new(signer)
@@ -150,32 +214,71 @@ impl CallCommand {
             .manifest_path(self.extrinsic_cli_opts.manifest_path.clone())
             .url(chain.url())
             .storage_deposit_limit(storage_deposit_limit)
+            .mortal(self.extrinsic_cli_opts.mortal)
+            .mortal_checkpoint(self.extrinsic_cli_opts.mortal_checkpoint()?)
+            .nonce(self.extrinsic_cli_opts.nonce)
+            .tip(self.extrinsic_cli_opts.tip(&token_metadata)?)
+            .ss58_prefix(self.extrinsic_cli_opts.ss58_prefix())
+            .pallet_name(self.extrinsic_cli_opts.chain_cli_opts.pallet_name.clone())
+            .rpc_retries(self.extrinsic_cli_opts.rpc_retries)
+            .rpc_retry_delay(self.extrinsic_cli_opts.rpc_retry_delay())
+            .tx_timeout(self.extrinsic_cli_opts.tx_timeout())
+            .ws_keepalive(self.extrinsic_cli_opts.ws_keepalive())
             .verbosity(self.extrinsic_cli_opts.verbosity()?)
             .done();
 
-        let call_exec = CallCommandBuilder::new(contract, &self.message, extrinsic_opts)
-            .args(self.args.clone())
-            .gas_limit(self.gas_limit)
-            .proof_size(self.proof_size)
-            .value(value)
-            .done()
-            .await?;
+        let args = if self.interactive {
+            let transcoder =
+                extrinsic_opts.contract_artifacts()?.contract_transcoder()?;
+            prompt_missing_args(&transcoder, &self.message, &self.args)?
+        } else {
+            self.args.clone()
+        };
+        let call_builder =
+            CallCommandBuilder::new(contract, &self.message, extrinsic_opts)
+                .args(args)
+                .args_json(self.args_json.as_deref().map(load_json_args).transpose()?)
+                .allow_default_fields(self.allow_default_fields)
+                .gas_limit(self.gas_limit)
+                .proof_size(self.proof_size)
+                .value(value);
+
+        if let Some(offline_output) = &self.extrinsic_cli_opts.offline_output {
+            let client = self.extrinsic_cli_opts.offline_client::<C>(
+                true,
+                self.gas_limit,
+                self.proof_size,
+            )?;
+            let extrinsic = call_builder.sign_offline(&client)?;
+            write_signed_extrinsic(offline_output, &extrinsic)?;
+            return Ok(())
+        }
+        let call_exec = call_builder.done().await?;
         let metadata = call_exec.client().metadata();
 
         if !self.extrinsic_cli_opts.execute {
-            let result = call_exec.call_dry_run().await?;
+            let (result, events) = call_exec.call_dry_run_with_events().await?;
+            let decoded_return = result.result.as_ref().ok().map(|ret_val| {
+                call_exec
+                    .transcoder()
+                    .decode_message_return(call_exec.message(), &mut &ret_val.data[..])
+                    .context(format!("Failed to decode return value {:?}", &ret_val))
+            });
+            // the result is written even if the return value fails to decode
+            if let Some(path) = &self.dry_run_json {
+                write_dry_run_json(
+                    path,
+                    &result,
+                    decoded_return
+                        .as_ref()
+                        .and_then(|value| value.as_ref().ok()),
+                    events.as_ref(),
+                )?;
+            }
             match result.result {
                 Ok(ref ret_val) => {
-                    let value = call_exec
-                        .transcoder()
-                        .decode_message_return(
-                            call_exec.message(),
-                            &mut &ret_val.data[..],
-                        )
-                        .context(format!(
-                            "Failed to decode return value {:?}",
-                            &ret_val
-                        ))?;
+                    let value = decoded_return
+                        .expect("the return value is decoded for a successful dry-run")?;
                     let dry_run_result = CallDryRunResult {
                         reverted: ret_val.did_revert(),
                         data: value,
@@ -186,15 +289,36 @@ impl CallCommand {
                     if self.output_json() {
                         println!("{}", dry_run_result.to_json()?);
                     } else {
-                        dry_run_result.print();
-                        display_contract_exec_result_debug::<_, DEFAULT_KEY_COL_WIDTH, _>(
-                            &result,
-                        )?;
-                        display_dry_run_result_warning("message");
+                        dry_run_result.print(self.pretty);
+                        if self.show_debug || dry_run_result.reverted {
+                            display_contract_exec_result_debug::<
+                                _,
+                                DEFAULT_KEY_COL_WIDTH,
+                                _,
+                            >(&result)?;
+                        }
+                        if !self.rpc_only {
+                            display_dry_run_result_warning("message");
+                        }
+                        if self.fiat_cli_opts.enabled() {
+                            let gas_limit = weight_with_overrides(
+                                result.gas_required,
+                                call_exec.gas_limit(),
+                                call_exec.proof_size(),
+                            );
+                            let fee = call_exec.estimate_fee(Some(gas_limit)).await;
+                            self.fiat_cli_opts
+                                .print_fee_estimate(fee, &token_metadata)
+                                .await;
+                        }
                     };
                 }
                 Err(ref err) => {
-                    let object = ErrorVariant::from_dispatch_error(err, &metadata)?;
+                    let object = ErrorVariant::from_dispatch_error(
+                        err,
+                        &metadata,
+                        call_exec.opts().pallet_name(),
+                    )?;
                     if self.output_json() {
                         return Err(object)
                     } else {
@@ -206,11 +330,20 @@ impl CallCommand {
         } else {
             let gas_limit = pre_submit_dry_run_gas_estimate_call(
                 &call_exec,
-                self.output_json(),
+                self.output_json() || self.output_csv,
                 self.extrinsic_cli_opts.skip_dry_run,
             )
             .await?;
+            if self.fiat_cli_opts.enabled() && !(self.output_json() || self.output_csv) {
+                let fee = call_exec.estimate_fee(Some(gas_limit)).await;
+                self.fiat_cli_opts
+                    .print_fee_estimate(fee, &token_metadata)
+                    .await;
+            }
             if !self.extrinsic_cli_opts.skip_confirm {
+                let tip = call_exec.opts().tip();
+                let tip_display =
+                    BalanceVariant::<u128>::from(tip, Some(&token_metadata))?;
                 prompt_confirm_tx(|| {
                     name_value_println!(
                         "Message",
@@ -224,17 +357,34 @@ impl CallCommand {
                     );
                     name_value_println!(
                         "Gas limit",
-                        gas_limit.to_string(),
+                        display_gas_limit(
+                            gas_limit,
+                            call_exec.gas_limit(),
+                            call_exec.proof_size()
+                        ),
                         DEFAULT_KEY_COL_WIDTH
                     );
+                    if tip > 0 {
+                        name_value_println!(
+                            "Tip",
+                            tip_display.to_string(),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                    }
                 })?;
             }
             let events = call_exec.call(Some(gas_limit)).await?;
-            let display_events =
-                DisplayEvents::from_events::<C, C>(&events, None, &metadata)?;
+            let display_events = DisplayEvents::from_events::<C, C>(
+                &events,
+                None,
+                &metadata,
+                call_exec.opts().pallet_name(),
+            )?;
 
             let output = if self.output_json() {
                 display_events.to_json()?
+            } else if self.output_csv {
+                display_events.to_csv(&format!("{:?}", events.block_hash()))?
             } else {
                 display_events.display_events::<C>(
                     self.extrinsic_cli_opts.verbosity().unwrap(),
@@ -242,6 +392,42 @@ impl CallCommand {
                 )?
             };
             println!("{output}");
+
+            if self.subscribe {
+                let timeout = self.subscribe_timeout.map(Duration::from_secs);
+                call_exec
+                    .subscribe_events(events.block_hash(), timeout, |event| {
+                        if self.output_json() {
+                            match serde_json::to_string(&event.value) {
+                                Ok(json) => println!("{json}"),
+                                Err(err) => tracing::warn!("{err}"),
+                            }
+                        } else if self.output_csv {
+                            match serde_json::to_string(&event.value) {
+                                Ok(fields) => {
+                                    println!(
+                                        "{}",
+                                        csv_row(
+                                            &format!("{:?}", event.block_hash),
+                                            event.index,
+                                            call_exec.opts().pallet_name(),
+                                            "ContractEmitted",
+                                            &fields,
+                                        )
+                                    )
+                                }
+                                Err(err) => tracing::warn!("{err}"),
+                            }
+                        } else {
+                            name_value_println!(
+                                "Event",
+                                event.value.to_string(),
+                                DEFAULT_KEY_COL_WIDTH
+                            );
+                        }
+                    })
+                    .await?;
+            }
         }
         Ok(())
     }
@@ -280,17 +466,18 @@ where
                 print_gas_required_success(call_result.gas_required);
             }
             // use user specified values where provided, otherwise use the estimates
-            let ref_time = call_exec
-                .gas_limit()
-                .unwrap_or_else(|| call_result.gas_required.ref_time());
-            let proof_size = call_exec
-                .proof_size()
-                .unwrap_or_else(|| call_result.gas_required.proof_size());
-            Ok(Weight::from_parts(ref_time, proof_size))
+            Ok(weight_with_overrides(
+                call_result.gas_required,
+                call_exec.gas_limit(),
+                call_exec.proof_size(),
+            ))
         }
         Err(ref err) => {
-            let object =
-                ErrorVariant::from_dispatch_error(err, &call_exec.client().metadata())?;
+            let object = ErrorVariant::from_dispatch_error(
+                err,
+                &call_exec.client().metadata(),
+                call_exec.opts().pallet_name(),
+            )?;
             if output_json {
                 Err(anyhow!("{}", serde_json::to_string_pretty(&object)?))
             } else {
@@ -321,8 +508,19 @@ impl<Balance: Serialize> CallDryRunResult<Balance> {
         Ok(serde_json::to_string_pretty(self)?)
     }
 
-    pub fn print(&self) {
-        name_value_println!("Result", format!("{}", self.data), DEFAULT_KEY_COL_WIDTH);
+    /// Prints the result, with the decoded return value indented over multiple lines if
+    /// `pretty`.
+    pub fn print(&self, pretty: bool) {
+        let data = if pretty {
+            // align the following lines with the first line of the value
+            format!("{:#}", self.data).replace(
+                '\n',
+                &format!("\n{:width$} ", "", width = DEFAULT_KEY_COL_WIDTH),
+            )
+        } else {
+            format!("{}", self.data)
+        };
+        name_value_println!("Result", data, DEFAULT_KEY_COL_WIDTH);
         name_value_println!(
             "Reverted",
             format!("{:?}", self.reverted),
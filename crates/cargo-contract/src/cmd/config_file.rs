@@ -0,0 +1,230 @@
+// Copyright (C) Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use serde::Deserialize;
+
+/// The name of the file default values for the command line options are read from, in
+/// the directory of the contract or one of its parents and in the home directory.
+const CONFIG_FILE_NAME: &str = ".cargo-contract.toml";
+
+/// Default values for the command line options, read from a TOML file, e.g.
+///
+/// ```toml
+/// url = "wss://rpc.shibuya.astar.network:443"
+/// config = "Polkadot"
+/// storage-deposit-limit = "1.5SBY"
+/// ```
+///
+/// Options given on the command line take precedence. The chain is selected by each
+/// file as a whole: if a file sets any of `url`, `config` and `chain`, none of them are
+/// taken from a file with lower precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Websockets url of a Substrate node.
+    pub url: Option<url::Url>,
+    /// Chain config to be used as part of the call.
+    pub config: Option<String>,
+    /// Name of a production chain to be communicated with.
+    pub chain: Option<String>,
+    /// The maximum amount of balance that can be charged for the storage.
+    pub storage_deposit_limit: Option<String>,
+}
+
+impl ConfigFile {
+    /// Loads the default values, from the given file or otherwise from the
+    /// `.cargo-contract.toml` closest to the contract, falling back to the one in the
+    /// home directory for values not set there.
+    ///
+    /// The closest file is searched for in the directory of the `manifest_path`, or the
+    /// current directory if not given, and then in its parents up to e.g. the workspace
+    /// root.
+    pub fn load(
+        config_file: Option<&Path>,
+        manifest_path: Option<&Path>,
+    ) -> Result<Self> {
+        let project_file = match config_file {
+            Some(config_file) => Some(Self::read(config_file)?),
+            None => {
+                match Self::find_project_file(manifest_path)? {
+                    Some(path) => Some(Self::read(&path)?),
+                    None => None,
+                }
+            }
+        };
+        let home_file = match std::env::var_os("HOME") {
+            Some(home) => {
+                Self::read_if_exists(&PathBuf::from(home).join(CONFIG_FILE_NAME))?
+            }
+            None => None,
+        };
+        Ok(project_file
+            .unwrap_or_default()
+            .or(home_file.unwrap_or_default()))
+    }
+
+    /// Returns the values of `self`, taking the values not set from `fallback`.
+    ///
+    /// The chain is only taken from `fallback` if `self` sets none of `url`, `config`
+    /// and `chain`, so that e.g. the `url` of `self` is not combined with the `chain` of
+    /// `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        let selects_chain =
+            self.url.is_some() || self.config.is_some() || self.chain.is_some();
+        let (url, config, chain) = if selects_chain {
+            (self.url, self.config, self.chain)
+        } else {
+            (fallback.url, fallback.config, fallback.chain)
+        };
+        Self {
+            url,
+            config,
+            chain,
+            storage_deposit_limit: self
+                .storage_deposit_limit
+                .or(fallback.storage_deposit_limit),
+        }
+    }
+
+    /// Returns the path of the `.cargo-contract.toml` closest to the contract, if any.
+    fn find_project_file(manifest_path: Option<&Path>) -> Result<Option<PathBuf>> {
+        let current_dir = std::env::current_dir()?;
+        let dir = match manifest_path.and_then(Path::parent) {
+            Some(manifest_dir) => current_dir.join(manifest_dir),
+            None => current_dir,
+        };
+        Ok(dir
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file()))
+    }
+
+    fn read_if_exists(path: &Path) -> Result<Option<Self>> {
+        if path.exists() {
+            Self::read(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_file_takes_precedence_over_home_file() {
+        let project_file: ConfigFile = toml::from_str(
+            r#"
+            url = "wss://project.example:443"
+            "#,
+        )
+        .unwrap();
+        let home_file: ConfigFile = toml::from_str(
+            r#"
+            url = "wss://home.example:443"
+            config = "Substrate"
+            storage-deposit-limit = "1DOT"
+            "#,
+        )
+        .unwrap();
+
+        let config_file = project_file.or(home_file);
+        assert_eq!(
+            config_file,
+            ConfigFile {
+                url: Some("wss://project.example:443".parse().unwrap()),
+                config: None,
+                chain: None,
+                storage_deposit_limit: Some("1DOT".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn chain_is_taken_from_home_file_if_not_set_in_project_file() {
+        let project_file: ConfigFile = toml::from_str(
+            r#"
+            storage-deposit-limit = "2DOT"
+            "#,
+        )
+        .unwrap();
+        let home_file: ConfigFile = toml::from_str(
+            r#"
+            chain = "astar"
+            storage-deposit-limit = "1DOT"
+            "#,
+        )
+        .unwrap();
+
+        let config_file = project_file.or(home_file);
+        assert_eq!(config_file.chain.as_deref(), Some("astar"));
+        assert_eq!(config_file.storage_deposit_limit.as_deref(), Some("2DOT"));
+    }
+
+    #[test]
+    fn project_file_is_found_next_to_the_manifest_or_in_a_parent() {
+        let workspace = tempfile::tempdir().unwrap();
+        let contract_dir = workspace.path().join("contracts").join("flipper");
+        std::fs::create_dir_all(&contract_dir).unwrap();
+        let manifest_path = contract_dir.join("Cargo.toml");
+        std::fs::write(
+            workspace.path().join(CONFIG_FILE_NAME),
+            r#"url = "wss://workspace.example:443""#,
+        )
+        .unwrap();
+
+        let config_file = ConfigFile::load(None, Some(&manifest_path)).unwrap();
+        assert_eq!(
+            config_file.url,
+            Some("wss://workspace.example:443".parse().unwrap())
+        );
+
+        std::fs::write(
+            contract_dir.join(CONFIG_FILE_NAME),
+            r#"url = "wss://contract.example:443""#,
+        )
+        .unwrap();
+
+        let config_file = ConfigFile::load(None, Some(&manifest_path)).unwrap();
+        assert_eq!(
+            config_file.url,
+            Some("wss://contract.example:443".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let result = toml::from_str::<ConfigFile>(r#"suri = "//Alice""#);
+        assert!(result.is_err());
+    }
+}
@@ -28,12 +28,23 @@ use contract_build::{
     util,
     CrateMetadata,
 };
-use contract_transcode::ContractMessageTranscoder;
+use contract_transcode::{
+    ContractMessageTranscoder,
+    RegistryDisplay,
+};
+use std::path::PathBuf;
 
 #[derive(Debug, Args)]
 pub struct DecodeCommand {
     #[clap(subcommand)]
     commands: DecodeCommands,
+    /// Export the decoded data in JSON format.
+    #[clap(long, global = true)]
+    output_json: bool,
+    /// Truncate byte sequences longer than the given number of bytes to their first
+    /// and last bytes in the human readable output. The JSON output is always complete.
+    #[clap(long, global = true)]
+    max_hex_len: Option<usize>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -47,6 +58,13 @@ pub enum DecodeCommands {
     /// Decode an event as input
     #[clap(name = "event")]
     Event(DecodeEvent),
+    /// Print the type registry of the contract metadata, with the id, path and
+    /// definition of every type
+    #[clap(name = "types")]
+    Types(DecodeTypes),
+    /// Decode data as the type with the given path in the contract metadata
+    #[clap(name = "value")]
+    Value(DecodeValue),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -67,30 +85,70 @@ pub struct DecodeConstructor {
 pub struct DecodeEvent {
     /// The signature topic of the event to be decoded; this has to be a hex value
     /// starting with `0x`.
+    #[clap(short, long, required_unless_present = "index")]
+    signature_topic: Option<String>,
+    /// The index of the event in the contract metadata, for decoding anonymous events
+    /// which have no signature topic.
+    #[clap(long, conflicts_with = "signature_topic")]
+    index: Option<usize>,
+    /// The data to decode; this has to be a hex value starting with `0x`.
     #[clap(short, long)]
-    signature_topic: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct DecodeValue {
+    /// The path of the type in the contract metadata, e.g.
+    /// `ink_primitives::types::AccountId`, or the name of a primitive type, e.g. `u128`.
+    #[clap(long = "type")]
+    type_path: String,
     /// The data to decode; this has to be a hex value starting with `0x`.
     #[clap(short, long)]
     data: String,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct DecodeTypes {
+    /// Path to the contract metadata file, by default the metadata of the contract in
+    /// the current directory is used.
+    #[clap(value_parser)]
+    metadata: Option<PathBuf>,
+}
+
 impl DecodeCommand {
     pub fn run(&self) -> Result<()> {
-        let crate_metadata =
-            CrateMetadata::from_manifest_path(None, contract_build::Target::Wasm)?;
-        let transcoder = ContractMessageTranscoder::load(crate_metadata.metadata_path())?;
+        let metadata_path = match &self.commands {
+            DecodeCommands::Types(DecodeTypes {
+                metadata: Some(metadata),
+            }) => metadata.clone(),
+            _ => {
+                CrateMetadata::from_manifest_path(None, contract_build::Target::Wasm)?
+                    .metadata_path()
+            }
+        };
+        let transcoder = ContractMessageTranscoder::load(metadata_path)?;
 
         const ERR_MSG: &str = "Failed to decode specified data as a hex value";
         let decoded_data = match &self.commands {
             DecodeCommands::Event(event) => {
-                let signature_topic_data =
-                    util::decode_hex(&event.signature_topic).context(ERR_MSG)?;
-                let signature_topic =
-                    primitive_types::H256::from_slice(&signature_topic_data);
-                transcoder.decode_contract_event(
-                    &signature_topic,
-                    &mut &util::decode_hex(&event.data).context(ERR_MSG)?[..],
-                )?
+                let data = util::decode_hex(&event.data).context(ERR_MSG)?;
+                match (&event.signature_topic, event.index) {
+                    (Some(signature_topic), _) => {
+                        let signature_topic_data =
+                            util::decode_hex(signature_topic).context(ERR_MSG)?;
+                        let signature_topic =
+                            primitive_types::H256::from_slice(&signature_topic_data);
+                        transcoder
+                            .decode_contract_event(&signature_topic, &mut &data[..])?
+                    }
+                    (None, Some(index)) => {
+                        transcoder
+                            .decode_contract_event_by_index(index, &mut &data[..])?
+                    }
+                    (None, None) => {
+                        anyhow::bail!("Either --signature-topic or --index is required")
+                    }
+                }
             }
             DecodeCommands::Message(message) => {
                 transcoder.decode_contract_message(
@@ -102,14 +160,37 @@ impl DecodeCommand {
                     &mut &util::decode_hex(&constructor.data).context(ERR_MSG)?[..],
                 )?
             }
+            DecodeCommands::Value(value) => {
+                transcoder.decode_type_path(
+                    &value.type_path,
+                    &mut &util::decode_hex(&value.data).context(ERR_MSG)?[..],
+                )?
+            }
+            DecodeCommands::Types(_) => {
+                let registry = transcoder.metadata().registry();
+                if self.output_json {
+                    println!("{}", serde_json::to_string_pretty(registry)?);
+                } else {
+                    print!("{}", RegistryDisplay(registry));
+                }
+                return Ok(())
+            }
         };
 
-        println!(
-            "{:>width$} {}",
-            "Decoded data:".bright_green().bold(),
-            decoded_data,
-            width = DEFAULT_KEY_COL_WIDTH
-        );
+        if self.output_json {
+            println!("{}", serde_json::to_string_pretty(&decoded_data.to_json())?);
+        } else {
+            let decoded_data = match self.max_hex_len {
+                Some(max_len) => decoded_data.display_truncated(max_len).to_string(),
+                None => decoded_data.to_string(),
+            };
+            println!(
+                "{:>width$} {}",
+                "Decoded data:".bright_green().bold(),
+                decoded_data,
+                width = DEFAULT_KEY_COL_WIDTH
+            );
+        }
 
         Ok(())
     }
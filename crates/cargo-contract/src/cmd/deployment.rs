@@ -0,0 +1,167 @@
+// Copyright (C) Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use colored::Colorize;
+use contract_build::{
+    CrateMetadata,
+    Target,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use url::Url;
+
+/// The file in the target directory of a contract project the address of the last
+/// instantiated contract is saved to.
+const LAST_DEPLOYMENT_FILE: &str = ".cargo-contract/last-deployment.json";
+
+/// The last instantiated contract of a project.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LastDeployment {
+    contract: String,
+    /// The url of the node the contract was instantiated on.
+    url: String,
+}
+
+/// Saves the address of a contract instantiated on the node at `url` as the last
+/// deployment of the project with the given manifest.
+pub fn save_last_deployment(
+    manifest_path: Option<&PathBuf>,
+    contract: &str,
+    url: &Url,
+) -> Result<()> {
+    let crate_metadata = CrateMetadata::from_manifest_path(manifest_path, Target::Wasm)?;
+    let deployment = LastDeployment {
+        contract: contract.to_string(),
+        url: url.to_string(),
+    };
+    write_last_deployment(&crate_metadata.target_directory, &deployment)
+}
+
+/// Returns the given contract address, or if there is none the address of the last
+/// contract instantiated from the project with the given manifest.
+///
+/// The last deployment is only used if it was instantiated on the node at `url`, the
+/// address is most likely meaningless on any other chain.
+pub fn contract_or_last_deployment(
+    contract: Option<&String>,
+    manifest_path: Option<&PathBuf>,
+    url: &Url,
+) -> Result<String> {
+    if let Some(contract) = contract {
+        return Ok(contract.clone())
+    }
+    let crate_metadata = CrateMetadata::from_manifest_path(manifest_path, Target::Wasm)
+        .context(
+        "No --contract given, and no contract project to look up the last \
+            deployed contract address in",
+    )?;
+    last_deployment_on(&crate_metadata.target_directory, url)
+}
+
+/// Returns the address of the last contract instantiated on the node at `url`.
+fn last_deployment_on(target_directory: &Path, url: &Url) -> Result<String> {
+    let deployment = read_last_deployment(target_directory)?.context(
+        "No --contract given, and no contract of this project was instantiated yet",
+    )?;
+    if deployment.url != url.as_str() {
+        anyhow::bail!(
+            "No --contract given, and the last deployed contract {} of this project \
+            was instantiated on {}, not on {}",
+            deployment.contract,
+            deployment.url,
+            url
+        )
+    }
+    eprintln!(
+        "{} using last deployed address {}",
+        "info:".green().bold(),
+        deployment.contract
+    );
+    Ok(deployment.contract)
+}
+
+fn write_last_deployment(
+    target_directory: &Path,
+    deployment: &LastDeployment,
+) -> Result<()> {
+    let path = target_directory.join(LAST_DEPLOYMENT_FILE);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(deployment)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn read_last_deployment(target_directory: &Path) -> Result<Option<LastDeployment>> {
+    let path = target_directory.join(LAST_DEPLOYMENT_FILE);
+    if !path.exists() {
+        return Ok(None)
+    }
+    let deployment = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let deployment: LastDeployment = serde_json::from_str(&deployment)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(deployment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_deployment_roundtrip() -> Result<()> {
+        let target_dir = tempfile::tempdir()?;
+        assert_eq!(read_last_deployment(target_dir.path())?, None);
+
+        let deployment = LastDeployment {
+            contract: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            url: "ws://localhost:9944/".to_string(),
+        };
+        write_last_deployment(target_dir.path(), &deployment)?;
+        assert_eq!(read_last_deployment(target_dir.path())?, Some(deployment));
+        Ok(())
+    }
+
+    #[test]
+    fn last_deployment_is_only_used_on_the_same_node() -> Result<()> {
+        let target_dir = tempfile::tempdir()?;
+        let contract = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let local: Url = "ws://localhost:9944".parse()?;
+        let deployment = LastDeployment {
+            contract: contract.to_string(),
+            url: local.to_string(),
+        };
+        write_last_deployment(target_dir.path(), &deployment)?;
+
+        assert_eq!(last_deployment_on(target_dir.path(), &local)?, contract);
+        let remote: Url = "wss://rococo-contracts-rpc.polkadot.io".parse()?;
+        let err = last_deployment_on(target_dir.path(), &remote).unwrap_err();
+        assert!(err.to_string().contains("not on"), "{err}");
+        Ok(())
+    }
+}
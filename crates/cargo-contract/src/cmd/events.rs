@@ -0,0 +1,159 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    call_with_config,
+    ErrorVariant,
+};
+use anyhow::Result;
+use colored::Colorize;
+use contract_build::{
+    Verbosity,
+    VerbosityFlags,
+};
+use contract_extrinsics::{
+    BlockEvents,
+    ContractArtifacts,
+    DisplayEvents,
+    TokenMetadata,
+};
+use ink_env::Environment;
+use std::{
+    fmt::Display,
+    path::PathBuf,
+    str::FromStr,
+};
+use subxt::{
+    ext::scale_decode::IntoVisitor,
+    Config,
+};
+
+use super::{
+    parse_account,
+    CLIChainOpts,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "events",
+    about = "Decode the events a contract emitted in a block"
+)]
+pub struct EventsCommand {
+    /// The number or `0x` prefixed hash of the block to fetch the events from.
+    #[clap(long)]
+    block: String,
+    /// The address of the contract to decode the events of.
+    #[clap(name = "contract", long, env = "CONTRACT")]
+    contract: String,
+    /// Path to a contract build artifact file: a raw `.wasm` file, a `.contract` bundle,
+    /// or a `.json` metadata file.
+    #[clap(value_parser, conflicts_with = "manifest_path")]
+    file: Option<PathBuf>,
+    /// Path to the `Cargo.toml` of the contract.
+    #[clap(long, value_parser)]
+    manifest_path: Option<PathBuf>,
+    /// Export the events in JSON format.
+    #[clap(name = "output-json", long, conflicts_with = "verbose")]
+    output_json: bool,
+    /// Export the events in CSV format, one row per event.
+    #[clap(long, conflicts_with_all = ["verbose", "output_json"])]
+    output_csv: bool,
+    #[clap(flatten)]
+    verbosity: VerbosityFlags,
+    /// Arguments required for communicating with a Substrate node.
+    #[clap(flatten)]
+    chain_cli_opts: CLIChainOpts,
+}
+
+impl EventsCommand {
+    pub async fn handle(&self) -> Result<(), ErrorVariant> {
+        call_with_config!(
+            self,
+            run,
+            self.chain_cli_opts
+                .chain(self.manifest_path.as_deref())?
+                .config()
+        )
+    }
+
+    async fn run<C: Config + Environment>(&self) -> Result<(), ErrorVariant>
+    where
+        <C as Config>::AccountId: IntoVisitor + FromStr + PartialEq,
+        <<C as Config>::AccountId as FromStr>::Err: Display,
+        C::Balance: From<u128> + Display,
+    {
+        let contract = parse_account(&self.contract)
+            .map_err(|e| anyhow::anyhow!("Failed to parse contract option: {}", e))?;
+        let verbosity = Verbosity::try_from(&self.verbosity)?;
+        // without metadata the event data is displayed as raw hex
+        let transcoder = ContractArtifacts::from_manifest_or_file(
+            self.manifest_path.as_ref(),
+            self.file.as_ref(),
+        )
+        .and_then(|artifacts| artifacts.contract_transcoder())
+        .map_err(|err| {
+            if !self.output_json && !self.output_csv {
+                eprintln!(
+                    "{} Displaying raw event data: no valid contract metadata \
+                    artifacts found: {err}",
+                    "Info:".cyan().bold(),
+                );
+            }
+        })
+        .ok();
+
+        let url = self
+            .chain_cli_opts
+            .chain(self.manifest_path.as_deref())?
+            .url();
+        let block_events = BlockEvents::<C>::fetch(
+            &url,
+            &self.block,
+            &self.chain_cli_opts.rpc_connection(),
+        )
+        .await?;
+        let display_events = DisplayEvents::from_block_events::<C, C>(
+            &block_events,
+            &contract,
+            transcoder.as_ref(),
+            self.chain_cli_opts.pallet_name(),
+        )?;
+
+        if self.output_json {
+            println!("{}", display_events.to_json()?);
+        } else if self.output_csv {
+            print!(
+                "{}",
+                display_events.to_csv(&format!("{:?}", block_events.hash()))?
+            );
+        } else if display_events.is_empty() {
+            println!(
+                "No events of contract {} in block {:?}",
+                self.contract,
+                block_events.hash()
+            );
+        } else {
+            let token_metadata =
+                TokenMetadata::query::<C>(&url, &self.chain_cli_opts.rpc_connection())
+                    .await?;
+            println!(
+                "{}",
+                display_events.display_events::<C>(verbosity, &token_metadata)?
+            );
+        }
+        Ok(())
+    }
+}
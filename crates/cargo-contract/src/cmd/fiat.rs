@@ -0,0 +1,174 @@
+// Copyright (C) Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use anyhow::{
+    Context,
+    Result,
+};
+use contract_build::{
+    name_value_println,
+    util::DEFAULT_KEY_COL_WIDTH,
+};
+use contract_extrinsics::{
+    BalanceVariant,
+    ErrorVariant,
+    TokenMetadata,
+};
+
+/// The default endpoint for looking up the price of the chain's token.
+const DEFAULT_PRICE_URL: &str =
+    "https://min-api.cryptocompare.com/data/price?fsym={symbol}&tsyms={currency}";
+
+/// Time after which the price lookup is abandoned.
+const PRICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Arguments for estimating the transaction fee in a fiat currency.
+#[derive(Debug, clap::Args)]
+pub struct FiatCliOpts {
+    /// Print an estimate of the transaction fee in the given fiat currency, e.g. `USD`.
+    #[clap(long)]
+    fiat: Option<String>,
+    /// The endpoint used to look up the price of the chain's token for `--fiat`.
+    ///
+    /// The `{symbol}` and `{currency}` placeholders are replaced by the token symbol
+    /// and the fiat currency. The endpoint has to respond with a JSON object holding
+    /// the price under the currency key, e.g. `{"USD": 4.2}`.
+    #[clap(long, env = "CONTRACT_FIAT_PRICE_URL", default_value = DEFAULT_PRICE_URL)]
+    fiat_price_url: String,
+}
+
+impl FiatCliOpts {
+    /// Returns whether an estimate of the fee in a fiat currency was requested.
+    pub fn enabled(&self) -> bool {
+        self.fiat.is_some()
+    }
+
+    /// Print the estimated fee in the chain's token and in the fiat currency.
+    ///
+    /// This never fails: if either the fee or the price is not available,
+    /// "fiat estimate unavailable" is printed instead, so that submitting the
+    /// extrinsic is not blocked.
+    pub async fn print_fee_estimate(
+        &self,
+        fee: Result<u128, ErrorVariant>,
+        token_metadata: &TokenMetadata,
+    ) {
+        let Some(currency) = &self.fiat else { return };
+        let fee = match fee {
+            Ok(fee) => fee,
+            Err(err) => {
+                tracing::debug!("Failed to estimate the fee: {err}");
+                name_value_println!(
+                    "Fee estimate",
+                    "fiat estimate unavailable",
+                    DEFAULT_KEY_COL_WIDTH
+                );
+                return
+            }
+        };
+        let fee_display = BalanceVariant::<u128>::from(fee, Some(token_metadata))
+            .map(|balance| balance.to_string())
+            .unwrap_or_else(|_| fee.to_string());
+        let fiat = match self.query_price(&token_metadata.symbol, currency).await {
+            Ok(price) => {
+                format!(
+                    "\u{2248} {:.2} {}",
+                    fiat_value(fee, token_metadata.token_decimals, price),
+                    currency.to_uppercase()
+                )
+            }
+            Err(err) => {
+                tracing::debug!("Failed to query the {currency} price: {err:?}");
+                "fiat estimate unavailable".to_string()
+            }
+        };
+        name_value_println!(
+            "Fee estimate",
+            format!("{fee_display} ({fiat})"),
+            DEFAULT_KEY_COL_WIDTH
+        );
+    }
+
+    /// Query the price of one token in the fiat currency from the price endpoint.
+    async fn query_price(&self, symbol: &str, currency: &str) -> Result<f64> {
+        let url = self
+            .fiat_price_url
+            .replace("{symbol}", symbol)
+            .replace("{currency}", currency);
+        let client = reqwest::Client::builder()
+            .timeout(PRICE_REQUEST_TIMEOUT)
+            .build()?;
+        let response: serde_json::Value = client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        find_price(&response, currency)
+            .with_context(|| format!("No {currency} price found in response of {url}"))
+    }
+}
+
+/// Find the price under the currency key, which is matched case-insensitively.
+///
+/// Nested objects are searched as well, to support endpoints which group the prices
+/// by token, e.g. `{"polkadot": {"usd": 4.2}}`.
+fn find_price(value: &serde_json::Value, currency: &str) -> Option<f64> {
+    let object = value.as_object()?;
+    object
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(currency))
+        .and_then(|(_, price)| price.as_f64())
+        .or_else(|| {
+            object
+                .values()
+                .find_map(|value| find_price(value, currency))
+        })
+}
+
+/// Convert the fee, given in the smallest unit of the token, into the fiat currency.
+fn fiat_value(fee: u128, token_decimals: usize, price: f64) -> f64 {
+    fee as f64 / 10f64.powi(token_decimals as i32) * price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn find_price_works() {
+        assert_eq!(find_price(&json!({ "USD": 4.2 }), "usd"), Some(4.2));
+        assert_eq!(
+            find_price(&json!({ "polkadot": { "eur": 3.9 } }), "EUR"),
+            Some(3.9)
+        );
+        assert_eq!(find_price(&json!({ "USD": 4.2 }), "EUR"), None);
+        assert_eq!(find_price(&json!({ "USD": "n/a" }), "USD"), None);
+        assert_eq!(find_price(&json!([4.2]), "USD"), None);
+    }
+
+    #[test]
+    fn fiat_value_works() {
+        // 0.1 tokens with 10 decimals at 4.2 per token
+        let value = fiat_value(1_000_000_000, 10, 4.2);
+        assert!((value - 0.42).abs() < f64::EPSILON);
+        assert_eq!(fiat_value(0, 12, 4.2), 0.0);
+    }
+}
@@ -18,12 +18,21 @@ use crate::call_with_config;
 
 use super::{
     basic_display_format_extended_contract_info,
+    deployment::contract_or_last_deployment,
     display_all_contracts,
     parse_account,
     CLIChainOpts,
 };
 use anyhow::Result;
-use contract_analyze::determine_language;
+use colored::Colorize;
+use comfy_table::{
+    ContentArrangement,
+    Table,
+};
+use contract_analyze::{
+    determine_language,
+    Module,
+};
 use contract_extrinsics::{
     fetch_all_contracts,
     fetch_contract_info,
@@ -33,8 +42,16 @@ use contract_extrinsics::{
     ErrorVariant,
     TrieId,
 };
+use futures::{
+    stream,
+    StreamExt,
+    TryStreamExt,
+};
 use ink_env::Environment;
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::{
     fmt::{
         Debug,
@@ -44,10 +61,7 @@ use std::{
     str::FromStr,
 };
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     ext::{
         codec::Decode,
         scale_decode::IntoVisitor,
@@ -59,13 +73,9 @@ use subxt::{
 #[derive(Debug, clap::Args)]
 #[clap(name = "info", about = "Get infos from a contract")]
 pub struct InfoCommand {
-    /// The address of the contract to display info of.
-    #[clap(
-        name = "contract",
-        long,
-        env = "CONTRACT",
-        required_unless_present = "all"
-    )]
+    /// The address of the contract to display info of. Defaults to the contract last
+    /// instantiated from the project in the current directory on the same node.
+    #[clap(name = "contract", long, env = "CONTRACT")]
     contract: Option<String>,
     /// Export the instantiate output in JSON format.
     #[clap(name = "output-json", long)]
@@ -76,6 +86,12 @@ pub struct InfoCommand {
     /// Display all contracts addresses
     #[clap(name = "all", long)]
     all: bool,
+    /// Display the code hash and source language of each contract listed with `--all`.
+    #[clap(long, requires = "all")]
+    detailed: bool,
+    /// Maximum number of contracts listed with `--all`.
+    #[clap(long, requires = "all")]
+    limit: Option<u32>,
     /// Arguments required for communicating with a Substrate node.
     #[clap(flatten)]
     chain_cli_opts: CLIChainOpts,
@@ -83,7 +99,7 @@ pub struct InfoCommand {
 
 impl InfoCommand {
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(self, run, self.chain_cli_opts.chain().config())
+        call_with_config!(self, run, self.chain_cli_opts.chain(None)?.config())
     }
 
     pub async fn run<C: Config + Environment>(&self) -> Result<(), ErrorVariant>
@@ -91,21 +107,34 @@ impl InfoCommand {
         <C as Config>::AccountId:
             Serialize + Display + IntoVisitor + Decode + AsRef<[u8]> + FromStr,
         <C as Config>::Hash: IntoVisitor + Display,
-        <C as Environment>::Balance: Serialize + Debug + IntoVisitor,
+        <C as Environment>::Balance: Serialize + Debug + Display + IntoVisitor,
         <<C as Config>::AccountId as FromStr>::Err:
             Into<Box<(dyn std::error::Error)>> + Display,
     {
-        let rpc_cli =
-            RpcClient::from_url(url_to_string(&self.chain_cli_opts.chain().url()))
-                .await?;
+        let url = self.chain_cli_opts.chain(None)?.url();
+        let rpc_cli = self
+            .chain_cli_opts
+            .rpc_connection()
+            .connect(&url_to_string(&url))
+            .await?;
         let client = OnlineClient::<C>::from_rpc_client(rpc_cli.clone()).await?;
         let rpc = LegacyRpcMethods::<C>::new(rpc_cli.clone());
+        let pallet_name = self.chain_cli_opts.pallet_name();
 
         // All flag applied
         if self.all {
-            let contracts = fetch_all_contracts(&client, &rpc).await?;
+            let contracts = fetch_all_contracts(&rpc, pallet_name, self.limit).await?;
 
-            if self.output_json {
+            if self.detailed {
+                let summaries =
+                    fetch_contract_summaries::<C>(&contracts, &client, &rpc, pallet_name)
+                        .await?;
+                if self.output_json {
+                    println!("{}", serde_json::to_string_pretty(&summaries)?);
+                } else {
+                    ContractSummaryTable::new(&summaries).display();
+                }
+            } else if self.output_json {
                 let contracts_json = serde_json::json!({
                     "contracts": contracts
                 });
@@ -115,28 +144,31 @@ impl InfoCommand {
             }
             Ok(())
         } else {
-            // Contract arg shall be always present in this case, it is enforced by
-            // clap configuration
-            let contract = self
-                .contract
-                .as_ref()
-                .map(|c| parse_account(c))
-                .transpose()?
-                .expect("Contract argument shall be present");
+            let contract =
+                contract_or_last_deployment(self.contract.as_ref(), None, &url)?;
+            let contract = parse_account(&contract)?;
 
             let info_to_json =
-                fetch_contract_info::<C, C>(&contract, &rpc, &client).await?;
+                fetch_contract_info::<C, C>(&contract, &rpc, &client, pallet_name)
+                    .await?;
 
             let wasm_code =
-                fetch_wasm_code(&client, &rpc, info_to_json.code_hash()).await?;
+                fetch_wasm_code(&client, &rpc, info_to_json.code_hash(), pallet_name)
+                    .await?;
             // Binary flag applied
             if self.binary {
+                let analysis = CodeAnalysis::new(&wasm_code);
                 if self.output_json {
                     let wasm = serde_json::json!({
-                        "wasm": format!("0x{}", hex::encode(wasm_code))
+                        "wasm": format!("0x{}", hex::encode(&wasm_code)),
+                        "source_language": analysis.source_language,
+                        "imports": analysis.imports,
                     });
                     println!("{}", serde_json::to_string_pretty(&wasm)?);
                 } else {
+                    // The binary is written to stdout, so the analysis goes to stderr
+                    // in order to keep the output usable for piping.
+                    analysis.display();
                     std::io::stdout()
                         .write_all(&wasm_code)
                         .expect("Writing to stdout failed")
@@ -164,16 +196,180 @@ impl InfoCommand {
     }
 }
 
+/// Maximum number of contracts whose info and code are fetched concurrently.
+const MAX_CONCURRENT_LOOKUPS: usize = 16;
+
+/// The code hash and source language of a contract.
 #[derive(serde::Serialize)]
+struct ContractSummary {
+    address: String,
+    code_hash: String,
+    language: Option<String>,
+}
+
+/// Fetch the code hash and code of every contract, in order to determine its source
+/// language.
+///
+/// The lookups run concurrently, with at most [`MAX_CONCURRENT_LOOKUPS`] in flight.
+async fn fetch_contract_summaries<C: Config + Environment>(
+    contracts: &[<C as Config>::AccountId],
+    client: &OnlineClient<C>,
+    rpc: &LegacyRpcMethods<C>,
+    pallet_name: &str,
+) -> Result<Vec<ContractSummary>>
+where
+    <C as Config>::AccountId: Display + IntoVisitor + AsRef<[u8]>,
+    <C as Config>::Hash: IntoVisitor + Display,
+    C::Balance: IntoVisitor + Serialize,
+{
+    stream::iter(contracts)
+        .map(|contract| {
+            async move {
+                let info =
+                    fetch_contract_info::<C, C>(contract, rpc, client, pallet_name)
+                        .await?;
+                let code =
+                    fetch_wasm_code(client, rpc, info.code_hash(), pallet_name).await?;
+                anyhow::Ok(ContractSummary {
+                    address: contract.to_string(),
+                    code_hash: format!("{:?}", info.code_hash()),
+                    language: determine_language(&code).ok().map(|l| l.to_string()),
+                })
+            }
+        })
+        .buffered(MAX_CONCURRENT_LOOKUPS)
+        .try_collect()
+        .await
+}
+
+struct ContractSummaryTable(Table);
+
+impl ContractSummaryTable {
+    const ADDRESS_LABEL: &'static str = "Contract";
+    const CODE_HASH_LABEL: &'static str = "Code Hash";
+    const LANGUAGE_LABEL: &'static str = "Language";
+
+    fn new(summaries: &[ContractSummary]) -> Self {
+        let mut table = Table::new();
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec![
+            Self::ADDRESS_LABEL,
+            Self::CODE_HASH_LABEL,
+            Self::LANGUAGE_LABEL,
+        ]);
+        for summary in summaries {
+            table.add_row(vec![
+                summary.address.as_str(),
+                summary.code_hash.as_str(),
+                summary.language.as_deref().unwrap_or("Unknown"),
+            ]);
+        }
+        Self(table)
+    }
+
+    fn display(&self) {
+        println!("{}", self.0);
+    }
+}
+
+/// The results of analyzing the on-chain code of a contract.
+struct CodeAnalysis {
+    /// The detected source language, if recognized.
+    source_language: Option<String>,
+    /// The functions imported by the code, as `module::name`.
+    imports: Vec<String>,
+}
+
+impl CodeAnalysis {
+    fn new(code: &[u8]) -> Self {
+        let source_language = determine_language(code).ok().map(|l| l.to_string());
+        let imports = Module::new(code)
+            .map(|module| {
+                module
+                    .import_sections
+                    .iter()
+                    .map(|import| format!("{}::{}", import.module, import.name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            source_language,
+            imports,
+        }
+    }
+
+    fn display(&self) {
+        match &self.source_language {
+            Some(language) => {
+                eprintln!("{} {}", "Source language:".bright_purple().bold(), language)
+            }
+            None => {
+                eprintln!(
+                    "{} Language unsupported or unrecognized.",
+                    "Note:".bright_purple().bold()
+                )
+            }
+        }
+        if !self.imports.is_empty() {
+            eprintln!("{}", "Imports:".bright_purple().bold());
+            for import in &self.imports {
+                eprintln!("  {import}");
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "Hash: Serialize, Balance: Display",
+    deserialize = "Hash: Deserialize<'de>, Balance: FromStr, <Balance as FromStr>::Err: Display"
+))]
 pub struct ExtendedContractInfo<Hash, Balance> {
     pub trie_id: TrieId,
     pub code_hash: Hash,
     pub storage_items: u32,
+    /// Balances are serialized as strings, since JSON numbers cannot represent every
+    /// `u128` without losing precision.
+    #[serde(with = "balance_as_string")]
     pub storage_items_deposit: Balance,
+    #[serde(with = "balance_as_string")]
     pub storage_total_deposit: Balance,
     pub source_language: String,
 }
 
+mod balance_as_string {
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+    use std::{
+        fmt::Display,
+        str::FromStr,
+    };
+
+    pub fn serialize<S, Balance>(
+        balance: &Balance,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        Balance: Display,
+    {
+        serializer.collect_str(balance)
+    }
+
+    pub fn deserialize<'de, D, Balance>(deserializer: D) -> Result<Balance, D::Error>
+    where
+        D: Deserializer<'de>,
+        Balance: FromStr,
+        <Balance as FromStr>::Err: Display,
+    {
+        let balance = String::deserialize(deserializer)?;
+        balance.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl<Hash, Balance> ExtendedContractInfo<Hash, Balance>
 where
     Hash: serde::Serialize + Copy,
@@ -194,3 +390,30 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::utils::H256;
+
+    #[test]
+    fn extended_contract_info_json_roundtrip() {
+        let info = ExtendedContractInfo::<H256, u128> {
+            trie_id: vec![1, 2, 3, 4].into(),
+            code_hash: H256::from([7u8; 32]),
+            storage_items: 3,
+            storage_items_deposit: 1_000_000_000_000,
+            storage_total_deposit: u128::MAX,
+            source_language: "ink! 5.0.0".to_string(),
+        };
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["trie_id"], "0x01020304");
+        assert_eq!(json["storage_items_deposit"], "1000000000000");
+        assert_eq!(json["storage_total_deposit"], u128::MAX.to_string());
+
+        let decoded: ExtendedContractInfo<H256, u128> =
+            serde_json::from_value(json).unwrap();
+        assert_eq!(info, decoded);
+    }
+}
@@ -16,25 +16,39 @@
 
 use super::{
     config::SignerConfig,
+    deployment::save_last_deployment,
     display_contract_exec_result,
     display_contract_exec_result_debug,
     display_dry_run_result_warning,
+    display_gas_limit,
+    display_multisig_result,
+    fiat::FiatCliOpts,
+    load_json_args,
     parse_balance,
+    parse_multisig,
     print_dry_running_status,
     print_gas_required_success,
     prompt_confirm_tx,
+    prompt_missing_args,
+    weight_with_overrides,
+    write_dry_run_json,
+    write_signed_extrinsic,
     CLIExtrinsicOpts,
     MAX_KEY_COL_WIDTH,
 };
 use crate::{
     anyhow,
     call_with_config,
-    cmd::prompt_confirm_unverifiable_upload,
+    cmd::{
+        check_debug_build_for_production,
+        prompt_confirm_unverifiable_upload,
+    },
     ErrorVariant,
     InstantiateExec,
     Weight,
 };
 use anyhow::Result;
+use colored::Colorize;
 use contract_build::{
     name_value_println,
     util::{
@@ -44,6 +58,7 @@ use contract_build::{
     Verbosity,
 };
 use contract_extrinsics::{
+    BalanceVariant,
     Code,
     DisplayEvents,
     ExtrinsicOptsBuilder,
@@ -60,6 +75,7 @@ use std::{
         Debug,
         Display,
     },
+    path::PathBuf,
     str::FromStr,
 };
 use subxt::{
@@ -83,15 +99,55 @@ pub struct InstantiateCommand {
     /// The constructor arguments, encoded as strings
     #[clap(long, num_args = 0..)]
     args: Vec<String>,
+    /// Path to a JSON file with the constructor arguments, either as an array or as an
+    /// object keyed by the argument names.
+    #[clap(long, value_parser, conflicts_with = "args")]
+    args_json: Option<PathBuf>,
+    /// Submit the extrinsic as one of the signatories of a multisig account, given as
+    /// `<threshold>:<signatory>,<signatory>,...`. The call is dispatched from the
+    /// multisig account once enough signatories submitted the same command.
+    ///
+    /// Requires `--gas`, `--proof-size` and `--storage-deposit-limit`, so that all
+    /// signatories approve the same call instead of their own dry-run estimates.
+    #[clap(
+        long,
+        requires_all = ["execute", "gas", "proof_size", "storage_deposit_limit"],
+        conflicts_with = "offline_output"
+    )]
+    multisig: Option<String>,
+    /// Allow omitting fields of struct arguments, which are then set to the default
+    /// value of their type, e.g. `0` for numbers or `None` for options.
+    #[clap(long, conflicts_with = "args_json")]
+    allow_default_fields: bool,
+    /// Prompt for the arguments which are not given via `--args`, validating each of
+    /// them against the type of its parameter.
+    #[clap(long, conflicts_with = "args_json")]
+    interactive: bool,
+    /// Write the complete result of the dry-run, including the debug message, the
+    /// decoded return value and the emitted events, as JSON to the given file.
+    #[clap(long, value_parser, conflicts_with_all = ["execute", "offline_output"])]
+    dry_run_json: Option<PathBuf>,
+    /// Print the debug message of a successful dry-run, e.g. the output of
+    /// `ink::env::debug_println!` in contracts built in debug mode. It is always printed
+    /// if the dry-run failed or reverted.
+    #[clap(long, conflicts_with = "execute")]
+    show_debug: bool,
+    /// Do not save the address of the instantiated contract as the default `--contract`
+    /// of the `call`, `storage` and `info` commands for this project.
+    #[clap(long)]
+    no_save: bool,
     #[clap(flatten)]
     extrinsic_cli_opts: CLIExtrinsicOpts,
-    /// Transfers an initial balance to the instantiated contract
+    #[clap(flatten)]
+    fiat_cli_opts: FiatCliOpts,
+    /// Transfers an initial balance to the instantiated contract, e.g. `1.5DOT` or
+    /// `100mUNIT`.
     #[clap(name = "value", long, default_value = "0")]
     value: String,
     /// Maximum amount of gas to be used for this command.
     /// If not specified will perform a dry-run to estimate the gas consumed for the
     /// instantiation.
-    #[clap(name = "gas", long)]
+    #[clap(name = "gas", long, alias = "gas-limit")]
     gas_limit: Option<u64>,
     /// Maximum proof size for this instantiation.
     /// If not specified will perform a dry-run to estimate the proof size required.
@@ -99,16 +155,28 @@ pub struct InstantiateCommand {
     proof_size: Option<u64>,
     /// A salt used in the address derivation of the new contract. Use to create multiple
     /// instances of the same contract code from the same account.
-    #[clap(long, value_parser = parse_hex_bytes)]
+    ///
+    /// Given either hex encoded with a `0x` prefix or as a string, whose UTF-8 bytes are
+    /// used as the salt.
+    #[clap(long, value_parser = parse_salt)]
     salt: Option<Bytes>,
     /// Export the instantiate output in JSON format.
     #[clap(long, conflicts_with = "verbose")]
     output_json: bool,
+    /// Allow uploading code built in debug mode to a production chain.
+    #[clap(long)]
+    allow_debug: bool,
 }
 
-/// Parse hex encoded bytes.
-fn parse_hex_bytes(input: &str) -> Result<Bytes> {
-    let bytes = decode_hex(input)?;
+/// Parse the salt either from hex encoded bytes or from a string.
+///
+/// Input prefixed with `0x` has to be valid hex, any other input is taken as a string.
+fn parse_salt(input: &str) -> Result<Bytes> {
+    let bytes = if input.starts_with("0x") {
+        decode_hex(input)?
+    } else {
+        input.as_bytes().to_vec()
+    };
     Ok(bytes.into())
 }
 
@@ -119,11 +187,7 @@ impl InstantiateCommand {
     }
 
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(
-            self,
-            run,
-            self.extrinsic_cli_opts.chain_cli_opts.chain().config()
-        )
+        call_with_config!(self, run, self.extrinsic_cli_opts.chain()?.config())
     }
 
     async fn run<C: Config + Environment + SignerConfig<C>>(
@@ -131,7 +195,13 @@ impl InstantiateCommand {
     ) -> Result<(), ErrorVariant>
     where
         <C as SignerConfig<C>>::Signer: subxt::tx::Signer<C> + Clone + FromStr,
-        <C as Config>::AccountId: IntoVisitor + FromStr + EncodeAsType + Decode + Display,
+        <C as Config>::AccountId: IntoVisitor
+            + FromStr
+            + EncodeAsType
+            + Decode
+            + Display
+            + PartialEq
+            + Serialize,
         <<C as Config>::AccountId as FromStr>::Err: Display,
         C::Balance:
             From<u128> + Display + Default + FromStr + Serialize + Debug + EncodeAsType,
@@ -139,20 +209,17 @@ impl InstantiateCommand {
             From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
         <C as Config>::Hash: From<[u8; 32]> + IntoVisitor + EncodeAsType,
     {
-        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri)
+        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri()?)
             .map_err(|_| anyhow::anyhow!("Failed to parse suri option"))?;
-        let chain = self.extrinsic_cli_opts.chain_cli_opts.chain();
-        let token_metadata = TokenMetadata::query::<C>(&chain.url()).await?;
+        let chain = self.extrinsic_cli_opts.chain()?;
+        let token_metadata = self
+            .extrinsic_cli_opts
+            .token_metadata::<C>(&chain.url())
+            .await?;
 
         let storage_deposit_limit = self
             .extrinsic_cli_opts
-            .storage_deposit_limit
-            .clone()
-            .map(|b| parse_balance(&b, &token_metadata))
-            .transpose()
-            .map_err(|e| {
-                anyhow::anyhow!("Failed to parse storage_deposit_limit option: {}", e)
-            })?;
+            .storage_deposit_limit(&token_metadata)?;
         let value = parse_balance(&self.value, &token_metadata)
             .map_err(|e| anyhow::anyhow!("Failed to parse value option: {}", e))?;
         let extrinsic_opts = ExtrinsicOptsBuilder::new(signer)
@@ -160,31 +227,83 @@ impl InstantiateCommand {
             .manifest_path(self.extrinsic_cli_opts.manifest_path.clone())
             .url(chain.url())
             .storage_deposit_limit(storage_deposit_limit)
+            .mortal(self.extrinsic_cli_opts.mortal)
+            .mortal_checkpoint(self.extrinsic_cli_opts.mortal_checkpoint()?)
+            .nonce(self.extrinsic_cli_opts.nonce)
+            .tip(self.extrinsic_cli_opts.tip(&token_metadata)?)
+            .ss58_prefix(self.extrinsic_cli_opts.ss58_prefix())
+            .pallet_name(self.extrinsic_cli_opts.chain_cli_opts.pallet_name.clone())
+            .rpc_retries(self.extrinsic_cli_opts.rpc_retries)
+            .rpc_retry_delay(self.extrinsic_cli_opts.rpc_retry_delay())
+            .tx_timeout(self.extrinsic_cli_opts.tx_timeout())
+            .ws_keepalive(self.extrinsic_cli_opts.ws_keepalive())
+            .verbosity(self.extrinsic_cli_opts.verbosity()?)
             .done();
 
+        let args = if self.interactive {
+            let transcoder =
+                extrinsic_opts.contract_artifacts()?.contract_transcoder()?;
+            prompt_missing_args(&transcoder, &self.constructor, &self.args)?
+        } else {
+            self.args.clone()
+        };
+        let instantiate_builder = InstantiateCommandBuilder::new(extrinsic_opts)
+            .constructor(self.constructor.clone())
+            .args(args)
+            .args_json(self.args_json.as_deref().map(load_json_args).transpose()?)
+            .allow_default_fields(self.allow_default_fields)
+            .value(value)
+            .gas_limit(self.gas_limit)
+            .proof_size(self.proof_size)
+            .salt(self.salt.clone());
+
+        if let Some(offline_output) = &self.extrinsic_cli_opts.offline_output {
+            let client = self.extrinsic_cli_opts.offline_client::<C>(
+                true,
+                self.gas_limit,
+                self.proof_size,
+            )?;
+            let extrinsic = instantiate_builder.sign_offline(&client)?;
+            write_signed_extrinsic(offline_output, &extrinsic)?;
+            return Ok(())
+        }
         let instantiate_exec: InstantiateExec<C, C, _> =
-            InstantiateCommandBuilder::new(extrinsic_opts)
-                .constructor(self.constructor.clone())
-                .args(self.args.clone())
-                .value(value)
-                .gas_limit(self.gas_limit)
-                .proof_size(self.proof_size)
-                .salt(self.salt.clone())
-                .done()
-                .await?;
+            instantiate_builder.done().await?;
 
         if !self.extrinsic_cli_opts.execute {
-            let result = instantiate_exec.instantiate_dry_run().await?;
-            match instantiate_exec.decode_instantiate_dry_run(&result).await {
+            let (result, events) =
+                instantiate_exec.instantiate_dry_run_with_events().await?;
+            let decoded = instantiate_exec.decode_instantiate_dry_run(&result).await;
+            if let Some(path) = &self.dry_run_json {
+                let decoded_return = decoded.as_ref().ok().map(|r| &r.result);
+                write_dry_run_json(path, &result, decoded_return, events.as_ref())?;
+            }
+            match decoded {
                 Ok(dry_run_result) => {
                     if self.output_json() {
                         println!("{}", dry_run_result.to_json()?);
                     } else {
                         print_instantiate_dry_run_result(&dry_run_result);
-                        display_contract_exec_result_debug::<_, DEFAULT_KEY_COL_WIDTH, _>(
-                            &result,
-                        )?;
+                        if self.show_debug || dry_run_result.reverted {
+                            display_contract_exec_result_debug::<
+                                _,
+                                DEFAULT_KEY_COL_WIDTH,
+                                _,
+                            >(&result)?;
+                        }
                         display_dry_run_result_warning("instantiate");
+                        if self.fiat_cli_opts.enabled() {
+                            let gas_limit = weight_with_overrides(
+                                result.gas_required,
+                                instantiate_exec.args().gas_limit(),
+                                instantiate_exec.args().proof_size(),
+                            );
+                            let fee =
+                                instantiate_exec.estimate_fee(Some(gas_limit)).await;
+                            self.fiat_cli_opts
+                                .print_fee_estimate(fee, &token_metadata)
+                                .await;
+                        }
                     }
                     Ok(())
                 }
@@ -200,6 +319,11 @@ impl InstantiateCommand {
             }
         } else {
             if let Some(chain) = chain.production() {
+                check_debug_build_for_production(
+                    &chain.to_string(),
+                    instantiate_exec.opts().contract_artifacts()?.build_mode(),
+                    self.allow_debug,
+                )?;
                 if !instantiate_exec
                     .opts()
                     .contract_artifacts()?
@@ -215,9 +339,25 @@ impl InstantiateCommand {
                 self.extrinsic_cli_opts.skip_dry_run,
             )
             .await?;
+            if self.fiat_cli_opts.enabled() && !self.output_json() {
+                let fee = instantiate_exec.estimate_fee(Some(gas_limit)).await;
+                self.fiat_cli_opts
+                    .print_fee_estimate(fee, &token_metadata)
+                    .await;
+            }
             if !self.extrinsic_cli_opts.skip_confirm {
+                let tip = instantiate_exec.opts().tip();
+                let tip_display =
+                    BalanceVariant::<u128>::from(tip, Some(&token_metadata))?;
                 prompt_confirm_tx(|| {
                     print_default_instantiate_preview(&instantiate_exec, gas_limit);
+                    if tip > 0 {
+                        name_value_println!(
+                            "Tip",
+                            tip_display.to_string(),
+                            DEFAULT_KEY_COL_WIDTH
+                        );
+                    }
                     if let Code::Existing(code_hash) =
                         instantiate_exec.args().code().clone()
                     {
@@ -229,8 +369,24 @@ impl InstantiateCommand {
                     }
                 })?;
             }
+            if let Some(multisig) = &self.multisig {
+                let multisig = parse_multisig(multisig)?;
+                let result = instantiate_exec
+                    .instantiate_multisig(Some(gas_limit), &multisig)
+                    .await?;
+                display_multisig_result(
+                    &result,
+                    &instantiate_exec.client().metadata(),
+                    instantiate_exec.opts().pallet_name(),
+                    &token_metadata,
+                    self.output_json(),
+                    self.extrinsic_cli_opts.verbosity()?,
+                )?;
+                return Ok(())
+            }
             let instantiate_result =
                 instantiate_exec.instantiate(Some(gas_limit)).await?;
+            let contract_address = instantiate_result.contract_address.to_string();
             display_result(
                 &instantiate_exec,
                 instantiate_result,
@@ -239,6 +395,19 @@ impl InstantiateCommand {
                 self.extrinsic_cli_opts.verbosity().unwrap(),
             )
             .await?;
+            // Contracts instantiated from an artifact file do not belong to a project.
+            if !self.no_save && self.extrinsic_cli_opts.file.is_none() {
+                if let Err(err) = save_last_deployment(
+                    self.extrinsic_cli_opts.manifest_path.as_ref(),
+                    &contract_address,
+                    &chain.url(),
+                ) {
+                    eprintln!(
+                        "{} Failed to save the last deployed contract address: {err:#}",
+                        "warning:".yellow().bold()
+                    );
+                }
+            }
             Ok(())
         }
     }
@@ -280,20 +449,17 @@ where
                 print_gas_required_success(instantiate_result.gas_required);
             }
             // use user specified values where provided, otherwise use the estimates
-            let ref_time = instantiate_exec
-                .args()
-                .gas_limit()
-                .unwrap_or_else(|| instantiate_result.gas_required.ref_time());
-            let proof_size = instantiate_exec
-                .args()
-                .proof_size()
-                .unwrap_or_else(|| instantiate_result.gas_required.proof_size());
-            Ok(Weight::from_parts(ref_time, proof_size))
+            Ok(weight_with_overrides(
+                instantiate_result.gas_required,
+                instantiate_exec.args().gas_limit(),
+                instantiate_exec.args().proof_size(),
+            ))
         }
         Err(ref err) => {
             let object = ErrorVariant::from_dispatch_error(
                 err,
                 &instantiate_exec.client().metadata(),
+                instantiate_exec.opts().pallet_name(),
             )?;
             if output_json {
                 Err(anyhow!("{}", serde_json::to_string_pretty(&object)?))
@@ -329,13 +495,27 @@ where
         &instantiate_exec_result.events,
         Some(instantiate_exec.transcoder()),
         &instantiate_exec.client().metadata(),
+        instantiate_exec.opts().pallet_name(),
     )?;
     let contract_address = instantiate_exec_result.contract_address.to_string();
+    let predicted_address = instantiate_exec
+        .predicted_contract_address()
+        .map(|address| address.to_string())
+        .ok();
+    if let Some(predicted_address) = &predicted_address {
+        if predicted_address != &contract_address {
+            eprintln!(
+                "{} The contract was instantiated at {contract_address}, but the predicted address was {predicted_address}",
+                "warning:".yellow().bold(),
+            );
+        }
+    }
     if output_json {
         let display_instantiate_result = InstantiateResult {
             code_hash: instantiate_exec_result
                 .code_hash
                 .map(|ch| format!("{ch:?}")),
+            predicted_address,
             contract: Some(contract_address),
             events,
         };
@@ -371,7 +551,25 @@ pub fn print_default_instantiate_preview<C: Config + Environment + SignerConfig<
         instantiate_exec.args().raw_args().join(" "),
         DEFAULT_KEY_COL_WIDTH
     );
-    name_value_println!("Gas limit", gas_limit.to_string(), DEFAULT_KEY_COL_WIDTH);
+    name_value_println!(
+        "Gas limit",
+        display_gas_limit(
+            gas_limit,
+            instantiate_exec.args().gas_limit(),
+            instantiate_exec.args().proof_size()
+        ),
+        DEFAULT_KEY_COL_WIDTH
+    );
+    match instantiate_exec.predicted_contract_address() {
+        Ok(contract) => {
+            name_value_println!(
+                "Predicted address",
+                contract.to_string(),
+                DEFAULT_KEY_COL_WIDTH
+            )
+        }
+        Err(err) => tracing::debug!("{err}"),
+    }
 }
 
 /// Result of a successful contract instantiation for displaying.
@@ -383,6 +581,9 @@ pub struct InstantiateResult {
     /// Instantiated code hash
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code_hash: Option<String>,
+    /// Contract address computed locally before the instantiation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_address: Option<String>,
     /// The events emitted from the instantiate extrinsic invocation.
     pub events: DisplayEvents,
 }
@@ -406,6 +607,13 @@ pub fn print_instantiate_dry_run_result<Balance: Serialize>(
         format!("{:?}", result.reverted),
         DEFAULT_KEY_COL_WIDTH
     );
+    if let Some(predicted_address) = &result.predicted_address {
+        name_value_println!(
+            "Predicted address",
+            predicted_address,
+            DEFAULT_KEY_COL_WIDTH
+        );
+    }
     name_value_println!("Contract", result.contract, DEFAULT_KEY_COL_WIDTH);
     name_value_println!(
         "Gas consumed",
@@ -0,0 +1,80 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::build::LintLevelFlags;
+use anyhow::Result;
+use contract_build::{
+    lint_contract,
+    LintReport,
+    ManifestPath,
+    Target,
+    VerbosityFlags,
+};
+use std::path::PathBuf;
+
+/// Runs the ink! lints on the contract and reports the findings.
+#[derive(Debug, clap::Args)]
+#[clap(name = "lint")]
+pub struct LintCommand {
+    /// Path to the `Cargo.toml` of the contract to lint.
+    #[clap(long, value_parser)]
+    manifest_path: Option<PathBuf>,
+    /// Run the extra ink! lints in addition to the mandatory ones.
+    #[clap(long)]
+    extra_lints: bool,
+    #[clap(flatten)]
+    lint_levels: LintLevelFlags,
+    /// Which bytecode the contract is linted for.
+    #[clap(long, default_value = "wasm")]
+    target: Target,
+    /// The format of the lint findings.
+    ///
+    /// - `human`: The findings as rendered by the compiler.
+    ///
+    /// - `json`: A JSON object with the `lint`, `severity`, `span` and `message` of each
+    ///   finding.
+    #[clap(long, value_enum, default_value = "human")]
+    format: LintFormat,
+    #[clap(flatten)]
+    verbosity: VerbosityFlags,
+}
+
+/// The format of the lint findings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LintFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+impl LintCommand {
+    /// Returns whether to export the findings in JSON format.
+    pub fn output_json(&self) -> bool {
+        self.format == LintFormat::Json
+    }
+
+    pub fn run(&self) -> Result<LintReport> {
+        let manifest_path = ManifestPath::try_from(self.manifest_path.as_ref())?;
+        let verbosity = TryFrom::<&VerbosityFlags>::try_from(&self.verbosity)?;
+        lint_contract(
+            &manifest_path,
+            self.extra_lints,
+            &self.lint_levels.to_lint_levels(),
+            self.target,
+            verbosity,
+        )
+    }
+}
@@ -15,41 +15,53 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 mod config;
+mod config_file;
+mod deployment;
+mod fiat;
 mod prod_chains;
 
+pub mod batch;
 pub mod build;
 pub mod call;
 pub mod decode;
 pub mod encode;
+pub mod events;
 pub mod info;
 pub mod instantiate;
+pub mod lint;
 pub mod remove;
 pub mod rpc;
 pub mod schema;
 pub mod storage;
+pub mod submit;
 pub mod upload;
 pub mod verify;
 
 pub(crate) use self::{
+    batch::BatchCommand,
     build::{
         BuildCommand,
         CheckCommand,
     },
     call::CallCommand,
     decode::DecodeCommand,
+    events::EventsCommand,
     info::{
         ExtendedContractInfo,
         InfoCommand,
     },
     instantiate::InstantiateCommand,
+    lint::LintCommand,
     prod_chains::ProductionChain,
     remove::RemoveCommand,
     rpc::RpcCommand,
     schema::{
+        DiffSchemaCommand,
         GenerateSchemaCommand,
         VerifySchemaCommand,
     },
     storage::StorageCommand,
+    submit::SubmitCommand,
     upload::UploadCommand,
     verify::VerifyCommand,
 };
@@ -64,8 +76,10 @@ use anyhow::{
     Result,
 };
 use colored::Colorize;
+use config_file::ConfigFile;
 use contract_build::{
     name_value_println,
+    BuildMode,
     Verbosity,
     VerbosityFlags,
     DEFAULT_KEY_COL_WIDTH,
@@ -74,7 +88,32 @@ pub(crate) use contract_extrinsics::ErrorVariant;
 use contract_extrinsics::{
     pallet_contracts_primitives::ContractResult,
     BalanceVariant,
+    DisplayEvents,
+    DryRunEvents,
+    Multisig,
+    MultisigResult,
+    RpcConnection,
     TokenMetadata,
+    TokenMetadataOverride,
+    DEFAULT_PALLET_NAME,
+};
+use contract_transcode::{
+    ContractMessageTranscoder,
+    Value,
+};
+use ink_env::Environment;
+use serde::Serialize;
+use subxt::{
+    backend::RuntimeVersion,
+    ext::{
+        codec::{
+            Decode,
+            Encode,
+        },
+        scale_decode::IntoVisitor,
+    },
+    Config,
+    OfflineClient,
 };
 
 use std::{
@@ -84,9 +123,14 @@ use std::{
     },
     io::{
         self,
+        BufRead,
+        IsTerminal,
         Write,
     },
+    path::Path,
     str::FromStr,
+    sync::OnceLock,
+    time::Duration,
 };
 
 /// Arguments required for creating and sending an extrinsic to a Substrate node.
@@ -104,8 +148,15 @@ pub struct CLIExtrinsicOpts {
     /// e.g.
     /// - for a dev account "//Alice"
     /// - with a password "//Alice///SECRET_PASSWORD"
+    ///
+    /// To keep the secret out of the shell history use `--suri-file` or the
+    /// `CARGO_CONTRACT_SURI` environment variable instead.
     #[clap(name = "suri", long, short)]
-    suri: String,
+    suri: Option<String>,
+    /// Path to a file containing the secret key URI for the account deploying the
+    /// contract.
+    #[clap(long, value_parser)]
+    suri_file: Option<PathBuf>,
     #[clap(flatten)]
     verbosity: VerbosityFlags,
     /// Submit the extrinsic for on-chain execution.
@@ -113,8 +164,75 @@ pub struct CLIExtrinsicOpts {
     execute: bool,
     /// The maximum amount of balance that can be charged from the caller to pay for the
     /// storage. consumed.
+    ///
+    /// Can also be set in the config file, see `--config-file`.
     #[clap(long)]
     storage_deposit_limit: Option<String>,
+    /// Make the extrinsic mortal: it stays valid for the given number of blocks
+    /// after the current best block. If not set, the extrinsic is immortal.
+    #[clap(long)]
+    mortal: Option<u64>,
+    /// The block from which the mortality period starts, given as `<number>:<hash>`.
+    /// If not set, the current best block is used.
+    #[clap(long, requires = "mortal")]
+    mortal_checkpoint: Option<String>,
+    /// The nonce of the signer account to use for the extrinsic. If not set, the nonce
+    /// is queried from the best block. Required for `--offline-output`.
+    #[clap(long)]
+    nonce: Option<u64>,
+    /// A tip for the block author to prioritize the transaction, e.g. `1.5mDOT`.
+    #[clap(long)]
+    tip: Option<String>,
+    /// The SS58 prefix used to display decoded account ids. If not set, the
+    /// `System::SS58Prefix` constant of the chain is used.
+    #[clap(long)]
+    ss58_prefix: Option<u16>,
+    /// How often a failed read-only RPC request, e.g. querying the nonce or a dry-run,
+    /// is retried. Submitting the extrinsic itself is never retried. With retries a
+    /// dropped connection is re-established, as with `--ws-keepalive`.
+    #[clap(long, default_value = "0")]
+    rpc_retries: u32,
+    /// The delay in milliseconds before the first retry of a failed RPC request. It
+    /// doubles with every further retry.
+    #[clap(long, default_value = "500")]
+    rpc_retry_delay: u64,
+    /// Fail if the transaction is not included in a block within the given number of
+    /// seconds. The error contains the transaction hash to look it up later on.
+    #[clap(long)]
+    tx_timeout: Option<u64>,
+    /// Sign the extrinsic and write it hex encoded to the given file instead of
+    /// submitting it. It can be submitted later on with `cargo contract submit`.
+    ///
+    /// The node is not connected to: the chain is described by `--chain-metadata`,
+    /// `--genesis-hash`, `--spec-version` and `--transaction-version`, and the gas
+    /// limit has to be given by `--gas` and `--proof-size` with `--skip-dry-run`.
+    #[clap(
+        long,
+        value_parser,
+        requires_all = [
+            "nonce",
+            "chain_metadata",
+            "genesis_hash",
+            "spec_version",
+            "transaction_version",
+        ],
+        conflicts_with = "execute"
+    )]
+    offline_output: Option<PathBuf>,
+    /// Path to the SCALE encoded metadata of the chain, for signing with
+    /// `--offline-output`.
+    #[clap(long, value_parser, requires = "offline_output")]
+    chain_metadata: Option<PathBuf>,
+    /// The hex encoded genesis hash of the chain, for signing with `--offline-output`.
+    #[clap(long, requires = "offline_output")]
+    genesis_hash: Option<String>,
+    /// The spec version of the chain's runtime, for signing with `--offline-output`.
+    #[clap(long, requires = "offline_output")]
+    spec_version: Option<u32>,
+    /// The transaction version of the chain's runtime, for signing with
+    /// `--offline-output`.
+    #[clap(long, requires = "offline_output")]
+    transaction_version: Option<u32>,
     /// Before submitting a transaction, do not dry-run it via RPC first.
     #[clap(long)]
     skip_dry_run: bool,
@@ -124,43 +242,395 @@ pub struct CLIExtrinsicOpts {
     /// Arguments required for communicating with a Substrate node.
     #[clap(flatten)]
     chain_cli_opts: CLIChainOpts,
+    /// Arguments for denominating balances without querying the node.
+    #[clap(flatten)]
+    token_cli_opts: CLITokenOpts,
 }
 
 impl CLIExtrinsicOpts {
+    /// Returns the secret key URI of the signer, from either `--suri`, `--suri-file` or
+    /// the `CARGO_CONTRACT_SURI` environment variable.
+    pub fn suri(&self) -> Result<String> {
+        resolve_suri(
+            self.suri.as_deref(),
+            self.suri_file.as_deref(),
+            std::env::var(SURI_ENV_VAR).ok(),
+        )
+    }
+
     /// Returns the verbosity
     pub fn verbosity(&self) -> Result<Verbosity> {
         TryFrom::try_from(&self.verbosity)
     }
+
+    /// Returns the SS58 prefix used to display decoded account ids.
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        self.ss58_prefix
+    }
+
+    /// Returns the delay before the first retry of a failed RPC request.
+    pub fn rpc_retry_delay(&self) -> Duration {
+        Duration::from_millis(self.rpc_retry_delay)
+    }
+
+    /// Returns how long to wait for the transaction to be included in a block.
+    pub fn tx_timeout(&self) -> Option<Duration> {
+        self.tx_timeout.map(Duration::from_secs)
+    }
+
+    /// Returns the interval in which the node is pinged to keep the connection alive.
+    pub fn ws_keepalive(&self) -> Option<Duration> {
+        self.chain_cli_opts.ws_keepalive()
+    }
+
+    /// Returns the chain, falling back to the config files of the contract.
+    pub fn chain(&self) -> Result<Chain> {
+        self.chain_cli_opts.chain(self.manifest_path.as_deref())
+    }
+
+    /// Returns the block number and hash from which the mortality period starts.
+    pub fn mortal_checkpoint<Hash>(&self) -> Result<Option<(u64, Hash)>>
+    where
+        Hash: From<[u8; 32]>,
+    {
+        self.mortal_checkpoint
+            .as_ref()
+            .map(|checkpoint| parse_mortal_checkpoint(checkpoint))
+            .transpose()
+            .map_err(|e| anyhow!("Failed to parse mortal-checkpoint option: {}", e))
+    }
+
+    /// Returns the maximum storage deposit, from `--storage-deposit-limit` or otherwise
+    /// from the config file.
+    pub fn storage_deposit_limit<Balance: FromStr + From<u128> + Clone>(
+        &self,
+        token_metadata: &TokenMetadata,
+    ) -> Result<Option<Balance>> {
+        let storage_deposit_limit = match &self.storage_deposit_limit {
+            Some(storage_deposit_limit) => Some(storage_deposit_limit.clone()),
+            None => {
+                self.chain_cli_opts
+                    .config_file(self.manifest_path.as_deref())?
+                    .storage_deposit_limit
+                    .clone()
+            }
+        };
+        storage_deposit_limit
+            .map(|limit| parse_balance(&limit, token_metadata))
+            .transpose()
+            .map_err(|e| anyhow!("Failed to parse storage_deposit_limit option: {}", e))
+    }
+
+    /// Returns the client for signing with `--offline-output`, built from the given
+    /// chain metadata, genesis hash and runtime version instead of querying the node.
+    ///
+    /// Fails if a mortal extrinsic is requested without `--mortal-checkpoint`, or if
+    /// `weight_required` is set and the gas limit is not given by `--gas` and
+    /// `--proof-size` with `--skip-dry-run`.
+    pub fn offline_client<C: Config>(
+        &self,
+        weight_required: bool,
+        gas_limit: Option<u64>,
+        proof_size: Option<u64>,
+    ) -> Result<OfflineClient<C>>
+    where
+        C::Hash: From<[u8; 32]>,
+    {
+        if weight_required
+            && (gas_limit.is_none() || proof_size.is_none() || !self.skip_dry_run)
+        {
+            anyhow::bail!(
+                "--offline-output requires --gas, --proof-size and --skip-dry-run, the \
+                gas limit can not be estimated without a connection to the node"
+            )
+        }
+        if self.mortal.is_some() && self.mortal_checkpoint.is_none() {
+            anyhow::bail!(
+                "--offline-output requires --mortal-checkpoint for a mortal extrinsic"
+            )
+        }
+        let (
+            Some(chain_metadata),
+            Some(genesis_hash),
+            Some(spec_version),
+            Some(transaction_version),
+        ) = (
+            &self.chain_metadata,
+            &self.genesis_hash,
+            self.spec_version,
+            self.transaction_version,
+        )
+        else {
+            anyhow::bail!(
+                "--offline-output requires --chain-metadata, --genesis-hash, \
+                --spec-version and --transaction-version"
+            )
+        };
+        let metadata = std::fs::read(chain_metadata).with_context(|| {
+            format!("Failed to read chain metadata {}", chain_metadata.display())
+        })?;
+        let metadata =
+            subxt::Metadata::decode(&mut &metadata[..]).with_context(|| {
+                format!(
+                    "Failed to decode chain metadata {}",
+                    chain_metadata.display()
+                )
+            })?;
+        let genesis_hash: [u8; 32] = contract_build::util::decode_hex(genesis_hash)?
+            .try_into()
+            .map_err(|_| anyhow!("Genesis hash should be 32 bytes in length"))?;
+        let runtime_version = RuntimeVersion {
+            spec_version,
+            transaction_version,
+        };
+        Ok(OfflineClient::new(
+            genesis_hash.into(),
+            runtime_version,
+            metadata,
+        ))
+    }
+
+    /// Returns the token metadata used to denominate balances, see [`CLITokenOpts`].
+    ///
+    /// With `--offline-output` the node is never queried, so the token decimals have to
+    /// be given as with `--offline`.
+    pub async fn token_metadata<C: Config>(
+        &self,
+        url: &url::Url,
+    ) -> Result<TokenMetadata> {
+        let mut token_override = self.token_cli_opts.token_override()?;
+        if self.offline_output.is_some() {
+            if token_override.token_decimals.is_none() {
+                anyhow::bail!(
+                    "--offline-output requires the token decimals, from either \
+                    --token-decimals or the properties of the --chain-spec"
+                )
+            }
+            token_override.offline = true;
+        }
+        TokenMetadata::query_with_override::<C>(
+            url,
+            &self.chain_cli_opts.rpc_connection(),
+            &token_override,
+        )
+        .await
+    }
+
+    /// Returns the tip for the block author, zero if not specified.
+    pub fn tip(&self, token_metadata: &TokenMetadata) -> Result<u128> {
+        self.tip
+            .as_ref()
+            .map(|tip| parse_balance(tip, token_metadata))
+            .transpose()
+            .map_err(|e| anyhow!("Failed to parse tip option: {}", e))
+            .map(Option::unwrap_or_default)
+    }
+}
+
+/// The environment variable the secret key URI is read from if neither `--suri` nor
+/// `--suri-file` is given.
+const SURI_ENV_VAR: &str = "CARGO_CONTRACT_SURI";
+
+/// Resolves the secret key URI, the explicit `suri` takes precedence over the
+/// `suri_file` and the `env_suri` environment variable.
+///
+/// Giving both `suri` and `suri_file` is ambiguous and therefore an error.
+fn resolve_suri(
+    suri: Option<&str>,
+    suri_file: Option<&Path>,
+    env_suri: Option<String>,
+) -> Result<String> {
+    match (suri, suri_file) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("Only one of --suri and --suri-file can be used")
+        }
+        (Some(suri), None) => Ok(suri.to_string()),
+        (None, Some(path)) => {
+            let contents = std::fs::read_to_string(path).with_context(|| {
+                format!("Failed to read the suri from {}", path.display())
+            })?;
+            let suri = contents.trim();
+            anyhow::ensure!(
+                !suri.is_empty(),
+                "The suri file {} is empty",
+                path.display()
+            );
+            Ok(suri.to_string())
+        }
+        (None, None) => {
+            env_suri
+                .map(|suri| suri.trim().to_string())
+                .filter(|suri| !suri.is_empty())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No suri given, use --suri, --suri-file or the {} environment \
+                        variable",
+                        SURI_ENV_VAR
+                    )
+                })
+        }
+    }
 }
 
 /// Arguments required for communicating with a Substrate node.
 #[derive(Clone, Debug, clap::Args)]
 pub struct CLIChainOpts {
-    /// Websockets url of a Substrate node.
-    #[clap(
-        name = "url",
-        long,
-        value_parser,
-        default_value = "ws://localhost:9944"
-    )]
-    url: url::Url,
-    /// Chain config to be used as part of the call.
-    #[clap(name = "config", long, default_value = "Polkadot")]
-    config: String,
+    /// Websockets url of a Substrate node. [default: ws://localhost:9944]
+    #[clap(name = "url", long, value_parser)]
+    url: Option<url::Url>,
+    /// Chain config to be used as part of the call. [default: Polkadot]
+    #[clap(name = "config", long)]
+    config: Option<String>,
     /// Name of a production chain to be communicated with.
     #[clap(name = "chain", long, conflicts_with_all = ["url", "config"])]
     chain: Option<ProductionChain>,
+    /// Path to a TOML file with default values for `--url`, `--config`, `--chain` and
+    /// `--storage-deposit-limit`. By default the `.cargo-contract.toml` next to the
+    /// contract's manifest or in one of its parent directories, e.g. the workspace
+    /// root, and the one in the home directory are read, if present.
+    #[clap(long, value_parser)]
+    config_file: Option<PathBuf>,
+    /// The name of the contracts pallet, for runtimes which do not use the canonical
+    /// `Contracts`.
+    #[clap(long)]
+    pallet_name: Option<String>,
+    /// Ping the node every given number of seconds to keep the connection alive, and
+    /// re-establish it if it dropped. Commands submitting an extrinsic retry in-flight
+    /// read-only requests according to `--rpc-retries`, the extrinsic itself is never
+    /// submitted again.
+    #[clap(long)]
+    ws_keepalive: Option<u64>,
+    /// The config files, which are only read once.
+    #[clap(skip)]
+    loaded_config_file: OnceLock<ConfigFile>,
 }
 
 impl CLIChainOpts {
-    pub fn chain(&self) -> Chain {
-        if let Some(chain) = &self.chain {
-            Chain::Production(chain.clone())
-        } else if let Some(prod) = ProductionChain::from_parts(&self.url, &self.config) {
-            Chain::Production(prod)
-        } else {
-            Chain::Custom(self.url.clone(), self.config.clone())
+    /// Returns the chain given on the command line, falling back to the config files of
+    /// the contract at `manifest_path`.
+    pub fn chain(&self, manifest_path: Option<&Path>) -> Result<Chain> {
+        resolve_chain(
+            self.chain.clone(),
+            self.url.clone(),
+            self.config.clone(),
+            self.config_file(manifest_path)?,
+        )
+    }
+
+    /// Returns the default values of the config files of the contract at
+    /// `manifest_path`, see [`ConfigFile::load`].
+    ///
+    /// The files are only read on the first call, a command always passes the same
+    /// `manifest_path`.
+    pub fn config_file(&self, manifest_path: Option<&Path>) -> Result<&ConfigFile> {
+        if let Some(config_file) = self.loaded_config_file.get() {
+            return Ok(config_file)
         }
+        let config_file = ConfigFile::load(self.config_file.as_deref(), manifest_path)?;
+        Ok(self.loaded_config_file.get_or_init(|| config_file))
+    }
+
+    /// Returns the name of the contracts pallet.
+    pub fn pallet_name(&self) -> &str {
+        self.pallet_name.as_deref().unwrap_or(DEFAULT_PALLET_NAME)
+    }
+
+    /// Returns the interval in which the node is pinged to keep the connection alive.
+    pub fn ws_keepalive(&self) -> Option<Duration> {
+        self.ws_keepalive.map(Duration::from_secs)
+    }
+
+    /// Returns how to connect to the node, see `--ws-keepalive`.
+    pub fn rpc_connection(&self) -> RpcConnection {
+        RpcConnection::from_keepalive(self.ws_keepalive())
+    }
+}
+
+/// The node the commands connect to if neither given on the command line nor in the
+/// config file.
+const DEFAULT_URL: &str = "ws://localhost:9944";
+
+/// The chain config used if neither given on the command line nor in the config file.
+const DEFAULT_CONFIG: &str = "Polkadot";
+
+/// Resolves the chain from the command line options, falling back to the values of the
+/// config file and then to the built-in defaults.
+fn resolve_chain(
+    chain: Option<ProductionChain>,
+    url: Option<url::Url>,
+    config: Option<String>,
+    config_file: &ConfigFile,
+) -> Result<Chain> {
+    if let Some(chain) = chain {
+        return Ok(Chain::Production(chain))
+    }
+    // a production chain of the config file is overridden by any chain option
+    if let (None, None, Some(chain)) = (&url, &config, &config_file.chain) {
+        let chain = <ProductionChain as clap::ValueEnum>::from_str(chain, true)
+            .map_err(|e| anyhow!("Invalid chain '{}' in config file: {}", chain, e))?;
+        return Ok(Chain::Production(chain))
+    }
+    let url = match url.or_else(|| config_file.url.clone()) {
+        Some(url) => url,
+        None => url::Url::parse(DEFAULT_URL)?,
+    };
+    let config = config
+        .or_else(|| config_file.config.clone())
+        .unwrap_or_else(|| DEFAULT_CONFIG.to_string());
+    if let Some(prod) = ProductionChain::from_parts(&url, &config) {
+        Ok(Chain::Production(prod))
+    } else {
+        Ok(Chain::Custom(url, config))
+    }
+}
+
+/// Token metadata for denominating balances, e.g. `1.5mDOT`, which is otherwise queried
+/// from the node.
+#[derive(Clone, Debug, clap::Args)]
+pub struct CLITokenOpts {
+    /// The number of decimals of the chain's token. The value reported by the node
+    /// takes precedence unless `--offline` is set.
+    #[clap(long)]
+    token_decimals: Option<usize>,
+    /// The symbol of the chain's token. The value reported by the node takes
+    /// precedence unless `--offline` is set.
+    #[clap(long)]
+    token_symbol: Option<String>,
+    /// Read the token decimals and symbol from the `properties` of a chain spec file.
+    /// `--token-decimals` and `--token-symbol` take precedence.
+    #[clap(long, value_parser)]
+    chain_spec: Option<PathBuf>,
+    /// Do not query the node for the token metadata, only use `--token-decimals`,
+    /// `--token-symbol` and `--chain-spec`. The decimals have to be given by either
+    /// `--token-decimals` or `--chain-spec`.
+    #[clap(long)]
+    offline: bool,
+}
+
+impl CLITokenOpts {
+    /// Returns the token metadata given by the user.
+    ///
+    /// Fails if `--offline` is set without the token decimals, balances would silently
+    /// be denominated with a made up number of decimals otherwise.
+    pub fn token_override(&self) -> Result<TokenMetadataOverride> {
+        let mut token_override = match &self.chain_spec {
+            Some(chain_spec) => TokenMetadataOverride::from_chain_spec(chain_spec)?,
+            None => TokenMetadataOverride::default(),
+        };
+        if self.token_decimals.is_some() {
+            token_override.token_decimals = self.token_decimals;
+        }
+        if self.token_symbol.is_some() {
+            token_override.symbol = self.token_symbol.clone();
+        }
+        if self.offline && token_override.token_decimals.is_none() {
+            anyhow::bail!(
+                "--offline requires the token decimals, from either --token-decimals or \
+                the properties of the --chain-spec"
+            )
+        }
+        token_override.offline = self.offline;
+        Ok(token_override)
     }
 }
 
@@ -196,16 +666,16 @@ impl Chain {
 const STORAGE_DEPOSIT_KEY: &str = "Storage Total Deposit";
 pub const MAX_KEY_COL_WIDTH: usize = STORAGE_DEPOSIT_KEY.len() + 1;
 
-/// Print to stdout the fields of the result of a `instantiate` or `call` dry-run via RPC.
+/// Print to stdout the fields of the result of a failed `instantiate` or `call` dry-run
+/// via RPC.
+///
+/// The debug message is always printed, it usually explains the failure.
 pub fn display_contract_exec_result<R, const WIDTH: usize, Balance>(
     result: &ContractResult<R, Balance>,
 ) -> Result<()>
 where
     Balance: Debug,
 {
-    let mut debug_message_lines = std::str::from_utf8(&result.debug_message)
-        .context("Error decoding UTF8 debug message bytes")?
-        .lines();
     name_value_println!("Gas Consumed", format!("{:?}", result.gas_consumed), WIDTH);
     name_value_println!("Gas Required", format!("{:?}", result.gas_required), WIDTH);
     name_value_println!(
@@ -213,34 +683,41 @@ where
         format!("{:?}", result.storage_deposit),
         WIDTH
     );
-
-    // print debug messages aligned, only first line has key
-    if let Some(debug_message) = debug_message_lines.next() {
-        name_value_println!("Debug Message", format!("{debug_message}"), WIDTH);
-    }
-
-    for debug_message in debug_message_lines {
-        name_value_println!("", format!("{debug_message}"), WIDTH);
-    }
-    Ok(())
+    display_contract_exec_result_debug::<_, WIDTH, _>(result)
 }
 
+/// Print to stdout the debug message of the result of a dry-run, e.g. the output of
+/// `ink::env::debug_println!` in contracts built in debug mode.
 pub fn display_contract_exec_result_debug<R, const WIDTH: usize, Balance>(
     result: &ContractResult<R, Balance>,
 ) -> Result<()> {
-    let mut debug_message_lines = std::str::from_utf8(&result.debug_message)
-        .context("Error decoding UTF8 debug message bytes")?
-        .lines();
-    if let Some(debug_message) = debug_message_lines.next() {
-        name_value_println!("Debug Message", format!("{debug_message}"), WIDTH);
-    }
-
-    for debug_message in debug_message_lines {
-        name_value_println!("", format!("{debug_message}"), WIDTH);
+    for line in format_debug_message::<WIDTH>(&result.debug_message)? {
+        println!("{line}");
     }
     Ok(())
 }
 
+/// Format the UTF-8 debug message of a dry-run into lines aligned at `WIDTH`, only the
+/// first line has a key.
+fn format_debug_message<const WIDTH: usize>(debug_message: &[u8]) -> Result<Vec<String>> {
+    let debug_message = std::str::from_utf8(debug_message)
+        .context("Error decoding UTF8 debug message bytes")?;
+    let lines = debug_message
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let key = if i == 0 { "Debug Message" } else { "" };
+            format!(
+                "{:>width$} {}",
+                key.bright_purple().bold(),
+                line,
+                width = WIDTH
+            )
+        })
+        .collect();
+    Ok(lines)
+}
+
 pub fn display_dry_run_result_warning(command: &str) {
     println!("Your {} call {} been executed.", command, "has not".bold());
     println!(
@@ -249,6 +726,60 @@ pub fn display_dry_run_result_warning(command: &str) {
         );
 }
 
+/// Write the signed extrinsic hex encoded to the given file.
+pub fn write_signed_extrinsic(path: &Path, extrinsic: &[u8]) -> Result<()> {
+    std::fs::write(path, format!("0x{}", hex::encode(extrinsic))).with_context(|| {
+        format!("Failed to write signed extrinsic to {}", path.display())
+    })?;
+    name_value_println!(
+        "Signed extrinsic",
+        format!("written to {}", path.display()),
+        DEFAULT_KEY_COL_WIDTH
+    );
+    Ok(())
+}
+
+/// The complete outcome of a dry-run, see [`write_dry_run_json`].
+#[derive(Serialize)]
+struct DryRunJson<'a, R, Balance> {
+    #[serde(flatten)]
+    result: &'a ContractResult<R, Balance>,
+    /// The return value decoded with the contract metadata, if the dry-run succeeded.
+    decoded_return: Option<&'a Value>,
+    /// The events emitted during the dry-run, if the node collected them.
+    events: Option<&'a DryRunEvents>,
+}
+
+/// Write the complete dry-run result, including the debug message, the decoded
+/// return value and the emitted events, as JSON to the given file.
+pub fn write_dry_run_json<R, Balance>(
+    path: &Path,
+    result: &ContractResult<R, Balance>,
+    decoded_return: Option<&Value>,
+    events: Option<&DryRunEvents>,
+) -> Result<()>
+where
+    R: Serialize,
+    Balance: Serialize,
+{
+    let json = serde_json::to_string_pretty(&DryRunJson {
+        result,
+        decoded_return,
+        events,
+    })?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write dry-run result to {}", path.display()))
+}
+
+/// Read constructor or message arguments from the given JSON file.
+pub fn load_json_args(path: &Path) -> Result<serde_json::Value> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read arguments from {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| {
+        format!("Failed to parse JSON arguments from {}", path.display())
+    })
+}
+
 /// Prompt the user to confirm transaction submission.
 pub fn prompt_confirm_tx<F: FnOnce()>(show_details: F) -> Result<()> {
     println!(
@@ -262,9 +793,7 @@ pub fn prompt_confirm_tx<F: FnOnce()>(show_details: F) -> Result<()> {
         "Y".bright_white().bold()
     );
 
-    let mut buf = String::new();
-    io::stdout().flush()?;
-    io::stdin().read_line(&mut buf)?;
+    let buf = read_confirmation("pass --skip-confirm or -y to submit without it")?;
     match buf.trim().to_lowercase().as_str() {
         // default is 'y'
         "y" | "" => Ok(()),
@@ -282,6 +811,44 @@ pub fn print_dry_running_status(msg: &str) {
     );
 }
 
+/// Returns the `estimate`, with its ref time and proof size replaced by the
+/// user specified `gas_limit` and `proof_size` where provided.
+pub fn weight_with_overrides(
+    estimate: Weight,
+    gas_limit: Option<u64>,
+    proof_size: Option<u64>,
+) -> Weight {
+    Weight::from_parts(
+        gas_limit.unwrap_or_else(|| estimate.ref_time()),
+        proof_size.unwrap_or_else(|| estimate.proof_size()),
+    )
+}
+
+/// Formats the gas limit for the confirmation prompt, marking the parts which are user
+/// specified manual overrides of the dry-run estimate.
+pub fn display_gas_limit(
+    gas_limit: Weight,
+    gas_override: Option<u64>,
+    proof_size_override: Option<u64>,
+) -> String {
+    let overrides = [
+        gas_override.map(|_| "--gas"),
+        proof_size_override.map(|_| "--proof-size"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if overrides.is_empty() {
+        gas_limit.to_string()
+    } else {
+        format!(
+            "{} {}",
+            gas_limit,
+            format!("(manual override: {})", overrides.join(", ")).yellow()
+        )
+    }
+}
+
 pub fn print_gas_required_success(gas: Weight) {
     println!(
         "{:>width$} Gas required estimated at {}",
@@ -353,6 +920,104 @@ where
         .map_err(|e| anyhow::anyhow!("Account address parsing failed: {e}"))
 }
 
+/// Parse a multisig given as `<threshold>:<signatory>,<signatory>,...`.
+pub fn parse_multisig<AccountId>(input: &str) -> Result<Multisig<AccountId>>
+where
+    AccountId: FromStr + Encode + Clone + PartialEq,
+    <AccountId as FromStr>::Err: Display,
+{
+    let (threshold, signatories) = input
+        .split_once(':')
+        .context("Expected a threshold and signatories separated by `:`")?;
+    let threshold = threshold.parse().context("Invalid multisig threshold")?;
+    let signatories = signatories
+        .split(',')
+        .map(|signatory| parse_account(signatory.trim()))
+        .collect::<Result<_>>()?;
+    Multisig::new(threshold, signatories)
+}
+
+/// Display the result of submitting a call via a multisig.
+///
+/// Until the call is dispatched, the call hash and data are shown for the co-signers.
+pub fn display_multisig_result<C>(
+    result: &MultisigResult<C>,
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+    token_metadata: &TokenMetadata,
+    output_json: bool,
+    verbosity: Verbosity,
+) -> Result<()>
+where
+    C: Config + Environment,
+    <C as Config>::AccountId: IntoVisitor + Display,
+    <C as Environment>::Balance: Display + From<u128>,
+{
+    let display_events =
+        DisplayEvents::from_events::<C, C>(&result.events, None, metadata, pallet_name)?;
+    let call_hash = format!("0x{}", hex::encode(result.call_hash));
+    let call_data = format!("0x{}", hex::encode(&result.call_data));
+    let dispatch_error = match &result.dispatch_result {
+        Some(Err(err)) => Some(err.to_string()),
+        _ => None,
+    };
+    if output_json {
+        let json_object = serde_json::json!({
+            "multisig": result.multisig_account.to_string(),
+            "call_hash": call_hash,
+            "call_data": call_data,
+            "approvals": result.approvals,
+            "threshold": result.threshold,
+            "executed": result.is_executed(),
+            "dispatch_error": dispatch_error,
+            "events": serde_json::from_str::<serde_json::Value>(&display_events.to_json()?)?,
+        });
+        println!("{}", serde_json::to_string_pretty(&json_object)?);
+    } else {
+        println!(
+            "{}",
+            display_events.display_events::<C>(verbosity, token_metadata)?
+        );
+        name_value_println!(
+            "Multisig",
+            result.multisig_account.to_string(),
+            DEFAULT_KEY_COL_WIDTH
+        );
+        name_value_println!("Call hash", call_hash, DEFAULT_KEY_COL_WIDTH);
+        name_value_println!(
+            "Approvals",
+            format!("{}/{}", result.approvals, result.threshold),
+            DEFAULT_KEY_COL_WIDTH
+        );
+        if let Some(dispatch_error) = dispatch_error {
+            name_value_println!("Dispatch error", dispatch_error, DEFAULT_KEY_COL_WIDTH);
+        } else if result.dispatch_result.is_none() {
+            name_value_println!("Call data", call_data, DEFAULT_KEY_COL_WIDTH);
+            println!(
+                "The call is dispatched once {} signatories submitted the same command.",
+                result.threshold
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Parse a block number and hash given as `<number>:<hash>`.
+fn parse_mortal_checkpoint<Hash>(input: &str) -> Result<(u64, Hash)>
+where
+    Hash: From<[u8; 32]>,
+{
+    let (number, hash) = input
+        .split_once(':')
+        .context("Expected a block number and hash separated by `:`")?;
+    let number = number.parse().context("Invalid block number")?;
+    let bytes = contract_build::util::decode_hex(hash)?;
+    let hash: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("Block hash should be 32 bytes in length"))?;
+    Ok((number, hash.into()))
+}
+
 /// Parse a hex encoded 32 byte hash. Returns error if not exactly 32 bytes.
 pub fn parse_code_hash<Hash>(input: &str) -> Result<Hash>
 where
@@ -367,6 +1032,24 @@ where
     Ok(arr.into())
 }
 
+/// Refuse to upload code built in debug mode to a production chain, unless explicitly
+/// allowed.
+pub fn check_debug_build_for_production(
+    chain: &str,
+    build_mode: Option<BuildMode>,
+    allow_debug: bool,
+) -> Result<()> {
+    if build_mode == Some(BuildMode::Debug) && !allow_debug {
+        anyhow::bail!(
+            "The contract was built in debug mode and should not be deployed to {} \
+            mainnet.\nBuild the contract with `cargo contract build --release`, or pass \
+            `--allow-debug` to deploy it anyway.",
+            chain
+        )
+    }
+    Ok(())
+}
+
 /// Prompt the user to confirm the upload of unverifiable code to the production chain.
 pub fn prompt_confirm_unverifiable_upload(chain: &str) -> Result<()> {
     println!("{}", "Confirm upload:".bright_white().bold());
@@ -387,9 +1070,10 @@ pub fn prompt_confirm_unverifiable_upload(chain: &str) -> Result<()> {
         "y/N".bright_white().bold()
     );
 
-    let mut buf = String::new();
-    io::stdout().flush()?;
-    io::stdin().read_line(&mut buf)?;
+    let buf = read_confirmation(
+        "build the contract with `cargo contract build --verifiable` to upload it \
+        without confirmation",
+    )?;
     match buf.trim().to_lowercase().as_str() {
         // default is 'n'
         "y" => Ok(()),
@@ -398,6 +1082,112 @@ pub fn prompt_confirm_unverifiable_upload(chain: &str) -> Result<()> {
     }
 }
 
+/// A parameter of a constructor or message to prompt an argument for.
+struct ArgPrompt {
+    label: String,
+    type_name: String,
+}
+
+/// Prompts for the arguments of the constructor or message which were not given,
+/// validating each input against the type of its parameter.
+///
+/// Returns the given arguments if all of them are present.
+pub fn prompt_missing_args(
+    transcoder: &ContractMessageTranscoder,
+    name: &str,
+    args: &[String],
+) -> Result<Vec<String>> {
+    let params = transcoder.params(name)?;
+    if args.len() >= params.len() {
+        return Ok(args.to_vec())
+    }
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        anyhow::bail!("Cannot prompt for arguments: stdin is not a terminal")
+    }
+    let missing = &params[args.len()..];
+    let prompts: Vec<_> = missing
+        .iter()
+        .map(|param| {
+            ArgPrompt {
+                label: param.label().to_string(),
+                type_name: param.ty().display_name().segments.join("::"),
+            }
+        })
+        .collect();
+    let prompted = read_args_from(&prompts, stdin.lock(), io::stdout(), |i, arg| {
+        transcoder.encode_arg(&missing[i], arg).map(|_| ())
+    })?;
+    Ok(args.iter().cloned().chain(prompted).collect())
+}
+
+/// Reads an argument for each prompt, re-prompting until the input passes `validate`.
+fn read_args_from<F>(
+    prompts: &[ArgPrompt],
+    mut input: impl BufRead,
+    mut output: impl Write,
+    validate: F,
+) -> Result<Vec<String>>
+where
+    F: Fn(usize, &str) -> Result<()>,
+{
+    let mut args = Vec::new();
+    for (i, prompt) in prompts.iter().enumerate() {
+        loop {
+            write!(
+                output,
+                "{} ({}): ",
+                prompt.label.bright_white().bold(),
+                prompt.type_name
+            )?;
+            output.flush()?;
+            let mut buf = String::new();
+            if input.read_line(&mut buf)? == 0 {
+                anyhow::bail!("No value given for the argument `{}`", prompt.label)
+            }
+            let arg = buf.trim();
+            match validate(i, arg) {
+                Ok(()) => {
+                    args.push(arg.to_string());
+                    break
+                }
+                Err(err) => {
+                    writeln!(
+                        output,
+                        "{} expected a value of type `{}`: {err}",
+                        "Invalid value,".red().bold(),
+                        prompt.type_name
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Reads the answer to a confirmation prompt from stdin.
+///
+/// Fails instead of waiting forever if stdin is not a terminal, e.g. in a CI job,
+/// suggesting how to proceed without a confirmation by the given `hint`.
+fn read_confirmation(hint: &str) -> Result<String> {
+    io::stdout().flush()?;
+    let stdin = io::stdin();
+    read_confirmation_from(stdin.is_terminal(), stdin.lock(), hint)
+}
+
+fn read_confirmation_from(
+    is_terminal: bool,
+    mut input: impl BufRead,
+    hint: &str,
+) -> Result<String> {
+    if !is_terminal {
+        anyhow::bail!("No confirmation received: stdin is not a terminal, {hint}")
+    }
+    let mut buf = String::new();
+    input.read_line(&mut buf)?;
+    Ok(buf)
+}
+
 #[cfg(test)]
 mod tests {
     use subxt::{
@@ -407,6 +1197,402 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn offline_output_does_not_require_a_node() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            extrinsic_cli_opts: CLIExtrinsicOpts,
+        }
+        let offline_args = |args: &[&str]| {
+            let mut offline_args = vec![
+                "call",
+                "--offline-output",
+                "signed.hex",
+                "--nonce",
+                "0",
+                "--chain-metadata",
+                "../extrinsics/src/test_runtime_api/metadata_v11.scale",
+                "--genesis-hash",
+                "0x0101010101010101010101010101010101010101010101010101010101010101",
+                "--spec-version",
+                "100",
+                "--transaction-version",
+                "1",
+            ];
+            offline_args.extend_from_slice(args);
+            <Cli as clap::Parser>::try_parse_from(offline_args)
+                .map(|cli| cli.extrinsic_cli_opts)
+        };
+
+        // the chain has to be described without the node
+        assert!(<Cli as clap::Parser>::try_parse_from([
+            "call",
+            "--offline-output",
+            "signed.hex",
+            "--nonce",
+            "0"
+        ])
+        .is_err());
+
+        let opts = offline_args(&[]).unwrap();
+        assert!(opts
+            .offline_client::<SubstrateConfig>(false, None, None)
+            .is_ok());
+        // the gas limit can not be estimated without a dry-run
+        assert!(opts
+            .offline_client::<SubstrateConfig>(true, Some(5_000), Some(6_000))
+            .is_err());
+        let opts = offline_args(&["--skip-dry-run"]).unwrap();
+        assert!(opts
+            .offline_client::<SubstrateConfig>(true, Some(5_000), None)
+            .is_err());
+        let client = opts
+            .offline_client::<SubstrateConfig>(true, Some(5_000), Some(6_000))
+            .unwrap();
+        assert_eq!(client.genesis_hash(), [0x01; 32].into());
+        assert_eq!(client.runtime_version().transaction_version, 1);
+
+        // the best block can not be queried for a mortal extrinsic
+        let opts = offline_args(&["--mortal", "64"]).unwrap();
+        assert!(opts
+            .offline_client::<SubstrateConfig>(false, None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_chain_precedence() -> Result<()> {
+        let config_file = ConfigFile {
+            url: Some("wss://file.example:443".parse()?),
+            config: Some("Substrate".to_string()),
+            ..Default::default()
+        };
+
+        let chain = resolve_chain(None, None, None, &ConfigFile::default())?;
+        assert_eq!(chain.url(), url::Url::parse(DEFAULT_URL)?);
+        assert_eq!(chain.config(), DEFAULT_CONFIG);
+
+        let chain = resolve_chain(None, None, None, &config_file)?;
+        assert_eq!(chain.url().as_str(), "wss://file.example/");
+        assert_eq!(chain.config(), "Substrate");
+
+        let cli_url: url::Url = "ws://cli.example:9944".parse()?;
+        let chain = resolve_chain(None, Some(cli_url.clone()), None, &config_file)?;
+        assert_eq!(chain.url(), cli_url);
+        assert_eq!(chain.config(), "Substrate");
+
+        // the url of a project file is not combined with the chain of the home file
+        let home_file = ConfigFile {
+            chain: Some("astar".to_string()),
+            ..Default::default()
+        };
+        let project_file = ConfigFile {
+            url: Some("wss://project.example:443".parse()?),
+            ..Default::default()
+        };
+        let chain = resolve_chain(None, None, None, &project_file.or(home_file.clone()))?;
+        assert_eq!(chain.url().as_str(), "wss://project.example/");
+        assert_eq!(chain.config(), DEFAULT_CONFIG);
+        let chain =
+            resolve_chain(None, None, None, &ConfigFile::default().or(home_file))?;
+        assert_eq!(chain.production(), Some(&ProductionChain::Astar));
+
+        let config_file = ConfigFile {
+            chain: Some("astar".to_string()),
+            ..config_file
+        };
+        let chain = resolve_chain(None, None, None, &config_file)?;
+        assert_eq!(chain.production(), Some(&ProductionChain::Astar));
+        let chain = resolve_chain(None, Some(cli_url.clone()), None, &config_file)?;
+        assert_eq!(chain.url(), cli_url);
+        let chain =
+            resolve_chain(Some(ProductionChain::Shiden), None, None, &config_file)?;
+        assert_eq!(chain.production(), Some(&ProductionChain::Shiden));
+        Ok(())
+    }
+
+    /// Removes the ANSI colour codes from the `output`, which are only present if the
+    /// tests are run in a terminal.
+    fn strip_colours(output: &str) -> String {
+        regex::Regex::new("\x1b\\[[0-9;]*m")
+            .unwrap()
+            .replace_all(output, "")
+            .into_owned()
+    }
+
+    #[test]
+    fn format_debug_message_aligns_lines() {
+        let debug_message = b"panicked at lib.rs:10:5\nfirst line\n  indented";
+
+        let lines = format_debug_message::<15>(debug_message).unwrap();
+
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| strip_colours(line))
+                .collect::<Vec<_>>(),
+            vec![
+                "  Debug Message panicked at lib.rs:10:5",
+                "                first line",
+                "                  indented",
+            ]
+        );
+        assert!(format_debug_message::<15>(&[]).unwrap().is_empty());
+        assert!(format_debug_message::<15>(&[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn parse_multisig_works() {
+        let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let bob = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+        let multisig = parse_multisig::<<SubstrateConfig as Config>::AccountId>(
+            &format!("2:{alice}, {bob}"),
+        )
+        .unwrap();
+
+        assert_eq!(multisig.threshold(), 2);
+        assert_eq!(multisig.signatories().len(), 2);
+        assert!(parse_multisig::<<SubstrateConfig as Config>::AccountId>(alice).is_err());
+        assert!(
+            parse_multisig::<<SubstrateConfig as Config>::AccountId>(&format!(
+                "3:{alice},{bob}"
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn check_debug_build_for_production_works() {
+        assert!(
+            check_debug_build_for_production("Astar", Some(BuildMode::Debug), false)
+                .is_err()
+        );
+        assert!(
+            check_debug_build_for_production("Astar", Some(BuildMode::Debug), true)
+                .is_ok()
+        );
+        assert!(check_debug_build_for_production(
+            "Astar",
+            Some(BuildMode::Release),
+            false
+        )
+        .is_ok());
+        assert!(check_debug_build_for_production("Astar", None, false).is_ok());
+    }
+
+    #[test]
+    fn read_args_from_reprompts_invalid_values() {
+        let prompts = [
+            ArgPrompt {
+                label: "value".to_string(),
+                type_name: "u32".to_string(),
+            },
+            ArgPrompt {
+                label: "flag".to_string(),
+                type_name: "bool".to_string(),
+            },
+        ];
+        let mut output = Vec::new();
+
+        let args = read_args_from(
+            &prompts,
+            &b"abc
+ 42 
+true
+"[..],
+            &mut output,
+            |i, arg| {
+                match i {
+                    0 => arg.parse::<u32>().map(|_| ()).map_err(Into::into),
+                    _ => arg.parse::<bool>().map(|_| ()).map_err(Into::into),
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(args, ["42", "true"]);
+        assert_eq!(
+            strip_colours(&String::from_utf8(output).unwrap()),
+            "value (u32): Invalid value, expected a value of type `u32`: \
+            invalid digit found in string\nvalue (u32): flag (bool): "
+        );
+    }
+
+    #[test]
+    fn read_args_from_fails_without_input() {
+        let prompts = [ArgPrompt {
+            label: "value".to_string(),
+            type_name: "u32".to_string(),
+        }];
+
+        let err =
+            read_args_from(&prompts, &b""[..], io::sink(), |_, _| Ok(())).unwrap_err();
+
+        assert_eq!(err.to_string(), "No value given for the argument `value`");
+    }
+
+    #[test]
+    fn read_confirmation_from_terminal() {
+        let answer = read_confirmation_from(true, &b"n\n"[..], "pass -y").unwrap();
+
+        assert_eq!(answer, "n\n");
+    }
+
+    #[test]
+    fn read_confirmation_fails_without_terminal() {
+        let err = read_confirmation_from(false, &b"y\n"[..], "pass -y").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "No confirmation received: stdin is not a terminal, pass -y"
+        );
+    }
+
+    #[test]
+    fn weight_with_overrides_works() {
+        let estimate = Weight::from_parts(1_000, 2_000);
+
+        assert_eq!(weight_with_overrides(estimate, None, None), estimate);
+        assert_eq!(
+            weight_with_overrides(estimate, Some(5_000), None),
+            Weight::from_parts(5_000, 2_000)
+        );
+        assert_eq!(
+            weight_with_overrides(estimate, Some(5_000), Some(6_000)),
+            Weight::from_parts(5_000, 6_000)
+        );
+    }
+
+    #[test]
+    fn display_gas_limit_marks_manual_overrides() {
+        let gas_limit = Weight::from_parts(5_000, 2_000);
+
+        assert_eq!(
+            display_gas_limit(gas_limit, None, None),
+            gas_limit.to_string()
+        );
+        assert!(display_gas_limit(gas_limit, Some(5_000), None)
+            .contains("(manual override: --gas)"));
+        assert!(display_gas_limit(gas_limit, Some(5_000), Some(2_000))
+            .contains("(manual override: --gas, --proof-size)"));
+    }
+
+    #[test]
+    fn resolve_suri_prefers_explicit_suri_over_env() {
+        let suri = resolve_suri(Some("//Alice"), None, Some("//Bob".to_string()));
+
+        assert_eq!(suri.unwrap(), "//Alice");
+    }
+
+    #[test]
+    fn resolve_suri_prefers_file_over_env() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "  //Alice///SECRET  ").unwrap();
+
+        let suri = resolve_suri(None, Some(file.path()), Some("//Bob".to_string()));
+
+        assert_eq!(suri.unwrap(), "//Alice///SECRET");
+    }
+
+    #[test]
+    fn resolve_suri_falls_back_to_env() {
+        let suri = resolve_suri(None, None, Some("//Bob\n".to_string()));
+
+        assert_eq!(suri.unwrap(), "//Bob");
+    }
+
+    #[test]
+    fn resolve_suri_rejects_suri_and_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        let err = resolve_suri(Some("//Alice"), Some(file.path()), None).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Only one of --suri and --suri-file can be used"
+        );
+    }
+
+    #[test]
+    fn resolve_suri_requires_a_source() {
+        assert!(resolve_suri(None, None, None).is_err());
+        assert!(resolve_suri(None, None, Some(" ".to_string())).is_err());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        assert!(resolve_suri(None, Some(file.path()), None).is_err());
+    }
+
+    #[test]
+    fn chain_opts_connect_with_keepalive() {
+        let chain_opts = CLIChainOpts {
+            url: None,
+            config: None,
+            chain: None,
+            config_file: None,
+            pallet_name: None,
+            ws_keepalive: None,
+            loaded_config_file: OnceLock::new(),
+        };
+        assert_eq!(chain_opts.rpc_connection(), RpcConnection::Plain);
+
+        let chain_opts = CLIChainOpts {
+            ws_keepalive: Some(6),
+            ..chain_opts
+        };
+        assert_eq!(
+            chain_opts.rpc_connection(),
+            RpcConnection::Reconnecting {
+                keepalive: Duration::from_secs(6)
+            }
+        );
+    }
+
+    #[test]
+    fn offline_token_override_requires_decimals() {
+        let token_opts = CLITokenOpts {
+            token_decimals: None,
+            token_symbol: Some("DOT".to_string()),
+            chain_spec: None,
+            offline: true,
+        };
+        assert!(token_opts.token_override().is_err());
+
+        let token_opts = CLITokenOpts {
+            token_decimals: Some(10),
+            ..token_opts
+        };
+        let token_override = token_opts.token_override().unwrap();
+        assert_eq!(token_override.token_decimals, Some(10));
+        assert!(token_override.offline);
+    }
+
+    #[test]
+    fn parse_mortal_checkpoint_works() {
+        let (number, hash) =
+            parse_mortal_checkpoint::<<SubstrateConfig as Config>::Hash>(
+                "42:0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d",
+            )
+            .unwrap();
+        assert_eq!(number, 42);
+        assert_eq!(
+            hex::encode(hash),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        // missing block number
+        assert!(
+            parse_mortal_checkpoint::<<SubstrateConfig as Config>::Hash>(
+                "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+            )
+            .is_err()
+        );
+        // hash of wrong length
+        assert!(
+            parse_mortal_checkpoint::<<SubstrateConfig as Config>::Hash>("42:0xd435")
+                .is_err()
+        );
+    }
+
     #[test]
     fn parse_code_hash_works() {
         // with 0x prefix
@@ -28,7 +28,6 @@ use std::{
 
 use super::{
     config::SignerConfig,
-    parse_balance,
     parse_code_hash,
     CLIExtrinsicOpts,
 };
@@ -39,7 +38,6 @@ use contract_extrinsics::{
     ExtrinsicOptsBuilder,
     RemoveCommandBuilder,
     RemoveExec,
-    TokenMetadata,
 };
 use ink_env::Environment;
 use serde::Serialize;
@@ -75,11 +73,7 @@ impl RemoveCommand {
     }
 
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(
-            self,
-            run,
-            self.extrinsic_cli_opts.chain_cli_opts.chain().config()
-        )
+        call_with_config!(self, run, self.extrinsic_cli_opts.chain()?.config())
     }
 
     async fn run<C: Config + Environment + SignerConfig<C>>(
@@ -100,19 +94,22 @@ impl RemoveCommand {
             From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
         <C as Config>::Hash: IntoVisitor + EncodeAsType + From<[u8; 32]>,
     {
-        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri)
+        if self.extrinsic_cli_opts.offline_output.is_some() {
+            return Err(anyhow::anyhow!(
+                "--offline-output is not supported for removing code"
+            )
+            .into())
+        }
+        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri()?)
             .map_err(|_| anyhow::anyhow!("Failed to parse suri option"))?;
-        let chain = self.extrinsic_cli_opts.chain_cli_opts.chain();
-        let token_metadata = TokenMetadata::query::<C>(&chain.url()).await?;
+        let chain = self.extrinsic_cli_opts.chain()?;
+        let token_metadata = self
+            .extrinsic_cli_opts
+            .token_metadata::<C>(&chain.url())
+            .await?;
         let storage_deposit_limit = self
             .extrinsic_cli_opts
-            .storage_deposit_limit
-            .clone()
-            .map(|b| parse_balance(&b, &token_metadata))
-            .transpose()
-            .map_err(|e| {
-                anyhow::anyhow!("Failed to parse storage_deposit_limit option: {}", e)
-            })?;
+            .storage_deposit_limit(&token_metadata)?;
         let code_hash = self
             .code_hash
             .clone()
@@ -124,6 +121,17 @@ impl RemoveCommand {
             .manifest_path(self.extrinsic_cli_opts.manifest_path.clone())
             .url(chain.url())
             .storage_deposit_limit(storage_deposit_limit)
+            .mortal(self.extrinsic_cli_opts.mortal)
+            .mortal_checkpoint(self.extrinsic_cli_opts.mortal_checkpoint()?)
+            .nonce(self.extrinsic_cli_opts.nonce)
+            .tip(self.extrinsic_cli_opts.tip(&token_metadata)?)
+            .ss58_prefix(self.extrinsic_cli_opts.ss58_prefix())
+            .pallet_name(self.extrinsic_cli_opts.chain_cli_opts.pallet_name.clone())
+            .rpc_retries(self.extrinsic_cli_opts.rpc_retries)
+            .rpc_retry_delay(self.extrinsic_cli_opts.rpc_retry_delay())
+            .tx_timeout(self.extrinsic_cli_opts.tx_timeout())
+            .ws_keepalive(self.extrinsic_cli_opts.ws_keepalive())
+            .verbosity(self.extrinsic_cli_opts.verbosity()?)
             .done();
 
         let remove_exec: RemoveExec<C, C, _> = RemoveCommandBuilder::new(extrinsic_opts)
@@ -135,6 +143,7 @@ impl RemoveCommand {
             &remove_result.events,
             Some(remove_exec.transcoder()),
             &remove_exec.client().metadata(),
+            remove_exec.opts().pallet_name(),
         )?;
 
         let output_events = if self.output_json() {
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::path::PathBuf;
+
 use contract_build::name_value_println;
 use contract_extrinsics::{
     ErrorVariant,
@@ -35,6 +37,10 @@ pub struct RpcCommand {
     /// The arguments of the method to call.
     #[clap(num_args = 0..)]
     params: Vec<String>,
+    /// Path to a JSON file with an array of further arguments, which are appended to
+    /// the arguments given on the command line.
+    #[clap(long, value_parser)]
+    params_file: Option<PathBuf>,
     /// Export the call output in JSON format.
     #[clap(long)]
     output_json: bool,
@@ -45,8 +51,15 @@ pub struct RpcCommand {
 
 impl RpcCommand {
     pub async fn run(&self) -> Result<(), ErrorVariant> {
-        let request = RpcRequest::new(&self.chain_cli_opts.chain().url()).await?;
-        let params = RawParams::new(&self.params)?;
+        let request = RpcRequest::new(
+            &self.chain_cli_opts.chain(None)?.url(),
+            &self.chain_cli_opts.rpc_connection(),
+        )
+        .await?;
+        let mut params = RawParams::new(&self.params)?;
+        if let Some(params_file) = &self.params_file {
+            params = params.extend(RawParams::from_json_file(params_file)?);
+        }
 
         let result = request.raw_call(&self.method, params).await;
 
@@ -1,6 +1,10 @@
 use std::{
+    collections::BTreeMap,
     fs::File,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 
 use anyhow::{
@@ -13,7 +17,21 @@ use contract_build::{
     Verbosity,
     VerbosityFlags,
 };
+use contract_metadata::ContractMetadata;
+use ink_metadata::{
+    layout::Layout,
+    InkProject,
+    MessageParamSpec,
+    ReturnTypeSpec,
+    Selector,
+};
 use jsonschema::JSONSchema;
+use scale_info::{
+    form::PortableForm,
+    Field,
+    PortableRegistry,
+    TypeDef,
+};
 use schemars::schema_for;
 
 #[derive(Debug, Clone, Default, clap::ValueEnum)]
@@ -170,3 +188,607 @@ impl SchemaVerificationResult {
         Ok(serde_json::to_string_pretty(self)?)
     }
 }
+
+/// Compares the metadata of two versions of a contract for changes which break an
+/// upgrade of the contract code.
+#[derive(Debug, clap::Args)]
+pub struct DiffSchemaCommand {
+    /// The `.contract` bundle or `.json` metadata of the old version of the contract.
+    #[clap(value_parser)]
+    old: PathBuf,
+    /// The `.contract` bundle or `.json` metadata of the new version of the contract.
+    #[clap(value_parser)]
+    new: PathBuf,
+    /// Do not fail if there are breaking changes.
+    #[clap(long)]
+    allow_breaking: bool,
+    /// Output the result in JSON format
+    #[clap(long)]
+    output_json: bool,
+}
+
+impl DiffSchemaCommand {
+    pub fn run(&self) -> Result<MetadataDiff> {
+        let old = load_ink_project(&self.old)?;
+        let new = load_ink_project(&self.new)?;
+        let mut diff = MetadataDiff {
+            breaking: Vec::new(),
+            non_breaking: Vec::new(),
+            allow_breaking: self.allow_breaking,
+            output_json: self.output_json,
+        };
+        diff.compare(&old, &new);
+        Ok(diff)
+    }
+}
+
+/// Loads the ink! metadata from a `.contract` bundle or `.json` metadata file.
+fn load_ink_project(path: &Path) -> Result<InkProject> {
+    let metadata = ContractMetadata::load(path)?;
+    serde_json::from_value(serde_json::Value::Object(metadata.abi)).context(format!(
+        "Failed to deserialize the ink! metadata of {}",
+        path.display()
+    ))
+}
+
+/// The changes between the metadata of two versions of a contract.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MetadataDiff {
+    /// Changes which break callers of the contract or the decoding of its storage.
+    pub breaking: Vec<String>,
+    /// Changes which are backwards compatible.
+    pub non_breaking: Vec<String>,
+    #[serde(skip_serializing, skip_deserializing)]
+    allow_breaking: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub output_json: bool,
+}
+
+impl MetadataDiff {
+    fn compare(&mut self, old: &InkProject, new: &InkProject) {
+        let (old_types, new_types) = (old.registry(), new.registry());
+
+        let messages = |project: &InkProject, types: &PortableRegistry| {
+            project
+                .spec()
+                .messages()
+                .iter()
+                .map(|m| {
+                    let spec = Callable {
+                        selector: selector_bytes(m.selector()),
+                        signature: signature(types, m.args(), m.return_type()),
+                        payable: m.payable(),
+                        mutates: m.mutates(),
+                    };
+                    (m.label().to_string(), spec)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.compare_callables(
+            "message",
+            messages(old, old_types),
+            messages(new, new_types),
+            true,
+        );
+
+        let constructors = |project: &InkProject, types: &PortableRegistry| {
+            project
+                .spec()
+                .constructors()
+                .iter()
+                .map(|c| {
+                    let spec = Callable {
+                        selector: selector_bytes(c.selector()),
+                        signature: signature(types, c.args(), c.return_type()),
+                        payable: *c.payable(),
+                        mutates: true,
+                    };
+                    (c.label().to_string(), spec)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        // an upgraded contract is not instantiated again, so constructor changes only
+        // affect new instances
+        self.compare_callables(
+            "constructor",
+            constructors(old, old_types),
+            constructors(new, new_types),
+            false,
+        );
+
+        let events = |project: &InkProject, types: &PortableRegistry| {
+            project
+                .spec()
+                .events()
+                .iter()
+                .map(|e| {
+                    let topic = e
+                        .signature_topic()
+                        .map(|topic| format!("0x{}", hex::encode(topic.as_bytes())))
+                        .unwrap_or_else(|| "anonymous".to_string());
+                    let args = e
+                        .args()
+                        .iter()
+                        .map(|arg| {
+                            let indexed =
+                                if arg.indexed() { "#[ink(topic)] " } else { "" };
+                            format!(
+                                "{indexed}{}: {}",
+                                arg.label(),
+                                describe_type(types, arg.ty().ty().id)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (e.label().to_string(), (topic, args.join(", ")))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let (old_events, new_events) = (events(old, old_types), events(new, new_types));
+        for (label, (topic, fields)) in &old_events {
+            match new_events.get(label) {
+                None => self.breaking.push(format!("event `{label}` removed")),
+                Some((new_topic, _)) if new_topic != topic => {
+                    self.breaking.push(format!(
+                        "event `{label}` signature topic changed from {topic} to \
+                        {new_topic}"
+                    ))
+                }
+                Some((_, new_fields)) if new_fields != fields => {
+                    self.breaking.push(format!(
+                        "event `{label}` fields changed from `{fields}` to `{new_fields}`"
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        for label in new_events.keys().filter(|l| !old_events.contains_key(*l)) {
+            self.non_breaking.push(format!("event `{label}` added"));
+        }
+
+        let (old_cells, new_cells) = (
+            storage_cells(old.layout(), old_types),
+            storage_cells(new.layout(), new_types),
+        );
+        for (root_key, old_cell) in &old_cells {
+            let Some(new_cell) = new_cells.get(root_key) else {
+                self.breaking.push(format!(
+                    "storage cell `{}` at root key 0x{root_key:08x} removed",
+                    old_cell.path
+                ));
+                continue
+            };
+            // the fields of a cell are encoded together, so any change of them breaks
+            // the decoding of the stored value
+            let len = old_cell.fields.len().max(new_cell.fields.len());
+            for i in 0..len {
+                let change = match (old_cell.fields.get(i), new_cell.fields.get(i)) {
+                    (Some(old), Some(new)) if old != new => {
+                        format!("field `{old}` changed to `{new}`")
+                    }
+                    (Some(old), None) => format!("field `{old}` removed"),
+                    (None, Some(new)) => format!("field `{new}` added"),
+                    _ => continue,
+                };
+                self.breaking.push(format!(
+                    "storage cell `{}` at root key 0x{root_key:08x}: {change}",
+                    new_cell.path
+                ));
+            }
+        }
+        for (root_key, new_cell) in &new_cells {
+            if !old_cells.contains_key(root_key) {
+                self.non_breaking.push(format!(
+                    "storage cell `{}` at root key 0x{root_key:08x} added",
+                    new_cell.path
+                ));
+            }
+        }
+    }
+
+    fn compare_callables(
+        &mut self,
+        kind: &str,
+        old: BTreeMap<String, Callable>,
+        new: BTreeMap<String, Callable>,
+        breaking: bool,
+    ) {
+        let mut changes = Vec::new();
+        for (label, old_spec) in &old {
+            let Some(new_spec) = new.get(label) else {
+                changes.push((breaking, format!("{kind} `{label}` removed")));
+                continue
+            };
+            if old_spec.selector != new_spec.selector {
+                changes.push((
+                    breaking,
+                    format!(
+                        "{kind} `{label}` selector changed from 0x{} to 0x{}",
+                        hex::encode(old_spec.selector),
+                        hex::encode(new_spec.selector)
+                    ),
+                ));
+            }
+            if old_spec.signature != new_spec.signature {
+                changes.push((
+                    breaking,
+                    format!(
+                        "{kind} `{label}` signature changed from `{}` to `{}`",
+                        old_spec.signature, new_spec.signature
+                    ),
+                ));
+            }
+            if old_spec.payable != new_spec.payable {
+                // callers transferring value fail if the callable is no longer payable
+                changes.push((
+                    breaking && old_spec.payable,
+                    format!(
+                        "{kind} `{label}` payable changed from {} to {}",
+                        old_spec.payable, new_spec.payable
+                    ),
+                ));
+            }
+            if old_spec.mutates != new_spec.mutates {
+                changes.push((
+                    false,
+                    format!(
+                        "{kind} `{label}` mutates changed from {} to {}",
+                        old_spec.mutates, new_spec.mutates
+                    ),
+                ));
+            }
+        }
+        for (label, new_spec) in new.iter().filter(|(l, _)| !old.contains_key(*l)) {
+            changes.push((
+                false,
+                format!(
+                    "{kind} `{label}` added with selector 0x{}",
+                    hex::encode(new_spec.selector)
+                ),
+            ));
+        }
+        for (breaking, change) in changes {
+            match breaking {
+                true => self.breaking.push(change),
+                false => self.non_breaking.push(change),
+            }
+        }
+    }
+
+    /// Fails if there are breaking changes, unless they are allowed.
+    pub fn check(&self) -> Result<()> {
+        anyhow::ensure!(
+            self.breaking.is_empty() || self.allow_breaking,
+            "Found {} breaking change(s), pass --allow-breaking to ignore them",
+            self.breaking.len()
+        );
+        Ok(())
+    }
+
+    /// Display the changes in a human readable format.
+    pub fn display(&self) -> String {
+        if self.breaking.is_empty() && self.non_breaking.is_empty() {
+            return format!("{}", "No changes found".bright_green().bold())
+        }
+        let mut out = String::new();
+        if !self.breaking.is_empty() {
+            out.push_str(&format!("{}\n", "Breaking changes:".bright_red().bold()));
+            for change in &self.breaking {
+                out.push_str(&format!("  - {change}\n"));
+            }
+        }
+        if !self.non_breaking.is_empty() {
+            out.push_str(&format!(
+                "{}\n",
+                "Non-breaking changes:".bright_green().bold()
+            ));
+            for change in &self.non_breaking {
+                out.push_str(&format!("  - {change}\n"));
+            }
+        }
+        out
+    }
+
+    /// Display the changes in a pretty formatted JSON string.
+    pub fn serialize_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// The parts of a message or constructor which are relevant to its callers.
+struct Callable {
+    selector: [u8; 4],
+    signature: String,
+    payable: bool,
+    mutates: bool,
+}
+
+/// The 4 bytes of a message or constructor selector.
+fn selector_bytes(selector: &Selector) -> [u8; 4] {
+    selector
+        .to_bytes()
+        .try_into()
+        .expect("a selector is always 4 bytes")
+}
+
+/// Describes the arguments and return type of a message or constructor.
+fn signature(
+    types: &PortableRegistry,
+    args: &[MessageParamSpec<PortableForm>],
+    return_type: &ReturnTypeSpec<PortableForm>,
+) -> String {
+    let args = args
+        .iter()
+        .map(|arg| {
+            format!(
+                "{}: {}",
+                arg.label(),
+                describe_type(types, arg.ty().ty().id)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "({}) -> {}",
+        args.join(", "),
+        describe_type(types, return_type.ret_type().ty().id)
+    )
+}
+
+/// A value stored under a single root key of the contract storage.
+struct StorageCell {
+    /// The path of the storage field the cell belongs to.
+    path: String,
+    /// The fields encoded together in the cell, with their types.
+    fields: Vec<String>,
+}
+
+/// Collects the cells of the contract storage by their root key.
+fn storage_cells(
+    layout: &Layout<PortableForm>,
+    types: &PortableRegistry,
+) -> BTreeMap<u32, StorageCell> {
+    fn collect(
+        layout: &Layout<PortableForm>,
+        types: &PortableRegistry,
+        path: &mut Vec<String>,
+        root_key: Option<u32>,
+        cells: &mut BTreeMap<u32, StorageCell>,
+    ) {
+        match layout {
+            Layout::Root(root) => {
+                let key = *root.root_key().key();
+                cells.entry(key).or_insert_with(|| {
+                    StorageCell {
+                        path: path.join("."),
+                        fields: Vec::new(),
+                    }
+                });
+                collect(root.layout(), types, path, Some(key), cells);
+            }
+            Layout::Struct(struct_layout) => {
+                for field in struct_layout.fields() {
+                    path.push(field.name().to_string());
+                    collect(field.layout(), types, path, root_key, cells);
+                    path.pop();
+                }
+            }
+            Layout::Enum(enum_layout) => {
+                for struct_layout in enum_layout.variants().values() {
+                    path.push(struct_layout.name().to_string());
+                    for field in struct_layout.fields() {
+                        path.push(field.name().to_string());
+                        collect(field.layout(), types, path, root_key, cells);
+                        path.pop();
+                    }
+                    path.pop();
+                }
+            }
+            Layout::Array(array) => {
+                path.push(format!("[{}]", array.len()));
+                collect(array.layout(), types, path, root_key, cells);
+                path.pop();
+            }
+            Layout::Leaf(leaf) => {
+                if let Some(cell) = root_key.and_then(|key| cells.get_mut(&key)) {
+                    cell.fields.push(format!(
+                        "{}: {}",
+                        path.join("."),
+                        describe_type(types, leaf.ty().id)
+                    ));
+                }
+            }
+            Layout::Hash(_) => {}
+        }
+    }
+
+    let mut cells = BTreeMap::new();
+    collect(layout, types, &mut Vec::new(), None, &mut cells);
+    cells
+}
+
+/// Describes a type by its structure, so that the same type has the same description in
+/// the metadata of different versions of a contract, where its type id may differ.
+fn describe_type(types: &PortableRegistry, id: u32) -> String {
+    fn describe(types: &PortableRegistry, id: u32, visiting: &mut Vec<u32>) -> String {
+        let Some(ty) = types.resolve(id) else {
+            return format!("<unknown type {id}>")
+        };
+        let path = ty.path.segments.join("::");
+        // recursive types are described by their path where they recur
+        if visiting.contains(&id) {
+            return path
+        }
+        visiting.push(id);
+        let params = ty
+            .type_params
+            .iter()
+            .filter_map(|param| {
+                param.ty.as_ref().map(|ty| describe(types, ty.id, visiting))
+            })
+            .collect::<Vec<_>>();
+        let fields = |fields: &[Field<PortableForm>], visiting: &mut Vec<u32>| {
+            let described = fields
+                .iter()
+                .map(|field| {
+                    let ty = describe(types, field.ty.id, visiting);
+                    match &field.name {
+                        Some(name) => format!("{name}: {ty}"),
+                        None => ty,
+                    }
+                })
+                .collect::<Vec<_>>();
+            match fields.first() {
+                None => String::new(),
+                Some(field) if field.name.is_some() => {
+                    format!(" {{ {} }}", described.join(", "))
+                }
+                Some(_) => format!("({})", described.join(", ")),
+            }
+        };
+        let def = match &ty.type_def {
+            TypeDef::Composite(composite) => fields(&composite.fields, visiting),
+            TypeDef::Variant(variant) => {
+                let variants = variant
+                    .variants
+                    .iter()
+                    .map(|v| format!("{}{}", v.name, fields(&v.fields, visiting)))
+                    .collect::<Vec<_>>();
+                format!(" {{ {} }}", variants.join(" | "))
+            }
+            TypeDef::Sequence(seq) => {
+                format!("[{}]", describe(types, seq.type_param.id, visiting))
+            }
+            TypeDef::Array(array) => {
+                format!(
+                    "[{}; {}]",
+                    describe(types, array.type_param.id, visiting),
+                    array.len
+                )
+            }
+            TypeDef::Tuple(tuple) => {
+                let fields = tuple
+                    .fields
+                    .iter()
+                    .map(|field| describe(types, field.id, visiting))
+                    .collect::<Vec<_>>();
+                format!("({})", fields.join(", "))
+            }
+            TypeDef::Primitive(primitive) => format!("{primitive:?}").to_lowercase(),
+            TypeDef::Compact(compact) => {
+                format!(
+                    "Compact<{}>",
+                    describe(types, compact.type_param.id, visiting)
+                )
+            }
+            TypeDef::BitSequence(_) => "BitSequence".to_string(),
+        };
+        visiting.pop();
+        match params.is_empty() {
+            true => format!("{path}{def}"),
+            false => format!("{path}<{}>{def}", params.join(", ")),
+        }
+    }
+    describe(types, id, &mut Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale_info::{
+        Registry,
+        TypeInfo,
+    };
+
+    #[allow(dead_code)]
+    #[derive(TypeInfo)]
+    struct Account {
+        owner: [u8; 32],
+        balance: u128,
+        history: Vec<u64>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(TypeInfo)]
+    enum Tree {
+        Leaf(u32),
+        Node(Box<Tree>, Box<Tree>),
+    }
+
+    fn portable<T: TypeInfo + 'static>() -> (PortableRegistry, u32) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&scale_info::meta_type::<T>()).id;
+        (registry.into(), id)
+    }
+
+    #[test]
+    fn describe_type_works() {
+        let (types, id) = portable::<Account>();
+
+        assert_eq!(
+            describe_type(&types, id),
+            "cargo_contract::cmd::schema::tests::Account { owner: [u8; 32], \
+            balance: u128, history: [u64] }"
+        );
+    }
+
+    #[test]
+    fn describe_recursive_type_works() {
+        let (types, id) = portable::<Tree>();
+
+        assert!(describe_type(&types, id).ends_with(
+            "Tree { Leaf(u32) | Node(cargo_contract::cmd::schema::tests::Tree, \
+            cargo_contract::cmd::schema::tests::Tree) }"
+        ));
+    }
+
+    #[test]
+    fn compare_callables_classifies_changes() {
+        let callable = |selector, signature: &str, payable| {
+            Callable {
+                selector,
+                signature: signature.to_string(),
+                payable,
+                mutates: true,
+            }
+        };
+        let old = BTreeMap::from([
+            ("flip".to_string(), callable([1; 4], "() -> ()", true)),
+            ("get".to_string(), callable([2; 4], "() -> bool", false)),
+            (
+                "set".to_string(),
+                callable([3; 4], "(value: bool) -> ()", false),
+            ),
+        ]);
+        let new = BTreeMap::from([
+            ("flip".to_string(), callable([1; 4], "() -> ()", false)),
+            ("get".to_string(), callable([4; 4], "() -> bool", false)),
+            (
+                "get_all".to_string(),
+                callable([5; 4], "() -> [bool]", false),
+            ),
+        ]);
+        let mut diff = MetadataDiff {
+            breaking: Vec::new(),
+            non_breaking: Vec::new(),
+            allow_breaking: false,
+            output_json: false,
+        };
+
+        diff.compare_callables("message", old, new, true);
+
+        assert_eq!(
+            diff.breaking,
+            vec![
+                "message `flip` payable changed from true to false",
+                "message `get` selector changed from 0x02020202 to 0x04040404",
+                "message `set` removed",
+            ]
+        );
+        assert_eq!(
+            diff.non_breaking,
+            vec!["message `get_all` added with selector 0x05050505"]
+        );
+        assert!(diff.check().is_err());
+        diff.allow_breaking = true;
+        assert!(diff.check().is_ok());
+    }
+}
@@ -14,15 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
-use anyhow::Result;
+use anyhow::{
+    Context,
+    Result,
+};
 use colored::Colorize;
 use comfy_table::{
     ContentArrangement,
     Table,
 };
+use contract_build::util::decode_hex;
 use contract_extrinsics::{
     ContractArtifacts,
     ContractStorage,
+    ContractStorageData,
     ContractStorageLayout,
     ContractStorageRpc,
     ErrorVariant,
@@ -35,6 +40,7 @@ use std::{
     str::FromStr,
 };
 use subxt::{
+    backend::legacy::rpc_methods::Bytes,
     ext::scale_decode::IntoVisitor,
     Config,
 };
@@ -42,6 +48,7 @@ use subxt::{
 use crate::call_with_config;
 
 use super::{
+    deployment::contract_or_last_deployment,
     parse_account,
     CLIChainOpts,
 };
@@ -49,20 +56,24 @@ use super::{
 #[derive(Debug, clap::Args)]
 #[clap(name = "storage", about = "Inspect contract storage")]
 pub struct StorageCommand {
-    /// The address of the contract to inspect storage of.
-    #[clap(
-        name = "contract",
-        long,
-        env = "CONTRACT",
-        required_unless_present = "version"
-    )]
+    /// The address of the contract to inspect storage of. Defaults to the contract last
+    /// instantiated from this project on the same node.
+    #[clap(name = "contract", long, env = "CONTRACT")]
     contract: Option<String>,
     /// Fetch the "raw" storage keys and values for the contract.
     #[clap(long)]
     raw: bool,
-    /// Export the instantiate output in JSON format.
+    /// Decode the storage cells using the contract's storage layout, showing the raw
+    /// hex value of cells which can not be decoded instead of failing.
+    #[clap(long, conflicts_with = "raw")]
+    decode: bool,
+    /// Export the storage entries in JSON format, each with its root key, mapping key,
+    /// decoded value and raw hex value.
     #[clap(name = "output-json", long, conflicts_with = "raw")]
     output_json: bool,
+    /// Write the JSON output to the given file instead of stdout.
+    #[clap(long, value_parser, requires = "output-json")]
+    output_file: Option<PathBuf>,
     /// Path to a contract build artifact file: a raw `.wasm` file, a `.contract` bundle,
     /// or a `.json` metadata file.
     #[clap(value_parser, conflicts_with = "manifest_path")]
@@ -70,6 +81,19 @@ pub struct StorageCommand {
     /// Path to the `Cargo.toml` of the contract.
     #[clap(long, value_parser)]
     manifest_path: Option<PathBuf>,
+    /// Fetch at most the given number of raw storage keys. If there are more keys, the
+    /// key to continue from is printed.
+    ///
+    /// Pages are cut at raw storage keys, not at decoded entries. The keys are ordered
+    /// by their hash, so the entries of a `Mapping` or `StorageVec` are spread over
+    /// all pages: these cells are flagged as truncated. Keys without a value are
+    /// skipped so a page may hold fewer entries than the limit.
+    #[clap(long)]
+    limit: Option<u32>,
+    /// Fetch the storage entries following the given hex encoded key, as printed for
+    /// a previous `--limit`.
+    #[clap(long, requires = "limit")]
+    start_key: Option<String>,
     /// Fetch the storage version of the pallet contracts (state query:
     /// contracts::palletVersion()).
     #[clap(long, short)]
@@ -81,7 +105,13 @@ pub struct StorageCommand {
 
 impl StorageCommand {
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(self, run, self.chain_cli_opts.chain().config())
+        call_with_config!(
+            self,
+            run,
+            self.chain_cli_opts
+                .chain(self.manifest_path.as_deref())?
+                .config()
+        )
     }
 
     pub async fn run<C: Config + Environment>(&self) -> Result<(), ErrorVariant>
@@ -92,26 +122,29 @@ impl StorageCommand {
         C::Balance: Serialize + IntoVisitor,
         <C as Config>::Hash: IntoVisitor,
     {
+        let url = self
+            .chain_cli_opts
+            .chain(self.manifest_path.as_deref())?
+            .url();
         let rpc =
-            ContractStorageRpc::<C>::new(&self.chain_cli_opts.chain().url()).await?;
+            ContractStorageRpc::<C>::new(&url, &self.chain_cli_opts.rpc_connection())
+                .await?
+                .pallet_name(self.chain_cli_opts.pallet_name());
         let storage_layout = ContractStorage::<C, C>::new(rpc);
         if self.version {
             println!("{}", storage_layout.version().await?);
             return Ok(())
         }
 
-        // Contract arg shall be always present in this case, it is enforced by
-        // clap configuration
-        let contract = self
-            .contract
-            .as_ref()
-            .map(|c| parse_account(c))
-            .transpose()?
-            .expect("Contract argument shall be present");
+        let contract = contract_or_last_deployment(
+            self.contract.as_ref(),
+            self.manifest_path.as_ref(),
+            &url,
+        )?;
+        let contract = parse_account(&contract)?;
 
         if self.raw {
-            let storage_data =
-                storage_layout.load_contract_storage_data(&contract).await?;
+            let storage_data = self.load_storage_data(&storage_layout, &contract).await?;
             println!(
                 "{json}",
                 json = serde_json::to_string_pretty(&storage_data)?
@@ -127,26 +160,46 @@ impl StorageCommand {
         match contract_artifacts {
             Ok(contract_artifacts) => {
                 let transcoder = contract_artifacts.contract_transcoder()?;
-                let contract_storage = storage_layout
-                    .load_contract_storage_with_layout(&contract, &transcoder)
-                    .await?;
+                let data = self.load_storage_data(&storage_layout, &contract).await?;
+                let contract_storage = if self.decode {
+                    ContractStorageLayout::new_lossy(data, &transcoder)?
+                } else {
+                    ContractStorageLayout::new(data, &transcoder)?
+                };
+                for warning in contract_storage.warnings() {
+                    eprintln!("{} {}", "warning:".yellow().bold(), warning);
+                }
                 if self.output_json {
-                    println!(
-                        "{json}",
-                        json = serde_json::to_string_pretty(&contract_storage)?
-                    );
+                    let json = contract_storage.to_json()?;
+                    match &self.output_file {
+                        Some(output_file) => {
+                            std::fs::write(output_file, json).with_context(|| {
+                                format!(
+                                    "Failed to write storage to {}",
+                                    output_file.display()
+                                )
+                            })?
+                        }
+                        None => println!("{json}"),
+                    }
                 } else {
                     let table = StorageDisplayTable::new(&contract_storage);
                     table.display();
                 }
             }
+            Err(err) if self.decode => {
+                return Err(anyhow::anyhow!(
+                    "Decoding storage requires the contract metadata: {err}"
+                )
+                .into())
+            }
             Err(_) => {
                 eprintln!(
                     "{} Displaying raw storage: no valid contract metadata artifacts found",
                     "Info:".cyan().bold(),
                 );
                 let storage_data =
-                    storage_layout.load_contract_storage_data(&contract).await?;
+                    self.load_storage_data(&storage_layout, &contract).await?;
                 println!(
                     "{json}",
                     json = serde_json::to_string_pretty(&storage_data)?
@@ -156,6 +209,39 @@ impl StorageCommand {
 
         Ok(())
     }
+
+    /// Load the raw storage of the contract, only a single page if `--limit` is set.
+    async fn load_storage_data<C: Config + Environment>(
+        &self,
+        storage_layout: &ContractStorage<C, C>,
+        contract: &<C as Config>::AccountId,
+    ) -> Result<ContractStorageData>
+    where
+        <C as Config>::AccountId: Display + IntoVisitor + AsRef<[u8]>,
+        C::Balance: Serialize + IntoVisitor,
+        <C as Config>::Hash: IntoVisitor,
+    {
+        let Some(limit) = self.limit else {
+            return storage_layout.load_contract_storage_data(contract).await
+        };
+        let start_key = self
+            .start_key
+            .as_ref()
+            .map(|key| decode_hex(key).map(Bytes::from))
+            .transpose()
+            .context("Failed to parse start-key option")?;
+        let (storage_data, next_key) = storage_layout
+            .load_contract_storage_data_page(contract, start_key.as_ref(), limit)
+            .await?;
+        if let Some(next_key) = next_key {
+            eprintln!(
+                "{} More storage entries available, continue with --start-key 0x{}",
+                "Info:".cyan().bold(),
+                hex::encode(&next_key.0)
+            );
+        }
+        Ok(storage_data)
+    }
 }
 
 struct StorageDisplayTable(Table);
@@ -187,7 +273,10 @@ impl StorageDisplayTable {
 
     fn table_add_rows(table: &mut Table, storage_layout: &ContractStorageLayout) {
         for (index, cell) in storage_layout.iter().enumerate() {
-            let formatted_cell = format!("{cell}");
+            let mut formatted_cell = format!("{cell}");
+            if cell.is_truncated() {
+                formatted_cell.push_str("\n... (truncated, more entries on other pages)");
+            }
             let values = formatted_cell.split('\n');
             for (i, v) in values.enumerate() {
                 table.add_row(vec![
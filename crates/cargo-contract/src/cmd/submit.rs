@@ -0,0 +1,130 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    call_with_config,
+    ErrorVariant,
+};
+use std::{
+    fmt::{
+        Debug,
+        Display,
+    },
+    path::PathBuf,
+    time::Duration,
+};
+
+use super::CLIChainOpts;
+use anyhow::{
+    Context,
+    Result,
+};
+use contract_build::{
+    util::decode_hex,
+    Verbosity,
+    VerbosityFlags,
+};
+use contract_extrinsics::{
+    submit_signed_extrinsic,
+    url_to_string,
+    DisplayEvents,
+    TokenMetadata,
+    TokenMetadataOverride,
+};
+use ink_env::Environment;
+use subxt::{
+    backend::legacy::LegacyRpcMethods,
+    ext::scale_decode::IntoVisitor,
+    Config,
+    OnlineClient,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(name = "submit", about = "Submit a signed extrinsic")]
+pub struct SubmitCommand {
+    /// Path to a file containing the hex encoded signed extrinsic, as written by
+    /// `--offline-output`.
+    #[clap(value_parser)]
+    file: PathBuf,
+    /// Fail if the transaction is not included in a block within the given number of
+    /// seconds. The error contains the transaction hash to look it up later on.
+    #[clap(long)]
+    tx_timeout: Option<u64>,
+    #[clap(flatten)]
+    verbosity: VerbosityFlags,
+    /// Export the submission output in JSON format.
+    #[clap(long, conflicts_with = "verbose")]
+    output_json: bool,
+    /// Arguments required for communicating with a Substrate node.
+    #[clap(flatten)]
+    chain_cli_opts: CLIChainOpts,
+}
+
+impl SubmitCommand {
+    /// Returns whether to export the call output in JSON format.
+    pub fn output_json(&self) -> bool {
+        self.output_json
+    }
+
+    pub async fn handle(&self) -> Result<(), ErrorVariant> {
+        call_with_config!(self, run, self.chain_cli_opts.chain(None)?.config())
+    }
+
+    async fn run<C: Config + Environment>(&self) -> Result<(), ErrorVariant>
+    where
+        <C as Config>::AccountId: IntoVisitor,
+        C::Balance: From<u128> + Display + Debug,
+    {
+        let extrinsic = std::fs::read_to_string(&self.file).with_context(|| {
+            format!("Failed to read signed extrinsic {}", self.file.display())
+        })?;
+        let extrinsic = decode_hex(extrinsic.trim())
+            .context("Failed to decode the signed extrinsic as hex")?;
+
+        let url = self.chain_cli_opts.chain(None)?.url();
+        let rpc_cli = self
+            .chain_cli_opts
+            .rpc_connection()
+            .connect(&url_to_string(&url))
+            .await?;
+        let client = OnlineClient::<C>::from_rpc_client(rpc_cli.clone()).await?;
+        let rpc = LegacyRpcMethods::<C>::new(rpc_cli);
+        let token_metadata =
+            TokenMetadata::query_rpc(&rpc, &TokenMetadataOverride::default()).await?;
+
+        let events = submit_signed_extrinsic(
+            &client,
+            extrinsic,
+            self.tx_timeout.map(Duration::from_secs),
+        )
+        .await?;
+        let display_events = DisplayEvents::from_events::<C, C>(
+            &events,
+            None,
+            &client.metadata(),
+            self.chain_cli_opts.pallet_name(),
+        )?;
+
+        let output = if self.output_json() {
+            display_events.to_json()?
+        } else {
+            let verbosity: Verbosity = TryFrom::try_from(&self.verbosity)?;
+            display_events.display_events::<C>(verbosity, &token_metadata)?
+        };
+        println!("{output}");
+        Ok(())
+    }
+}
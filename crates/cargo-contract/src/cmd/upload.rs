@@ -27,10 +27,13 @@ use std::{
 };
 
 use super::{
+    check_debug_build_for_production,
     config::SignerConfig,
     display_dry_run_result_warning,
-    parse_balance,
+    display_multisig_result,
+    parse_multisig,
     prompt_confirm_unverifiable_upload,
+    write_signed_extrinsic,
     CLIExtrinsicOpts,
 };
 use anyhow::Result;
@@ -38,9 +41,10 @@ use contract_build::name_value_println;
 use contract_extrinsics::{
     DisplayEvents,
     ExtrinsicOptsBuilder,
-    TokenMetadata,
     UploadCommandBuilder,
     UploadExec,
+    UploadOutcome,
+    UploadStatus,
 };
 use ink_env::Environment;
 use serde::Serialize;
@@ -50,6 +54,7 @@ use subxt::{
         ExtrinsicParams,
     },
     ext::{
+        codec::Decode,
         scale_decode::IntoVisitor,
         scale_encode::EncodeAsType,
     },
@@ -64,6 +69,17 @@ pub struct UploadCommand {
     /// Export the call output in JSON format.
     #[clap(long, conflicts_with = "verbose")]
     output_json: bool,
+    /// Allow uploading code built in debug mode to a production chain.
+    #[clap(long)]
+    allow_debug: bool,
+    /// Submit the extrinsic as one of the signatories of a multisig account, given as
+    /// `<threshold>:<signatory>,<signatory>,...`. The call is dispatched from the
+    /// multisig account once enough signatories submitted the same command.
+    #[clap(long, requires = "execute", conflicts_with = "offline_output")]
+    multisig: Option<String>,
+    /// Submit the upload even if the code is already stored on-chain.
+    #[clap(long, requires = "execute")]
+    force: bool,
 }
 
 impl UploadCommand {
@@ -73,18 +89,15 @@ impl UploadCommand {
     }
 
     pub async fn handle(&self) -> Result<(), ErrorVariant> {
-        call_with_config!(
-            self,
-            run,
-            self.extrinsic_cli_opts.chain_cli_opts.chain().config()
-        )
+        call_with_config!(self, run, self.extrinsic_cli_opts.chain()?.config())
     }
 
     async fn run<C: Config + Environment + SignerConfig<C>>(
         &self,
     ) -> Result<(), ErrorVariant>
     where
-        <C as Config>::AccountId: IntoVisitor + FromStr + EncodeAsType,
+        <C as Config>::AccountId:
+            IntoVisitor + FromStr + EncodeAsType + Decode + PartialEq + Display,
         <<C as Config>::AccountId as FromStr>::Err: Display,
         C::Balance: Into<u128>
             + From<u128>
@@ -98,28 +111,45 @@ impl UploadCommand {
             From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
         <C as Config>::Hash: IntoVisitor + EncodeAsType + From<[u8; 32]>,
     {
-        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri)
+        let signer = C::Signer::from_str(&self.extrinsic_cli_opts.suri()?)
             .map_err(|_| anyhow::anyhow!("Failed to parse suri option"))?;
-        let chain = self.extrinsic_cli_opts.chain_cli_opts.chain();
-        let token_metadata = TokenMetadata::query::<C>(&chain.url()).await?;
+        let chain = self.extrinsic_cli_opts.chain()?;
+        let token_metadata = self
+            .extrinsic_cli_opts
+            .token_metadata::<C>(&chain.url())
+            .await?;
         let storage_deposit_limit = self
             .extrinsic_cli_opts
-            .storage_deposit_limit
-            .clone()
-            .map(|b| parse_balance(&b, &token_metadata))
-            .transpose()
-            .map_err(|e| {
-                anyhow::anyhow!("Failed to parse storage_deposit_limit option: {}", e)
-            })?;
+            .storage_deposit_limit(&token_metadata)?;
         let extrinsic_opts = ExtrinsicOptsBuilder::new(signer)
             .file(self.extrinsic_cli_opts.file.clone())
             .manifest_path(self.extrinsic_cli_opts.manifest_path.clone())
             .url(chain.url())
             .storage_deposit_limit(storage_deposit_limit)
+            .mortal(self.extrinsic_cli_opts.mortal)
+            .mortal_checkpoint(self.extrinsic_cli_opts.mortal_checkpoint()?)
+            .nonce(self.extrinsic_cli_opts.nonce)
+            .tip(self.extrinsic_cli_opts.tip(&token_metadata)?)
+            .ss58_prefix(self.extrinsic_cli_opts.ss58_prefix())
+            .pallet_name(self.extrinsic_cli_opts.chain_cli_opts.pallet_name.clone())
+            .rpc_retries(self.extrinsic_cli_opts.rpc_retries)
+            .rpc_retry_delay(self.extrinsic_cli_opts.rpc_retry_delay())
+            .tx_timeout(self.extrinsic_cli_opts.tx_timeout())
+            .ws_keepalive(self.extrinsic_cli_opts.ws_keepalive())
+            .verbosity(self.extrinsic_cli_opts.verbosity()?)
             .done();
 
-        let upload_exec: UploadExec<C, C, _> =
-            UploadCommandBuilder::new(extrinsic_opts).done().await?;
+        let upload_builder = UploadCommandBuilder::new(extrinsic_opts);
+
+        if let Some(offline_output) = &self.extrinsic_cli_opts.offline_output {
+            let client = self
+                .extrinsic_cli_opts
+                .offline_client::<C>(false, None, None)?;
+            let extrinsic = upload_builder.sign_offline(&client)?;
+            write_signed_extrinsic(offline_output, &extrinsic)?;
+            return Ok(())
+        }
+        let upload_exec: UploadExec<C, C, _> = upload_builder.done().await?;
         let code_hash = upload_exec.code().code_hash();
         let metadata = upload_exec.client().metadata();
 
@@ -139,7 +169,11 @@ impl UploadCommand {
                     }
                 }
                 Err(err) => {
-                    let err = ErrorVariant::from_dispatch_error(&err, &metadata)?;
+                    let err = ErrorVariant::from_dispatch_error(
+                        &err,
+                        &metadata,
+                        upload_exec.opts().pallet_name(),
+                    )?;
                     if self.output_json() {
                         return Err(err)
                     } else {
@@ -148,16 +182,49 @@ impl UploadCommand {
                 }
             }
         } else {
+            if !self.force && upload_exec.code_exists().await? {
+                let code_hash: <C as Config>::Hash = code_hash.into();
+                if self.output_json() {
+                    let outcome =
+                        UploadOutcome::<_, C::Balance>::already_exists(code_hash);
+                    println!("{}", outcome.to_json()?);
+                } else {
+                    name_value_println!(
+                        "Result",
+                        format!("Code already uploaded (hash {:?})", code_hash)
+                    );
+                }
+                return Ok(())
+            }
             if let Some(chain) = chain.production() {
+                check_debug_build_for_production(
+                    &chain.to_string(),
+                    upload_exec.opts().contract_artifacts()?.build_mode(),
+                    self.allow_debug,
+                )?;
                 if !upload_exec.opts().contract_artifacts()?.is_verifiable() {
                     prompt_confirm_unverifiable_upload(&chain.to_string())?
                 }
             }
+            if let Some(multisig) = &self.multisig {
+                let multisig = parse_multisig(multisig)?;
+                let result = upload_exec.upload_code_multisig(&multisig).await?;
+                display_multisig_result(
+                    &result,
+                    &metadata,
+                    upload_exec.opts().pallet_name(),
+                    &token_metadata,
+                    self.output_json(),
+                    self.extrinsic_cli_opts.verbosity()?,
+                )?;
+                return Ok(())
+            }
             let upload_result = upload_exec.upload_code().await?;
             let display_events = DisplayEvents::from_events::<C, C>(
                 &upload_result.events,
                 None,
                 &metadata,
+                upload_exec.opts().pallet_name(),
             )?;
             let output_events = if self.output_json() {
                 display_events.to_json()?
@@ -167,18 +234,21 @@ impl UploadCommand {
                     &token_metadata,
                 )?
             };
-            if let Some(code_stored) = upload_result.code_stored {
-                let code_hash: <C as Config>::Hash = code_stored.code_hash;
-                if self.output_json() {
-                    // Create a JSON object with the events and the code hash.
-                    let json_object = serde_json::json!({
-                        "events": serde_json::from_str::<serde_json::Value>(&output_events)?,
-                        "code_hash": code_hash,
-                    });
-                    println!("{}", serde_json::to_string_pretty(&json_object)?);
-                } else {
-                    println!("{}", output_events);
-                    name_value_println!("Code hash", format!("{:?}", code_hash));
+            if self.output_json() {
+                // Report already existing code as well, so that scripts can upload
+                // idempotently.
+                let mut json_object = serde_json::to_value(upload_result.outcome())?;
+                json_object["events"] =
+                    serde_json::from_str::<serde_json::Value>(&output_events)?;
+                println!("{}", serde_json::to_string_pretty(&json_object)?);
+            } else if upload_result.status() == UploadStatus::Uploaded {
+                println!("{}", output_events);
+                name_value_println!(
+                    "Code hash",
+                    format!("{:?}", upload_result.code_hash)
+                );
+                if let Some(deposit) = upload_result.deposit {
+                    name_value_println!("Deposit", format!("{:?}", deposit));
                 }
             } else {
                 let code_hash = hex::encode(code_hash);
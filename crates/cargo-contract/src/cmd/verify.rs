@@ -14,6 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::call_with_config;
+
+use super::{
+    parse_account,
+    CLIChainOpts,
+};
 use anyhow::{
     Context,
     Result,
@@ -22,6 +28,7 @@ use colored::Colorize;
 use contract_build::{
     code_hash,
     execute,
+    image_digest,
     verbose_eprintln,
     BuildArtifacts,
     BuildInfo,
@@ -32,14 +39,39 @@ use contract_build::{
     Verbosity,
     VerbosityFlags,
 };
+use contract_extrinsics::{
+    fetch_contract_info,
+    fetch_wasm_code,
+    url_to_string,
+    ErrorVariant,
+};
 use contract_metadata::{
     CodeHash,
     ContractMetadata,
 };
+use ink_env::Environment;
+use subxt::{
+    backend::{
+        legacy::LegacyRpcMethods,
+        rpc::RpcClient,
+    },
+    ext::scale_decode::IntoVisitor,
+    Config,
+    OnlineClient,
+};
 
 use std::{
-    fs::File,
-    path::PathBuf,
+    fmt::Display,
+    fs::{
+        self,
+        File,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::Command,
+    str::FromStr,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -65,12 +97,37 @@ pub struct VerifyCommand {
     /// Output the result in JSON format
     #[clap(long, conflicts_with = "verbose")]
     output_json: bool,
+    /// The source archive (`.tar.gz`) of a contract, given as a URL or a path, to verify
+    /// against the code of the contract deployed at `--address`.
+    ///
+    /// The archive is built with a verifiable build, use `--manifest-path` to select the
+    /// contract relative to the root of the archive.
+    #[clap(
+        long,
+        value_name = "url|path",
+        conflicts_with_all = ["contract", "wasm"],
+        requires = "address"
+    )]
+    source_archive: Option<String>,
+    /// The address of the deployed contract to verify the `--source-archive` against.
+    #[clap(long, requires = "source_archive")]
+    address: Option<String>,
+    /// The image used for the verifiable build of the `--source-archive`.
+    #[clap(long, requires = "source_archive")]
+    image: Option<String>,
+    /// Arguments required for fetching the code of the deployed contract.
+    #[clap(flatten)]
+    chain_cli_opts: CLIChainOpts,
 }
 
 impl VerifyCommand {
     pub fn run(&self) -> Result<VerificationResult> {
-        let manifest_path = ManifestPath::try_from(self.manifest_path.as_ref())?;
         let verbosity: Verbosity = TryFrom::<&VerbosityFlags>::try_from(&self.verbosity)?;
+        if let (Some(archive), Some(address)) = (&self.source_archive, &self.address) {
+            // the manifest path is relative to the archive, which is not unpacked yet
+            return self.verify_source_archive(verbosity, archive, address)
+        }
+        let manifest_path = ManifestPath::try_from(self.manifest_path.as_ref())?;
         if let Some(path) = &self.contract {
             self.verify_contract(manifest_path, verbosity, path)
         } else if let Some(path) = &self.wasm {
@@ -192,11 +249,11 @@ impl VerifyCommand {
         // 2. Check that the build info from the metadata matches our current setup.
         // if the build mode is `Verifiable` we skip
         if build_mode != BuildMode::Verifiable {
-            let expected_rust_toolchain = build_info.rust_toolchain;
+            let expected_rust_toolchain = &build_info.rust_toolchain;
             let rust_toolchain = contract_build::util::rust_toolchain()
                 .expect("`rustc` always has a version associated with it.");
 
-            let rustc_matches = rust_toolchain == expected_rust_toolchain;
+            let rustc_matches = &rust_toolchain == expected_rust_toolchain;
             let mismatched_rustc = format!(
             "\nYou are trying to `verify` a contract using the `{rust_toolchain}` toolchain.\n\
              However, the original contract was built using `{expected_rust_toolchain}`. Please\n\
@@ -204,14 +261,14 @@ impl VerifyCommand {
              re-run the `verify` command.",);
             anyhow::ensure!(rustc_matches, mismatched_rustc.bright_yellow());
 
-            let expected_cargo_contract_version = build_info.cargo_contract_version;
+            let expected_cargo_contract_version = &build_info.cargo_contract_version;
             let cargo_contract_version = semver::Version::parse(VERSION)?;
 
             // Note, assuming both versions of `cargo-contract` were installed with the
             // same lockfile (e.g `--locked`) then the versions of `wasm-opt`
             // should also match.
             let cargo_contract_matches =
-                cargo_contract_version == expected_cargo_contract_version;
+                &cargo_contract_version == expected_cargo_contract_version;
             let mismatched_cargo_contract = format!(
                 "\nYou are trying to `verify` a contract using `cargo-contract` version \
             `{cargo_contract_version}`.\n\
@@ -233,6 +290,7 @@ impl VerifyCommand {
             build_artifact: BuildArtifacts::All,
             optimization_passes: Some(build_info.wasm_opt_settings.optimization_passes),
             keep_debug_symbols: build_info.wasm_opt_settings.keep_debug_symbols,
+            rustflags: build_info.rustflags.clone(),
             image: ImageVariant::from(metadata.image.clone()),
             extra_lints: false,
             ..Default::default()
@@ -269,6 +327,69 @@ impl VerifyCommand {
             ))?;
 
         let target_code_hash = built_contract.source.hash;
+        let built_build_info = built_contract
+            .source
+            .build_info
+            .clone()
+            .and_then(|info| serde_json::from_value::<BuildInfo>(info.into()).ok());
+
+        // Only an image pinned by its digest guarantees the same toolchain was used.
+        if let Some(reference_image) = &metadata.image {
+            match image_digest(reference_image)? {
+                Some(reference_digest) => {
+                    let built_image = built_contract.image.as_deref().unwrap_or_default();
+                    let built_digest = image_digest(built_image)?;
+                    anyhow::ensure!(
+                        built_digest == Some(reference_digest),
+                        format!(
+                            "\nThe reference contract was built with the image \
+                            `{reference_image}`,\nbut the image `{built_image}` was used to \
+                            verify it."
+                        )
+                        .bright_red()
+                    );
+                }
+                None => {
+                    eprintln!(
+                        "{} {}",
+                        "warning:".yellow().bold(),
+                        format!(
+                            "The reference contract was built with the image \
+                            `{reference_image}`, which is not pinned by its digest. The \
+                            image might have changed since."
+                        )
+                        .bold()
+                    );
+                }
+            }
+        }
+
+        // Diverging dependencies are the most likely reason for a code hash mismatch,
+        // so we warn about them before comparing the code hashes.
+        if let Some(expected_cargo_lock_hash) = build_info.cargo_lock_hash {
+            let cargo_lock_hash = built_build_info
+                .as_ref()
+                .and_then(|info| info.cargo_lock_hash);
+            if cargo_lock_hash != Some(expected_cargo_lock_hash) {
+                let cargo_lock_hash = cargo_lock_hash
+                    .map(|hash| format!("0x{}", hex::encode(hash)))
+                    .unwrap_or_else(|| "none".to_string());
+                eprintln!(
+                    "{} {}",
+                    "warning:".yellow().bold(),
+                    format!(
+                        "The `Cargo.lock` of the workspace does not match the one the \
+                        reference contract was built with.\n\
+                        Expected Cargo.lock hash: `0x{}`\n\
+                        Got Cargo.lock hash: `{cargo_lock_hash}`\n\
+                        The dependencies may have been resolved differently, use the \
+                        original `Cargo.lock` to reproduce the build.",
+                        hex::encode(expected_cargo_lock_hash),
+                    )
+                    .bold()
+                );
+            }
+        }
 
         if reference_code_hash != target_code_hash {
             verbose_eprintln!(
@@ -277,12 +398,35 @@ impl VerifyCommand {
                 &reference_code_hash,
                 &target_code_hash
             );
+            let diff = match &built_build_info {
+                Some(built_build_info) => {
+                    build_info_diff(
+                        &build_info,
+                        metadata.image.as_deref(),
+                        built_build_info,
+                        built_contract.image.as_deref(),
+                    )
+                }
+                None => vec!["build info: missing from the workspace build".to_string()],
+            };
+            let reason = if diff.is_empty() {
+                "The build info matches the reference contract, the source code or the \
+                dependencies differ."
+                    .to_string()
+            } else {
+                format!(
+                    "The build info differs from the reference contract:\n  {}",
+                    diff.join("\n  ")
+                )
+            };
             anyhow::bail!(format!(
                 "\nFailed to verify the authenticity of {} contract against the workspace \n\
-                found at {}.",
+                found at {}.\n{}",
                 format!("`{}`", metadata.contract.name).bright_white(),
-                format!("{:?}", manifest_path.as_ref()).bright_white()).bright_red()
-            );
+                format!("{:?}", manifest_path.as_ref()).bright_white(),
+                reason
+            )
+            .bright_red());
         }
 
         Ok(VerificationResult {
@@ -294,6 +438,263 @@ impl VerifyCommand {
             verbosity,
         })
     }
+
+    /// Verify a source archive against the code of a deployed contract.
+    fn verify_source_archive(
+        &self,
+        verbosity: Verbosity,
+        archive: &str,
+        address: &str,
+    ) -> Result<VerificationResult> {
+        // 1. Fetch the code of the deployed contract.
+        let runtime = tokio::runtime::Runtime::new()?;
+        let reference_code = runtime
+            .block_on(async {
+                call_with_config!(
+                    self,
+                    fetch_contract_code,
+                    self.chain_cli_opts
+                        .chain(self.manifest_path.as_deref())?
+                        .config()
+                )
+            })
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let reference_code_hash = CodeHash(code_hash(&reference_code));
+
+        // 2. Download and unpack the source archive.
+        let source_dir = tempfile::tempdir()?;
+        let archive_path =
+            if archive.starts_with("http://") || archive.starts_with("https://") {
+                let path = source_dir.path().join("source.tar.gz");
+                runtime.block_on(download(archive, &path))?;
+                path
+            } else {
+                PathBuf::from(archive)
+            };
+        let unpacked = source_dir.path().join("source");
+        fs::create_dir(&unpacked)?;
+        unpack_archive(&archive_path, &unpacked)?;
+        let manifest_path =
+            archive_manifest_path(&unpacked, self.manifest_path.as_deref())?;
+
+        // 3. Build the contract in the archive with a verifiable build.
+        let args = ExecuteArgs {
+            manifest_path,
+            verbosity,
+            build_mode: BuildMode::Verifiable,
+            build_artifact: BuildArtifacts::All,
+            image: ImageVariant::from(self.image.clone()),
+            extra_lints: false,
+            ..Default::default()
+        };
+        let build_result = execute(args)?;
+        let Some(metadata_result) = build_result.metadata_result else {
+            anyhow::bail!(
+                "\nThe build of the source archive does not contain a contract bundle,\n\
+                therefore we are unable to verify the contract."
+                    .to_string()
+                    .bright_yellow()
+            )
+        };
+        let target_bundle = &metadata_result.dest_bundle;
+        let file = File::open(target_bundle).context(format!(
+            "Failed to open contract bundle {}",
+            target_bundle.display()
+        ))?;
+        let built_contract: ContractMetadata =
+            serde_json::from_reader(file).context(format!(
+                "Failed to deserialize contract bundle {}",
+                target_bundle.display()
+            ))?;
+
+        // 4. Compare the code hash of the build with the one of the deployed code.
+        let target_code_hash = built_contract.source.hash;
+        if reference_code_hash != target_code_hash {
+            let rust_toolchain = built_contract
+                .source
+                .build_info
+                .and_then(|info| serde_json::from_value::<BuildInfo>(info.into()).ok())
+                .map(|info| info.rust_toolchain)
+                .unwrap_or_else(|| "unknown".to_string());
+            let image = built_contract.image.as_deref().unwrap_or("unknown");
+            anyhow::bail!(format!(
+                "\nFailed to verify the authenticity of the contract at {} against the \
+                source archive {}.\n\
+                Expected Code Hash: `{}`\n\
+                Got Code Hash: `{}`\n\
+                The source archive was built with:\n\
+                Rust toolchain: `{}`\n\
+                Image: `{}`\n\
+                Make sure they match the ones used to build the deployed contract.",
+                format!("`{address}`").bright_white(),
+                format!("`{archive}`").bright_white(),
+                reference_code_hash,
+                target_code_hash,
+                rust_toolchain,
+                image,
+            )
+            .bright_red());
+        }
+
+        Ok(VerificationResult {
+            is_verified: true,
+            image: built_contract.image,
+            contract: archive.to_string(),
+            reference_contract: address.to_string(),
+            output_json: self.output_json,
+            verbosity,
+        })
+    }
+
+    /// Fetch the code of the contract deployed at `--address`.
+    async fn fetch_contract_code<C: Config + Environment>(
+        &self,
+    ) -> Result<Vec<u8>, ErrorVariant>
+    where
+        <C as Config>::AccountId: IntoVisitor + FromStr + Display + AsRef<[u8]>,
+        <<C as Config>::AccountId as FromStr>::Err: Display,
+        <C as Config>::Hash: IntoVisitor + Display,
+        <C as Environment>::Balance: IntoVisitor + serde::Serialize,
+    {
+        let address = self
+            .address
+            .as_ref()
+            .expect("--address is required with --source-archive");
+        let contract = parse_account(address)
+            .map_err(|e| anyhow::anyhow!("Failed to parse address option: {}", e))?;
+        let rpc_cli = RpcClient::from_url(url_to_string(
+            &self
+                .chain_cli_opts
+                .chain(self.manifest_path.as_deref())?
+                .url(),
+        ))
+        .await?;
+        let client = OnlineClient::<C>::from_rpc_client(rpc_cli.clone()).await?;
+        let rpc = LegacyRpcMethods::<C>::new(rpc_cli);
+        let pallet_name = self.chain_cli_opts.pallet_name();
+        let info =
+            fetch_contract_info::<C, C>(&contract, &rpc, &client, pallet_name).await?;
+        Ok(fetch_wasm_code(&client, &rpc, info.code_hash(), pallet_name).await?)
+    }
+}
+
+/// Lists the fields of the build info, including the image of verifiable builds, which
+/// differ between the reference contract and the workspace build.
+fn build_info_diff(
+    expected: &BuildInfo,
+    expected_image: Option<&str>,
+    actual: &BuildInfo,
+    actual_image: Option<&str>,
+) -> Vec<String> {
+    let hash = |hash: Option<[u8; 32]>| {
+        hash.map(|hash| format!("0x{}", hex::encode(hash)))
+            .unwrap_or_else(|| "none".to_string())
+    };
+    let fields = [
+        (
+            "rust_toolchain",
+            expected.rust_toolchain.clone(),
+            actual.rust_toolchain.clone(),
+        ),
+        (
+            "cargo_contract_version",
+            expected.cargo_contract_version.to_string(),
+            actual.cargo_contract_version.to_string(),
+        ),
+        (
+            "build_mode",
+            expected.build_mode.to_string(),
+            actual.build_mode.to_string(),
+        ),
+        (
+            "wasm_opt_settings.optimization_passes",
+            expected.wasm_opt_settings.optimization_passes.to_string(),
+            actual.wasm_opt_settings.optimization_passes.to_string(),
+        ),
+        (
+            "wasm_opt_settings.keep_debug_symbols",
+            expected.wasm_opt_settings.keep_debug_symbols.to_string(),
+            actual.wasm_opt_settings.keep_debug_symbols.to_string(),
+        ),
+        (
+            "cargo_lock_hash",
+            hash(expected.cargo_lock_hash),
+            hash(actual.cargo_lock_hash),
+        ),
+        (
+            "rustflags",
+            expected.rustflags.join(" "),
+            actual.rustflags.join(" "),
+        ),
+        (
+            "image",
+            expected_image.unwrap_or("none").to_string(),
+            actual_image.unwrap_or("none").to_string(),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(field, expected, actual)| {
+            format!("{field}: expected `{expected}`, got `{actual}`")
+        })
+        .collect()
+}
+
+/// Download the source archive from `url` to `dest`.
+async fn download(url: &str, dest: &Path) -> Result<()> {
+    let bytes = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .context(format!("Failed to download the source archive {url}"))?
+        .bytes()
+        .await?;
+    fs::write(dest, bytes)?;
+    Ok(())
+}
+
+/// Unpack the `.tar.gz` `archive` into `dest`.
+fn unpack_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dest)
+        .status()
+        .context("Failed to run `tar` to unpack the source archive")?;
+    anyhow::ensure!(
+        status.success(),
+        "Failed to unpack the source archive {}",
+        archive.display()
+    );
+    Ok(())
+}
+
+/// Returns the manifest path of the contract in the unpacked archive at `root`.
+///
+/// Archives of repositories usually contain a single top level directory, in which case
+/// the `manifest_path` is relative to it.
+fn archive_manifest_path(
+    root: &Path,
+    manifest_path: Option<&Path>,
+) -> Result<ManifestPath> {
+    let entries = fs::read_dir(root)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let root = match entries.as_slice() {
+        [dir] if dir.is_dir() => dir.as_path(),
+        _ => root,
+    };
+    let manifest_path = root.join(manifest_path.unwrap_or(Path::new("Cargo.toml")));
+    anyhow::ensure!(
+        manifest_path.exists(),
+        "The source archive does not contain the manifest {}",
+        manifest_path
+            .strip_prefix(root)
+            .unwrap_or(&manifest_path)
+            .display()
+    );
+    ManifestPath::new(manifest_path)
 }
 
 /// The result of verification process
@@ -325,3 +726,99 @@ impl VerificationResult {
         Ok(serde_json::to_string_pretty(self)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use contract_build::{
+        OptimizationPasses,
+        WasmOptSettings,
+    };
+
+    fn build_info() -> BuildInfo {
+        BuildInfo {
+            rust_toolchain: "stable-x86_64-unknown-linux-gnu".to_string(),
+            cargo_contract_version: semver::Version::new(4, 1, 1),
+            build_mode: BuildMode::Release,
+            wasm_opt_settings: WasmOptSettings {
+                optimization_passes: OptimizationPasses::Z,
+                keep_debug_symbols: false,
+            },
+            cargo_lock_hash: Some([1; 32]),
+            rustflags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn build_info_diff_of_equal_builds_is_empty() {
+        let diff = build_info_diff(&build_info(), None, &build_info(), None);
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn build_info_diff_lists_divergent_fields() {
+        let expected = build_info();
+        let mut actual = build_info();
+        actual.rust_toolchain = "1.78.0-x86_64-unknown-linux-gnu".to_string();
+        actual.wasm_opt_settings.keep_debug_symbols = true;
+        actual.cargo_lock_hash = None;
+
+        let diff = build_info_diff(
+            &expected,
+            Some("useink/contracts-verifiable:4.1.1"),
+            &actual,
+            Some("useink/contracts-verifiable:4.1.0"),
+        );
+
+        assert_eq!(
+            diff,
+            vec![
+                "rust_toolchain: expected `stable-x86_64-unknown-linux-gnu`, got \
+                `1.78.0-x86_64-unknown-linux-gnu`"
+                    .to_string(),
+                "wasm_opt_settings.keep_debug_symbols: expected `false`, got `true`"
+                    .to_string(),
+                format!(
+                    "cargo_lock_hash: expected `0x{}`, got `none`",
+                    "01".repeat(32)
+                ),
+                "image: expected `useink/contracts-verifiable:4.1.1`, got \
+                `useink/contracts-verifiable:4.1.0`"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn archive_manifest_path_in_top_level_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let contract_dir = root.path().join("flipper-1.0.0");
+        fs::create_dir(&contract_dir).unwrap();
+        fs::write(contract_dir.join("Cargo.toml"), "").unwrap();
+
+        let manifest_path = archive_manifest_path(root.path(), None).unwrap();
+
+        assert_eq!(manifest_path.as_ref(), contract_dir.join("Cargo.toml"));
+    }
+
+    #[test]
+    fn archive_manifest_path_relative_to_root() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("contracts/flipper")).unwrap();
+        fs::write(root.path().join("contracts/flipper/Cargo.toml"), "").unwrap();
+        fs::write(root.path().join("README.md"), "").unwrap();
+
+        let manifest_path = archive_manifest_path(
+            root.path(),
+            Some(Path::new("contracts/flipper/Cargo.toml")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest_path.as_ref(),
+            root.path().join("contracts/flipper/Cargo.toml")
+        );
+        assert!(archive_manifest_path(root.path(), None).is_err());
+    }
+}
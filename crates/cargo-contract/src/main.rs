@@ -19,17 +19,22 @@
 mod cmd;
 
 use self::cmd::{
+    BatchCommand,
     BuildCommand,
     CallCommand,
     CheckCommand,
     DecodeCommand,
+    DiffSchemaCommand,
     ErrorVariant,
+    EventsCommand,
     GenerateSchemaCommand,
     InfoCommand,
     InstantiateCommand,
+    LintCommand,
     RemoveCommand,
     RpcCommand,
     StorageCommand,
+    SubmitCommand,
     UploadCommand,
     VerifyCommand,
     VerifySchemaCommand,
@@ -112,6 +117,18 @@ enum Command {
         /// The optional target directory for the contract project
         #[clap(short, long, value_parser)]
         target_dir: Option<PathBuf>,
+        /// Create the project from a template in a remote git repository, given as
+        /// `<git-url>[#rev]` where `rev` is an optional branch, tag or commit
+        #[clap(long, value_name = "git-url[#rev]")]
+        template: Option<String>,
+        /// Define a variable for the `{{key}}` placeholders in the template, in
+        /// addition to the built-in `name`, `camel_name`, `snake_name` and `year`
+        #[clap(long = "define", value_name = "key=value", value_parser = parse_define)]
+        defines: Vec<(String, String)>,
+        /// Add the contract as a member to the enclosing cargo workspace, instead of
+        /// creating a standalone project
+        #[clap(long)]
+        into_workspace: bool,
     },
     /// Compiles the contract, generates metadata, bundles both together in a
     /// `<name>.contract` file
@@ -121,6 +138,9 @@ enum Command {
     /// artifact to the `target/` directory
     #[clap(name = "check")]
     Check(CheckCommand),
+    /// Run the ink! lints on the contract and report the findings
+    #[clap(name = "lint")]
+    Lint(LintCommand),
     /// Upload contract code
     #[clap(name = "upload")]
     Upload(UploadCommand),
@@ -130,6 +150,12 @@ enum Command {
     /// Call a contract
     #[clap(name = "call")]
     Call(CallCommand),
+    /// Call multiple contracts in a single extrinsic
+    #[clap(name = "batch")]
+    Batch(BatchCommand),
+    /// Submit an extrinsic signed with `--offline-output`
+    #[clap(name = "submit")]
+    Submit(SubmitCommand),
     /// Encodes a contracts input calls and their arguments
     #[clap(name = "encode")]
     Encode(EncodeCommand),
@@ -145,6 +171,9 @@ enum Command {
     /// Inspect the on-chain storage of a contract.
     #[clap(name = "storage")]
     Storage(StorageCommand),
+    /// Decode the events a contract emitted in a block.
+    #[clap(name = "events")]
+    Events(EventsCommand),
     /// Verifies that a given contract binary matches the build result of the specified
     /// workspace.
     #[clap(name = "verify")]
@@ -155,6 +184,9 @@ enum Command {
     /// Verify schema from the current metadata specification.
     #[clap(name = "verify-schema")]
     VerifySchema(VerifySchemaCommand),
+    /// Compare the metadata of two versions of a contract for breaking changes.
+    #[clap(name = "diff-schema")]
+    DiffSchema(DiffSchemaCommand),
     /// Make a raw RPC call.
     #[clap(name = "rpc")]
     Rpc(RpcCommand),
@@ -177,9 +209,46 @@ fn main() {
 fn exec(cmd: Command) -> Result<()> {
     let runtime = Runtime::new().expect("Failed to create Tokio runtime");
     match &cmd {
-        Command::New { name, target_dir } => {
-            contract_build::new_contract_project(name, target_dir.as_ref())?;
-            println!("Created contract {name}");
+        Command::New {
+            name,
+            target_dir,
+            template,
+            defines,
+            into_workspace,
+        } => {
+            let workspace_manifest = into_workspace
+                .then(|| contract_build::find_enclosing_workspace(target_dir.as_ref()))
+                .transpose()?;
+            match template {
+                Some(template) => {
+                    contract_build::new_contract_project_from_git(
+                        name,
+                        target_dir.as_ref(),
+                        template,
+                        defines,
+                    )?
+                }
+                None => {
+                    contract_build::new_contract_project_with_defines(
+                        name,
+                        target_dir.as_ref(),
+                        defines,
+                    )?
+                }
+            }
+            if let Some(workspace_manifest) = workspace_manifest {
+                contract_build::add_workspace_member(
+                    &workspace_manifest,
+                    name,
+                    target_dir.as_ref(),
+                )?;
+                println!(
+                    "Created contract {name} in the workspace {}",
+                    workspace_manifest.display()
+                );
+            } else {
+                println!("Created contract {name}");
+            }
             Ok(())
         }
         Command::Build(build) => {
@@ -200,6 +269,16 @@ fn exec(cmd: Command) -> Result<()> {
             );
             Ok(())
         }
+        Command::Lint(lint) => {
+            let report = lint.run().map_err(format_err)?;
+
+            if lint.output_json() {
+                println!("{}", report.serialize_json()?)
+            } else {
+                println!("{}", report.display())
+            }
+            report.check().map_err(format_err)
+        }
         Command::Upload(upload) => {
             runtime.block_on(async {
                 upload
@@ -223,6 +302,22 @@ fn exec(cmd: Command) -> Result<()> {
                     .map_err(|err| map_extrinsic_err(err, call.output_json()))
             })
         }
+        Command::Batch(batch) => {
+            runtime.block_on(async {
+                batch
+                    .handle()
+                    .await
+                    .map_err(|err| map_extrinsic_err(err, batch.output_json()))
+            })
+        }
+        Command::Submit(submit) => {
+            runtime.block_on(async {
+                submit
+                    .handle()
+                    .await
+                    .map_err(|err| map_extrinsic_err(err, submit.output_json()))
+            })
+        }
         Command::Encode(encode) => encode.run().map_err(format_err),
         Command::Decode(decode) => decode.run().map_err(format_err),
         Command::Remove(remove) => {
@@ -239,6 +334,9 @@ fn exec(cmd: Command) -> Result<()> {
         Command::Storage(storage) => {
             runtime.block_on(async { storage.handle().await.map_err(format_err) })
         }
+        Command::Events(events) => {
+            runtime.block_on(async { events.handle().await.map_err(format_err) })
+        }
         Command::Verify(verify) => {
             let result = verify.run().map_err(format_err)?;
 
@@ -264,6 +362,16 @@ fn exec(cmd: Command) -> Result<()> {
             }
             Ok(())
         }
+        Command::DiffSchema(diff) => {
+            let result = diff.run().map_err(format_err)?;
+
+            if result.output_json {
+                println!("{}", result.serialize_json()?)
+            } else {
+                println!("{}", result.display())
+            }
+            result.check().map_err(format_err)
+        }
         Command::Rpc(rpc) => {
             runtime.block_on(async { rpc.run().await.map_err(format_err) })
         }
@@ -289,3 +397,11 @@ fn format_err<E: Debug>(err: E) -> Error {
         format!("{err:?}").bright_red()
     )
 }
+
+/// Parse a template variable given as `key=value`.
+fn parse_define(input: &str) -> Result<(String, String)> {
+    let (key, value) = input.split_once('=').ok_or_else(|| {
+        anyhow!("Expected a template variable as key=value, got: {input}")
+    })?;
+    Ok((key.to_string(), value.to_string()))
+}
@@ -91,7 +91,7 @@ fn decode_works() {
 
     // and when
     let wrong_msg_data: &str = "babebabe010A";
-    let error_msg: &str = "input length was longer than expected by 1 byte(s).\nManaged to decode `switch`, `value` but `0A` bytes were left unread";
+    let error_msg: &str = "input length was longer than expected by 1 byte(s): decoded 5 of 6 bytes, stopped at offset 5.\nManaged to decode `switch`, `value` but `0A` bytes were left unread";
 
     // then
     // wrong message data is being handled properly
@@ -125,7 +125,7 @@ fn decode_works() {
 
     // and when
     let wrong_event_data: &str = "00010C";
-    let error_msg: &str = "input length was longer than expected by 1 byte(s).\nManaged to decode `Switched`, `new_value` but `0C` bytes were left unread";
+    let error_msg: &str = "input length was longer than expected by 1 byte(s): decoded 2 of 3 bytes, stopped at offset 2.\nManaged to decode `Switched`, `new_value` but `0C` bytes were left unread";
 
     // then
     // wrong event data is being handled properly
@@ -157,7 +157,7 @@ fn decode_works() {
 
     // and when
     let wrong_constructor_data: &str = "babebabe00AC";
-    let error_msg: &str = "input length was longer than expected by 1 byte(s).\nManaged to decode `new`, `init_value` but `AC` bytes were left unread";
+    let error_msg: &str = "input length was longer than expected by 1 byte(s): decoded 5 of 6 bytes, stopped at offset 5.\nManaged to decode `new`, `init_value` but `AC` bytes were left unread";
 
     // then
     // wrong constructor data is being handled properly
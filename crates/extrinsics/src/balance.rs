@@ -16,6 +16,7 @@
 
 use std::{
     fmt::Display,
+    path::Path,
     result::Result::Ok,
     str::FromStr,
 };
@@ -24,12 +25,12 @@ use rust_decimal::{
     prelude::FromPrimitive,
     Decimal,
 };
-use serde_json::json;
+use serde_json::{
+    Map,
+    Value,
+};
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     Config,
 };
 
@@ -40,7 +41,10 @@ use anyhow::{
 };
 use url::Url;
 
-use crate::url_to_string;
+use crate::{
+    url_to_string,
+    RpcConnection,
+};
 
 /// Represents different formats of a balance
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -77,33 +81,114 @@ pub enum UnitPrefix {
     Nano,
 }
 
+/// Token metadata provided by the user, for denominating balances without a node.
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadataOverride {
+    /// Number of token_decimals used for denomination
+    pub token_decimals: Option<usize>,
+    /// Token symbol
+    pub symbol: Option<String>,
+    /// Do not query the node, use only the provided values.
+    pub offline: bool,
+}
+
+impl TokenMetadataOverride {
+    /// Reads the token decimals and symbol from the `properties` of a chain spec file.
+    pub fn from_chain_spec(path: &Path) -> Result<Self> {
+        let chain_spec = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read chain spec {}", path.display()))?;
+        let chain_spec: Value = serde_json::from_str(&chain_spec)
+            .with_context(|| format!("Failed to parse chain spec {}", path.display()))?;
+        let properties = chain_spec
+            .get("properties")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        Ok(Self {
+            token_decimals: token_decimals(&properties)?,
+            symbol: token_symbol(&properties)?,
+            offline: false,
+        })
+    }
+}
+
 impl TokenMetadata {
+    const DEFAULT_DECIMALS: usize = 12;
+    const DEFAULT_SYMBOL: &'static str = "UNIT";
+
     /// Query [TokenMetadata] through the node's RPC
-    pub async fn query<C: Config>(url: &Url) -> Result<Self> {
-        let rpc_cli = RpcClient::from_url(url_to_string(url)).await?;
-        let rpc = LegacyRpcMethods::<C>::new(rpc_cli.clone());
+    pub async fn query<C: Config>(url: &Url, connection: &RpcConnection) -> Result<Self> {
+        Self::query_with_override::<C>(url, connection, &TokenMetadataOverride::default())
+            .await
+    }
+
+    /// Query [TokenMetadata] through the node's RPC, falling back to the values of the
+    /// `token_override` the node does not provide.
+    ///
+    /// If the override is `offline`, the node is not queried at all.
+    pub async fn query_with_override<C: Config>(
+        url: &Url,
+        connection: &RpcConnection,
+        token_override: &TokenMetadataOverride,
+    ) -> Result<Self> {
+        if token_override.offline {
+            return Self::from_properties(&Map::new(), token_override)
+        }
+        let rpc_cli = connection.connect(&url_to_string(url)).await?;
+        let rpc = LegacyRpcMethods::<C>::new(rpc_cli);
+        Self::query_rpc(&rpc, token_override).await
+    }
+
+    /// Query [TokenMetadata] through an already established connection to the node.
+    pub async fn query_rpc<C: Config>(
+        rpc: &LegacyRpcMethods<C>,
+        token_override: &TokenMetadataOverride,
+    ) -> Result<Self> {
         let sys_props = rpc.system_properties().await?;
+        Self::from_properties(&sys_props, token_override)
+    }
 
-        let default_decimals = json!(12);
-        let default_units = json!("UNIT");
-        let token_decimals = sys_props
-            .get("tokenDecimals")
-            .unwrap_or(&default_decimals)
-            .as_u64()
-            .context("error converting decimal to u64")?
-            as usize;
-        let symbol = sys_props
-            .get("tokenSymbol")
-            .unwrap_or(&default_units)
-            .as_str()
-            .context("error converting symbol to string")?;
+    fn from_properties(
+        properties: &Map<String, Value>,
+        token_override: &TokenMetadataOverride,
+    ) -> Result<Self> {
+        let token_decimals = token_decimals(properties)?
+            .or(token_override.token_decimals)
+            .unwrap_or(Self::DEFAULT_DECIMALS);
+        let symbol = token_symbol(properties)?
+            .or_else(|| token_override.symbol.clone())
+            .unwrap_or_else(|| Self::DEFAULT_SYMBOL.to_string());
         Ok(Self {
             token_decimals,
-            symbol: symbol.to_string(),
+            symbol,
         })
     }
 }
 
+fn token_decimals(properties: &Map<String, Value>) -> Result<Option<usize>> {
+    properties
+        .get("tokenDecimals")
+        .map(|decimals| {
+            decimals
+                .as_u64()
+                .context("error converting decimal to u64")
+                .map(|decimals| decimals as usize)
+        })
+        .transpose()
+}
+
+fn token_symbol(properties: &Map<String, Value>) -> Result<Option<String>> {
+    properties
+        .get("tokenSymbol")
+        .map(|symbol| {
+            symbol
+                .as_str()
+                .context("error converting symbol to string")
+                .map(ToString::to_string)
+        })
+        .transpose()
+}
+
 impl<Balance> FromStr for BalanceVariant<Balance>
 where
     Balance: FromStr,
@@ -152,7 +237,7 @@ impl FromStr for DenominatedBalance {
                 .context("cannot find the first char's index")?;
             symbols[start..].to_string()
         } else {
-            String::new()
+            symbols.to_string()
         };
         let value = value.trim_end_matches(|ch: char| ch.is_alphabetic());
         let value = Decimal::from_str_exact(value)
@@ -166,6 +251,46 @@ impl FromStr for DenominatedBalance {
     }
 }
 
+impl DenominatedBalance {
+    /// Resolves the unit prefix of the balance against the chain's token symbol.
+    ///
+    /// A symbol starting with one of the prefix characters (e.g. `GLMR`) is parsed
+    /// as a prefixed unit, so it is reinterpreted as a whole unit if the prefix and
+    /// symbol together match the chain's token symbol. An omitted symbol is accepted
+    /// as is.
+    fn resolve_unit(&self, token_metadata: &TokenMetadata) -> Result<UnitPrefix> {
+        if self.symbol.is_empty() || self.symbol == token_metadata.symbol {
+            return Ok(self.unit.clone())
+        }
+        if self.unit != UnitPrefix::One
+            && format!("{}{}", self.unit.prefix(), self.symbol) == token_metadata.symbol
+        {
+            return Ok(UnitPrefix::One)
+        }
+        Err(anyhow!(
+            "Unknown token symbol '{}{}': the chain's token symbol is '{}'",
+            self.unit.prefix(),
+            self.symbol,
+            token_metadata.symbol
+        ))
+    }
+}
+
+impl UnitPrefix {
+    /// The character(s) denoting the prefix in a denominated balance.
+    fn prefix(&self) -> &'static str {
+        match self {
+            UnitPrefix::Giga => "G",
+            UnitPrefix::Mega => "M",
+            UnitPrefix::Kilo => "k",
+            UnitPrefix::One => "",
+            UnitPrefix::Milli => "m",
+            UnitPrefix::Micro => "μ",
+            UnitPrefix::Nano => "n",
+        }
+    }
+}
+
 impl<Balance> BalanceVariant<Balance>
 where
     Balance: From<u128> + Clone,
@@ -174,7 +299,8 @@ where
     ///
     /// It is a reverse process of `from<T: Into<u128>>()`
     ///
-    /// Throws Error if `value` is of nigher precision that allowed.
+    /// Throws Error if `value` is of nigher precision that allowed, or if the token
+    /// symbol does not match the one in [TokenMetadata].
     ///
     /// ```rust
     /// use contract_extrinsics::{
@@ -207,12 +333,27 @@ where
     /// let result = sample_den_balance.denominate_balance(&tm).unwrap();
     /// assert_eq!(balance, result);
     /// ```
+    ///
+    /// A symbol other than the chain's token symbol is rejected:
+    /// ```rust
+    /// use contract_extrinsics::{
+    ///     BalanceVariant,
+    ///     TokenMetadata,
+    /// };
+    /// let tm = TokenMetadata {
+    ///     token_decimals: 10,
+    ///     symbol: String::from("DOT"),
+    /// };
+    /// let sample_den_balance: BalanceVariant<u128> = "1.5KSM".parse().unwrap();
+    /// assert!(sample_den_balance.denominate_balance(&tm).is_err());
+    /// ```
     pub fn denominate_balance(&self, token_metadata: &TokenMetadata) -> Result<Balance> {
         match self {
             BalanceVariant::Default(balance) => Ok(balance.clone()),
             BalanceVariant::Denominated(den_balance) => {
+                let unit = den_balance.resolve_unit(token_metadata)?;
                 let zeros: usize = (token_metadata.token_decimals as isize
-                    + match den_balance.unit {
+                    + match unit {
                         UnitPrefix::Giga => 9,
                         UnitPrefix::Mega => 6,
                         UnitPrefix::Kilo => 3,
@@ -375,16 +516,12 @@ where
 
 impl Display for DenominatedBalance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let prefix = match self.unit {
-            UnitPrefix::Giga => "G",
-            UnitPrefix::Mega => "M",
-            UnitPrefix::Kilo => "k",
-            UnitPrefix::One => "",
-            UnitPrefix::Milli => "m",
-            UnitPrefix::Micro => "μ",
-            UnitPrefix::Nano => "n",
-        };
-        f.write_fmt(format_args!("{}{}{}", self.value, prefix, self.symbol))
+        f.write_fmt(format_args!(
+            "{}{}{}",
+            self.value,
+            self.unit.prefix(),
+            self.symbol
+        ))
     }
 }
 
@@ -440,6 +577,46 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn denominate_balance_with_offline_token_metadata() -> Result<()> {
+        let token_override = TokenMetadataOverride {
+            token_decimals: Some(10),
+            symbol: None,
+            offline: true,
+        };
+        // no node is listening on this url, offline the node is not queried
+        let url = Url::parse("ws://localhost:1").unwrap();
+        let tm = TokenMetadata::query_with_override::<subxt::PolkadotConfig>(
+            &url,
+            &RpcConnection::Plain,
+            &token_override,
+        )
+        .await?;
+        assert_eq!(tm.token_decimals, 10);
+        assert_eq!(tm.symbol, "UNIT");
+
+        let balance: BalanceVariant<u128> = BalanceVariant::from_str("1.5UNIT")?;
+        assert_eq!(balance.denominate_balance(&tm)?, 15_000_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn token_metadata_prefers_node_properties() -> Result<()> {
+        let token_override = TokenMetadataOverride {
+            token_decimals: Some(10),
+            symbol: Some("DOT".to_string()),
+            offline: false,
+        };
+        let properties = serde_json::json!({ "tokenDecimals": 12 });
+        let tm = TokenMetadata::from_properties(
+            properties.as_object().unwrap(),
+            &token_override,
+        )?;
+        assert_eq!(tm.token_decimals, 12);
+        assert_eq!(tm.symbol, "DOT");
+        Ok(())
+    }
+
     #[test]
     fn balance_variant_denominated_success() {
         let tm = TokenMetadata {
@@ -572,6 +749,63 @@ mod tests {
         assert_eq!(balance, balance_parsed);
     }
 
+    #[test]
+    fn fractional_unit_with_symbol() {
+        let tm = TokenMetadata {
+            token_decimals: 10,
+            symbol: String::from("DOT"),
+        };
+        let balance: <DefaultEnvironment as Environment>::Balance = 15_000_000_000;
+        let bv = BalanceVariant::from_str("1.5DOT").expect("successful parsing. qed");
+        let balance_parsed = bv.denominate_balance(&tm).expect("successful parsing. qed");
+        assert_eq!(balance, balance_parsed);
+    }
+
+    #[test]
+    fn sub_unit_prefix_with_symbol() {
+        let tm = TokenMetadata {
+            token_decimals: 12,
+            symbol: String::from("UNIT"),
+        };
+        let balance: <DefaultEnvironment as Environment>::Balance = 100_000_000_000;
+        let bv = BalanceVariant::from_str("100mUNIT").expect("successful parsing. qed");
+        let balance_parsed = bv.denominate_balance(&tm).expect("successful parsing. qed");
+        assert_eq!(balance, balance_parsed);
+    }
+
+    #[test]
+    fn symbol_starting_with_prefix_char() {
+        let tm = TokenMetadata {
+            token_decimals: 18,
+            symbol: String::from("GLMR"),
+        };
+        let balance: <DefaultEnvironment as Environment>::Balance =
+            2_000_000_000_000_000_000;
+        let bv = BalanceVariant::from_str("2GLMR").expect("successful parsing. qed");
+        let balance_parsed = bv.denominate_balance(&tm).expect("successful parsing. qed");
+        assert_eq!(balance, balance_parsed);
+    }
+
+    #[test]
+    fn unknown_symbol_is_rejected() {
+        let tm = TokenMetadata {
+            token_decimals: 10,
+            symbol: String::from("DOT"),
+        };
+        let bv =
+            BalanceVariant::<<DefaultEnvironment as Environment>::Balance>::from_str(
+                "5mKSM",
+            )
+            .expect("successful parsing. qed");
+        let err = bv
+            .denominate_balance(&tm)
+            .expect_err("mismatched symbol must fail");
+        assert_eq!(
+            err.to_string(),
+            "Unknown token symbol 'mKSM': the chain's token symbol is 'DOT'"
+        );
+    }
+
     #[test]
     fn milli() {
         let decimals = 10;
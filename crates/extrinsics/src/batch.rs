@@ -0,0 +1,232 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{
+    pallet_contracts_primitives::ContractExecResult,
+    sign_extrinsic_offline,
+    submit_extrinsic,
+    CallCommandBuilder,
+    CallExec,
+    ContractMessageTranscoder,
+    ErrorVariant,
+};
+use crate::extrinsic_calls::Batch;
+
+use anyhow::{
+    anyhow,
+    Result,
+};
+use ink_env::Environment;
+use scale::Encode;
+use sp_weights::Weight;
+use subxt::{
+    blocks::ExtrinsicEvents,
+    config::{
+        DefaultExtrinsicParams,
+        ExtrinsicParams,
+    },
+    ext::{
+        scale_decode::IntoVisitor,
+        scale_encode::EncodeAsType,
+    },
+    tx,
+    Config,
+    OfflineClient,
+};
+
+/// A builder for the batch command.
+pub struct BatchCommandBuilder<C: Config, E: Environment, Signer: Clone> {
+    calls: Vec<CallExec<C, E, Signer>>,
+    atomic: bool,
+}
+
+impl<C: Config, E: Environment, Signer> BatchCommandBuilder<C, E, Signer>
+where
+    Signer: tx::Signer<C> + Clone,
+{
+    /// Returns a clean builder for [`BatchExec`].
+    pub fn new(calls: Vec<CallExec<C, E, Signer>>) -> BatchCommandBuilder<C, E, Signer> {
+        BatchCommandBuilder {
+            calls,
+            atomic: false,
+        }
+    }
+
+    /// Sets whether all calls are reverted if any of them fails.
+    ///
+    /// Dispatches the calls via `utility.batch_all` instead of `utility.batch`.
+    pub fn atomic(self, atomic: bool) -> Self {
+        let mut this = self;
+        this.atomic = atomic;
+        this
+    }
+
+    /// Returns the [`BatchExec`] for submitting the calls in a single extrinsic, or an
+    /// error if there are no calls to batch.
+    pub fn done(self) -> Result<BatchExec<C, E, Signer>> {
+        if self.calls.is_empty() {
+            return Err(anyhow!("A batch requires at least one call"))
+        }
+        Ok(BatchExec {
+            calls: self.calls,
+            atomic: self.atomic,
+        })
+    }
+}
+
+pub struct BatchExec<C: Config, E: Environment, Signer: Clone> {
+    calls: Vec<CallExec<C, E, Signer>>,
+    atomic: bool,
+}
+
+impl<C: Config, E: Environment, Signer> BatchExec<C, E, Signer>
+where
+    <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+        From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+    C::AccountId: EncodeAsType + IntoVisitor,
+    E::Balance: EncodeAsType,
+    Signer: tx::Signer<C> + Clone,
+{
+    /// Simulates each of the batched calls without modifying the blockchain.
+    ///
+    /// The calls are simulated independently of each other, so a call depending on
+    /// the state changes of a previous call in the batch might yield a different
+    /// result once submitted.
+    pub async fn calls_dry_run(&self) -> Result<Vec<ContractExecResult<E::Balance>>> {
+        let mut results = Vec::with_capacity(self.calls.len());
+        for call in &self.calls {
+            results.push(call.call_dry_run().await?);
+        }
+        Ok(results)
+    }
+
+    /// Estimates the gas required for each of the batched calls.
+    pub async fn estimate_gas(&self) -> Result<Vec<Weight>> {
+        let mut gas_limits = Vec::with_capacity(self.calls.len());
+        for call in &self.calls {
+            gas_limits.push(call.estimate_gas().await?);
+        }
+        Ok(gas_limits)
+    }
+
+    /// Submits the batched calls in a single extrinsic.
+    ///
+    /// `gas_limits` holds the gas limit for each call in the order of the batch. If
+    /// not given, the gas limits are estimated via a dry-run of each call.
+    pub async fn batch(
+        &self,
+        gas_limits: Option<Vec<Weight>>,
+    ) -> Result<ExtrinsicEvents<C>, ErrorVariant> {
+        let batch = self.build_batch(gas_limits).await?;
+        let first = self.first();
+        let result =
+            submit_extrinsic(first.client(), first.rpc(), &batch, first.opts()).await?;
+
+        Ok(result)
+    }
+
+    /// Builds the `utility` extrinsic payload wrapping all contract calls.
+    async fn build_batch(
+        &self,
+        gas_limits: Option<Vec<Weight>>,
+    ) -> Result<tx::Payload<Batch<C::AccountId, E::Balance>>, ErrorVariant> {
+        if let Some(gas_limits) = &gas_limits {
+            if gas_limits.len() != self.calls.len() {
+                return Err(anyhow!(
+                    "Expected {} gas limits, got {}",
+                    self.calls.len(),
+                    gas_limits.len()
+                )
+                .into())
+            }
+        }
+        let mut calls = Vec::with_capacity(self.calls.len());
+        for (index, call) in self.calls.iter().enumerate() {
+            let gas_limit = gas_limits.as_ref().map(|gas_limits| gas_limits[index]);
+            calls.push(call.contract_call(gas_limit).await?);
+        }
+        let pallet_name = self.first().opts().pallet_name();
+        Ok(Batch::new(calls, pallet_name).build(self.atomic))
+    }
+
+    /// Returns the transcoder of the first batched call to the given contract.
+    ///
+    /// Used to decode the events emitted by the contracts called in the batch.
+    pub fn transcoder_for(
+        &self,
+        contract: &C::AccountId,
+    ) -> Option<&ContractMessageTranscoder> {
+        let contract = contract.encode();
+        self.calls
+            .iter()
+            .find(|call| call.contract().encode() == contract)
+            .map(|call| call.transcoder())
+    }
+
+    /// Returns the batched calls.
+    pub fn calls(&self) -> &[CallExec<C, E, Signer>] {
+        &self.calls
+    }
+
+    /// Returns whether all calls are reverted if any of them fails.
+    pub fn atomic(&self) -> bool {
+        self.atomic
+    }
+
+    /// Returns the first call, whose client and options are used for submitting.
+    fn first(&self) -> &CallExec<C, E, Signer> {
+        self.calls.first().expect(
+            "a batch contains at least one call after BatchCommandBuilder::done()",
+        )
+    }
+}
+
+/// Signs the given calls in a single batch extrinsic without a connection to the node.
+///
+/// The calls are checked against the chain metadata of the given [`OfflineClient`].
+/// Since there is no dry-run, the gas limit and the proof size have to be set for each
+/// call. The extrinsic options of the first call are used for signing.
+///
+/// Returns the SCALE encoded signed extrinsic, which can be submitted later on with
+/// [`crate::submit_signed_extrinsic`].
+pub fn sign_batch_offline<C, E, Signer>(
+    calls: &[CallCommandBuilder<C, E, Signer>],
+    atomic: bool,
+    client: &OfflineClient<C>,
+) -> Result<Vec<u8>>
+where
+    C: Config,
+    E: Environment,
+    E::Balance: Default + EncodeAsType,
+    <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+        From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+    C::AccountId: EncodeAsType,
+    Signer: tx::Signer<C> + Clone,
+{
+    let first = calls
+        .first()
+        .ok_or_else(|| anyhow!("A batch requires at least one call"))?;
+    let mut contract_calls = Vec::with_capacity(calls.len());
+    for (index, call) in calls.iter().enumerate() {
+        let contract_call = call
+            .offline_call(client)
+            .map_err(|err| anyhow!("Failed to build call #{index}: {err}"))?;
+        contract_calls.push(contract_call);
+    }
+    let opts = first.extrinsic_opts();
+    let batch = Batch::new(contract_calls, opts.pallet_name()).build(atomic);
+    Ok(sign_extrinsic_offline(client, &batch, opts)?)
+}
@@ -15,31 +15,49 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
+    estimate_extrinsic_fee,
+    offline_gas_limit,
     pallet_contracts_primitives::ContractExecResult,
+    sign_extrinsic_offline,
     state_call,
+    state_call_with_events,
     submit_extrinsic,
     ContractMessageTranscoder,
+    DryRunEvents,
     ErrorVariant,
 };
 use crate::{
     check_env_types,
-    extrinsic_calls::Call,
+    events::{
+        as_contracts_event,
+        ContractEmitted,
+    },
+    extrinsic_calls::{
+        check_contracts_pallet,
+        Call,
+    },
     extrinsic_opts::ExtrinsicOpts,
+    with_ss58_prefix,
 };
 
 use anyhow::{
     anyhow,
     Result,
 };
+use contract_transcode::{
+    Hex,
+    Value,
+};
 use ink_env::Environment;
 use scale::Encode;
 use sp_weights::Weight;
+use std::{
+    str::FromStr,
+    time::Duration,
+};
 
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     blocks::ExtrinsicEvents,
     config::{
         DefaultExtrinsicParams,
@@ -51,6 +69,7 @@ use subxt::{
     },
     tx,
     Config,
+    OfflineClient,
     OnlineClient,
 };
 
@@ -59,6 +78,8 @@ pub struct CallCommandBuilder<C: Config, E: Environment, Signer: Clone> {
     contract: C::AccountId,
     message: String,
     args: Vec<String>,
+    args_json: Option<serde_json::Value>,
+    allow_default_fields: bool,
     extrinsic_opts: ExtrinsicOpts<C, E, Signer>,
     gas_limit: Option<u64>,
     proof_size: Option<u64>,
@@ -80,6 +101,8 @@ where
             contract,
             message: message.to_string(),
             args: Vec::new(),
+            args_json: None,
+            allow_default_fields: false,
             extrinsic_opts,
             gas_limit: None,
             proof_size: None,
@@ -94,6 +117,24 @@ where
         this
     }
 
+    /// Sets the arguments of the contract message to call as JSON, either as an array in
+    /// the order of the parameters or as an object keyed by the parameter names.
+    ///
+    /// Takes precedence over [`Self::args`].
+    pub fn args_json(self, args_json: Option<serde_json::Value>) -> Self {
+        let mut this = self;
+        this.args_json = args_json;
+        this
+    }
+
+    /// Allows omitting struct fields of the arguments, which are then encoded with the
+    /// default value of their type.
+    pub fn allow_default_fields(self, allow_default_fields: bool) -> Self {
+        let mut this = self;
+        this.allow_default_fields = allow_default_fields;
+        this
+    }
+
     /// Sets the maximum amount of gas to be used for this command.
     pub fn gas_limit(self, gas_limit: Option<u64>) -> Self {
         let mut this = self;
@@ -125,22 +166,22 @@ where
     /// Returns the `CallExec` containing the preprocessed data for the contract call,
     /// or an error in case of failure.
     pub async fn done(self) -> Result<CallExec<C, E, Signer>> {
-        let artifacts = self.extrinsic_opts.contract_artifacts()?;
-        let transcoder = artifacts.contract_transcoder()?;
-
-        let call_data = transcoder.encode(&self.message, &self.args)?;
-        tracing::debug!("Message data: {:?}", hex::encode(&call_data));
+        let transcoder = self.transcoder()?;
+        let (call_data, args) = self.call_data(&transcoder)?;
 
         let url = self.extrinsic_opts.url();
-        let rpc = RpcClient::from_url(&url).await?;
+        let rpc = self.extrinsic_opts.rpc_connection().connect(&url).await?;
         let client = OnlineClient::from_rpc_client(rpc.clone()).await?;
         let rpc = LegacyRpcMethods::new(rpc);
         check_env_types(&client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        let transcoder =
+            with_ss58_prefix(transcoder, &client, self.extrinsic_opts.ss58_prefix());
 
         Ok(CallExec {
             contract: self.contract,
             message: self.message.clone(),
-            args: self.args.clone(),
+            args,
             opts: self.extrinsic_opts,
             gas_limit: self.gas_limit,
             proof_size: self.proof_size,
@@ -151,6 +192,83 @@ where
             call_data,
         })
     }
+
+    /// Signs the contract call without a connection to the node.
+    ///
+    /// The call is checked against the chain metadata of the given [`OfflineClient`].
+    /// Since there is no dry-run, the gas limit and the proof size have to be set.
+    ///
+    /// Returns the SCALE encoded signed extrinsic, which can be submitted later on with
+    /// [`crate::submit_signed_extrinsic`].
+    pub fn sign_offline(&self, client: &OfflineClient<C>) -> Result<Vec<u8>>
+    where
+        <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+            From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+        C::AccountId: EncodeAsType,
+        E::Balance: EncodeAsType,
+    {
+        let call = self
+            .offline_call(client)?
+            .build(self.extrinsic_opts.pallet_name());
+        Ok(sign_extrinsic_offline(client, &call, &self.extrinsic_opts)?)
+    }
+
+    /// Builds the raw `pallet-contracts` call without a connection to the node.
+    pub(crate) fn offline_call(
+        &self,
+        client: &OfflineClient<C>,
+    ) -> Result<Call<C::AccountId, E::Balance>> {
+        let transcoder = self.transcoder()?;
+        let (call_data, _) = self.call_data(&transcoder)?;
+        check_env_types(client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        check_message_mutates(&transcoder, &self.message)?;
+        let gas_limit = offline_gas_limit(self.gas_limit, self.proof_size)?;
+        Ok(Call::new(
+            self.contract.clone().into(),
+            self.value,
+            gas_limit,
+            self.extrinsic_opts.storage_deposit_limit(),
+            call_data,
+        ))
+    }
+
+    /// Returns the extrinsic options of the call.
+    pub(crate) fn extrinsic_opts(&self) -> &ExtrinsicOpts<C, E, Signer> {
+        &self.extrinsic_opts
+    }
+
+    /// Returns the transcoder of the contract artifacts.
+    fn transcoder(&self) -> Result<ContractMessageTranscoder> {
+        let artifacts = self.extrinsic_opts.contract_artifacts()?;
+        Ok(artifacts
+            .contract_transcoder()?
+            .with_default_fields(self.allow_default_fields))
+    }
+
+    /// Encodes the message and its arguments, returning the call data along with the
+    /// raw arguments.
+    fn call_data(
+        &self,
+        transcoder: &ContractMessageTranscoder,
+    ) -> Result<(Vec<u8>, Vec<String>)> {
+        let (call_data, args) = match &self.args_json {
+            Some(args_json) => {
+                (
+                    transcoder.encode_from_json(&self.message, args_json)?,
+                    vec![args_json.to_string()],
+                )
+            }
+            None => {
+                (
+                    transcoder.encode(&self.message, &self.args)?,
+                    self.args.clone(),
+                )
+            }
+        };
+        tracing::debug!("Message data: {:?}", hex::encode(&call_data));
+        Ok((call_data, args))
+    }
 }
 
 pub struct CallExec<C: Config, E: Environment, Signer: Clone> {
@@ -185,16 +303,40 @@ where
     /// Returns the dry run simulation result of type [`ContractExecResult`], which
     /// includes information about the simulated call, or an error in case of failure.
     pub async fn call_dry_run(&self) -> Result<ContractExecResult<E::Balance>> {
-        let storage_deposit_limit = self.opts.storage_deposit_limit();
-        let call_request = CallRequest {
+        state_call(
+            &self.rpc,
+            self.opts.rpc_retry_policy(),
+            "ContractsApi_call",
+            self.call_request(),
+        )
+        .await
+    }
+
+    /// Simulates a contract call like [`Self::call_dry_run`], additionally returning
+    /// the events emitted during the dry-run if the node collected them.
+    pub async fn call_dry_run_with_events(
+        &self,
+    ) -> Result<(ContractExecResult<E::Balance>, Option<DryRunEvents>)> {
+        state_call_with_events(
+            &self.rpc,
+            self.opts.rpc_retry_policy(),
+            "ContractsApi_call",
+            self.call_request(),
+            &self.client.metadata(),
+        )
+        .await
+    }
+
+    /// Returns the request of a dry-run of the call.
+    fn call_request(&self) -> CallRequest<C::AccountId, E::Balance> {
+        CallRequest {
             origin: self.opts.signer().account_id(),
             dest: self.contract.clone(),
             value: self.value,
             gas_limit: None,
-            storage_deposit_limit,
+            storage_deposit_limit: self.opts.storage_deposit_limit(),
             input_data: self.call_data.clone(),
-        };
-        state_call(&self.rpc, "ContractsApi_call", call_request).await
+        }
     }
 
     /// Calls a contract on the blockchain with a specified gas limit.
@@ -209,22 +351,43 @@ where
         &self,
         gas_limit: Option<Weight>,
     ) -> Result<ExtrinsicEvents<C>, ErrorVariant> {
-        if !self
-            .transcoder()
-            .metadata()
-            .spec()
-            .messages()
-            .iter()
-            .find(|msg| msg.label() == &self.message)
-            .expect("message exist after calling CallExec::done()")
-            .mutates()
-        {
-            let inner = anyhow!(
-                "Tried to execute a call on the immutable contract message '{}'. Please do a dry-run instead.",
-                &self.message
-            );
-            return Err(inner.into())
-        }
+        let call = self.build_call(gas_limit).await?;
+        let result = submit_extrinsic(&self.client, &self.rpc, &call, &self.opts).await?;
+
+        Ok(result)
+    }
+
+    /// Estimates the fee for submitting the contract call, including the tip.
+    ///
+    /// Returns the fee in the smallest unit of the chain's token.
+    pub async fn estimate_fee(
+        &self,
+        gas_limit: Option<Weight>,
+    ) -> Result<u128, ErrorVariant> {
+        let call = self.build_call(gas_limit).await?;
+        let fee =
+            estimate_extrinsic_fee(&self.client, &self.rpc, &call, &self.opts).await?;
+
+        Ok(fee)
+    }
+
+    /// Builds the extrinsic payload for the contract call.
+    async fn build_call(
+        &self,
+        gas_limit: Option<Weight>,
+    ) -> Result<tx::Payload<Call<C::AccountId, E::Balance>>, ErrorVariant> {
+        Ok(self
+            .contract_call(gas_limit)
+            .await?
+            .build(self.opts.pallet_name()))
+    }
+
+    /// Builds the raw `pallet-contracts` call, estimating the gas limit if not given.
+    pub(crate) async fn contract_call(
+        &self,
+        gas_limit: Option<Weight>,
+    ) -> Result<Call<C::AccountId, E::Balance>, ErrorVariant> {
+        check_message_mutates(self.transcoder(), &self.message)?;
 
         // use user specified values where provided, otherwise estimate
         let gas_limit = match gas_limit {
@@ -240,13 +403,84 @@ where
             gas_limit,
             storage_deposit_limit,
             self.call_data.clone(),
-        )
-        .build();
+        );
 
-        let result =
-            submit_extrinsic(&self.client, &self.rpc, &call, self.opts.signer()).await?;
+        Ok(call)
+    }
 
-        Ok(result)
+    /// Subscribes to new finalized blocks and decodes the events emitted by the contract.
+    ///
+    /// Blocks up to and including `after_block`, usually the block the call was included
+    /// in, are skipped: they have been reported already once they get finalized.
+    ///
+    /// `on_event` is invoked with every decoded event of the contract until either the
+    /// subscription ends or the `timeout` has elapsed. Events whose signature topic is
+    /// not found in the contract metadata are passed on as raw hex.
+    pub async fn subscribe_events<F>(
+        &self,
+        after_block: C::Hash,
+        timeout: Option<Duration>,
+        mut on_event: F,
+    ) -> Result<()>
+    where
+        F: FnMut(SubscribedEvent<C::Hash>),
+    {
+        let subscription = async {
+            let metadata = self.client.metadata();
+            let after_number: u64 =
+                self.client.blocks().at(after_block).await?.number().into();
+            let mut blocks = self.client.blocks().subscribe_finalized().await?;
+            while let Some(block) = blocks.next().await {
+                let block = block?;
+                if block.number().into() <= after_number {
+                    continue
+                }
+                let events = block.events().await?;
+                for event in events.iter() {
+                    let event = event?;
+                    let emitted = as_contracts_event::<ContractEmitted<C::AccountId>, C>(
+                        &event,
+                        &metadata,
+                        self.opts.pallet_name(),
+                    )?;
+                    let Some(emitted) = emitted else { continue };
+                    if emitted.contract.encode() != self.contract.encode() {
+                        continue
+                    }
+                    // the transcoder expects the data with its length prefix
+                    let data = emitted.data.encode();
+                    let value = event.topics().first().and_then(|sig_topic| {
+                        self.transcoder
+                            .decode_contract_event(sig_topic, &mut &data[..])
+                            .map_err(|err| {
+                                tracing::warn!(
+                                    "Decoding contract event failed: {:?}",
+                                    err
+                                )
+                            })
+                            .ok()
+                    });
+                    let value = match value {
+                        Some(value) => value,
+                        None => Value::Hex(Hex::from_str(&hex::encode(&emitted.data))?),
+                    };
+                    on_event(SubscribedEvent {
+                        block_hash: block.hash(),
+                        index: event.index(),
+                        value,
+                    });
+                }
+            }
+            anyhow::Ok(())
+        };
+        match timeout {
+            Some(timeout) => {
+                tokio::time::timeout(timeout, subscription)
+                    .await
+                    .unwrap_or(Ok(()))
+            }
+            None => subscription.await,
+        }
     }
 
     /// Estimates the gas required for a contract call without modifying the blockchain.
@@ -280,6 +514,7 @@ where
                         let object = ErrorVariant::from_dispatch_error(
                             err,
                             &self.client.metadata(),
+                            self.opts.pallet_name(),
                         )?;
                         Err(anyhow!("Pre-submission dry-run failed. Error: {}", object))
                     }
@@ -328,6 +563,11 @@ where
         &self.client
     }
 
+    /// Returns the legacy RPC methods.
+    pub(crate) fn rpc(&self) -> &LegacyRpcMethods<C> {
+        &self.rpc
+    }
+
     /// Returns the contract message transcoder.
     pub fn transcoder(&self) -> &ContractMessageTranscoder {
         &self.transcoder
@@ -339,6 +579,39 @@ where
     }
 }
 
+/// A contract event observed by [`CallExec::subscribe_events`].
+pub struct SubscribedEvent<Hash> {
+    /// The hash of the finalized block the event was emitted in.
+    pub block_hash: Hash,
+    /// The index of the event within the block.
+    pub index: u32,
+    /// The decoded event, or its raw data as hex if it could not be decoded.
+    pub value: Value,
+}
+
+/// Fails if the message does not mutate the contract state, calling it in an
+/// extrinsic would only waste fees.
+fn check_message_mutates(
+    transcoder: &ContractMessageTranscoder,
+    message: &str,
+) -> Result<()> {
+    let mutates = transcoder
+        .metadata()
+        .spec()
+        .messages()
+        .iter()
+        .find(|msg| msg.label() == message)
+        .expect("message exists after encoding the call data")
+        .mutates();
+    if !mutates {
+        return Err(anyhow!(
+            "Tried to execute a call on the immutable contract message '{}'. Please do a dry-run instead.",
+            message
+        ))
+    }
+    Ok(())
+}
+
 /// A struct that encodes RPC parameters required for a call to a smart contract.
 ///
 /// Copied from `pallet-contracts-rpc-runtime-api`.
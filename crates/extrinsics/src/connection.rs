@@ -0,0 +1,190 @@
+// Copyright (C) Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::RetryPolicy;
+use subxt::backend::rpc::{
+    reconnecting_rpc_client::{
+        Client as ReconnectingRpcClient,
+        ExponentialBackoff,
+        PingConfig,
+    },
+    RpcClient,
+};
+
+/// How often a dropped connection is attempted to be re-established.
+const MAX_RECONNECTS: usize = 10;
+
+/// The maximum delay between two attempts to re-establish a dropped connection.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// The number of keepalive intervals without any message from the node after which the
+/// connection is considered to be dropped.
+const INACTIVE_INTERVALS: u32 = 3;
+
+/// The interval in which the node is pinged if the connection is only re-established
+/// to retry failed requests.
+const RETRY_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// How the RPC client connects to the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RpcConnection {
+    /// A websocket connection which is not re-established once it dropped.
+    #[default]
+    Plain,
+    /// A websocket connection which is re-established once it dropped, pinging the node
+    /// in the given interval to detect a silently dropped connection.
+    ///
+    /// Requests which are in-flight while the connection drops fail with a transient
+    /// error, so that only read-only requests are retried according to the
+    /// [`crate::RetryPolicy`]. A submitted extrinsic is never submitted again.
+    Reconnecting { keepalive: Duration },
+}
+
+impl RpcConnection {
+    /// Returns a reconnecting connection if a keepalive interval is given.
+    pub fn from_keepalive(keepalive: Option<Duration>) -> Self {
+        match keepalive {
+            Some(keepalive) => Self::Reconnecting { keepalive },
+            None => Self::Plain,
+        }
+    }
+
+    /// Returns a reconnecting connection if the `retry_policy` retries failed requests.
+    ///
+    /// A plain connection is not re-established once it dropped, so that every retry
+    /// would fail again.
+    pub fn for_retries(self, retry_policy: &RetryPolicy) -> Self {
+        match self {
+            Self::Plain if retry_policy.retries() > 0 => {
+                Self::Reconnecting {
+                    keepalive: RETRY_KEEPALIVE,
+                }
+            }
+            connection => connection,
+        }
+    }
+
+    /// Returns the time without any message from the node after which the connection
+    /// is considered to be dropped, if it is kept alive.
+    pub fn inactive_limit(&self) -> Option<Duration> {
+        match self {
+            Self::Plain => None,
+            Self::Reconnecting { keepalive } => {
+                Some(keepalive.saturating_mul(INACTIVE_INTERVALS))
+            }
+        }
+    }
+
+    /// Connects to the node at the given URL.
+    pub async fn connect(&self, url: &str) -> Result<RpcClient> {
+        self.connect_with_reconnects(url, MAX_RECONNECTS).await
+    }
+
+    /// Connects to the node at the given URL, attempting to (re-)establish the
+    /// connection at most `max_reconnects` more times.
+    async fn connect_with_reconnects(
+        &self,
+        url: &str,
+        max_reconnects: usize,
+    ) -> Result<RpcClient> {
+        match (self, self.inactive_limit()) {
+            (Self::Reconnecting { keepalive }, Some(inactive_limit)) => {
+                let client = ReconnectingRpcClient::builder()
+                    .retry_policy(
+                        ExponentialBackoff::from_millis(100)
+                            .max_delay(MAX_RECONNECT_DELAY)
+                            .take(max_reconnects),
+                    )
+                    .enable_ws_ping(
+                        PingConfig::new()
+                            .ping_interval(*keepalive)
+                            .inactive_limit(inactive_limit),
+                    )
+                    .build(url.to_string())
+                    .await?;
+                Ok(RpcClient::new(client))
+            }
+            _ => Ok(RpcClient::from_url(url).await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_is_only_kept_alive_with_keepalive() {
+        assert_eq!(RpcConnection::from_keepalive(None), RpcConnection::Plain);
+        assert_eq!(RpcConnection::Plain.inactive_limit(), None);
+
+        let connection = RpcConnection::from_keepalive(Some(Duration::from_secs(6)));
+
+        assert_eq!(
+            connection,
+            RpcConnection::Reconnecting {
+                keepalive: Duration::from_secs(6)
+            }
+        );
+        assert_eq!(connection.inactive_limit(), Some(Duration::from_secs(18)));
+    }
+
+    #[test]
+    fn connection_is_re_established_for_retries() {
+        let no_retries = RetryPolicy::default();
+        let retries = RetryPolicy::new(3, Duration::from_millis(100));
+        let keepalive = RpcConnection::Reconnecting {
+            keepalive: Duration::from_secs(6),
+        };
+
+        assert_eq!(
+            RpcConnection::Plain.for_retries(&no_retries),
+            RpcConnection::Plain
+        );
+        assert_eq!(
+            RpcConnection::Plain.for_retries(&retries),
+            RpcConnection::Reconnecting {
+                keepalive: RETRY_KEEPALIVE
+            }
+        );
+        assert_eq!(keepalive.for_retries(&retries), keepalive);
+    }
+
+    #[tokio::test]
+    async fn connecting_fails_without_a_node() {
+        // no node is listening on this url
+        let result = RpcConnection::Plain.connect("ws://localhost:1").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn reconnecting_fails_without_a_node() {
+        let connection = RpcConnection::Reconnecting {
+            keepalive: Duration::from_secs(1),
+        };
+
+        // no node is listening on this url, so the connection is never established
+        let result = connection
+            .connect_with_reconnects("ws://localhost:1", 1)
+            .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -25,6 +25,11 @@ use anyhow::{
     Result,
 };
 use colored::Colorize;
+use contract_build::{
+    load_contract_bundle,
+    BuildMode,
+    COMPRESSED_BUNDLE_EXTENSION,
+};
 use ink_metadata::InkProject;
 use std::path::{
     Path,
@@ -59,6 +64,8 @@ impl ContractArtifacts {
 
                 if crate_metadata.contract_bundle_path().exists() {
                     crate_metadata.contract_bundle_path()
+                } else if crate_metadata.compressed_contract_bundle_path().exists() {
+                    crate_metadata.compressed_contract_bundle_path()
                 } else if crate_metadata.metadata_path().exists() {
                     crate_metadata.metadata_path()
                 } else {
@@ -86,6 +93,11 @@ impl ContractArtifacts {
                     let code = metadata.clone().source.wasm.map(|wasm| WasmCode(wasm.0));
                     (PathBuf::from(path), Some(metadata), code)
                 }
+                Some(COMPRESSED_BUNDLE_EXTENSION) => {
+                    let metadata = load_contract_bundle(path)?;
+                    let code = metadata.clone().source.wasm.map(|wasm| WasmCode(wasm.0));
+                    (PathBuf::from(path), Some(metadata), code)
+                }
                 Some("wasm") => {
                     let file_name = path.file_stem()
                         .context("WASM bundle file has unreadable name")?
@@ -102,11 +114,11 @@ impl ContractArtifacts {
                     }
                 }
                 Some(ext) => anyhow::bail!(
-                    "Invalid artifact extension {ext}, expected `.contract`, `.json` or `.wasm`"
+                    "Invalid artifact extension {ext}, expected `.contract`, `.contract.zst`, `.json` or `.wasm`"
                 ),
                 None => {
                     anyhow::bail!(
-                        "Artifact path has no extension, expected `.contract`, `.json`, or `.wasm`"
+                        "Artifact path has no extension, expected `.contract`, `.contract.zst`, `.json`, or `.wasm`"
                     )
                 }
             };
@@ -170,6 +182,15 @@ impl ContractArtifacts {
             .context("Failed to deserialize ink project metadata from contract metadata")
     }
 
+    /// Returns the [`BuildMode`] the contract was built with, as recorded in the build
+    /// info of the metadata.
+    ///
+    /// Returns `None` if the metadata or the build info is not available.
+    pub fn build_mode(&self) -> Option<BuildMode> {
+        let build_info = self.metadata().ok()?.source.build_info?;
+        serde_json::from_value(build_info.get("build_mode")?.clone()).ok()
+    }
+
     /// Returns `true` if the image is verifiable.
     ///
     /// If the metadata cannot be extracted we assume that it can't be verified.
@@ -19,7 +19,10 @@ use anyhow::{
     anyhow,
     Result,
 };
-use contract_metadata::byte_str::serialize_as_byte_str;
+use contract_metadata::byte_str::{
+    deserialize_from_byte_str,
+    serialize_as_byte_str,
+};
 use std::fmt::{
     Display,
     Formatter,
@@ -68,11 +71,13 @@ where
     Ok(data)
 }
 
-/// Fetch the contract info from the storage using the provided client.
+/// Fetch the contract info from the storage of the contracts pallet named `pallet_name`
+/// using the provided client.
 pub async fn fetch_contract_info<C: Config, E: Environment>(
     contract: &C::AccountId,
     rpc: &LegacyRpcMethods<C>,
     client: &OnlineClient<C>,
+    pallet_name: &str,
 ) -> Result<ContractInfo<C::Hash, E::Balance>>
 where
     C::AccountId: AsRef<[u8]> + Display + IntoVisitor,
@@ -82,7 +87,7 @@ where
     let best_block = get_best_block(rpc).await?;
 
     let contract_info_address = dynamic(
-        "Contracts",
+        pallet_name,
         "ContractInfoOf",
         vec![Value::from_bytes(contract)],
     );
@@ -229,8 +234,14 @@ where
 }
 
 /// A contract's child trie id.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
-pub struct TrieId(#[serde(serialize_with = "serialize_as_byte_str")] Vec<u8>);
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrieId(
+    #[serde(
+        serialize_with = "serialize_as_byte_str",
+        deserialize_with = "deserialize_from_byte_str"
+    )]
+    Vec<u8>,
+);
 
 impl TrieId {
     /// Encode the trie id as hex string.
@@ -257,11 +268,13 @@ impl Display for TrieId {
     }
 }
 
-/// Fetch the contract wasm code from the storage using the provided client and code hash.
+/// Fetch the contract wasm code from the storage of the contracts pallet named
+/// `pallet_name` using the provided client and code hash.
 pub async fn fetch_wasm_code<C: Config>(
     client: &OnlineClient<C>,
     rpc: &LegacyRpcMethods<C>,
     hash: &C::Hash,
+    pallet_name: &str,
 ) -> Result<Vec<u8>>
 where
     C::Hash: AsRef<[u8]> + Display + IntoVisitor,
@@ -269,7 +282,7 @@ where
     let best_block = get_best_block(rpc).await?;
 
     let pristine_code_address =
-        dynamic("Contracts", "PristineCode", vec![Value::from_bytes(hash)]);
+        dynamic(pallet_name, "PristineCode", vec![Value::from_bytes(hash)]);
     let pristine_code = client
         .storage()
         .at(best_block)
@@ -300,28 +313,50 @@ where
         .map_err(|err| anyhow!("AccountId deserialization error: {}", err))
 }
 
-/// Fetch all contract addresses from the storage using the provided client.
+/// Fetch the contract addresses from the storage of the contracts pallet named
+/// `pallet_name`, at most `limit` of them if given.
 pub async fn fetch_all_contracts<C: Config>(
-    client: &OnlineClient<C>,
     rpc: &LegacyRpcMethods<C>,
+    pallet_name: &str,
+    limit: Option<u32>,
 ) -> Result<Vec<C::AccountId>>
 where
     C::AccountId: Decode,
 {
+    // the maximum number of keys a node returns per page
+    const KEYS_COUNT: u32 = 1000;
+
     let best_block = get_best_block(rpc).await?;
     let root_key =
-        subxt::dynamic::storage("Contracts", "ContractInfoOf", ()).to_root_bytes();
-    let mut keys = client
-        .storage()
-        .at(best_block)
-        .fetch_raw_keys(root_key.clone())
-        .await?;
+        subxt::dynamic::storage(pallet_name, "ContractInfoOf", ()).to_root_bytes();
 
     let mut contract_accounts = Vec::new();
-    while let Some(result) = keys.next().await {
-        let key = result?;
-        let contract_account = parse_contract_account_address::<C>(&key, root_key.len())?;
-        contract_accounts.push(contract_account);
+    let mut start_key: Option<Vec<u8>> = None;
+    loop {
+        let fetched = contract_accounts.len() as u32;
+        let count = limit.map_or(KEYS_COUNT, |limit| {
+            limit.saturating_sub(fetched).min(KEYS_COUNT)
+        });
+        if count == 0 {
+            break
+        }
+        let keys = rpc
+            .state_get_keys_paged(
+                &root_key,
+                count,
+                start_key.as_deref(),
+                Some(best_block),
+            )
+            .await?;
+        for key in &keys {
+            let contract_account =
+                parse_contract_account_address::<C>(key, root_key.len())?;
+            contract_accounts.push(contract_account);
+        }
+        if (keys.len() as u32) < count {
+            break
+        }
+        start_key = keys.last().cloned();
     }
 
     Ok(contract_accounts)
@@ -20,6 +20,8 @@ use anyhow::{
 };
 use contract_transcode::{
     ContractMessageTranscoder,
+    Hex,
+    Tuple,
     Value,
 };
 use ink_env::Environment;
@@ -52,6 +54,7 @@ use std::{
         Formatter,
     },
     marker::PhantomData,
+    str::FromStr,
 };
 use subxt::{
     backend::{
@@ -70,9 +73,11 @@ use subxt::{
 };
 
 use super::{
+    extrinsic_calls::DEFAULT_PALLET_NAME,
     fetch_contract_info,
     url_to_string,
     ContractInfo,
+    RpcConnection,
     TrieId,
 };
 
@@ -103,7 +108,7 @@ where
             .storage()
             .at_latest()
             .await?
-            .storage_version("Contracts")
+            .storage_version(&self.rpc.pallet_name)
             .await
             .map_err(|e| {
                 anyhow!("The storage version for the contracts pallet could not be determined: {e}")
@@ -118,45 +123,54 @@ where
         let contract_info = self.rpc.fetch_contract_info::<E>(contract_account).await?;
         let trie_id = contract_info.trie_id();
 
-        let mut storage_keys = Vec::new();
-        let mut storage_values = Vec::new();
+        let mut storage = BTreeMap::new();
+        let mut start_key = None;
         const KEYS_COUNT: u32 = 1000;
         loop {
-            let mut keys = self
+            let (entries, next_key) = self
                 .rpc
-                .fetch_storage_keys_paged(
-                    trie_id,
-                    None,
-                    KEYS_COUNT,
-                    storage_keys.last().map(|k: &Bytes| k.as_bytes_ref()),
-                    None,
-                )
+                .fetch_storage_page(trie_id, start_key.as_ref(), KEYS_COUNT, None)
                 .await?;
-            let keys_count = keys.len();
-            let mut values = self.rpc.fetch_storage_entries(trie_id, &keys, None).await?;
-            assert_eq!(
-                keys_count,
-                values.len(),
-                "storage keys and values must be the same length"
-            );
-            storage_keys.append(&mut keys);
-            storage_values.append(&mut values);
-
-            if (keys_count as u32) < KEYS_COUNT {
-                break
+            storage.extend(entries);
+            match next_key {
+                Some(next_key) => start_key = Some(next_key),
+                None => break,
             }
         }
 
-        let storage = storage_keys
-            .into_iter()
-            .zip(storage_values.into_iter())
-            .filter_map(|(key, value)| value.map(|v| (key, v)))
-            .collect();
-
-        let contract_storage = ContractStorageData(storage);
+        let contract_storage = ContractStorageData::new(storage);
         Ok(contract_storage)
     }
 
+    /// Load a page of at most `limit` raw key/value storage entries for a given
+    /// contract, starting after `start_key`.
+    ///
+    /// The page is cut at raw storage keys. Since the keys are ordered by their hash,
+    /// the entries of a `Mapping` or `StorageVec` are spread over all pages, so
+    /// decoding a page with a [`ContractStorageLayout`] flags these cells as truncated.
+    ///
+    /// Returns the entries together with the key to pass as `start_key` for loading
+    /// the next page, or `None` if all entries have been loaded.
+    pub async fn load_contract_storage_data_page(
+        &self,
+        contract_account: &C::AccountId,
+        start_key: Option<&Bytes>,
+        limit: u32,
+    ) -> Result<(ContractStorageData, Option<Bytes>)> {
+        let contract_info = self.rpc.fetch_contract_info::<E>(contract_account).await?;
+        let (entries, next_key) = self
+            .rpc
+            .fetch_storage_page(contract_info.trie_id(), start_key, limit, None)
+            .await?;
+        let entries = entries.into_iter().collect();
+        let data = if start_key.is_some() || next_key.is_some() {
+            ContractStorageData::page(entries)
+        } else {
+            ContractStorageData::new(entries)
+        };
+        Ok((data, next_key))
+    }
+
     pub async fn load_contract_storage_with_layout(
         &self,
         contract_account: &C::AccountId,
@@ -169,12 +183,34 @@ where
 
 /// Represents the raw key/value storage for the contract.
 #[derive(Serialize, Debug)]
-pub struct ContractStorageData(BTreeMap<Bytes, Bytes>);
+#[serde(transparent)]
+pub struct ContractStorageData {
+    entries: BTreeMap<Bytes, Bytes>,
+    #[serde(skip)]
+    partial: bool,
+}
 
 impl ContractStorageData {
     /// Create a representation of raw contract storage
     pub fn new(data: BTreeMap<Bytes, Bytes>) -> Self {
-        Self(data)
+        Self {
+            entries: data,
+            partial: false,
+        }
+    }
+
+    /// Create a representation of a single page of the raw contract storage, which
+    /// does not hold all of its entries.
+    pub fn page(data: BTreeMap<Bytes, Bytes>) -> Self {
+        Self {
+            entries: data,
+            partial: true,
+        }
+    }
+
+    /// Returns `true` if the storage only holds a single page of the entries.
+    pub fn is_partial(&self) -> bool {
+        self.partial
     }
 }
 
@@ -201,12 +237,18 @@ pub struct Mapping {
     #[serde(flatten)]
     root: RootKeyEntry,
     map: Vec<(Value, Value)>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
 }
 
 impl Mapping {
     // Create new `Mapping`.
     pub fn new(root: RootKeyEntry, value: Vec<(Value, Value)>) -> Mapping {
-        Mapping { root, map: value }
+        Mapping {
+            root,
+            map: value,
+            truncated: false,
+        }
     }
 
     /// Return the root key entry of the `Mapping`.
@@ -269,6 +311,8 @@ pub struct StorageVec {
     root: RootKeyEntry,
     len: u32,
     vec: Vec<Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
 }
 
 impl StorageVec {
@@ -278,6 +322,7 @@ impl StorageVec {
             root,
             len,
             vec: value,
+            truncated: false,
         }
     }
 
@@ -339,6 +384,64 @@ impl Display for Packed {
     }
 }
 
+/// A storage cell which could not be decoded, holding the raw values.
+#[derive(Serialize, Debug)]
+pub struct Raw {
+    #[serde(serialize_with = "RootKeyEntry::key_as_hex")]
+    root_key: u32,
+    path: Vec<String>,
+    value: Value,
+}
+
+impl Raw {
+    /// Create new `Raw` from the storage entries of the root key.
+    ///
+    /// A single entry is represented by its hex value, multiple entries by a tuple of
+    /// `(key, value)` hex pairs.
+    fn new(
+        root_key: u32,
+        path: Vec<String>,
+        data: &[(Option<Bytes>, Bytes)],
+    ) -> Result<Raw> {
+        let value = match data {
+            [(None, value)] => Self::hex(value)?,
+            _ => {
+                let entries = data
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = match key {
+                            Some(key) => Self::hex(key)?,
+                            None => Value::Unit,
+                        };
+                        Ok(Value::Tuple(Tuple::new(None, vec![key, Self::hex(value)?])))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Tuple(Tuple::new(None, entries))
+            }
+        };
+        Ok(Raw {
+            root_key,
+            path,
+            value,
+        })
+    }
+
+    fn hex(bytes: &Bytes) -> Result<Value> {
+        Ok(Value::Hex(Hex::from_str(&hex::encode(&bytes.0))?))
+    }
+
+    /// Return the raw value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+impl Display for Raw {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Raw {{ {} }}", self.value)
+    }
+}
+
 /// Represents the storage cell value.
 #[derive(Serialize, Debug)]
 pub enum ContractStorageCell {
@@ -346,31 +449,55 @@ pub enum ContractStorageCell {
     Lazy(Lazy),
     StorageVec(StorageVec),
     Packed(Packed),
+    Raw(Raw),
 }
 
 impl ContractStorageCell {
-    fn root(&self) -> &RootKeyEntry {
-        match self {
+    fn root_parts(&self) -> (u32, &[String]) {
+        let root = match self {
             Self::Mapping(mapping) => mapping.root(),
             Self::Lazy(lazy) => lazy.root(),
             Self::StorageVec(storage_vec) => storage_vec.root(),
             Self::Packed(packed) => packed.root(),
-        }
+            Self::Raw(raw) => return (raw.root_key, &raw.path),
+        };
+        (root.root_key, &root.path)
     }
 
     /// Return the `RootKeyEntry` path as a string.
     pub fn path(&self) -> String {
-        self.root().path.join("::")
+        self.root_parts().1.join("::")
     }
 
     /// Return the parent.
     pub fn parent(&self) -> String {
-        self.root().path.last().cloned().unwrap_or_default()
+        self.root_parts().1.last().cloned().unwrap_or_default()
     }
 
     /// Return the root_key as a hex-encoded string.
     pub fn root_key(&self) -> String {
-        hex::encode(self.root().root_key.encode())
+        hex::encode(self.root_parts().0.encode())
+    }
+
+    /// Returns `true` if the cell is spread over several storage keys of which only
+    /// some were loaded, see [`ContractStorageData::page`].
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            Self::Mapping(mapping) => mapping.truncated,
+            Self::StorageVec(storage_vec) => storage_vec.truncated,
+            Self::Lazy(_) | Self::Packed(_) | Self::Raw(_) => false,
+        }
+    }
+
+    /// Flag a cell spread over several storage keys as truncated, returns `false` for
+    /// cells stored under a single key.
+    fn mark_truncated(&mut self) -> bool {
+        match self {
+            Self::Mapping(mapping) => mapping.truncated = true,
+            Self::StorageVec(storage_vec) => storage_vec.truncated = true,
+            Self::Lazy(_) | Self::Packed(_) | Self::Raw(_) => return false,
+        }
+        true
     }
 }
 
@@ -381,109 +508,285 @@ impl Display for ContractStorageCell {
             Self::Lazy(lazy) => lazy.fmt(f),
             Self::StorageVec(storage_vec) => storage_vec.fmt(f),
             Self::Packed(value) => value.fmt(f),
+            Self::Raw(raw) => raw.fmt(f),
         }
     }
 }
 
+/// A single raw storage entry of the contract together with its decoded value.
+#[derive(Serialize, Debug)]
+pub struct ContractStorageEntry {
+    #[serde(serialize_with = "RootKeyEntry::key_as_hex")]
+    root_key: u32,
+    path: String,
+    mapping_key: Option<Value>,
+    value: Value,
+    raw: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+}
+
+impl ContractStorageEntry {
+    /// Return the root key of the storage cell the entry belongs to.
+    pub fn root_key(&self) -> u32 {
+        self.root_key
+    }
+
+    /// Return the path of the storage cell the entry belongs to.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return the decoded mapping key, or the raw key for other cells with subkeys.
+    pub fn mapping_key(&self) -> Option<&Value> {
+        self.mapping_key.as_ref()
+    }
+
+    /// Return the decoded value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Return the raw value as a hex-encoded string.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns `true` if the storage cell the entry belongs to is truncated.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
 /// Represents storage cells containing values and type information for the contract.
 #[derive(Serialize, Debug)]
 pub struct ContractStorageLayout {
     cells: Vec<ContractStorageCell>,
+    #[serde(skip)]
+    entries: Vec<ContractStorageEntry>,
+    #[serde(skip)]
+    warnings: Vec<String>,
 }
 
 impl ContractStorageLayout {
     /// Create a representation of contract storage based on raw storage entries and
     /// metadata.
+    ///
+    /// A single page of the storage is decoded like with [`Self::new_lossy`], as the
+    /// entries a cell needs to be decoded may be on other pages.
     pub fn new(
         data: ContractStorageData,
         decoder: &ContractMessageTranscoder,
+    ) -> Result<Self> {
+        Self::decode(data, decoder, false)
+    }
+
+    /// Create a representation of contract storage like [`Self::new`], but keep the raw
+    /// hex values of cells which can not be decoded instead of failing.
+    ///
+    /// The reasons why cells could not be decoded are returned by [`Self::warnings`].
+    pub fn new_lossy(
+        data: ContractStorageData,
+        decoder: &ContractMessageTranscoder,
+    ) -> Result<Self> {
+        Self::decode(data, decoder, true)
+    }
+
+    fn decode(
+        data: ContractStorageData,
+        decoder: &ContractMessageTranscoder,
+        lossy: bool,
     ) -> Result<Self> {
         let layout = decoder.metadata().layout();
-        let registry = decoder.metadata().registry();
         let mut path_stack = vec!["root".to_string()];
         let mut root_key_entries: Vec<RootKeyEntry> = Vec::new();
         Self::collect_root_key_entries(layout, &mut path_stack, &mut root_key_entries);
 
-        let mut cells = data
-            .0
+        let partial = data.partial;
+        let mut cells = Vec::new();
+        let mut entries = Vec::new();
+        let mut warnings = Vec::new();
+        let grouped = data
+            .entries
             .into_iter()
             .map(|(key, value)| {
                 let (root_key, mapping_key) = Self::key_parts(&key);
                 (root_key, (mapping_key, value))
             })
-            .into_group_map()
-            .into_iter()
-            .map(|(root_key, mut data)| {
-                let root_key_entry = root_key_entries
-                    .iter()
-                    .find(|e| e.root_key == root_key)
-                    .ok_or(anyhow!(
-                        "Root key {} not found for the RootLayout",
-                        root_key
-                    ))?;
-                let type_def = registry.resolve(root_key_entry.type_id).ok_or(
-                    anyhow!("Type {} not found in the registry", root_key_entry.type_id),
-                )?;
-                let root = RootKeyEntry {
-                    path: root_key_entry.path.clone(),
-                    type_id: root_key_entry.type_id,
-                    root_key,
-                };
-                match type_def.path.to_string().as_str() {
-                    "ink_storage::lazy::mapping::Mapping" => {
-                        let key_type_id = Self::param_type_id(type_def, "K")
-                            .ok_or(anyhow!("Param `K` not found in type registry"))?;
-                        let value_type_id = Self::param_type_id(type_def, "V")
-                            .ok_or(anyhow!("Param `V` not found in type registry"))?;
-                        let value = Self::decode_to_mapping(
-                            data,
-                            key_type_id,
-                            value_type_id,
-                            decoder,
-                        )?;
-                        Ok(ContractStorageCell::Mapping(Mapping::new(root, value)))
-                    }
-                    "ink_storage::lazy::vec::StorageVec" => {
-                        // Sort by the key to get the Vec in the right order.
-                        data.sort_by(|a, b| a.0.cmp(&b.0));
-                        // First item is the `StorageVec` len.
-                        let raw_len = data
-                            .first()
-                            .ok_or(anyhow!("Length of the StorageVec not found"))?
-                            .1
-                            .clone();
-                        let len = u32::decode(&mut raw_len.as_bytes_ref())?;
-                        let value_type_id = Self::param_type_id(type_def, "V")
-                            .ok_or(anyhow!("Param `V` not found in type registry"))?;
-                        let value =
-                            Self::decode_to_vec(&data[1..], value_type_id, decoder)?;
-                        Ok(ContractStorageCell::StorageVec(StorageVec::new(
-                            root, len, value,
-                        )))
-                    }
-                    "ink_storage::lazy::Lazy" => {
-                        let value_type_id = Self::param_type_id(type_def, "V")
-                            .ok_or(anyhow!("Param `V` not found in type registry"))?;
-                        let raw_value =
-                            data.first().ok_or(anyhow!("Empty storage cell"))?.1.clone();
-                        let value = decoder
-                            .decode(value_type_id, &mut raw_value.as_bytes_ref())?;
-                        Ok(ContractStorageCell::Lazy(Lazy::new(root, value)))
+            .into_group_map();
+        for (root_key, data) in grouped {
+            let raw_data = data.clone();
+            match Self::decode_cell(root_key, data, &root_key_entries, decoder) {
+                Ok(mut cell) => {
+                    if partial && cell.mark_truncated() {
+                        warnings.push(format!(
+                            "Only {} entries of the storage cell `{}` are on this page, \
+                            the other entries are on other pages",
+                            raw_data.len(),
+                            cell.path()
+                        ));
                     }
-                    _ => {
-                        let raw_value =
-                            data.first().ok_or(anyhow!("Empty storage cell"))?.1.clone();
-                        let value = decoder
-                            .decode(root.type_id, &mut raw_value.as_bytes_ref())?;
-                        Ok(ContractStorageCell::Packed(Packed::new(root, value)))
+                    entries.append(&mut Self::cell_entries(&cell, raw_data)?);
+                    cells.push(cell);
+                }
+                Err(err) => {
+                    if !lossy && !partial {
+                        return Err(err)
                     }
+                    warnings.push(format!(
+                        "Failed to decode storage cell with root key 0x{}: {err}",
+                        hex::encode(root_key.encode())
+                    ));
+                    let path = root_key_entries
+                        .iter()
+                        .find(|e| e.root_key == root_key)
+                        .map(|e| e.path.clone())
+                        .unwrap_or_default();
+                    let cell =
+                        ContractStorageCell::Raw(Raw::new(root_key, path, &raw_data)?);
+                    entries.append(&mut Self::cell_entries(&cell, raw_data)?);
+                    cells.push(cell);
                 }
-            })
-            .collect::<Result<Vec<_>>>()?;
+            }
+        }
 
         cells.sort_by_key(|k| k.path());
+        // stable sort, keeping the order of the entries within a cell
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            cells,
+            entries,
+            warnings,
+        })
+    }
+
+    /// Pair the raw storage entries of a cell with their decoded values.
+    fn cell_entries(
+        cell: &ContractStorageCell,
+        mut data: Vec<(Option<Bytes>, Bytes)>,
+    ) -> Result<Vec<ContractStorageEntry>> {
+        let (root_key, path) = cell.root_parts();
+        let truncated = cell.is_truncated();
+        let entry = |mapping_key: Option<Value>, value: Value, raw: &Bytes| {
+            ContractStorageEntry {
+                root_key,
+                path: path.join("::"),
+                mapping_key,
+                value,
+                raw: format!("0x{}", hex::encode(&raw.0)),
+                truncated,
+            }
+        };
+        let raw_key = |key: &Option<Bytes>| key.as_ref().map(Raw::hex).transpose();
+        let entries: Vec<ContractStorageEntry> = match cell {
+            ContractStorageCell::Mapping(mapping) => {
+                data.iter()
+                    .zip(mapping.iter())
+                    .map(|((_, raw), (key, value))| {
+                        entry(Some(key.clone()), value.clone(), raw)
+                    })
+                    .collect()
+            }
+            ContractStorageCell::StorageVec(storage_vec) => {
+                // same order as when decoding, the first item is the length
+                data.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut entries = Vec::with_capacity(data.len());
+                if let Some((key, raw)) = data.first() {
+                    entries.push(entry(
+                        raw_key(key)?,
+                        Value::UInt(storage_vec.len().into()),
+                        raw,
+                    ));
+                }
+                for ((key, raw), value) in data.iter().skip(1).zip(storage_vec.values()) {
+                    entries.push(entry(raw_key(key)?, value.clone(), raw));
+                }
+                entries
+            }
+            ContractStorageCell::Lazy(Lazy { value, .. })
+            | ContractStorageCell::Packed(Packed { value, .. }) => {
+                match data.first() {
+                    Some((key, raw)) => vec![entry(raw_key(key)?, value.clone(), raw)],
+                    None => Vec::new(),
+                }
+            }
+            ContractStorageCell::Raw(_) => {
+                data.iter()
+                    .map(|(key, raw)| Ok(entry(raw_key(key)?, Raw::hex(raw)?, raw)))
+                    .collect::<Result<_>>()?
+            }
+        };
+        Ok(entries)
+    }
 
-        Ok(Self { cells })
+    /// Decode the storage entries sharing the same root key into a storage cell.
+    fn decode_cell(
+        root_key: u32,
+        mut data: Vec<(Option<Bytes>, Bytes)>,
+        root_key_entries: &[RootKeyEntry],
+        decoder: &ContractMessageTranscoder,
+    ) -> Result<ContractStorageCell> {
+        let registry = decoder.metadata().registry();
+        let root_key_entry = root_key_entries
+            .iter()
+            .find(|e| e.root_key == root_key)
+            .ok_or(anyhow!(
+                "Root key {} not found for the RootLayout",
+                root_key
+            ))?;
+        let type_def = registry.resolve(root_key_entry.type_id).ok_or(anyhow!(
+            "Type {} not found in the registry",
+            root_key_entry.type_id
+        ))?;
+        let root = RootKeyEntry {
+            path: root_key_entry.path.clone(),
+            type_id: root_key_entry.type_id,
+            root_key,
+        };
+        match type_def.path.to_string().as_str() {
+            "ink_storage::lazy::mapping::Mapping" => {
+                let key_type_id = Self::param_type_id(type_def, "K")
+                    .ok_or(anyhow!("Param `K` not found in type registry"))?;
+                let value_type_id = Self::param_type_id(type_def, "V")
+                    .ok_or(anyhow!("Param `V` not found in type registry"))?;
+                let value =
+                    Self::decode_to_mapping(data, key_type_id, value_type_id, decoder)?;
+                Ok(ContractStorageCell::Mapping(Mapping::new(root, value)))
+            }
+            "ink_storage::lazy::vec::StorageVec" => {
+                // Sort by the key to get the Vec in the right order.
+                data.sort_by(|a, b| a.0.cmp(&b.0));
+                // First item is the `StorageVec` len, stored without a subkey.
+                let raw_len = match data.first() {
+                    Some((None, raw_len)) => raw_len.clone(),
+                    _ => return Err(anyhow!("Length of the StorageVec not found")),
+                };
+                let len = u32::decode(&mut raw_len.as_bytes_ref())?;
+                let value_type_id = Self::param_type_id(type_def, "V")
+                    .ok_or(anyhow!("Param `V` not found in type registry"))?;
+                let value = Self::decode_to_vec(&data[1..], value_type_id, decoder)?;
+                Ok(ContractStorageCell::StorageVec(StorageVec::new(
+                    root, len, value,
+                )))
+            }
+            "ink_storage::lazy::Lazy" => {
+                let value_type_id = Self::param_type_id(type_def, "V")
+                    .ok_or(anyhow!("Param `V` not found in type registry"))?;
+                let raw_value =
+                    data.first().ok_or(anyhow!("Empty storage cell"))?.1.clone();
+                let value =
+                    decoder.decode(value_type_id, &mut raw_value.as_bytes_ref())?;
+                Ok(ContractStorageCell::Lazy(Lazy::new(root, value)))
+            }
+            _ => {
+                let raw_value =
+                    data.first().ok_or(anyhow!("Empty storage cell"))?.1.clone();
+                let value =
+                    decoder.decode(root.type_id, &mut raw_value.as_bytes_ref())?;
+                Ok(ContractStorageCell::Packed(Packed::new(root, value)))
+            }
+        }
     }
 
     /// Return the iterator over the storage cells.
@@ -491,6 +794,25 @@ impl ContractStorageLayout {
         self.cells.iter()
     }
 
+    /// Return the iterator over the raw storage entries with their decoded values.
+    pub fn entries(&self) -> impl Iterator<Item = &ContractStorageEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the storage entries in JSON format, each with its root key, mapping key
+    /// (if any), decoded value and raw hex value.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.entries)?)
+    }
+
+    /// Return the reasons why storage cells could not be decoded, and which cells are
+    /// truncated for a single page of the storage.
+    ///
+    /// Only populated by [`Self::new_lossy`] or for a single page of the storage.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     fn decode_to_mapping(
         data: Vec<(Option<Bytes>, Bytes)>,
         key_type_id: u32,
@@ -609,6 +931,7 @@ pub struct ContractStorageRpc<C: Config> {
     rpc_client: RpcClient,
     rpc_methods: LegacyRpcMethods<C>,
     client: OnlineClient<C>,
+    pallet_name: String,
 }
 
 impl<C: Config> ContractStorageRpc<C>
@@ -617,8 +940,8 @@ where
     C::Hash: IntoVisitor,
 {
     /// Create a new instance of the ContractsRpc.
-    pub async fn new(url: &url::Url) -> Result<Self> {
-        let rpc_client = RpcClient::from_url(url_to_string(url)).await?;
+    pub async fn new(url: &url::Url, connection: &RpcConnection) -> Result<Self> {
+        let rpc_client = connection.connect(&url_to_string(url)).await?;
         let client = OnlineClient::from_rpc_client(rpc_client.clone()).await?;
         let rpc_methods = LegacyRpcMethods::new(rpc_client.clone());
 
@@ -626,9 +949,17 @@ where
             rpc_client,
             rpc_methods,
             client,
+            pallet_name: DEFAULT_PALLET_NAME.to_string(),
         })
     }
 
+    /// Query the contracts pallet named `pallet_name` instead of the canonical
+    /// `Contracts`.
+    pub fn pallet_name(mut self, pallet_name: &str) -> Self {
+        self.pallet_name = pallet_name.to_string();
+        self
+    }
+
     /// Fetch the contract info to access the trie id for querying storage.
     pub async fn fetch_contract_info<E: Environment>(
         &self,
@@ -637,7 +968,13 @@ where
     where
         E::Balance: IntoVisitor,
     {
-        fetch_contract_info::<C, E>(contract, &self.rpc_methods, &self.client).await
+        fetch_contract_info::<C, E>(
+            contract,
+            &self.rpc_methods,
+            &self.client,
+            &self.pallet_name,
+        )
+        .await
     }
 
     /// Fetch the contract storage at the given key.
@@ -686,6 +1023,47 @@ where
         Ok(data)
     }
 
+    /// Fetch a page of at most `count` storage entries, starting after `start_key`.
+    ///
+    /// Returns the entries together with the key to continue from, or `None` if there
+    /// are no more entries. Keys without a value are skipped.
+    pub async fn fetch_storage_page(
+        &self,
+        trie_id: &TrieId,
+        start_key: Option<&Bytes>,
+        count: u32,
+        block_hash: Option<C::Hash>,
+    ) -> Result<(Vec<(Bytes, Bytes)>, Option<Bytes>)> {
+        let keys = self
+            .fetch_storage_keys_paged(
+                trie_id,
+                None,
+                count,
+                start_key.map(|k| k.as_bytes_ref()),
+                block_hash,
+            )
+            .await?;
+        let values = self
+            .fetch_storage_entries(trie_id, &keys, block_hash)
+            .await?;
+        assert_eq!(
+            keys.len(),
+            values.len(),
+            "storage keys and values must be the same length"
+        );
+        let next_key = if (keys.len() as u32) < count {
+            None
+        } else {
+            keys.last().cloned()
+        };
+        let entries = keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|v| (key, v)))
+            .collect();
+        Ok((entries, next_key))
+    }
+
     /// Fetch the storage values for the given keys.
     pub async fn fetch_storage_entries(
         &self,
@@ -236,3 +236,179 @@ fn storage_decode_mapping_type_works() {
     );
     assert_eq!(cell.root_key(), hex::encode(lazy_type_root_encoded));
 }
+
+#[test]
+fn storage_decode_lossy_keeps_undecodable_cells_as_hex() {
+    let root_key_encoded = Encode::encode(&ROOT_KEY);
+    const UNKNOWN_ROOT_KEY: u32 = 42;
+    let unknown_root_key_encoded = Encode::encode(&UNKNOWN_ROOT_KEY);
+    #[derive(scale_info::TypeInfo, StorageLayout, Storable)]
+    struct Data {
+        a: i32,
+    }
+
+    let Struct(data_layout) = <Data as StorageLayout>::layout(&ROOT_KEY) else {
+        panic!("Layout shall be created");
+    };
+    let storage_layout: Layout = RootLayout::new(
+        LayoutKey::from(ROOT_KEY),
+        data_layout,
+        scale_info::meta_type::<Data>(),
+    )
+    .into();
+
+    let metadata = InkProject::new(storage_layout, contract_default_spec());
+    let decoder = ContractMessageTranscoder::new(metadata);
+
+    let key = [BASE_KEY_RAW.to_vec(), root_key_encoded.clone()].concat();
+    let unknown_key = [BASE_KEY_RAW.to_vec(), unknown_root_key_encoded.clone()].concat();
+    let value = Data { a: 16 };
+
+    let storage = || {
+        let mut map = BTreeMap::new();
+        map.insert(Bytes::from(key.clone()), encode_storage_value(&value));
+        map.insert(Bytes::from(unknown_key.clone()), Bytes::from(vec![1u8, 2]));
+        ContractStorageData::new(map)
+    };
+
+    assert!(ContractStorageLayout::new(storage(), &decoder).is_err());
+
+    let layout = ContractStorageLayout::new_lossy(storage(), &decoder)
+        .expect("Contract storage layout shall be created");
+    assert_eq!(layout.warnings().len(), 1);
+    let mut iter = layout.iter();
+    let cell = iter.next().expect("Raw cell shall be in layout");
+    assert_eq!(cell.to_string(), "Raw { 0x0102 }".to_string());
+    assert_eq!(cell.root_key(), hex::encode(unknown_root_key_encoded));
+
+    let cell = iter.next().expect("Root cell shall be in layout");
+    assert_eq!(cell.to_string(), format!("Data {{ a: {} }}", value.a));
+    assert_eq!(cell.root_key(), hex::encode(root_key_encoded));
+}
+
+#[test]
+fn storage_entries_to_json_works() {
+    let root_key_encoded = Encode::encode(&ROOT_KEY);
+    let lazy_type_root_encoded = Encode::encode(&LAZY_TYPE_ROOT_KEY);
+    #[derive(scale_info::TypeInfo, StorageLayout, Storable)]
+    struct Data {
+        a: Mapping<u8, u8, ManualKey<LAZY_TYPE_ROOT_KEY>>,
+    }
+
+    let Struct(data_layout) = <Data as StorageLayout>::layout(&ROOT_KEY) else {
+        panic!("Layout shall be created");
+    };
+    let storage_layout: Layout = RootLayout::new(
+        LayoutKey::from(ROOT_KEY),
+        data_layout,
+        scale_info::meta_type::<Data>(),
+    )
+    .into();
+
+    let metadata = InkProject::new(storage_layout, contract_default_spec());
+    let decoder = ContractMessageTranscoder::new(metadata);
+
+    let value = Data { a: Mapping::new() };
+    let mapping_item = (4u8, 8u8);
+
+    let key = [BASE_KEY_RAW.to_vec(), root_key_encoded].concat();
+    let mapping_key = [
+        BASE_KEY_RAW.to_vec(),
+        lazy_type_root_encoded,
+        Encode::encode(&mapping_item.0),
+    ]
+    .concat();
+
+    let mut map = BTreeMap::new();
+    map.insert(Bytes::from(key), encode_storage_value(&value));
+    map.insert(
+        Bytes::from(mapping_key),
+        encode_storage_value(&mapping_item.1),
+    );
+
+    let data = ContractStorageData::new(map);
+    let layout = ContractStorageLayout::new(data, &decoder)
+        .expect("Contract storage layout shall be created");
+    let json: serde_json::Value =
+        serde_json::from_str(&layout.to_json().expect("JSON shall be created"))
+            .expect("JSON shall be valid");
+
+    assert_eq!(
+        json,
+        serde_json::json!([
+            {
+                "root_key": "0x00000000",
+                "path": "root",
+                "mapping_key": null,
+                "value": layout.entries().next().unwrap().value(),
+                "raw": "0x",
+            },
+            {
+                "root_key": "0x01000000",
+                "path": "root::Data::a",
+                "mapping_key": { "UInt": 4 },
+                "value": { "UInt": 8 },
+                "raw": "0x08",
+            },
+        ])
+    );
+}
+
+#[test]
+fn storage_decode_page_flags_truncated_cells() {
+    let root_key_encoded = Encode::encode(&ROOT_KEY);
+    let lazy_type_root_encoded = Encode::encode(&LAZY_TYPE_ROOT_KEY);
+    #[derive(scale_info::TypeInfo, StorageLayout, Storable)]
+    struct Data {
+        a: Mapping<u8, u8, ManualKey<LAZY_TYPE_ROOT_KEY>>,
+    }
+
+    let Struct(data_layout) = <Data as StorageLayout>::layout(&ROOT_KEY) else {
+        panic!("Layout shall be created");
+    };
+    let storage_layout: Layout = RootLayout::new(
+        LayoutKey::from(ROOT_KEY),
+        data_layout,
+        scale_info::meta_type::<Data>(),
+    )
+    .into();
+
+    let metadata = InkProject::new(storage_layout, contract_default_spec());
+    let decoder = ContractMessageTranscoder::new(metadata);
+
+    let value = Data { a: Mapping::new() };
+    let mapping_item = (4u8, 8u8);
+
+    let key = [BASE_KEY_RAW.to_vec(), root_key_encoded].concat();
+    let mapping_key = [
+        BASE_KEY_RAW.to_vec(),
+        lazy_type_root_encoded,
+        Encode::encode(&mapping_item.0),
+    ]
+    .concat();
+
+    let mut map = BTreeMap::new();
+    map.insert(Bytes::from(key), encode_storage_value(&value));
+    map.insert(
+        Bytes::from(mapping_key),
+        encode_storage_value(&mapping_item.1),
+    );
+
+    let data = ContractStorageData::page(map);
+    assert!(data.is_partial());
+    let layout = ContractStorageLayout::new(data, &decoder)
+        .expect("Contract storage layout shall be created");
+
+    let mut iter = layout.iter();
+    let cell = iter.next().expect("Root cell shall be in layout");
+    assert!(!cell.is_truncated());
+    let cell = iter.next().expect("Mapping type cell shall be in layout");
+    assert!(cell.is_truncated());
+    assert_eq!(layout.warnings().len(), 1);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&layout.to_json().expect("JSON shall be created"))
+            .expect("JSON shall be valid");
+    assert_eq!(json[0].get("truncated"), None);
+    assert_eq!(json[1]["truncated"], serde_json::json!(true));
+}
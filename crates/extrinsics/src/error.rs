@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::extrinsic_calls::DEFAULT_PALLET_NAME;
 use sp_runtime::DispatchError;
 use std::fmt::{
     self,
@@ -36,11 +37,11 @@ impl From<subxt::Error> for ErrorVariant {
                 module_err
                     .details()
                     .map(|details| {
-                        ErrorVariant::Module(ModuleError {
-                            pallet: details.pallet.name().to_string(),
-                            error: details.variant.name.to_string(),
-                            docs: details.variant.docs.clone(),
-                        })
+                        ErrorVariant::Module(ModuleError::new(
+                            details.pallet.name(),
+                            &details.variant.name,
+                            details.variant.docs.clone(),
+                        ))
                     })
                     .unwrap_or_else(|err| {
                         ErrorVariant::Generic(GenericError::from_message(format!(
@@ -56,7 +57,11 @@ impl From<subxt::Error> for ErrorVariant {
 
 impl From<anyhow::Error> for ErrorVariant {
     fn from(error: anyhow::Error) -> Self {
-        Self::Generic(GenericError::from_message(format!("{error:?}")))
+        // Keep the details of runtime errors which were propagated as `anyhow::Error`.
+        match error.downcast::<subxt::Error>() {
+            Ok(error) => error.into(),
+            Err(error) => Self::Generic(GenericError::from_message(format!("{error:?}"))),
+        }
     }
 }
 
@@ -83,6 +88,111 @@ pub struct ModuleError {
     pub pallet: String,
     pub error: String,
     pub docs: Vec<String>,
+    /// A human readable explanation and suggested fix, if the error is a known one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+}
+
+impl ModuleError {
+    /// Create a new `ModuleError`, attaching an explanation if the error is known.
+    pub fn new(pallet: &str, error: &str, docs: Vec<String>) -> Self {
+        ModuleError {
+            pallet: pallet.to_string(),
+            error: error.to_string(),
+            docs,
+            help: explain(pallet, DEFAULT_PALLET_NAME, error).map(ToString::to_string),
+        }
+    }
+
+    /// Explains the error of a runtime whose contracts pallet is named `pallet_name`.
+    pub fn for_contracts_pallet(self, pallet_name: &str) -> Self {
+        let help = explain(&self.pallet, pallet_name, &self.error);
+        ModuleError {
+            help: help.map(ToString::to_string),
+            ..self
+        }
+    }
+
+    /// Returns the documentation of the error as a single line.
+    fn docs_line(&self) -> String {
+        self.docs
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Returns a short explanation and suggested fix for common `pallet-contracts` errors,
+/// if the `pallet` is the contracts pallet named `contracts_pallet`.
+fn explain(pallet: &str, contracts_pallet: &str, error: &str) -> Option<&'static str> {
+    if pallet != contracts_pallet {
+        return None
+    }
+    let help = match error {
+        "StorageDepositLimitExhausted" => {
+            "The storage deposit required by the extrinsic exceeds the given limit. \
+             Increase it with `--storage-deposit-limit`, or omit the flag to use the \
+             amount estimated by the dry-run."
+        }
+        "StorageDepositNotEnoughFunds" => {
+            "The caller does not have enough free balance to pay the storage deposit. \
+             Top up the account or reduce the amount of storage the contract uses."
+        }
+        "CodeNotFound" => {
+            "No code with the given code hash is stored on-chain. Upload the code \
+             first with `cargo contract upload`, or check the code hash and the node \
+             `--url`."
+        }
+        "ContractNotFound" => {
+            "No contract exists at the given address. Check the contract address and \
+             that you are connected to the right node with `--url`."
+        }
+        "ContractTrapped" => {
+            "The contract panicked or hit an `unreachable` instruction during \
+             execution. Dry-run the call with `--verbose` and check the debug \
+             message for the cause, e.g. an `unwrap` or overflow."
+        }
+        "ContractReverted" => {
+            "The contract returned an error and reverted its state changes. Check the \
+             decoded return value of a dry-run for the error returned by the \
+             contract."
+        }
+        "OutOfGas" => {
+            "The extrinsic ran out of gas. Omit `--gas` and `--proof-size` to use the \
+             values estimated by the dry-run, or increase them."
+        }
+        "DuplicateContract" => {
+            "A contract with the same code hash, constructor arguments and salt \
+             already exists. Pass a different `--salt` to instantiate another \
+             instance."
+        }
+        "CodeInUse" => {
+            "The code cannot be removed because it is still used by at least one \
+             contract."
+        }
+        "CodeTooLarge" => {
+            "The contract code exceeds the maximum size allowed by the chain. Build \
+             with `--release` and reduce the size of the contract."
+        }
+        "CodeRejected" => {
+            "The chain rejected the contract code, e.g. because it uses features the \
+             chain does not support. Make sure the contract was built with a \
+             compatible version of `cargo-contract`."
+        }
+        "TransferFailed" => {
+            "Transferring the value failed, most likely because the sender does not \
+             have enough free balance or the transfer would leave the account below \
+             the existential deposit."
+        }
+        "MigrationInProgress" => {
+            "A migration of the contracts pallet is in progress. Retry once the \
+             migration has finished."
+        }
+        _ => return None,
+    };
+    Some(help)
 }
 
 #[derive(serde::Serialize)]
@@ -97,22 +207,21 @@ impl GenericError {
 }
 
 impl ErrorVariant {
+    /// Resolves the dispatch error against the metadata, explaining the errors of the
+    /// contracts pallet named `pallet_name`.
     pub fn from_dispatch_error(
         error: &DispatchError,
         metadata: &subxt::Metadata,
+        pallet_name: &str,
     ) -> anyhow::Result<ErrorVariant> {
         match error {
             DispatchError::Module(err) => {
-                let pallet = metadata.pallet_by_index_err(err.index)?;
-                let variant =
-                    pallet.error_variant_by_index(err.error[0]).ok_or_else(|| {
-                        anyhow::anyhow!("Error variant {} not found", err.error[0])
-                    })?;
-                Ok(ErrorVariant::Module(ModuleError {
-                    pallet: pallet.name().to_string(),
-                    error: variant.name.to_owned(),
-                    docs: variant.docs.to_owned(),
-                }))
+                Ok(Self::from_module_error(
+                    err.index,
+                    err.error[0],
+                    metadata,
+                    pallet_name,
+                ))
             }
             err => {
                 Ok(ErrorVariant::Generic(GenericError::from_message(format!(
@@ -123,6 +232,53 @@ impl ErrorVariant {
     }
 }
 
+impl ErrorVariant {
+    /// Resolves the error with the given pallet and error index against the metadata,
+    /// explaining the errors of the contracts pallet named `pallet_name`.
+    ///
+    /// If the error is not found in the metadata, e.g. because it is outdated, the
+    /// indices are reported instead.
+    pub fn from_module_error(
+        pallet_index: u8,
+        error_index: u8,
+        metadata: &subxt::Metadata,
+        pallet_name: &str,
+    ) -> ErrorVariant {
+        let variant = metadata.pallet_by_index(pallet_index).and_then(|pallet| {
+            pallet
+                .error_variant_by_index(error_index)
+                .map(|variant| (pallet.name(), variant))
+        });
+        match variant {
+            Some((pallet, variant)) => {
+                ErrorVariant::Module(
+                    ModuleError::new(pallet, &variant.name, variant.docs.to_owned())
+                        .for_contracts_pallet(pallet_name),
+                )
+            }
+            None => {
+                ErrorVariant::Generic(GenericError::from_message(format!(
+                    "ModuleError: unknown error {error_index} of the pallet with index \
+                     {pallet_index}, the chain metadata may be outdated"
+                )))
+            }
+        }
+    }
+}
+
+impl ErrorVariant {
+    /// Explains the module errors of a runtime whose contracts pallet is named
+    /// `pallet_name`.
+    pub fn for_contracts_pallet(self, pallet_name: &str) -> Self {
+        match self {
+            ErrorVariant::Module(err) => {
+                ErrorVariant::Module(err.for_contracts_pallet(pallet_name))
+            }
+            err => err,
+        }
+    }
+}
+
 impl Debug for ErrorVariant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         <Self as Display>::fmt(self, f)
@@ -133,12 +289,118 @@ impl Display for ErrorVariant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ErrorVariant::Module(err) => {
-                f.write_fmt(format_args!(
-                    "ModuleError: {}::{}: {:?}",
-                    err.pallet, err.error, err.docs
-                ))
+                write!(f, "ModuleError: {}::{}", err.pallet, err.error)?;
+                let docs = err.docs_line();
+                if !docs.is_empty() {
+                    write!(f, ": {docs}")?;
+                }
+                if let Some(help) = &err.help {
+                    write!(f, "\n\nHelp: {help}")?;
+                }
+                Ok(())
             }
             ErrorVariant::Generic(err) => write!(f, "{}", err.error),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_module_error_is_explained() {
+        let err = ErrorVariant::Module(ModuleError::new(
+            "Contracts",
+            "StorageDepositLimitExhausted",
+            Vec::new(),
+        ));
+        let display = err.to_string();
+        assert!(display.contains("Help:"));
+        assert!(display.contains("--storage-deposit-limit"));
+    }
+
+    fn metadata() -> subxt::Metadata {
+        use scale::Decode;
+
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        subxt::Metadata::decode(&mut &*metadata_bytes).expect("the metadata must decode")
+    }
+
+    #[test]
+    fn module_error_is_resolved_against_metadata() {
+        let metadata = metadata();
+        let pallet = metadata.pallet_by_name("Contracts").unwrap();
+        let (error_index, _) = pallet
+            .error_variants()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == "CodeNotFound")
+            .unwrap();
+        let error = DispatchError::Module(sp_runtime::ModuleError {
+            index: pallet.index(),
+            error: [error_index as u8, 0, 0, 0],
+            message: None,
+        });
+
+        let err =
+            ErrorVariant::from_dispatch_error(&error, &metadata, "Contracts").unwrap();
+
+        let display = err.to_string();
+        assert!(
+            display.starts_with(
+                "ModuleError: Contracts::CodeNotFound: No code could be found at the \
+                 supplied code hash."
+            ),
+            "{display}"
+        );
+        assert!(display.contains("Help:"), "{display}");
+    }
+
+    #[test]
+    fn unknown_module_error_reports_the_indices() {
+        let err = ErrorVariant::from_module_error(255, 7, &metadata(), "Contracts");
+
+        assert_eq!(
+            err.to_string(),
+            "ModuleError: unknown error 7 of the pallet with index 255, the chain \
+             metadata may be outdated"
+        );
+    }
+
+    #[test]
+    fn module_error_docs_are_rendered_on_one_line() {
+        let err = ErrorVariant::Module(ModuleError::new(
+            "Balances",
+            "InsufficientBalance",
+            vec![
+                "Balance too low ".into(),
+                "".into(),
+                " to send value.".into(),
+            ],
+        ));
+
+        assert_eq!(
+            err.to_string(),
+            "ModuleError: Balances::InsufficientBalance: Balance too low to send value."
+        );
+    }
+
+    #[test]
+    fn unknown_module_error_is_not_explained() {
+        let err = ModuleError::new("Contracts", "SomeUnknownError", Vec::new());
+        assert!(err.help.is_none());
+        let err = ModuleError::new("Balances", "CodeNotFound", Vec::new());
+        assert!(err.help.is_none());
+    }
+
+    #[test]
+    fn module_error_of_renamed_contracts_pallet_is_explained() {
+        let err = ModuleError::new("MyContracts", "CodeNotFound", Vec::new());
+        assert!(err.help.is_none());
+        let err = err.for_contracts_pallet("MyContracts");
+        assert!(err.help.is_some());
+    }
+}
@@ -18,7 +18,12 @@ use super::{
     BalanceVariant,
     TokenMetadata,
 };
-use crate::DEFAULT_KEY_COL_WIDTH;
+use crate::{
+    chain_ss58_prefix,
+    url_to_string,
+    RpcConnection,
+    DEFAULT_KEY_COL_WIDTH,
+};
 use colored::Colorize as _;
 use contract_build::Verbosity;
 use contract_transcode::{
@@ -40,8 +45,12 @@ use std::{
 };
 use subxt::{
     self,
+    backend::legacy::LegacyRpcMethods,
     blocks::ExtrinsicEvents,
-    events::StaticEvent,
+    events::{
+        EventDetails,
+        StaticEvent,
+    },
     ext::{
         scale_decode::{
             self,
@@ -50,6 +59,7 @@ use subxt::{
         scale_encode,
     },
     Config,
+    OnlineClient,
 };
 
 /// A custom event emitted by the contract.
@@ -149,6 +159,72 @@ where
     const EVENT: &'static str = "CodeRemoved";
 }
 
+/// Decodes the event if it is the event `E` of the contracts pallet named
+/// `pallet_name`.
+///
+/// Unlike [`EventDetails::as_event`], which only matches the canonical `Contracts`
+/// pallet of [`StaticEvent::PALLET`], this supports runtimes with a renamed pallet.
+pub fn as_contracts_event<E: StaticEvent, C: Config>(
+    event: &EventDetails<C>,
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+) -> Result<Option<E>, subxt::Error> {
+    decode_contracts_event(
+        event.pallet_name(),
+        event.event_metadata().variant,
+        event.field_bytes(),
+        metadata.types(),
+        pallet_name,
+    )
+}
+
+/// Returns the first event `E` of the contracts pallet named `pallet_name`.
+pub fn find_first_contracts_event<E: StaticEvent, C: Config>(
+    events: &ExtrinsicEvents<C>,
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+) -> Result<Option<E>, subxt::Error> {
+    for event in events.iter() {
+        if let Some(event) = as_contracts_event(&event?, metadata, pallet_name)? {
+            return Ok(Some(event))
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the last event `E` of the contracts pallet named `pallet_name`.
+pub fn find_last_contracts_event<E: StaticEvent, C: Config>(
+    events: &ExtrinsicEvents<C>,
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+) -> Result<Option<E>, subxt::Error> {
+    let mut last = None;
+    for event in events.iter() {
+        if let Some(event) = as_contracts_event(&event?, metadata, pallet_name)? {
+            last = Some(event);
+        }
+    }
+    Ok(last)
+}
+
+fn decode_contracts_event<E: StaticEvent>(
+    event_pallet: &str,
+    variant: &scale_info::Variant<PortableForm>,
+    field_bytes: &[u8],
+    types: &scale_info::PortableRegistry,
+    pallet_name: &str,
+) -> Result<Option<E>, subxt::Error> {
+    if event_pallet != pallet_name || variant.name != E::EVENT {
+        return Ok(None)
+    }
+    let mut fields = variant
+        .fields
+        .iter()
+        .map(|f| scale_decode::Field::new(&f.ty.id, f.name.as_deref()));
+    let event = E::decode_as_fields(&mut &field_bytes[..], &mut fields, types)?;
+    Ok(Some(event))
+}
+
 /// Field that represent data of an event from invoking a contract extrinsic.
 #[derive(serde::Serialize)]
 pub struct Field {
@@ -174,6 +250,9 @@ impl Field {
 /// An event produced from invoking a contract extrinsic.
 #[derive(serde::Serialize)]
 pub struct Event {
+    /// index of the event in its block
+    #[serde(skip_serializing)]
+    pub index: u32,
     /// name of a pallet
     pub pallet: String,
     /// name of the event
@@ -192,21 +271,122 @@ pub struct DisplayEvents(Vec<Event>);
 
 impl DisplayEvents {
     /// Parses events and returns an object which can be serialised
+    ///
+    /// The `ContractEmitted` events of the contracts pallet named `pallet_name` are
+    /// decoded with the `transcoder` if given.
     pub fn from_events<C: Config, E: Environment>(
         result: &ExtrinsicEvents<C>,
         transcoder: Option<&ContractMessageTranscoder>,
         subxt_metadata: &subxt::Metadata,
+        pallet_name: &str,
     ) -> Result<DisplayEvents>
     where
         C::AccountId: IntoVisitor,
+    {
+        Self::from_events_with_transcoders::<C, E, _>(
+            result,
+            |_| transcoder,
+            transcoder.and_then(ContractMessageTranscoder::ss58_prefix),
+            subxt_metadata,
+            pallet_name,
+        )
+    }
+
+    /// Parses events emitted by multiple contracts, decoding the events of each
+    /// contract with the transcoder returned by `transcoder_for`.
+    ///
+    /// Account ids in runtime events are displayed with the given SS58 prefix, or the
+    /// prefix of the chain if not given.
+    pub fn from_events_with_transcoders<'a, C: Config, E: Environment, F>(
+        result: &ExtrinsicEvents<C>,
+        transcoder_for: F,
+        ss58_prefix: Option<u16>,
+        subxt_metadata: &subxt::Metadata,
+        pallet_name: &str,
+    ) -> Result<DisplayEvents>
+    where
+        C::AccountId: IntoVisitor,
+        F: Fn(&C::AccountId) -> Option<&'a ContractMessageTranscoder>,
+    {
+        Self::from_event_details::<C, _, _>(
+            result.iter(),
+            transcoder_for,
+            ss58_prefix,
+            subxt_metadata,
+            pallet_name,
+        )
+    }
+
+    /// Parses the `ContractEmitted` events of the contracts pallet named `pallet_name`
+    /// emitted by the `contract` in a block, decoding them with the `transcoder` if
+    /// given.
+    ///
+    /// Returns no events if the contract did not emit any in the block.
+    pub fn from_block_events<C: Config, E: Environment>(
+        block_events: &BlockEvents<C>,
+        contract: &C::AccountId,
+        transcoder: Option<&ContractMessageTranscoder>,
+        pallet_name: &str,
+    ) -> Result<DisplayEvents>
+    where
+        C::AccountId: IntoVisitor + PartialEq,
+    {
+        let emitted_by_contract = block_events.events.iter().filter(|event| {
+            match event {
+                Ok(event) => {
+                    matches!(
+                        as_contracts_event::<ContractEmitted<C::AccountId>, C>(
+                            event,
+                            &block_events.metadata,
+                            pallet_name,
+                        ),
+                        Ok(Some(emitted)) if emitted.contract == *contract
+                    )
+                }
+                // surface the error when parsing the event
+                Err(_) => true,
+            }
+        });
+        Self::from_event_details::<C, _, _>(
+            emitted_by_contract,
+            |_| transcoder,
+            transcoder.and_then(ContractMessageTranscoder::ss58_prefix),
+            &block_events.metadata,
+            pallet_name,
+        )
+    }
+
+    /// Returns `true` if there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn from_event_details<'a, C: Config, F, I>(
+        event_details: I,
+        transcoder_for: F,
+        ss58_prefix: Option<u16>,
+        subxt_metadata: &subxt::Metadata,
+        pallet_name: &str,
+    ) -> Result<DisplayEvents>
+    where
+        C::AccountId: IntoVisitor,
+        F: Fn(&C::AccountId) -> Option<&'a ContractMessageTranscoder>,
+        I: Iterator<Item = Result<EventDetails<C>, subxt::Error>>,
     {
         let mut events: Vec<Event> = vec![];
 
-        let events_transcoder = TranscoderBuilder::new(subxt_metadata.types())
-            .with_default_custom_type_transcoders()
+        let events_transcoder = TranscoderBuilder::new(subxt_metadata.types());
+        let events_transcoder =
+            match ss58_prefix.or_else(|| chain_ss58_prefix(subxt_metadata)) {
+                Some(ss58_prefix) => {
+                    events_transcoder
+                        .with_default_custom_type_transcoders_for_ss58_prefix(ss58_prefix)
+                }
+                None => events_transcoder.with_default_custom_type_transcoders(),
+            }
             .done();
 
-        for event in result.iter() {
+        for event in event_details {
             let event = event?;
             tracing::debug!(
                 "displaying event {}:{}",
@@ -218,6 +398,7 @@ impl DisplayEvents {
             let event_fields = &event_metadata.variant.fields;
 
             let mut event_entry = Event {
+                index: event.index(),
                 pallet: event.pallet_name().to_string(),
                 name: event.variant_name().to_string(),
                 fields: vec![],
@@ -227,12 +408,16 @@ impl DisplayEvents {
             let event_sig_topic = event.topics().iter().next();
             let mut unnamed_field_name = 0;
             for field_metadata in event_fields {
-                if <ContractEmitted<C::AccountId> as StaticEvent>::is_event(
-                    event.pallet_name(),
-                    event.variant_name(),
-                ) && field_metadata.name == Some("data".to_string())
+                if event.pallet_name() == pallet_name
+                    && event.variant_name()
+                        == <ContractEmitted<C::AccountId> as StaticEvent>::EVENT
+                    && field_metadata.name == Some("data".to_string())
                 {
                     tracing::debug!("event data: {:?}", hex::encode(&event_data));
+                    let emitted: Option<ContractEmitted<C::AccountId>> =
+                        as_contracts_event(&event, subxt_metadata, pallet_name)?;
+                    let transcoder =
+                        emitted.and_then(|emitted| transcoder_for(&emitted.contract));
                     let field = contract_event_data_field::<C>(
                         transcoder,
                         field_metadata,
@@ -270,6 +455,25 @@ impl DisplayEvents {
         Ok(DisplayEvents(events))
     }
 
+    /// Splits the events of a `utility` batch into the events of each dispatched call.
+    ///
+    /// A call's events end with the `ItemCompleted` event of the `Utility` pallet. The
+    /// events following the last completed call, e.g. the fee payment, are returned as
+    /// the last element.
+    pub fn split_batch_items(self) -> Vec<DisplayEvents> {
+        let mut items = Vec::new();
+        let mut current = Vec::new();
+        for event in self.0 {
+            let completed = event.pallet == "Utility" && event.name == "ItemCompleted";
+            current.push(event);
+            if completed {
+                items.push(DisplayEvents(std::mem::take(&mut current)));
+            }
+        }
+        items.push(DisplayEvents(current));
+        items
+    }
+
     /// Displays events in a human readable format
     pub fn display_events<E: Environment>(
         &self,
@@ -327,6 +531,129 @@ impl DisplayEvents {
     pub fn to_json(&self) -> Result<String> {
         Ok(serde_json::to_string_pretty(self)?)
     }
+
+    /// Returns the events in CSV format, one row per event.
+    ///
+    /// Each row contains the block, the index of the event within that block, the
+    /// pallet and event names, followed by a single quoted cell with the event fields
+    /// as a JSON object.
+    pub fn to_csv(&self, block: &str) -> Result<String> {
+        let mut out = format!("{CSV_HEADER}\n");
+        for event in &self.0 {
+            let fields = event
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), field.value.to_json()))
+                .collect::<serde_json::Map<_, _>>();
+            let fields = serde_json::to_string(&fields)?;
+            let row = csv_row(block, event.index, &event.pallet, &event.name, &fields);
+            let _ = writeln!(out, "{row}");
+        }
+        Ok(out)
+    }
+}
+
+/// The header of the CSV export of events, see [`DisplayEvents::to_csv`].
+pub const CSV_HEADER: &str = "block,index,pallet,event,fields";
+
+/// Formats a single event as a row of the CSV export, see [`DisplayEvents::to_csv`].
+///
+/// `fields` holds the event fields as JSON, it is always quoted.
+pub fn csv_row(
+    block: &str,
+    index: u32,
+    pallet: &str,
+    event: &str,
+    fields: &str,
+) -> String {
+    [
+        csv_escape(block),
+        index.to_string(),
+        csv_escape(pallet),
+        csv_escape(event),
+        format!("\"{}\"", fields.replace('"', "\"\"")),
+    ]
+    .join(",")
+}
+
+/// The events of a historical block.
+pub struct BlockEvents<C: Config> {
+    hash: C::Hash,
+    events: subxt::events::Events<C>,
+    metadata: subxt::Metadata,
+}
+
+impl<C: Config> BlockEvents<C> {
+    /// Fetches the events of the block with the given number or `0x` prefixed hash.
+    pub async fn fetch(
+        url: &url::Url,
+        block: &str,
+        connection: &RpcConnection,
+    ) -> Result<Self> {
+        let rpc_client = connection.connect(&url_to_string(url)).await?;
+        let client = OnlineClient::<C>::from_rpc_client(rpc_client.clone()).await?;
+        let hash = match parse_block(block)? {
+            BlockId::Hash(hash) => {
+                scale::Decode::decode(&mut &hash[..]).map_err(|e| {
+                    anyhow::anyhow!("Failed to parse block hash {block}: {e}")
+                })?
+            }
+            BlockId::Number(number) => {
+                LegacyRpcMethods::<C>::new(rpc_client)
+                    .chain_get_block_hash(Some(number.into()))
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Block {number} not found"))?
+            }
+        };
+        let events = client.events().at(hash).await?;
+        Ok(Self {
+            hash,
+            events,
+            metadata: client.metadata(),
+        })
+    }
+
+    /// Returns the hash of the block.
+    pub fn hash(&self) -> C::Hash {
+        self.hash
+    }
+}
+
+/// A block given by its number or hash.
+#[derive(Debug, PartialEq)]
+enum BlockId {
+    Number(u64),
+    Hash([u8; 32]),
+}
+
+/// Parses a block number or a `0x` prefixed, hex encoded block hash.
+fn parse_block(block: &str) -> Result<BlockId> {
+    match block.strip_prefix("0x") {
+        Some(hash) => {
+            let hash = hex::decode(hash)
+                .ok()
+                .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Expected a 32 byte hex encoded block hash: {block}")
+                })?;
+            Ok(BlockId::Hash(hash))
+        }
+        None => {
+            let number = block.parse().map_err(|_| {
+                anyhow::anyhow!("Expected a block number or hash, got: {block}")
+            })?;
+            Ok(BlockId::Number(number))
+        }
+    }
+}
+
+/// Quotes a CSV cell if it contains a separator, a quote or a line break.
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
 }
 
 /// Construct the contract event data field, attempting to decode the event using the
@@ -362,3 +689,146 @@ fn contract_event_data_field<C: Config>(
         field_metadata.type_name.as_ref().map(|s| s.to_string()),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contracts_event_resolved_under_renamed_pallet() {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        enum Event {
+            Instantiated { deployer: u32, contract: u32 },
+        }
+
+        let mut registry = scale_info::Registry::new();
+        let ty = registry.register_type(&scale_info::MetaType::new::<Event>());
+        let types: scale_info::PortableRegistry = registry.into();
+        let scale_info::TypeDef::Variant(event_def) =
+            &types.resolve(ty.id).unwrap().type_def
+        else {
+            panic!("expected a variant type")
+        };
+        let variant = &event_def.variants[0];
+        let field_bytes = scale::Encode::encode(&(1u32, 2u32));
+
+        let instantiated: ContractInstantiated<u32> = decode_contracts_event(
+            "MyContracts",
+            variant,
+            &field_bytes,
+            &types,
+            "MyContracts",
+        )
+        .unwrap()
+        .expect("event of the renamed pallet should be found");
+        assert_eq!(instantiated.deployer, 1);
+        assert_eq!(instantiated.contract, 2);
+
+        let other_pallet = decode_contracts_event::<ContractInstantiated<u32>>(
+            "Contracts",
+            variant,
+            &field_bytes,
+            &types,
+            "MyContracts",
+        )
+        .unwrap();
+        assert!(other_pallet.is_none());
+    }
+
+    #[test]
+    fn parse_block_works() {
+        assert_eq!(parse_block("42").unwrap(), BlockId::Number(42));
+        assert_eq!(
+            parse_block(&format!("0x{}", "ab".repeat(32))).unwrap(),
+            BlockId::Hash([0xab; 32])
+        );
+        assert!(parse_block("0xabcd").is_err());
+        assert!(parse_block("latest").is_err());
+    }
+
+    #[test]
+    fn split_batch_items_works() {
+        let event = |pallet: &str, name: &str| {
+            Event {
+                index: 0,
+                pallet: pallet.to_string(),
+                name: name.to_string(),
+                fields: vec![],
+            }
+        };
+        let events = DisplayEvents(vec![
+            event("Contracts", "ContractEmitted"),
+            event("Contracts", "Called"),
+            event("Utility", "ItemCompleted"),
+            event("Contracts", "Called"),
+            event("Utility", "ItemCompleted"),
+            event("Utility", "BatchCompleted"),
+            event("TransactionPayment", "TransactionFeePaid"),
+        ]);
+
+        let items = events.split_batch_items();
+
+        let names = items
+            .iter()
+            .map(|item| item.0.iter().map(|e| e.name.as_str()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                vec!["ContractEmitted", "Called", "ItemCompleted"],
+                vec!["Called", "ItemCompleted"],
+                vec!["BatchCompleted", "TransactionFeePaid"],
+            ]
+        );
+    }
+
+    #[test]
+    fn to_csv_works() {
+        let events = DisplayEvents(vec![
+            Event {
+                index: 2,
+                pallet: "Balances".to_string(),
+                name: "Withdraw".to_string(),
+                fields: vec![Field::new("amount".to_string(), Value::UInt(42), None)],
+            },
+            Event {
+                index: 5,
+                pallet: "Contracts".to_string(),
+                name: "ContractEmitted".to_string(),
+                fields: vec![
+                    Field::new("contract".to_string(), Value::UInt(7), None),
+                    Field::new(
+                        "data".to_string(),
+                        Value::Seq(vec![Value::UInt(1), Value::UInt(2)].into()),
+                        None,
+                    ),
+                ],
+            },
+            Event {
+                index: 6,
+                pallet: "System".to_string(),
+                name: "ExtrinsicSuccess".to_string(),
+                fields: vec![],
+            },
+        ]);
+
+        let csv = events.to_csv("0x01").unwrap();
+
+        assert_eq!(
+            csv,
+            "block,index,pallet,event,fields\n\
+             0x01,2,Balances,Withdraw,\"{\"\"amount\"\":42}\"\n\
+             0x01,5,Contracts,ContractEmitted,\
+             \"{\"\"contract\"\":7,\"\"data\"\":[1,2]}\"\n\
+             0x01,6,System,ExtrinsicSuccess,\"{}\"\n"
+        );
+    }
+
+    #[test]
+    fn csv_escape_quotes_special_characters() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
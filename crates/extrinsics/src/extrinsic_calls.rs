@@ -15,17 +15,70 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
+    multisig::Timepoint,
     upload::Determinism,
     WasmCode,
 };
+use anyhow::Result;
+use scale::{
+    Encode,
+    Output,
+};
 use subxt::{
     ext::{
         codec::Compact,
-        scale_encode::EncodeAsType,
+        scale_encode::{
+            self,
+            Composite,
+            CompositeField,
+            EncodeAsType,
+            TypeResolver,
+            Variant,
+        },
     },
     utils::MultiAddress,
 };
 
+/// The name of `pallet-contracts` in the standard runtimes.
+pub const DEFAULT_PALLET_NAME: &str = "Contracts";
+
+/// The calls of `pallet-contracts` submitted by the extrinsic commands.
+const PALLET_CALLS: [&str; 5] = [
+    "call",
+    "instantiate",
+    "instantiate_with_code",
+    "upload_code",
+    "remove_code",
+];
+
+/// Checks that the chain has a pallet with the given name which provides the calls of
+/// `pallet-contracts`.
+pub(crate) fn check_contracts_pallet(
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+) -> Result<()> {
+    let Some(pallet) = metadata.pallet_by_name(pallet_name) else {
+        let pallets = metadata
+            .pallets()
+            .map(|pallet| pallet.name().to_owned())
+            .collect::<Vec<_>>();
+        anyhow::bail!(
+            "No pallet `{pallet_name}` found in the chain metadata, the available pallets are: {}",
+            pallets.join(", ")
+        )
+    };
+    let missing_calls = PALLET_CALLS
+        .into_iter()
+        .filter(|call| pallet.call_variant_by_name(call).is_none())
+        .collect::<Vec<_>>();
+    anyhow::ensure!(
+        missing_calls.is_empty(),
+        "The pallet `{pallet_name}` is not a contracts pallet, it has no calls {}",
+        missing_calls.join(", ")
+    );
+    Ok(())
+}
+
 /// Copied from `sp_weight` to additionally implement `scale_encode::EncodeAsType`.
 #[derive(Debug, EncodeAsType)]
 #[encode_as_type(crate_path = "subxt::ext::scale_encode")]
@@ -69,8 +122,8 @@ impl<Hash> RemoveCode<Hash> {
         Self { code_hash }
     }
 
-    pub fn build(self) -> subxt::tx::Payload<Self> {
-        subxt::tx::Payload::new("Contracts", "remove_code", self)
+    pub fn build(self, pallet_name: &str) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new(pallet_name, "remove_code", self)
     }
 }
 
@@ -96,8 +149,8 @@ impl<Balance> UploadCode<Balance> {
         }
     }
 
-    pub fn build(self) -> subxt::tx::Payload<Self> {
-        subxt::tx::Payload::new("Contracts", "upload_code", self)
+    pub fn build(self, pallet_name: &str) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new(pallet_name, "upload_code", self)
     }
 }
 
@@ -133,8 +186,8 @@ impl<Balance> InstantiateWithCode<Balance> {
         }
     }
 
-    pub fn build(self) -> subxt::tx::Payload<Self> {
-        subxt::tx::Payload::new("Contracts", "instantiate_with_code", self)
+    pub fn build(self, pallet_name: &str) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new(pallet_name, "instantiate_with_code", self)
     }
 }
 
@@ -176,8 +229,8 @@ where
         }
     }
 
-    pub fn build(self) -> subxt::tx::Payload<Self> {
-        subxt::tx::Payload::new("Contracts", "instantiate", self)
+    pub fn build(self, pallet_name: &str) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new(pallet_name, "instantiate", self)
     }
 }
 
@@ -210,7 +263,357 @@ impl<AccountId, Balance> Call<AccountId, Balance> {
         }
     }
 
+    pub fn build(self, pallet_name: &str) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new(pallet_name, "call", self)
+    }
+}
+
+/// A call which is already SCALE encoded, e.g. the call dispatched by a multisig.
+#[derive(Debug, Clone)]
+pub(crate) struct EncodedCall(pub Vec<u8>);
+
+impl Encode for EncodedCall {
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        dest.write(&self.0)
+    }
+}
+
+impl EncodeAsType for EncodedCall {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        _type_id: &R::TypeId,
+        _types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), scale_encode::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+/// A raw call to `pallet-multisig`'s `approve_as_multi`.
+#[derive(EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+pub(crate) struct ApproveAsMulti<AccountId> {
+    threshold: u16,
+    other_signatories: Vec<AccountId>,
+    maybe_timepoint: Option<Timepoint>,
+    call_hash: [u8; 32],
+    max_weight: Weight,
+}
+
+impl<AccountId> ApproveAsMulti<AccountId> {
+    pub fn new(
+        threshold: u16,
+        other_signatories: Vec<AccountId>,
+        maybe_timepoint: Option<Timepoint>,
+        call_hash: [u8; 32],
+        max_weight: sp_weights::Weight,
+    ) -> Self {
+        Self {
+            threshold,
+            other_signatories,
+            maybe_timepoint,
+            call_hash,
+            max_weight: max_weight.into(),
+        }
+    }
+
     pub fn build(self) -> subxt::tx::Payload<Self> {
-        subxt::tx::Payload::new("Contracts", "call", self)
+        subxt::tx::Payload::new("Multisig", "approve_as_multi", self)
+    }
+}
+
+/// A raw call to `pallet-multisig`'s `as_multi`.
+#[derive(EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+pub(crate) struct AsMulti<AccountId> {
+    threshold: u16,
+    other_signatories: Vec<AccountId>,
+    maybe_timepoint: Option<Timepoint>,
+    call: EncodedCall,
+    max_weight: Weight,
+}
+
+impl<AccountId> AsMulti<AccountId> {
+    pub fn new(
+        threshold: u16,
+        other_signatories: Vec<AccountId>,
+        maybe_timepoint: Option<Timepoint>,
+        call: EncodedCall,
+        max_weight: sp_weights::Weight,
+    ) -> Self {
+        Self {
+            threshold,
+            other_signatories,
+            maybe_timepoint,
+            call,
+            max_weight: max_weight.into(),
+        }
+    }
+
+    pub fn build(self) -> subxt::tx::Payload<Self> {
+        subxt::tx::Payload::new("Multisig", "as_multi", self)
+    }
+}
+
+/// A raw call to `pallet-utility`'s `batch` or `batch_all`, dispatching multiple
+/// contract calls from a single extrinsic.
+#[derive(EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+pub(crate) struct Batch<AccountId, Balance> {
+    calls: Vec<RuntimeCall<AccountId, Balance>>,
+}
+
+impl<AccountId, Balance> Batch<AccountId, Balance> {
+    /// The calls are dispatched to the contracts pallet named `pallet_name`.
+    pub fn new(calls: Vec<Call<AccountId, Balance>>, pallet_name: &str) -> Self {
+        Self {
+            calls: calls
+                .into_iter()
+                .map(|call| RuntimeCall::new(pallet_name, call))
+                .collect(),
+        }
+    }
+
+    /// Builds the payload, `batch_all` reverts all calls if any of them fails.
+    pub fn build(self, atomic: bool) -> subxt::tx::Payload<Self> {
+        let call_name = if atomic { "batch_all" } else { "batch" };
+        subxt::tx::Payload::new("Utility", call_name, self)
+    }
+}
+
+/// The runtime call dispatching to `pallet-contracts`.
+///
+/// The variant of the runtime call enum is named after the pallet, so it is encoded
+/// by the configured pallet name instead of a fixed `Contracts` variant.
+struct RuntimeCall<AccountId, Balance> {
+    pallet_name: String,
+    call: ContractsCall<AccountId, Balance>,
+}
+
+impl<AccountId, Balance> RuntimeCall<AccountId, Balance> {
+    fn new(pallet_name: &str, call: Call<AccountId, Balance>) -> Self {
+        Self {
+            pallet_name: pallet_name.to_owned(),
+            call: ContractsCall::call {
+                dest: call.dest,
+                value: call.value,
+                gas_limit: call.gas_limit,
+                storage_deposit_limit: call.storage_deposit_limit,
+                data: call.data,
+            },
+        }
+    }
+}
+
+impl<AccountId, Balance> EncodeAsType for RuntimeCall<AccountId, Balance>
+where
+    AccountId: EncodeAsType,
+    Balance: EncodeAsType,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: &R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), scale_encode::Error> {
+        Variant {
+            name: &self.pallet_name,
+            fields: Composite::new([(None, CompositeField::new(&self.call))].into_iter()),
+        }
+        .encode_variant_as_type_to(type_id, types, out)
+    }
+}
+
+/// The `pallet-contracts` call variant for calling a contract.
+#[allow(non_camel_case_types)]
+#[derive(EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+enum ContractsCall<AccountId, Balance> {
+    call {
+        dest: MultiAddress<AccountId, ()>,
+        value: Balance,
+        gas_limit: Weight,
+        storage_deposit_limit: Option<Compact<Balance>>,
+        data: Vec<u8>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::ext::codec::Decode;
+
+    fn metadata() -> subxt::Metadata {
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        subxt::Metadata::decode(&mut &*metadata_bytes).expect("the metadata must decode")
+    }
+
+    #[test]
+    fn check_contracts_pallet_accepts_the_contracts_pallet() {
+        assert!(check_contracts_pallet(&metadata(), DEFAULT_PALLET_NAME).is_ok());
+    }
+
+    #[test]
+    fn check_contracts_pallet_lists_the_available_pallets() {
+        let err = check_contracts_pallet(&metadata(), "Revive").unwrap_err();
+
+        let err = err.to_string();
+        assert!(
+            err.starts_with(
+                "No pallet `Revive` found in the chain metadata, the available pallets are: "
+            ),
+            "{err}"
+        );
+        assert!(err.contains("Contracts"), "{err}");
+    }
+
+    /// The test metadata with an additional `System` pallet, which has no calls.
+    fn metadata_with_system_pallet() -> subxt::Metadata {
+        use subxt::ext::frame_metadata::{
+            RuntimeMetadata,
+            RuntimeMetadataPrefixed,
+        };
+
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        let mut metadata = RuntimeMetadataPrefixed::decode(&mut &*metadata_bytes)
+            .expect("the metadata must decode");
+        let RuntimeMetadata::V15(runtime_metadata) = &mut metadata.1 else {
+            panic!("the test metadata is V15")
+        };
+        let mut system = runtime_metadata.pallets[0].clone();
+        system.name = "System".to_string();
+        system.index = u8::MAX;
+        system.calls = None;
+        runtime_metadata.pallets.push(system);
+        metadata.try_into().expect("the metadata must be valid")
+    }
+
+    #[test]
+    fn check_contracts_pallet_requires_the_contracts_calls() {
+        let err =
+            check_contracts_pallet(&metadata_with_system_pallet(), "System").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The pallet `System` is not a contracts pallet, it has no calls call, \
+             instantiate, instantiate_with_code, upload_code, remove_code"
+        );
+    }
+    #[test]
+    fn manual_gas_limit_reaches_the_call() {
+        use subxt::{
+            ext::scale_value::{
+                self,
+                At,
+            },
+            tx::TxPayload,
+            utils::AccountId32,
+        };
+
+        let metadata = metadata();
+        let gas_limit = sp_weights::Weight::from_parts(5_000, 6_000);
+        let call = Call::new(
+            MultiAddress::Id(AccountId32([1; 32])),
+            0u128,
+            gas_limit,
+            None,
+            Vec::new(),
+        )
+        .build(DEFAULT_PALLET_NAME);
+
+        let call_data = call
+            .encode_call_data(&metadata)
+            .expect("the call must encode");
+        let call = scale_value::scale::decode_as_type(
+            &mut &call_data[..],
+            &metadata.outer_enums().call_enum_ty(),
+            metadata.types(),
+        )
+        .expect("the call must decode");
+
+        let encoded_gas_limit = call
+            .at(0)
+            .and_then(|call| call.at("gas_limit"))
+            .expect("the call must have a gas limit");
+        assert_eq!(
+            encoded_gas_limit.at("ref_time").and_then(|v| v.as_u128()),
+            Some(5_000)
+        );
+        assert_eq!(
+            encoded_gas_limit.at("proof_size").and_then(|v| v.as_u128()),
+            Some(6_000)
+        );
+    }
+
+    #[test]
+    fn batched_calls_are_dispatched_to_a_renamed_pallet() {
+        use scale_info::{
+            PortableRegistry,
+            Registry,
+            TypeInfo,
+        };
+        use subxt::utils::AccountId32;
+
+        #[allow(dead_code)]
+        #[derive(Encode, TypeInfo)]
+        enum TestRuntimeCall {
+            System(()),
+            Revive(TestContractsCall),
+        }
+
+        #[allow(non_camel_case_types)]
+        #[derive(Encode, TypeInfo)]
+        enum TestContractsCall {
+            call {
+                dest: MultiAddress<AccountId32, ()>,
+                #[codec(compact)]
+                value: u128,
+                gas_limit: sp_weights::Weight,
+                storage_deposit_limit: Option<Compact<u128>>,
+                data: Vec<u8>,
+            },
+        }
+
+        let mut registry = Registry::new();
+        let type_id = registry
+            .register_type(&scale_info::meta_type::<TestRuntimeCall>())
+            .id;
+        let types: PortableRegistry = registry.into();
+
+        let dest = AccountId32([1; 32]);
+        let gas_limit = sp_weights::Weight::from_parts(100, 10);
+        let call = || {
+            Call::new(
+                MultiAddress::Id(dest.clone()),
+                5u128,
+                gas_limit,
+                Some(7),
+                vec![1, 2, 3],
+            )
+        };
+
+        let encoded = RuntimeCall::new("Revive", call())
+            .encode_as_type(&type_id, &types)
+            .expect("the call must encode as the renamed pallet variant");
+        let expected = TestRuntimeCall::Revive(TestContractsCall::call {
+            dest: MultiAddress::Id(dest.clone()),
+            value: 5,
+            gas_limit,
+            storage_deposit_limit: Some(Compact(7)),
+            data: vec![1, 2, 3],
+        })
+        .encode();
+        assert_eq!(encoded, expected);
+
+        assert!(RuntimeCall::new(DEFAULT_PALLET_NAME, call())
+            .encode_as_type(&type_id, &types)
+            .is_err());
     }
 }
@@ -25,13 +25,17 @@ use subxt::{
 use url::Url;
 
 use crate::{
+    extrinsic_calls::DEFAULT_PALLET_NAME,
     url_to_string,
     ContractArtifacts,
+    RetryPolicy,
+    RpcConnection,
 };
 use std::{
     marker::PhantomData,
     option::Option,
     path::PathBuf,
+    time::Duration,
 };
 
 /// Arguments required for creating and sending an extrinsic to a Substrate node.
@@ -43,6 +47,15 @@ pub struct ExtrinsicOpts<C: Config, E: Environment, Signer: Clone> {
     url: url::Url,
     signer: Signer,
     storage_deposit_limit: Option<E::Balance>,
+    mortal: Option<u64>,
+    mortal_checkpoint: Option<(u64, C::Hash)>,
+    nonce: Option<u64>,
+    tip: u128,
+    ss58_prefix: Option<u16>,
+    pallet_name: Option<String>,
+    rpc_retry_policy: RetryPolicy,
+    tx_timeout: Option<Duration>,
+    rpc_connection: RpcConnection,
     verbosity: Verbosity,
     _marker: PhantomData<C>,
 }
@@ -65,6 +78,15 @@ where
                 url: url::Url::parse("ws://localhost:9944").unwrap(),
                 signer,
                 storage_deposit_limit: None,
+                mortal: None,
+                mortal_checkpoint: None,
+                nonce: None,
+                tip: 0,
+                ss58_prefix: None,
+                pallet_name: None,
+                rpc_retry_policy: RetryPolicy::default(),
+                tx_timeout: None,
+                rpc_connection: RpcConnection::default(),
                 verbosity: Verbosity::Default,
                 _marker: PhantomData,
             },
@@ -103,6 +125,100 @@ where
         this
     }
 
+    /// Sets the number of blocks for which the extrinsic stays valid.
+    ///
+    /// If not set the extrinsic is immortal.
+    pub fn mortal(self, mortal: Option<u64>) -> Self {
+        let mut this = self;
+        this.opts.mortal = mortal;
+        this
+    }
+
+    /// Sets the block number and hash from which the mortality period of the extrinsic
+    /// starts.
+    ///
+    /// If not set the current best block is queried from the node. Signing still
+    /// requires a connection to the node, which provides the metadata and runtime
+    /// version the extrinsic is encoded for.
+    pub fn mortal_checkpoint(self, checkpoint: Option<(u64, C::Hash)>) -> Self {
+        let mut this = self;
+        this.opts.mortal_checkpoint = checkpoint;
+        this
+    }
+
+    /// Sets the nonce of the signer account to use for the extrinsic.
+    ///
+    /// If not set the nonce is queried from the best block when submitting.
+    pub fn nonce(self, nonce: Option<u64>) -> Self {
+        let mut this = self;
+        this.opts.nonce = nonce;
+        this
+    }
+
+    /// Sets the tip paid to the block author to prioritize the extrinsic.
+    pub fn tip(self, tip: u128) -> Self {
+        let mut this = self;
+        this.opts.tip = tip;
+        this
+    }
+
+    /// Sets the SS58 prefix used to display decoded account ids.
+    ///
+    /// If not set the `System::SS58Prefix` constant of the chain is used.
+    pub fn ss58_prefix(self, ss58_prefix: Option<u16>) -> Self {
+        let mut this = self;
+        this.opts.ss58_prefix = ss58_prefix;
+        this
+    }
+
+    /// Sets the name of the contracts pallet, for runtimes which do not use the
+    /// canonical `Contracts`.
+    pub fn pallet_name(self, pallet_name: Option<String>) -> Self {
+        let mut this = self;
+        this.opts.pallet_name = pallet_name;
+        this
+    }
+
+    /// Sets how often a read-only RPC request is retried after a transient failure.
+    ///
+    /// Submitting the extrinsic itself is never retried. Defaults to no retries. With
+    /// retries the connection to the node is re-established once it dropped.
+    pub fn rpc_retries(self, retries: u32) -> Self {
+        let mut this = self;
+        this.opts.rpc_retry_policy =
+            RetryPolicy::new(retries, this.opts.rpc_retry_policy.delay());
+        this
+    }
+
+    /// Sets the delay before the first retry of a failed RPC request, which doubles
+    /// with every further retry.
+    pub fn rpc_retry_delay(self, delay: Duration) -> Self {
+        let mut this = self;
+        this.opts.rpc_retry_policy =
+            RetryPolicy::new(this.opts.rpc_retry_policy.retries(), delay);
+        this
+    }
+
+    /// Sets how long to wait for the submitted extrinsic to be included in a block.
+    ///
+    /// If not set, waits until the extrinsic is included or rejected.
+    pub fn tx_timeout(self, tx_timeout: Option<Duration>) -> Self {
+        let mut this = self;
+        this.opts.tx_timeout = tx_timeout;
+        this
+    }
+
+    /// Sets the interval in which the node is pinged to keep the connection alive.
+    ///
+    /// If set, a dropped connection is re-established. Read-only requests which were
+    /// in-flight are retried according to [`Self::rpc_retries`], while an extrinsic is
+    /// never submitted again.
+    pub fn ws_keepalive(self, keepalive: Option<Duration>) -> Self {
+        let mut this = self;
+        this.opts.rpc_connection = RpcConnection::from_keepalive(keepalive);
+        this
+    }
+
     /// Set the verbosity level.
     pub fn verbosity(self, verbosity: Verbosity) -> Self {
         let mut this = self;
@@ -152,6 +268,54 @@ where
         self.storage_deposit_limit
     }
 
+    /// Return the number of blocks for which the extrinsic stays valid.
+    pub fn mortal(&self) -> Option<u64> {
+        self.mortal
+    }
+
+    /// Return the block number and hash from which the mortality period starts.
+    pub fn mortal_checkpoint(&self) -> Option<(u64, C::Hash)> {
+        self.mortal_checkpoint
+    }
+
+    /// Return the nonce of the signer account to use for the extrinsic.
+    pub fn nonce(&self) -> Option<u64> {
+        self.nonce
+    }
+
+    /// Return the tip paid to the block author.
+    pub fn tip(&self) -> u128 {
+        self.tip
+    }
+
+    /// Return the SS58 prefix used to display decoded account ids.
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        self.ss58_prefix
+    }
+
+    /// Return the name of the contracts pallet the extrinsics are dispatched to.
+    pub fn pallet_name(&self) -> &str {
+        self.pallet_name.as_deref().unwrap_or(DEFAULT_PALLET_NAME)
+    }
+
+    /// Return the policy for retrying failed read-only RPC requests.
+    pub fn rpc_retry_policy(&self) -> &RetryPolicy {
+        &self.rpc_retry_policy
+    }
+
+    /// Return how long to wait for the extrinsic to be included in a block.
+    pub fn tx_timeout(&self) -> Option<Duration> {
+        self.tx_timeout
+    }
+
+    /// Return how the RPC client connects to the node.
+    ///
+    /// If failed requests are retried, the connection is re-established once it
+    /// dropped, see [`RpcConnection::for_retries`].
+    pub fn rpc_connection(&self) -> RpcConnection {
+        self.rpc_connection.for_retries(&self.rpc_retry_policy)
+    }
+
     /// Verbosity for message reporting.
     pub fn verbosity(&self) -> &Verbosity {
         &self.verbosity
@@ -15,26 +15,41 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
+    estimate_extrinsic_fee,
     events::{
+        find_first_contracts_event,
+        find_last_contracts_event,
         CodeStored,
         ContractInstantiated,
     },
+    offline_gas_limit,
     pallet_contracts_primitives::{
         ContractInstantiateResult,
         StorageDeposit,
     },
+    sign_extrinsic_offline,
     state_call,
+    state_call_with_events,
     submit_extrinsic,
     ContractMessageTranscoder,
+    DryRunEvents,
     ErrorVariant,
 };
 use crate::{
     check_env_types,
+    contract_artifacts::ContractArtifacts,
     extrinsic_calls::{
+        check_contracts_pallet,
         Instantiate,
         InstantiateWithCode,
     },
     extrinsic_opts::ExtrinsicOpts,
+    multisig::{
+        submit_multisig,
+        Multisig,
+        MultisigResult,
+    },
+    with_ss58_prefix,
 };
 use anyhow::{
     anyhow,
@@ -49,14 +64,14 @@ use scale::{
     Decode,
     Encode,
 };
-use sp_core::Bytes;
+use sp_core::{
+    hashing::blake2_256,
+    Bytes,
+};
 use sp_weights::Weight;
 use std::fmt::Display;
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     blocks::ExtrinsicEvents,
     config::{
         DefaultExtrinsicParams,
@@ -68,6 +83,7 @@ use subxt::{
     },
     tx,
     Config,
+    OfflineClient,
     OnlineClient,
 };
 
@@ -75,6 +91,8 @@ use subxt::{
 pub struct InstantiateCommandBuilder<C: Config, E: Environment, Signer: Clone> {
     constructor: String,
     args: Vec<String>,
+    args_json: Option<serde_json::Value>,
+    allow_default_fields: bool,
     extrinsic_opts: ExtrinsicOpts<C, E, Signer>,
     value: E::Balance,
     gas_limit: Option<u64>,
@@ -95,6 +113,8 @@ where
         InstantiateCommandBuilder {
             constructor: String::from("new"),
             args: Vec::new(),
+            args_json: None,
+            allow_default_fields: false,
             extrinsic_opts,
             value: Default::default(),
             gas_limit: None,
@@ -117,6 +137,24 @@ where
         this
     }
 
+    /// Sets the constructor arguments as JSON, either as an array in the order of the
+    /// parameters or as an object keyed by the parameter names.
+    ///
+    /// Takes precedence over [`Self::args`].
+    pub fn args_json(self, args_json: Option<serde_json::Value>) -> Self {
+        let mut this = self;
+        this.args_json = args_json;
+        this
+    }
+
+    /// Allows omitting struct fields of the arguments, which are then encoded with the
+    /// default value of their type.
+    pub fn allow_default_fields(self, allow_default_fields: bool) -> Self {
+        let mut this = self;
+        this.allow_default_fields = allow_default_fields;
+        this
+    }
+
     /// Sets the initial balance to transfer to the instantiated contract.
     pub fn value(self, value: E::Balance) -> Self {
         let mut this = self;
@@ -156,9 +194,92 @@ where
     /// instantiation, or an error in case of failure.
     pub async fn done(self) -> Result<InstantiateExec<C, E, Signer>> {
         let artifacts = self.extrinsic_opts.contract_artifacts()?;
-        let transcoder = artifacts.contract_transcoder()?;
-        let data = transcoder.encode(&self.constructor, &self.args)?;
+        let transcoder = artifacts
+            .contract_transcoder()?
+            .with_default_fields(self.allow_default_fields);
+        let args = self.instantiate_args(artifacts, &transcoder)?;
+
         let url = self.extrinsic_opts.url();
+        let rpc_cli = self.extrinsic_opts.rpc_connection().connect(&url).await?;
+        let client = OnlineClient::from_rpc_client(rpc_cli.clone()).await?;
+        check_env_types(&client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        let transcoder =
+            with_ss58_prefix(transcoder, &client, self.extrinsic_opts.ss58_prefix());
+        let rpc = LegacyRpcMethods::new(rpc_cli);
+
+        Ok(InstantiateExec {
+            args,
+            opts: self.extrinsic_opts,
+            rpc,
+            client,
+            transcoder,
+        })
+    }
+
+    /// Signs the contract instantiation without a connection to the node.
+    ///
+    /// The instantiation is checked against the chain metadata of the given
+    /// [`OfflineClient`]. Since there is no dry-run, the gas limit and the proof size
+    /// have to be set.
+    ///
+    /// Returns the SCALE encoded signed extrinsic, which can be submitted later on with
+    /// [`crate::submit_signed_extrinsic`].
+    pub fn sign_offline(&self, client: &OfflineClient<C>) -> Result<Vec<u8>>
+    where
+        <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+            From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+        C::Hash: EncodeAsType,
+        E::Balance: EncodeAsType,
+    {
+        let artifacts = self.extrinsic_opts.contract_artifacts()?;
+        let transcoder = artifacts
+            .contract_transcoder()?
+            .with_default_fields(self.allow_default_fields);
+        let args = self.instantiate_args(artifacts, &transcoder)?;
+        check_env_types(client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+
+        let gas_limit = offline_gas_limit(self.gas_limit, self.proof_size)?;
+        let pallet_name = self.extrinsic_opts.pallet_name();
+        let extrinsic = match args.code.clone() {
+            Code::Upload(code) => {
+                let call = args.instantiate_with_code_call(code, gas_limit, pallet_name);
+                sign_extrinsic_offline(client, &call, &self.extrinsic_opts)?
+            }
+            Code::Existing(code_hash) => {
+                let call = args.instantiate_with_code_hash_call(
+                    code_hash,
+                    gas_limit,
+                    pallet_name,
+                );
+                sign_extrinsic_offline(client, &call, &self.extrinsic_opts)?
+            }
+        };
+        Ok(extrinsic)
+    }
+
+    /// Encodes the constructor call and resolves the code to instantiate from the
+    /// contract artifacts.
+    fn instantiate_args(
+        &self,
+        artifacts: ContractArtifacts,
+        transcoder: &ContractMessageTranscoder,
+    ) -> Result<InstantiateArgs<C, E>> {
+        let (data, raw_args) = match &self.args_json {
+            Some(args_json) => {
+                (
+                    transcoder.encode_from_json(&self.constructor, args_json)?,
+                    vec![args_json.to_string()],
+                )
+            }
+            None => {
+                (
+                    transcoder.encode(&self.constructor, &self.args)?,
+                    self.args.clone(),
+                )
+            }
+        };
         let code = if let Some(code) = artifacts.code {
             Code::Upload(code.0)
         } else {
@@ -167,14 +288,9 @@ where
         };
         let salt = self.salt.clone().map(|s| s.0).unwrap_or_default();
 
-        let rpc_cli = RpcClient::from_url(&url).await?;
-        let client = OnlineClient::from_rpc_client(rpc_cli.clone()).await?;
-        check_env_types(&client, &transcoder, self.extrinsic_opts.verbosity())?;
-        let rpc = LegacyRpcMethods::new(rpc_cli);
-
-        let args = InstantiateArgs {
+        Ok(InstantiateArgs {
             constructor: self.constructor.clone(),
-            raw_args: self.args.clone(),
+            raw_args,
             value: self.value,
             gas_limit: self.gas_limit,
             proof_size: self.proof_size,
@@ -182,14 +298,6 @@ where
             code,
             data,
             salt,
-        };
-
-        Ok(InstantiateExec {
-            args,
-            opts: self.extrinsic_opts,
-            rpc,
-            client,
-            transcoder,
         })
     }
 }
@@ -250,6 +358,44 @@ impl<C: Config, E: Environment> InstantiateArgs<C, E> {
     pub fn salt(&self) -> &[u8] {
         &self.salt
     }
+
+    fn instantiate_with_code_call(
+        &self,
+        code: Vec<u8>,
+        gas_limit: Weight,
+        pallet_name: &str,
+    ) -> tx::Payload<InstantiateWithCode<E::Balance>> {
+        InstantiateWithCode::new(
+            self.value,
+            gas_limit,
+            self.storage_deposit_limit,
+            code,
+            self.data.clone(),
+            self.salt.clone(),
+        )
+        .build(pallet_name)
+    }
+
+    fn instantiate_with_code_hash_call(
+        &self,
+        code_hash: C::Hash,
+        gas_limit: Weight,
+        pallet_name: &str,
+    ) -> tx::Payload<Instantiate<C::Hash, E::Balance>>
+    where
+        C::Hash: EncodeAsType,
+        E::Balance: EncodeAsType,
+    {
+        Instantiate::<C::Hash, E::Balance>::new(
+            self.value,
+            gas_limit,
+            self.storage_deposit_limit,
+            code_hash,
+            self.data.clone(),
+            self.salt.clone(),
+        )
+        .build(pallet_name)
+    }
 }
 
 pub struct InstantiateExec<C: Config, E: Environment, Signer: Clone> {
@@ -294,6 +440,13 @@ where
                     .context(format!("Failed to decode return value {:?}", &ret_val))?;
                 let dry_run_result = InstantiateDryRunResult {
                     result: value,
+                    // the prediction is informational only, failing to compute it
+                    // does not fail the dry-run
+                    predicted_address: self
+                        .predicted_contract_address()
+                        .map_err(|err| tracing::debug!("{err}"))
+                        .ok()
+                        .map(|address| address.to_string()),
                     contract: ret_val.account_id.to_string(),
                     reverted: ret_val.result.did_revert(),
                     gas_consumed: result.gas_consumed,
@@ -304,7 +457,11 @@ where
             }
             Err(ref err) => {
                 let metadata = self.client.metadata();
-                Err(ErrorVariant::from_dispatch_error(err, &metadata)?)
+                Err(ErrorVariant::from_dispatch_error(
+                    err,
+                    &metadata,
+                    self.opts.pallet_name(),
+                )?)
             }
         }
     }
@@ -320,17 +477,45 @@ where
     pub async fn instantiate_dry_run(
         &self,
     ) -> Result<ContractInstantiateResult<C::AccountId, E::Balance>> {
-        let storage_deposit_limit = self.args.storage_deposit_limit;
-        let call_request = InstantiateRequest::<C, E> {
+        state_call(
+            &self.rpc,
+            self.opts.rpc_retry_policy(),
+            "ContractsApi_instantiate",
+            &self.instantiate_request(),
+        )
+        .await
+    }
+
+    /// Simulates a contract instantiation like [`Self::instantiate_dry_run`],
+    /// additionally returning the events emitted during the dry-run if the node
+    /// collected them.
+    pub async fn instantiate_dry_run_with_events(
+        &self,
+    ) -> Result<(
+        ContractInstantiateResult<C::AccountId, E::Balance>,
+        Option<DryRunEvents>,
+    )> {
+        state_call_with_events(
+            &self.rpc,
+            self.opts.rpc_retry_policy(),
+            "ContractsApi_instantiate",
+            &self.instantiate_request(),
+            &self.client.metadata(),
+        )
+        .await
+    }
+
+    /// Returns the request of a dry-run of the instantiation.
+    fn instantiate_request(&self) -> InstantiateRequest<C, E> {
+        InstantiateRequest::<C, E> {
             origin: self.opts.signer().account_id(),
             value: self.args.value,
             gas_limit: None,
-            storage_deposit_limit,
+            storage_deposit_limit: self.args.storage_deposit_limit,
             code: self.args.code.clone(),
             data: self.args.data.clone(),
             salt: self.args.salt.clone(),
-        };
-        state_call(&self.rpc, "ContractsApi_instantiate", &call_request).await
+        }
     }
 
     async fn instantiate_with_code(
@@ -338,28 +523,29 @@ where
         code: Vec<u8>,
         gas_limit: Weight,
     ) -> Result<InstantiateExecResult<C>, ErrorVariant> {
-        let call = InstantiateWithCode::new(
-            self.args.value,
-            gas_limit,
-            self.args.storage_deposit_limit,
+        let call = self.args.instantiate_with_code_call(
             code,
-            self.args.data.clone(),
-            self.args.salt.clone(),
-        )
-        .build();
+            gas_limit,
+            self.opts.pallet_name(),
+        );
 
-        let events =
-            submit_extrinsic(&self.client, &self.rpc, &call, self.opts.signer()).await?;
+        let events = submit_extrinsic(&self.client, &self.rpc, &call, &self.opts).await?;
 
         // The CodeStored event is only raised if the contract has not already been
         // uploaded.
-        let code_hash = events
-            .find_first::<CodeStored<C::Hash>>()?
-            .map(|code_stored| code_stored.code_hash);
-
-        let instantiated = events
-            .find_last::<ContractInstantiated<C::AccountId>>()?
-            .ok_or_else(|| anyhow!("Failed to find Instantiated event"))?;
+        let metadata = self.client.metadata();
+        let code_hash = find_first_contracts_event::<CodeStored<C::Hash>, C>(
+            &events,
+            &metadata,
+            self.opts.pallet_name(),
+        )?
+        .map(|code_stored| code_stored.code_hash);
+
+        let instantiated = find_last_contracts_event::<
+            ContractInstantiated<C::AccountId>,
+            C,
+        >(&events, &metadata, self.opts.pallet_name())?
+        .ok_or_else(|| anyhow!("Failed to find Instantiated event"))?;
 
         Ok(InstantiateExecResult {
             events,
@@ -373,22 +559,21 @@ where
         code_hash: C::Hash,
         gas_limit: Weight,
     ) -> Result<InstantiateExecResult<C>, ErrorVariant> {
-        let call = Instantiate::<C::Hash, E::Balance>::new(
-            self.args.value,
-            gas_limit,
-            self.args.storage_deposit_limit,
+        let call = self.args.instantiate_with_code_hash_call(
             code_hash,
-            self.args.data.clone(),
-            self.args.salt.clone(),
-        )
-        .build();
+            gas_limit,
+            self.opts.pallet_name(),
+        );
 
-        let events =
-            submit_extrinsic(&self.client, &self.rpc, &call, self.opts.signer()).await?;
+        let events = submit_extrinsic(&self.client, &self.rpc, &call, &self.opts).await?;
 
-        let instantiated = events
-            .find_first::<ContractInstantiated<C::AccountId>>()?
-            .ok_or_else(|| anyhow!("Failed to find Instantiated event"))?;
+        let instantiated = find_first_contracts_event::<
+            ContractInstantiated<C::AccountId>,
+            C,
+        >(
+            &events, &self.client.metadata(), self.opts.pallet_name()
+        )?
+        .ok_or_else(|| anyhow!("Failed to find Instantiated event"))?;
 
         Ok(InstantiateExecResult {
             events,
@@ -424,6 +609,79 @@ where
         }
     }
 
+    /// Submits the contract instantiation via the given multisig, with the signer as one
+    /// of its signatories.
+    ///
+    /// The contract is instantiated from the multisig account with the final approval,
+    /// see [`crate::Multisig`].
+    pub async fn instantiate_multisig(
+        &self,
+        gas_limit: Option<Weight>,
+        multisig: &Multisig<C::AccountId>,
+    ) -> Result<MultisigResult<C>, ErrorVariant>
+    where
+        C::AccountId: PartialEq + EncodeAsType,
+    {
+        // use user specified values where provided, otherwise estimate
+        let gas_limit = match gas_limit {
+            Some(gas_limit) => gas_limit,
+            None => self.estimate_gas().await?,
+        };
+        match self.args.code.clone() {
+            Code::Upload(code) => {
+                let call = self.args.instantiate_with_code_call(
+                    code,
+                    gas_limit,
+                    self.opts.pallet_name(),
+                );
+                submit_multisig(&self.client, &self.rpc, &call, multisig, &self.opts)
+                    .await
+            }
+            Code::Existing(code_hash) => {
+                let call = self.args.instantiate_with_code_hash_call(
+                    code_hash,
+                    gas_limit,
+                    self.opts.pallet_name(),
+                );
+                submit_multisig(&self.client, &self.rpc, &call, multisig, &self.opts)
+                    .await
+            }
+        }
+    }
+
+    /// Estimates the fee for submitting the contract instantiation, including the tip.
+    ///
+    /// Returns the fee in the smallest unit of the chain's token.
+    pub async fn estimate_fee(
+        &self,
+        gas_limit: Option<Weight>,
+    ) -> Result<u128, ErrorVariant> {
+        // use user specified values where provided, otherwise estimate
+        let gas_limit = match gas_limit {
+            Some(gas_limit) => gas_limit,
+            None => self.estimate_gas().await?,
+        };
+        let fee = match self.args.code.clone() {
+            Code::Upload(code) => {
+                let call = self.args.instantiate_with_code_call(
+                    code,
+                    gas_limit,
+                    self.opts.pallet_name(),
+                );
+                estimate_extrinsic_fee(&self.client, &self.rpc, &call, &self.opts).await?
+            }
+            Code::Existing(code_hash) => {
+                let call = self.args.instantiate_with_code_hash_call(
+                    code_hash,
+                    gas_limit,
+                    self.opts.pallet_name(),
+                );
+                estimate_extrinsic_fee(&self.client, &self.rpc, &call, &self.opts).await?
+            }
+        };
+        Ok(fee)
+    }
+
     /// Estimates the gas required for the contract instantiation process without
     /// modifying the blockchain.
     ///
@@ -455,6 +713,7 @@ where
                         let object = ErrorVariant::from_dispatch_error(
                             err,
                             &self.client.metadata(),
+                            self.opts.pallet_name(),
                         )?;
                         Err(anyhow!("Pre-submission dry-run failed. Error: {}", object))
                     }
@@ -463,6 +722,29 @@ where
         }
     }
 
+    /// Returns the address the contract will be instantiated at.
+    ///
+    /// The address is derived from the signer, the code hash, the constructor input and
+    /// the salt, see [`contract_address`].
+    pub fn predicted_contract_address(&self) -> Result<C::AccountId> {
+        let deployer = self.opts.signer().account_id();
+        let address = match &self.args.code {
+            Code::Upload(code) => {
+                contract_address(
+                    &deployer,
+                    &contract_build::code_hash(code),
+                    &self.args.data,
+                    &self.args.salt,
+                )
+            }
+            Code::Existing(code_hash) => {
+                contract_address(&deployer, code_hash, &self.args.data, &self.args.salt)
+            }
+        };
+        C::AccountId::decode(&mut &address[..])
+            .context("Failed to decode the predicted contract address")
+    }
+
     /// Returns the extrinsic options.
     pub fn opts(&self) -> &ExtrinsicOpts<C, E, Signer> {
         &self.opts
@@ -484,6 +766,27 @@ where
     }
 }
 
+/// Computes the address of a contract the way the `DefaultAddressGenerator` of
+/// `pallet-contracts` does.
+///
+/// The address only depends on the deploying account, the code hash, the constructor
+/// input data and the salt, so it is known before the contract is instantiated.
+/// Instantiating the same code with the same input from the same account requires a
+/// different salt, otherwise the address is already taken.
+pub fn contract_address<AccountId, Hash>(
+    deployer: &AccountId,
+    code_hash: &Hash,
+    input_data: &[u8],
+    salt: &[u8],
+) -> [u8; 32]
+where
+    AccountId: Encode,
+    Hash: Encode,
+{
+    let entropy = (b"contract_addr_v1", deployer, code_hash, input_data, salt).encode();
+    blake2_256(&entropy)
+}
+
 /// A struct representing the result of an instantiate command execution.
 pub struct InstantiateExecResult<C: Config> {
     pub events: ExtrinsicEvents<C>,
@@ -496,6 +799,9 @@ pub struct InstantiateExecResult<C: Config> {
 pub struct InstantiateDryRunResult<Balance: Serialize> {
     /// The decoded result returned from the constructor
     pub result: Value,
+    /// contract address computed locally, see [`contract_address`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_address: Option<String>,
     /// contract address
     pub contract: String,
     /// Was the operation reverted
@@ -536,3 +842,41 @@ where
     /// The code hash of an on-chain Wasm blob.
     Existing(Hash),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use subxt::utils::{
+        AccountId32,
+        H256,
+    };
+
+    #[test]
+    fn contract_address_is_stable() {
+        let deployer =
+            AccountId32::from_str("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+                .unwrap();
+        let code_hash = H256::from([1u8; 32]);
+        let input_data = hex::decode("9bae9d5e").unwrap();
+
+        let address = contract_address(&deployer, &code_hash, &input_data, b"my-salt");
+
+        assert_eq!(
+            hex::encode(address),
+            "d19b6bfa3bd394a6a18eb13433fb2bc993a022099696da162463008d63d4add8"
+        );
+        assert_eq!(
+            address,
+            contract_address(&deployer, &code_hash, &input_data, b"my-salt")
+        );
+        assert_ne!(
+            address,
+            contract_address(&deployer, &code_hash, &input_data, b"other-salt")
+        );
+        assert_ne!(
+            address,
+            contract_address(&deployer, &code_hash, &input_data, &[])
+        );
+    }
+}
@@ -547,6 +547,7 @@ async fn api_build_upload_instantiate_call() {
         &call_result,
         None,
         &call.client().metadata(),
+        call.opts().pallet_name(),
     )
     .unwrap()
     .to_json()
@@ -15,7 +15,9 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 mod balance;
+mod batch;
 mod call;
+mod connection;
 mod contract_artifacts;
 mod contract_info;
 mod contract_storage;
@@ -25,8 +27,10 @@ mod events;
 mod extrinsic_calls;
 mod extrinsic_opts;
 mod instantiate;
+mod multisig;
 pub mod pallet_contracts_primitives;
 mod remove;
+mod retry;
 mod rpc;
 mod upload;
 
@@ -39,37 +43,61 @@ mod integration_tests;
 
 use env_check::compare_node_env_with_contract;
 
-use anyhow::Result;
+use anyhow::{
+    Context,
+    Result,
+};
+use colored::Colorize;
 use contract_build::{
+    verbose_eprintln,
     CrateMetadata,
     Verbosity,
     DEFAULT_KEY_COL_WIDTH,
 };
+use extrinsic_opts::ExtrinsicOpts;
+use ink_env::Environment;
 use scale::{
     Decode,
     Encode,
 };
+use sp_runtime::generic::Era;
+use sp_weights::Weight;
+use std::{
+    fmt::Debug,
+    time::Duration,
+};
 use subxt::{
     backend::legacy::LegacyRpcMethods,
     blocks,
+    client::OfflineClientT,
     config::{
         DefaultExtrinsicParams,
         DefaultExtrinsicParamsBuilder,
         ExtrinsicParams,
+        Header,
     },
     tx,
     Config,
+    OfflineClient,
     OnlineClient,
 };
 
 pub use balance::{
     BalanceVariant,
     TokenMetadata,
+    TokenMetadataOverride,
+};
+pub use batch::{
+    sign_batch_offline,
+    BatchCommandBuilder,
+    BatchExec,
 };
 pub use call::{
     CallCommandBuilder,
     CallExec,
+    SubscribedEvent,
 };
+pub use connection::RpcConnection;
 pub use contract_artifacts::ContractArtifacts;
 pub use contract_info::{
     fetch_all_contracts,
@@ -82,6 +110,8 @@ use contract_metadata::ContractMetadata;
 pub use contract_storage::{
     ContractStorage,
     ContractStorageCell,
+    ContractStorageData,
+    ContractStorageEntry,
     ContractStorageLayout,
     ContractStorageRpc,
 };
@@ -90,9 +120,15 @@ pub use error::{
     ErrorVariant,
     GenericError,
 };
-pub use events::DisplayEvents;
+pub use events::{
+    csv_row,
+    BlockEvents,
+    DisplayEvents,
+};
+pub use extrinsic_calls::DEFAULT_PALLET_NAME;
 pub use extrinsic_opts::ExtrinsicOptsBuilder;
 pub use instantiate::{
+    contract_address,
     Code,
     InstantiateArgs,
     InstantiateCommandBuilder,
@@ -100,6 +136,12 @@ pub use instantiate::{
     InstantiateExec,
     InstantiateExecResult,
 };
+pub use multisig::{
+    multisig_account_id,
+    Multisig,
+    MultisigResult,
+    Timepoint,
+};
 pub use remove::{
     RemoveCommandBuilder,
     RemoveExec,
@@ -109,9 +151,12 @@ pub use remove::{
 pub use upload::{
     UploadCommandBuilder,
     UploadExec,
+    UploadOutcome,
     UploadResult,
+    UploadStatus,
 };
 
+pub use retry::RetryPolicy;
 pub use rpc::{
     RawParams,
     RpcRequest,
@@ -136,35 +181,265 @@ impl WasmCode {
 /// Dynamic lookups of the actual error will be available once the following issue is
 /// resolved: <https://github.com/paritytech/subxt/issues/443>.
 ///
+/// # Mortality
+///
+/// If [`ExtrinsicOpts::mortal`] is set, the extrinsic is anchored at the current best
+/// block and becomes invalid once the mortality period has passed. Otherwise the
+/// extrinsic is immortal.
+///
 /// # Finality
 ///
 /// Currently this will report success once the transaction is included in a block. In the
 /// future there could be a flag to wait for finality before reporting success.
-async fn submit_extrinsic<C, Call, Signer>(
+///
+/// # Timeout
+///
+/// If [`ExtrinsicOpts::tx_timeout`] is set, an error with the hash of the transaction is
+/// returned if it is not included in a block in time. The transaction may still be
+/// included later on.
+///
+/// # Errors
+///
+/// Dispatch errors of the contracts pallet are explained for the pallet name configured
+/// via [`ExtrinsicOpts::pallet_name`].
+async fn submit_extrinsic<C, E, Call, Signer>(
     client: &OnlineClient<C>,
     rpc: &LegacyRpcMethods<C>,
     call: &Call,
-    signer: &Signer,
-) -> core::result::Result<blocks::ExtrinsicEvents<C>, subxt::Error>
+    opts: &ExtrinsicOpts<C, E, Signer>,
+) -> core::result::Result<blocks::ExtrinsicEvents<C>, ErrorVariant>
 where
     C: Config,
+    E: Environment,
     Call: tx::TxPayload,
-    Signer: tx::Signer<C>,
+    Signer: tx::Signer<C> + Clone,
     <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
         From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
 {
-    let account_id = Signer::account_id(signer);
-    let account_nonce = get_account_nonce(client, rpc, &account_id).await?;
+    let submit = async {
+        let signer = opts.signer();
+        let account_id = Signer::account_id(signer);
+        let mut account_nonce =
+            get_account_nonce(client, rpc, &account_id, opts.rpc_retry_policy()).await?;
+        if let Some(nonce) = opts.nonce() {
+            if let Some(warning) = check_nonce(nonce, account_nonce) {
+                eprintln!("{} {}", "warning:".yellow().bold(), warning);
+            }
+            account_nonce = nonce;
+        }
 
-    let params = DefaultExtrinsicParamsBuilder::new()
-        .nonce(account_nonce)
-        .build();
-    let mut tx = client
+        let params = extrinsic_params(client, rpc, opts, account_nonce).await?;
+        let tx = client
+            .tx()
+            .create_signed_offline(call, signer, params.into())?
+            .submit_and_watch()
+            .await?;
+
+        wait_for_in_block_within(tx, opts.tx_timeout()).await
+    };
+    submit
+        .await
+        .map_err(|err| ErrorVariant::from(err).for_contracts_pallet(opts.pallet_name()))
+}
+
+/// Sign the extrinsic without a connection to the node, returning the SCALE encoded
+/// extrinsic.
+///
+/// The chain metadata, genesis hash and runtime version are taken from the given
+/// [`OfflineClient`]. Neither the account nonce nor the best block can be queried, so
+/// the nonce has to be set via [`ExtrinsicOpts::nonce`] and a mortal extrinsic
+/// requires [`ExtrinsicOpts::mortal_checkpoint`].
+fn sign_extrinsic_offline<C, E, Call, Signer>(
+    client: &OfflineClient<C>,
+    call: &Call,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+) -> core::result::Result<Vec<u8>, subxt::Error>
+where
+    C: Config,
+    E: Environment,
+    Call: tx::TxPayload,
+    Signer: tx::Signer<C> + Clone,
+    <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+        From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+{
+    let account_nonce = opts.nonce().ok_or(subxt::Error::Other(
+        "A nonce is required for signing an extrinsic offline".into(),
+    ))?;
+    if opts.mortal().is_some() && opts.mortal_checkpoint().is_none() {
+        return Err(subxt::Error::Other(
+            "A mortal checkpoint is required for signing a mortal extrinsic offline"
+                .into(),
+        ))
+    }
+    let params = extrinsic_params_at(opts, account_nonce, opts.mortal_checkpoint());
+    let signed = client
         .tx()
-        .create_signed_offline(call, signer, params.into())?
+        .create_signed_offline(call, opts.signer(), params.into())?;
+    Ok(signed.into_encoded())
+}
+
+/// Returns the gas limit for signing an extrinsic offline, which can not be estimated
+/// by a dry-run.
+fn offline_gas_limit(gas_limit: Option<u64>, proof_size: Option<u64>) -> Result<Weight> {
+    match (gas_limit, proof_size) {
+        (Some(ref_time), Some(proof_size)) => {
+            Ok(Weight::from_parts(ref_time, proof_size))
+        }
+        _ => {
+            Err(anyhow::anyhow!(
+                "The gas limit and the proof size are required for signing offline, they \
+                can not be estimated without a connection to the node"
+            ))
+        }
+    }
+}
+
+/// Estimate the fee of the extrinsic, i.e. the partial fee queried from the
+/// `TransactionPaymentApi` of the node plus the configured tip.
+///
+/// If no nonce is set in the extrinsic options, the account nonce is queried from the
+/// best block.
+async fn estimate_extrinsic_fee<C, E, Call, Signer>(
+    client: &OnlineClient<C>,
+    rpc: &LegacyRpcMethods<C>,
+    call: &Call,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+) -> core::result::Result<u128, subxt::Error>
+where
+    C: Config,
+    E: Environment,
+    Call: tx::TxPayload,
+    Signer: tx::Signer<C> + Clone,
+    <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+        From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+{
+    let account_nonce = match opts.nonce() {
+        Some(nonce) => nonce,
+        None => {
+            let account_id = Signer::account_id(opts.signer());
+            get_account_nonce(client, rpc, &account_id, opts.rpc_retry_policy()).await?
+        }
+    };
+    let params = extrinsic_params(client, rpc, opts, account_nonce).await?;
+    let partial_fee = client
+        .tx()
+        .create_signed_offline(call, opts.signer(), params.into())?
+        .partial_fee_estimate()
+        .await?;
+    Ok(partial_fee.saturating_add(opts.tip()))
+}
+
+/// Submit a SCALE encoded, already signed extrinsic and wait for it to be included
+/// successfully into a block, failing if it is not included within the given timeout.
+///
+/// See [`submit_extrinsic`] for details.
+pub async fn submit_signed_extrinsic<C: Config>(
+    client: &OnlineClient<C>,
+    extrinsic: Vec<u8>,
+    timeout: Option<Duration>,
+) -> core::result::Result<blocks::ExtrinsicEvents<C>, subxt::Error> {
+    let tx = tx::SubmittableExtrinsic::from_bytes(client.clone(), extrinsic)
         .submit_and_watch()
         .await?;
+    wait_for_in_block_within(tx, timeout).await
+}
+
+/// Build the parameters for signing an extrinsic with the given nonce.
+async fn extrinsic_params<C, E, Signer>(
+    client: &OnlineClient<C>,
+    rpc: &LegacyRpcMethods<C>,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+    account_nonce: u64,
+) -> core::result::Result<
+    <DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params,
+    subxt::Error,
+>
+where
+    C: Config,
+    E: Environment,
+    Signer: tx::Signer<C> + Clone,
+{
+    let checkpoint = match (opts.mortal(), opts.mortal_checkpoint()) {
+        (Some(_), None) => {
+            let best_block = opts
+                .rpc_retry_policy()
+                .retry(|| get_best_block(rpc))
+                .await?;
+            let block = opts
+                .rpc_retry_policy()
+                .retry(|| client.blocks().at(best_block))
+                .await?;
+            Some((block.header().number().into(), best_block))
+        }
+        (_, checkpoint) => checkpoint,
+    };
+    Ok(extrinsic_params_at(opts, account_nonce, checkpoint))
+}
+
+/// Build the parameters for signing an extrinsic with the given nonce, anchoring a
+/// mortal extrinsic at the given checkpoint block.
+fn extrinsic_params_at<C, E, Signer>(
+    opts: &ExtrinsicOpts<C, E, Signer>,
+    account_nonce: u64,
+    checkpoint: Option<(u64, C::Hash)>,
+) -> <DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params
+where
+    C: Config,
+    E: Environment,
+    Signer: tx::Signer<C> + Clone,
+{
+    let mut params = DefaultExtrinsicParamsBuilder::new()
+        .nonce(account_nonce)
+        .tip(opts.tip());
+    if let (Some(for_n_blocks), Some((from_block, from_hash))) =
+        (opts.mortal(), checkpoint)
+    {
+        let valid_until = mortal_valid_until(from_block, for_n_blocks);
+        verbose_eprintln!(
+            opts.verbosity(),
+            "{:>width$} Transaction valid until block #{}",
+            "Mortal".bright_purple().bold(),
+            valid_until.to_string().bright_white(),
+            width = DEFAULT_KEY_COL_WIDTH
+        );
+        params = params.mortal_unchecked(from_block, from_hash, for_n_blocks);
+    }
+    params.build()
+}
+
+/// Wait until the submitted transaction is included in a block, failing if it is not
+/// included within the given timeout.
+async fn wait_for_in_block_within<C: Config>(
+    tx: tx::TxProgress<C, OnlineClient<C>>,
+    timeout: Option<Duration>,
+) -> core::result::Result<blocks::ExtrinsicEvents<C>, subxt::Error> {
+    // Capture the hash right after the broadcast, so that the transaction can still be
+    // looked up if waiting for it times out.
+    let tx_hash = tx.extrinsic_hash();
+    tracing::debug!("Submitted transaction with hash {:?}", tx_hash);
+    match timeout {
+        Some(timeout) => {
+            tokio::time::timeout(timeout, wait_for_in_block(tx))
+                .await
+                .map_err(|_| tx_timeout_error(timeout, tx_hash))?
+        }
+        None => wait_for_in_block(tx).await,
+    }
+}
+
+/// The error returned if a transaction is not included in a block in time.
+fn tx_timeout_error(timeout: Duration, tx_hash: impl Debug) -> subxt::Error {
+    subxt::Error::Other(format!(
+        "Transaction not included within {} seconds (hash: {:?})",
+        timeout.as_secs(),
+        tx_hash
+    ))
+}
 
+/// Wait until the submitted transaction is included in a block.
+async fn wait_for_in_block<C: Config>(
+    mut tx: tx::TxProgress<C, OnlineClient<C>>,
+) -> core::result::Result<blocks::ExtrinsicEvents<C>, subxt::Error> {
     // Below we use the low level API to replicate the `wait_for_in_block` behaviour which
     // was removed in subxt 0.33.0. See https://github.com/paritytech/subxt/pull/1237.
     //
@@ -204,25 +479,61 @@ async fn get_account_nonce<C>(
     client: &OnlineClient<C>,
     rpc: &LegacyRpcMethods<C>,
     account_id: &C::AccountId,
+    retry_policy: &RetryPolicy,
 ) -> core::result::Result<u64, subxt::Error>
 where
     C: Config,
 {
-    let best_block = rpc
-        .chain_get_block_hash(None)
-        .await?
-        .ok_or(subxt::Error::Other("Best block not found".into()))?;
-    let account_nonce = client
-        .blocks()
-        .at(best_block)
-        .await?
-        .account_nonce(account_id)
-        .await?;
-    Ok(account_nonce)
+    retry_policy
+        .retry(|| {
+            async {
+                let best_block = get_best_block(rpc).await?;
+                client
+                    .blocks()
+                    .at(best_block)
+                    .await?
+                    .account_nonce(account_id)
+                    .await
+            }
+        })
+        .await
+}
+
+/// The number of transactions a nonce override may be ahead of the account nonce
+/// before a warning is emitted.
+const MAX_NONCE_AHEAD: u64 = 64;
+
+/// Checks a user supplied `nonce` against the nonce of the account on-chain, returning
+/// a warning if the transaction is unlikely to be included.
+fn check_nonce(nonce: u64, account_nonce: u64) -> Option<String> {
+    if nonce < account_nonce {
+        Some(format!(
+            "nonce {nonce} has already been used, the account nonce is {account_nonce}. \
+            The transaction will be rejected unless it replaces a pending one."
+        ))
+    } else if nonce - account_nonce > MAX_NONCE_AHEAD {
+        Some(format!(
+            "nonce {nonce} is far ahead of the account nonce {account_nonce}. \
+            The transaction will not be included before all previous nonces are used."
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns the last block number in which a transaction that is mortal for
+/// `for_n_blocks` blocks, starting from the block `from_block`, can be included.
+///
+/// The period is rounded by the runtime to a power of two, see [`Era::mortal`].
+fn mortal_valid_until(from_block: u64, for_n_blocks: u64) -> u64 {
+    Era::mortal(for_n_blocks, from_block)
+        .death(from_block)
+        .saturating_sub(1)
 }
 
 async fn state_call<C, A: Encode, R: Decode>(
     rpc: &LegacyRpcMethods<C>,
+    retry_policy: &RetryPolicy,
     func: &str,
     args: A,
 ) -> Result<R>
@@ -230,10 +541,70 @@ where
     C: Config,
 {
     let params = args.encode();
-    let bytes = rpc.state_call(func, Some(&params), None).await?;
+    let bytes = retry_policy
+        .retry(|| rpc.state_call(func, Some(&params), None))
+        .await?;
     Ok(R::decode(&mut bytes.as_ref())?)
 }
 
+/// Performs a dry-run via `state_call`, decoding the events emitted during the dry-run
+/// which trail the result, see [`pallet_contracts_primitives::ContractResult`].
+async fn state_call_with_events<C, A: Encode, R: Decode>(
+    rpc: &LegacyRpcMethods<C>,
+    retry_policy: &RetryPolicy,
+    func: &str,
+    args: A,
+    metadata: &subxt::Metadata,
+) -> Result<(R, Option<DryRunEvents>)>
+where
+    C: Config,
+{
+    let params = args.encode();
+    let bytes = retry_policy
+        .retry(|| rpc.state_call(func, Some(&params), None))
+        .await?;
+    let mut bytes = bytes.as_ref();
+    let result = R::decode(&mut bytes)?;
+    let events = decode_dry_run_events(&mut bytes, metadata)?;
+    Ok((result, events))
+}
+
+/// The events emitted during a dry-run, decoded with the runtime metadata.
+pub type DryRunEvents = subxt::ext::scale_value::Value<u32>;
+
+/// Decodes the `Option<Vec<EventRecord>>` trailing a dry-run result.
+///
+/// Returns `None` if the node did not collect the events, or if it predates them being
+/// added to the result.
+fn decode_dry_run_events(
+    bytes: &mut &[u8],
+    metadata: &subxt::Metadata,
+) -> Result<Option<DryRunEvents>> {
+    if bytes.is_empty() {
+        return Ok(None)
+    }
+    match u8::decode(bytes)? {
+        0 => Ok(None),
+        1 => {
+            // the events have the same type as the `System::Events` storage
+            let events_ty = metadata
+                .pallet_by_name("System")
+                .and_then(|pallet| pallet.storage())
+                .and_then(|storage| storage.entry_by_name("Events"))
+                .map(|entry| entry.entry_type().value_ty())
+                .context("The metadata has no `System::Events` storage entry")?;
+            let events = subxt::ext::scale_value::scale::decode_as_type(
+                bytes,
+                &events_ty,
+                metadata.types(),
+            )
+            .context("Failed to decode the events of the dry-run")?;
+            Ok(Some(events))
+        }
+        tag => anyhow::bail!("Invalid option tag {tag} of the events of the dry-run"),
+    }
+}
+
 /// Fetch the hash of the *best* block (included but not guaranteed to be finalized).
 async fn get_best_block<C>(
     rpc: &LegacyRpcMethods<C>,
@@ -247,7 +618,7 @@ where
 }
 
 fn check_env_types<C>(
-    client: &OnlineClient<C>,
+    client: &impl OfflineClientT<C>,
     transcoder: &ContractMessageTranscoder,
     verbosity: &Verbosity,
 ) -> Result<()>
@@ -261,6 +632,28 @@ where
     )
 }
 
+/// Returns the SS58 prefix of the chain, as declared by its `System::SS58Prefix`
+/// constant.
+pub fn chain_ss58_prefix(metadata: &subxt::Metadata) -> Option<u16> {
+    let constant = metadata
+        .pallet_by_name("System")?
+        .constant_by_name("SS58Prefix")?;
+    u16::decode(&mut constant.value()).ok()
+}
+
+/// Renders the account ids decoded by the transcoder with the given SS58 prefix, falling
+/// back to the prefix of the chain.
+fn with_ss58_prefix<C: Config>(
+    transcoder: ContractMessageTranscoder,
+    client: &OnlineClient<C>,
+    ss58_prefix: Option<u16>,
+) -> ContractMessageTranscoder {
+    match ss58_prefix.or_else(|| chain_ss58_prefix(&client.metadata())) {
+        Some(ss58_prefix) => transcoder.with_ss58_prefix(ss58_prefix),
+        None => transcoder,
+    }
+}
+
 // Converts a Url into a String representation without excluding the default port.
 pub fn url_to_string(url: &url::Url) -> String {
     match (url.port(), url.port_or_known_default()) {
@@ -280,6 +673,186 @@ pub fn url_to_string(url: &url::Url) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn mortal_valid_until_works() {
+        // period is rounded up to the next power of two
+        assert_eq!(mortal_valid_until(100, 64), 163);
+        assert_eq!(mortal_valid_until(100, 50), 163);
+        // the minimum period is 4 blocks
+        assert_eq!(mortal_valid_until(10, 1), 13);
+        // the era starts at the beginning of the phase containing the block
+        assert_eq!(mortal_valid_until(1_000_000, 1024), 1_001_023);
+    }
+
+    #[test]
+    fn tx_timeout_error_contains_hash() {
+        let tx_hash = sp_core::H256::repeat_byte(0xab);
+
+        let err = tx_timeout_error(Duration::from_secs(30), tx_hash);
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Other error: Transaction not included within 30 seconds (hash: 0x{})",
+                "ab".repeat(32)
+            )
+        );
+    }
+
+    #[test]
+    fn sign_extrinsic_offline_works() {
+        use crate::extrinsic_calls::Call;
+        use ink_env::DefaultEnvironment;
+        use subxt::{
+            backend::RuntimeVersion,
+            utils::AccountId32,
+            SubstrateConfig,
+        };
+        use subxt_signer::sr25519::{
+            dev,
+            Keypair,
+        };
+
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        let metadata = subxt::Metadata::decode(&mut &*metadata_bytes)
+            .expect("the metadata must decode");
+        let client = OfflineClient::<SubstrateConfig>::new(
+            sp_core::H256::repeat_byte(0x01),
+            RuntimeVersion {
+                spec_version: 100,
+                transaction_version: 1,
+            },
+            metadata,
+        );
+        let call = Call::<AccountId32, u128>::new(
+            AccountId32([0x02; 32]).into(),
+            0,
+            Weight::from_parts(5_000, 6_000),
+            None,
+            vec![0x03; 4],
+        )
+        .build(DEFAULT_PALLET_NAME);
+        let opts = |nonce, mortal, checkpoint| {
+            ExtrinsicOptsBuilder::<SubstrateConfig, DefaultEnvironment, Keypair>::new(
+                dev::alice(),
+            )
+            .nonce(nonce)
+            .mortal(mortal)
+            .mortal_checkpoint(checkpoint)
+            .done()
+        };
+
+        let extrinsic =
+            sign_extrinsic_offline(&client, &call, &opts(Some(7), None, None))
+                .expect("the extrinsic must be signed");
+        let call_data = client.tx().call_data(&call).unwrap();
+        let mut bytes = &extrinsic[..];
+        let len = scale::Compact::<u32>::decode(&mut bytes).unwrap().0 as usize;
+        assert_eq!(len, bytes.len());
+        // a signed extrinsic of version 4, ending with the call
+        assert_eq!(bytes[0], 0x84);
+        assert!(bytes.ends_with(&call_data));
+
+        let checkpoint = Some((42, sp_core::H256::repeat_byte(0x04)));
+        assert!(sign_extrinsic_offline(
+            &client,
+            &call,
+            &opts(Some(7), Some(64), checkpoint)
+        )
+        .is_ok());
+        // neither the nonce nor the best block can be queried
+        assert!(sign_extrinsic_offline(&client, &call, &opts(None, None, None)).is_err());
+        assert!(
+            sign_extrinsic_offline(&client, &call, &opts(Some(7), Some(64), None))
+                .is_err()
+        );
+    }
+
+    /// The test metadata with an additional `System` pallet, storing its events as
+    /// a sequence of any type.
+    fn metadata_with_system_events() -> subxt::Metadata {
+        use scale_info::TypeDef;
+        use subxt::ext::frame_metadata::{
+            v15::{
+                PalletMetadata,
+                PalletStorageMetadata,
+                StorageEntryMetadata,
+                StorageEntryModifier,
+                StorageEntryType,
+            },
+            RuntimeMetadata,
+            RuntimeMetadataPrefixed,
+        };
+
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        let mut metadata = RuntimeMetadataPrefixed::decode(&mut &*metadata_bytes)
+            .expect("the metadata must decode");
+        let RuntimeMetadata::V15(runtime_metadata) = &mut metadata.1 else {
+            panic!("the test metadata is V15")
+        };
+        // types are resolved by their position in the registry
+        let events_ty = runtime_metadata
+            .types
+            .types
+            .iter()
+            .position(|ty| matches!(ty.ty.type_def, TypeDef::Sequence(_)))
+            .expect("the metadata must contain a sequence type")
+            as u32;
+        runtime_metadata.pallets.push(PalletMetadata {
+            name: "System".to_string(),
+            storage: Some(PalletStorageMetadata {
+                prefix: "System".to_string(),
+                entries: vec![StorageEntryMetadata {
+                    name: "Events".to_string(),
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Plain(events_ty.into()),
+                    default: vec![0],
+                    docs: Vec::new(),
+                }],
+            }),
+            calls: None,
+            event: None,
+            constants: Vec::new(),
+            error: None,
+            index: u8::MAX,
+            docs: Vec::new(),
+        });
+        metadata.try_into().expect("the metadata must be valid")
+    }
+
+    #[test]
+    fn decode_dry_run_events_works() {
+        let metadata = metadata_with_system_events();
+
+        // a node predating the events
+        assert!(decode_dry_run_events(&mut &[][..], &metadata)
+            .unwrap()
+            .is_none());
+        // a node not collecting the events
+        assert!(decode_dry_run_events(&mut &[0][..], &metadata)
+            .unwrap()
+            .is_none());
+
+        let mut bytes = &(Some(Vec::<()>::new()), 42u8).encode()[..];
+        let events = decode_dry_run_events(&mut bytes, &metadata)
+            .unwrap()
+            .expect("the events must be present");
+        assert_eq!(events.to_string(), "()");
+        assert_eq!(bytes, &[42]);
+
+        assert!(decode_dry_run_events(&mut &[2][..], &metadata).is_err());
+    }
+
+    #[test]
+    fn check_nonce_works() {
+        assert_eq!(check_nonce(5, 5), None);
+        assert_eq!(check_nonce(5 + MAX_NONCE_AHEAD, 5), None);
+        assert!(check_nonce(4, 5).is_some());
+        assert!(check_nonce(6 + MAX_NONCE_AHEAD, 5).is_some());
+    }
+
     #[test]
     fn url_to_string_works() {
         // with custom port
@@ -0,0 +1,579 @@
+// Copyright 2018-2023 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{
+    extrinsic_calls::{
+        ApproveAsMulti,
+        AsMulti,
+        EncodedCall,
+    },
+    extrinsic_opts::ExtrinsicOpts,
+    get_best_block,
+    state_call,
+    submit_extrinsic,
+    ErrorVariant,
+    GenericError,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use ink_env::Environment;
+use scale::{
+    Decode,
+    Encode,
+};
+use sp_core::hashing::blake2_256;
+use sp_weights::Weight;
+use subxt::{
+    backend::legacy::LegacyRpcMethods,
+    blocks::ExtrinsicEvents,
+    config::{
+        DefaultExtrinsicParams,
+        ExtrinsicParams,
+    },
+    ext::{
+        scale_decode::{
+            DecodeAsType,
+            IntoVisitor,
+        },
+        scale_encode::EncodeAsType,
+        scale_value::{
+            At,
+            Value,
+            ValueDef,
+        },
+    },
+    storage::dynamic,
+    tx,
+    Config,
+    OnlineClient,
+};
+
+/// The signatories of a multisig account and the number of approvals required to
+/// dispatch a call from it.
+#[derive(Clone, Debug)]
+pub struct Multisig<AccountId> {
+    threshold: u16,
+    signatories: Vec<AccountId>,
+}
+
+impl<AccountId> Multisig<AccountId>
+where
+    AccountId: Encode + Clone + PartialEq,
+{
+    /// Creates a multisig of the given signatories, requiring `threshold` approvals.
+    pub fn new(threshold: u16, signatories: Vec<AccountId>) -> Result<Self> {
+        let mut signatories = signatories;
+        // `pallet-multisig` requires the signatories to be sorted.
+        signatories.sort_by_key(Encode::encode);
+        signatories.dedup();
+        anyhow::ensure!(
+            threshold >= 2,
+            "The threshold of a multisig must be at least 2"
+        );
+        anyhow::ensure!(
+            usize::from(threshold) <= signatories.len(),
+            "The threshold {threshold} exceeds the number of signatories {}",
+            signatories.len()
+        );
+        Ok(Self {
+            threshold,
+            signatories,
+        })
+    }
+
+    /// Returns the number of approvals required to dispatch a call.
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Returns the signatories, sorted by their encoding.
+    pub fn signatories(&self) -> &[AccountId] {
+        &self.signatories
+    }
+
+    /// Returns the account id of the multisig, see [`multisig_account_id`].
+    pub fn account_id(&self) -> [u8; 32] {
+        multisig_account_id(&self.signatories, self.threshold)
+    }
+
+    /// Returns the signatories other than the given signer, who has to be one of them.
+    fn other_signatories(&self, signer: &AccountId) -> Result<Vec<AccountId>> {
+        anyhow::ensure!(
+            self.signatories.contains(signer),
+            "The signer is not one of the signatories of the multisig"
+        );
+        Ok(self
+            .signatories
+            .iter()
+            .filter(|signatory| *signatory != signer)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Computes the account id of a multisig the way `pallet-multisig` does.
+///
+/// The `signatories` have to be sorted. The account id only depends on the signatories
+/// and the threshold, so it is the same for every signatory.
+pub fn multisig_account_id<AccountId>(
+    signatories: &[AccountId],
+    threshold: u16,
+) -> [u8; 32]
+where
+    AccountId: Encode,
+{
+    let entropy = (b"modlpy/utilisuba", signatories, threshold).encode();
+    blake2_256(&entropy)
+}
+
+/// The block number and extrinsic index at which a multisig operation was opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EncodeAsType, DecodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+#[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+pub struct Timepoint {
+    pub height: u64,
+    pub index: u32,
+}
+
+/// A multisig operation awaiting approvals, as stored by `pallet-multisig`.
+#[derive(Debug, DecodeAsType)]
+#[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+struct PendingMultisig<AccountId> {
+    when: Timepoint,
+    approvals: Approvals<AccountId>,
+}
+
+/// The `BoundedVec` of the accounts which approved a multisig operation.
+#[derive(Debug, DecodeAsType)]
+#[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+struct Approvals<AccountId>(Vec<AccountId>);
+
+/// The extrinsic a signatory submits for a multisig operation.
+#[derive(Debug, PartialEq, Eq)]
+enum MultisigStep {
+    /// Approve the call without dispatching it, the first approval opens the operation.
+    Approve(Option<Timepoint>),
+    /// Dispatch the call with the final approval.
+    Execute(Option<Timepoint>),
+}
+
+impl MultisigStep {
+    /// Returns the step of the `signer`, given the pending operation of the call.
+    fn next<AccountId: PartialEq>(
+        pending: Option<&PendingMultisig<AccountId>>,
+        signer: &AccountId,
+        threshold: u16,
+    ) -> Result<Self> {
+        let Some(pending) = pending else {
+            return Ok(Self::Approve(None))
+        };
+        anyhow::ensure!(
+            !pending.approvals.0.contains(signer),
+            "The signer already approved the multisig call"
+        );
+        if pending.approvals.0.len() + 1 >= usize::from(threshold) {
+            Ok(Self::Execute(Some(pending.when)))
+        } else {
+            Ok(Self::Approve(Some(pending.when)))
+        }
+    }
+}
+
+/// Result of submitting a call via a multisig.
+pub struct MultisigResult<C: Config> {
+    /// The account of the multisig, which dispatches the call.
+    pub multisig_account: C::AccountId,
+    /// The hash of the call, which is approved by the signatories.
+    pub call_hash: [u8; 32],
+    /// The SCALE encoded call.
+    pub call_data: Vec<u8>,
+    /// The number of approvals, including the one just submitted.
+    pub approvals: usize,
+    /// The number of approvals required to dispatch the call.
+    pub threshold: u16,
+    /// The events of the submitted extrinsic.
+    pub events: ExtrinsicEvents<C>,
+    /// The outcome of the call, if it was dispatched with the final approval.
+    pub dispatch_result: Option<Result<(), ErrorVariant>>,
+}
+
+impl<C: Config> MultisigResult<C> {
+    /// Returns `true` if the call was dispatched with the final approval and
+    /// succeeded.
+    pub fn is_executed(&self) -> bool {
+        matches!(self.dispatch_result, Some(Ok(())))
+    }
+}
+
+/// Submits the call via the multisig, with the signer of the extrinsic options as one
+/// of its signatories.
+///
+/// A pending operation for the call is looked up on-chain: the signatories approve
+/// the call by its hash, and the last required approval dispatches the call from the
+/// multisig account.
+pub(crate) async fn submit_multisig<C, E, Call, Signer>(
+    client: &OnlineClient<C>,
+    rpc: &LegacyRpcMethods<C>,
+    call: &Call,
+    multisig: &Multisig<C::AccountId>,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+) -> Result<MultisigResult<C>, ErrorVariant>
+where
+    C: Config,
+    C::AccountId: Decode + IntoVisitor + PartialEq + EncodeAsType,
+    E: Environment,
+    Call: tx::TxPayload,
+    Signer: tx::Signer<C> + Clone,
+    <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+        From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+{
+    let call_data = client.tx().call_data(call)?;
+    let call_hash = blake2_256(&call_data);
+    let multisig_account = multisig.account_id();
+    let signer = Signer::account_id(opts.signer());
+    let other_signatories = multisig.other_signatories(&signer)?;
+
+    let pending =
+        fetch_pending_multisig::<C>(client, rpc, &multisig_account, &call_hash).await?;
+    let step = MultisigStep::next(pending.as_ref(), &signer, multisig.threshold())?;
+    let events = match step {
+        MultisigStep::Approve(timepoint) => {
+            let call = ApproveAsMulti::new(
+                multisig.threshold(),
+                other_signatories,
+                timepoint,
+                call_hash,
+                Weight::zero(),
+            )
+            .build();
+            submit_extrinsic(client, rpc, &call, opts).await?
+        }
+        MultisigStep::Execute(timepoint) => {
+            let max_weight = query_call_weight(rpc, opts, &call_data).await?;
+            let call = AsMulti::new(
+                multisig.threshold(),
+                other_signatories,
+                timepoint,
+                EncodedCall(call_data.clone()),
+                max_weight,
+            )
+            .build();
+            submit_extrinsic(client, rpc, &call, opts).await?
+        }
+    };
+    let dispatch_result = find_dispatch_result(&events, client, opts.pallet_name())?;
+
+    Ok(MultisigResult {
+        multisig_account: C::AccountId::decode(&mut &multisig_account[..])
+            .context("Failed to decode the multisig account id")?,
+        call_hash,
+        call_data,
+        approvals: pending.map_or(0, |pending| pending.approvals.0.len()) + 1,
+        threshold: multisig.threshold(),
+        events,
+        dispatch_result,
+    })
+}
+
+/// Returns the outcome of the call reported by the `MultisigExecuted` event, if the
+/// call was dispatched.
+fn find_dispatch_result<C: Config>(
+    events: &ExtrinsicEvents<C>,
+    client: &OnlineClient<C>,
+    pallet_name: &str,
+) -> Result<Option<Result<(), ErrorVariant>>> {
+    let metadata = client.metadata();
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() != "Multisig" || event.variant_name() != "MultisigExecuted"
+        {
+            continue
+        }
+        let fields = event.field_values()?;
+        let result = fields
+            .at("result")
+            .context("The `MultisigExecuted` event has no `result` field")?;
+        return dispatch_result(result, &metadata, pallet_name).map(Some)
+    }
+    Ok(None)
+}
+
+/// Converts the `DispatchResult` of an event into the outcome of the call, resolving
+/// module errors against the metadata.
+fn dispatch_result<T>(
+    result: &Value<T>,
+    metadata: &subxt::Metadata,
+    pallet_name: &str,
+) -> Result<Result<(), ErrorVariant>> {
+    let ValueDef::Variant(variant) = &result.value else {
+        anyhow::bail!("Expected a `DispatchResult`, found '{result}'")
+    };
+    match variant.name.as_str() {
+        "Ok" => Ok(Ok(())),
+        "Err" => {
+            let error = variant
+                .values
+                .at(0)
+                .context("Expected a `DispatchError` in the `Err` variant")?;
+            match &error.value {
+                ValueDef::Variant(error_variant) if error_variant.name == "Module" => {
+                    let module_error = error_variant.values.at(0);
+                    let pallet_index = module_error
+                        .at("index")
+                        .and_then(Value::as_u128)
+                        .context("Expected the pallet index of the module error")?;
+                    let error_index = module_error
+                        .at("error")
+                        .at(0)
+                        .and_then(Value::as_u128)
+                        .context("Expected the error index of the module error")?;
+                    Ok(Err(ErrorVariant::from_module_error(
+                        pallet_index.try_into()?,
+                        error_index.try_into()?,
+                        metadata,
+                        pallet_name,
+                    )))
+                }
+                _ => {
+                    Ok(Err(ErrorVariant::Generic(GenericError::from_message(
+                        format!("DispatchError: {error}"),
+                    ))))
+                }
+            }
+        }
+        name => {
+            anyhow::bail!("Expected `Ok` or `Err` for a `DispatchResult`, found '{name}'")
+        }
+    }
+}
+
+/// Fetches the pending operation of the multisig for the call with the given hash.
+async fn fetch_pending_multisig<C>(
+    client: &OnlineClient<C>,
+    rpc: &LegacyRpcMethods<C>,
+    multisig_account: &[u8; 32],
+    call_hash: &[u8; 32],
+) -> Result<Option<PendingMultisig<C::AccountId>>>
+where
+    C: Config,
+    C::AccountId: IntoVisitor,
+{
+    let best_block = get_best_block(rpc).await?;
+    let storage_query = dynamic(
+        "Multisig",
+        "Multisigs",
+        vec![
+            Value::from_bytes(multisig_account),
+            Value::from_bytes(call_hash),
+        ],
+    );
+    let pending = client
+        .storage()
+        .at(best_block)
+        .fetch(&storage_query)
+        .await?
+        .map(|pending| pending.as_type::<PendingMultisig<C::AccountId>>())
+        .transpose()?;
+    Ok(pending)
+}
+
+/// Queries the weight of the call, which is the maximum weight of its dispatch by the
+/// multisig.
+async fn query_call_weight<C, E, Signer>(
+    rpc: &LegacyRpcMethods<C>,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+    call_data: &[u8],
+) -> Result<Weight>
+where
+    C: Config,
+    E: Environment,
+    Signer: tx::Signer<C> + Clone,
+{
+    let len = call_data.len() as u32;
+    // The `RuntimeDispatchInfo` starts with the weight of the call.
+    state_call(
+        rpc,
+        opts.rpc_retry_policy(),
+        "TransactionPaymentCallApi_query_call_info",
+        (EncodedCall(call_data.to_vec()), len),
+    )
+    .await
+    .context("Failed to query the weight of the multisig call")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: [u8; 32] = [1; 32];
+    const BOB: [u8; 32] = [2; 32];
+    const CHARLIE: [u8; 32] = [3; 32];
+
+    fn pending(approvals: Vec<[u8; 32]>) -> PendingMultisig<[u8; 32]> {
+        PendingMultisig {
+            when: Timepoint {
+                height: 42,
+                index: 1,
+            },
+            approvals: Approvals(approvals),
+        }
+    }
+
+    #[test]
+    fn multisig_account_id_does_not_depend_on_the_order_of_signatories() {
+        let multisig = Multisig::new(2, vec![CHARLIE, ALICE, BOB]).unwrap();
+        let reordered = Multisig::new(2, vec![BOB, CHARLIE, ALICE]).unwrap();
+        let other_threshold = Multisig::new(3, vec![ALICE, BOB, CHARLIE]).unwrap();
+
+        assert_eq!(multisig.signatories(), &[ALICE, BOB, CHARLIE]);
+        assert_eq!(multisig.account_id(), reordered.account_id());
+        assert_ne!(multisig.account_id(), other_threshold.account_id());
+    }
+
+    #[test]
+    fn multisig_threshold_is_validated() {
+        assert_eq!(
+            Multisig::new(1, vec![ALICE, BOB]).unwrap_err().to_string(),
+            "The threshold of a multisig must be at least 2"
+        );
+        assert_eq!(
+            Multisig::new(3, vec![ALICE, BOB, BOB])
+                .unwrap_err()
+                .to_string(),
+            "The threshold 3 exceeds the number of signatories 2"
+        );
+    }
+
+    #[test]
+    fn signer_must_be_a_signatory() {
+        let multisig = Multisig::new(2, vec![ALICE, BOB]).unwrap();
+
+        assert_eq!(multisig.other_signatories(&BOB).unwrap(), vec![ALICE]);
+        assert_eq!(
+            multisig
+                .other_signatories(&CHARLIE)
+                .unwrap_err()
+                .to_string(),
+            "The signer is not one of the signatories of the multisig"
+        );
+    }
+
+    #[test]
+    fn first_approval_opens_the_operation() {
+        let step = MultisigStep::next(None, &ALICE, 2).unwrap();
+
+        assert_eq!(step, MultisigStep::Approve(None));
+    }
+
+    #[test]
+    fn final_approval_executes_the_call() {
+        let pending = pending(vec![ALICE]);
+
+        let step = MultisigStep::next(Some(&pending), &BOB, 2).unwrap();
+
+        assert_eq!(step, MultisigStep::Execute(Some(pending.when)));
+    }
+
+    #[test]
+    fn intermediate_approval_approves_the_pending_operation() {
+        let pending = pending(vec![ALICE]);
+
+        let step = MultisigStep::next(Some(&pending), &BOB, 3).unwrap();
+
+        assert_eq!(step, MultisigStep::Approve(Some(pending.when)));
+    }
+
+    fn metadata() -> subxt::Metadata {
+        let metadata_bytes = std::fs::read("src/test_runtime_api/metadata_v11.scale")
+            .expect("the metadata must be present");
+        subxt::Metadata::decode(&mut &*metadata_bytes).expect("the metadata must decode")
+    }
+
+    #[test]
+    fn successful_dispatch_result_is_ok() {
+        let result = Value::unnamed_variant("Ok", [Value::unnamed_composite([])]);
+
+        let outcome = dispatch_result(&result, &metadata(), "Contracts").unwrap();
+
+        assert!(outcome.is_ok());
+    }
+
+    #[test]
+    fn failed_dispatch_result_reports_the_module_error() {
+        let metadata = metadata();
+        let pallet = metadata.pallet_by_name("Contracts").unwrap();
+        let (error_index, _) = pallet
+            .error_variants()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == "CodeNotFound")
+            .unwrap();
+        let module_error = Value::named_composite([
+            ("index", Value::u128(pallet.index().into())),
+            (
+                "error",
+                Value::unnamed_composite([
+                    Value::u128(error_index as u128),
+                    Value::u128(0),
+                    Value::u128(0),
+                    Value::u128(0),
+                ]),
+            ),
+        ]);
+        let result = Value::unnamed_variant(
+            "Err",
+            [Value::unnamed_variant("Module", [module_error])],
+        );
+
+        let err = dispatch_result(&result, &metadata, "Contracts")
+            .unwrap()
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .starts_with("ModuleError: Contracts::CodeNotFound"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn failed_dispatch_result_reports_other_errors() {
+        let result =
+            Value::unnamed_variant("Err", [Value::unnamed_variant("BadOrigin", [])]);
+
+        let err = dispatch_result(&result, &metadata(), "Contracts")
+            .unwrap()
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "DispatchError: BadOrigin ()");
+    }
+
+    #[test]
+    fn signer_can_not_approve_twice() {
+        let pending = pending(vec![ALICE]);
+
+        let err = MultisigStep::next(Some(&pending), &ALICE, 2).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The signer already approved the multisig call"
+        );
+    }
+}
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
+use contract_metadata::byte_str::serialize_as_byte_str;
 use pallet_contracts_uapi::ReturnFlags;
 use scale::{
     Decode,
@@ -21,6 +22,7 @@ use scale::{
     MaxEncodedLen,
 };
 use scale_info::TypeInfo;
+use serde::Serializer;
 use sp_runtime::{
     DispatchError,
     RuntimeDebug,
@@ -39,7 +41,9 @@ use sp_weights::Weight;
 /// It has been extended to include `events` at the end of the struct while not bumping
 /// the `ContractsApi` version. Therefore when SCALE decoding a `ContractResult` its
 /// trailing data should be ignored to avoid any potential compatibility issues.
-#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[derive(
+    Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, serde::Serialize,
+)]
 pub struct ContractResult<R, Balance> {
     /// How much weight was consumed during execution.
     pub gas_consumed: Weight,
@@ -75,6 +79,7 @@ pub struct ContractResult<R, Balance> {
     ///
     /// The debug message is never generated during on-chain execution. It is reserved
     /// for RPC calls.
+    #[serde(serialize_with = "serialize_debug_message")]
     pub debug_message: Vec<u8>,
     /// The execution result of the wasm code.
     pub result: R,
@@ -109,11 +114,15 @@ pub enum ContractAccessError {
 }
 
 /// Output of a contract call or instantiation which ran to completion.
-#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[derive(
+    Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, serde::Serialize,
+)]
 pub struct ExecReturnValue {
     /// Flags passed along by `seal_return`. Empty when `seal_return` was never called.
+    #[serde(serialize_with = "serialize_return_flags")]
     pub flags: ReturnFlags,
     /// Buffer passed along by `seal_return`. Empty when `seal_return` was never called.
+    #[serde(serialize_with = "serialize_as_byte_str")]
     pub data: Vec<u8>,
 }
 
@@ -125,7 +134,9 @@ impl ExecReturnValue {
 }
 
 /// The result of a successful contract instantiation.
-#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[derive(
+    Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, serde::Serialize,
+)]
 pub struct InstantiateReturnValue<AccountId> {
     /// The output of the called constructor.
     pub result: ExecReturnValue,
@@ -178,3 +189,57 @@ pub enum StorageDeposit<Balance> {
     /// to the involved deposit accounts.
     Charge(Balance),
 }
+
+/// Serializes the debug message as a string, since it is valid UTF-8.
+fn serialize_debug_message<S>(
+    debug_message: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&String::from_utf8_lossy(debug_message))
+}
+
+/// Serializes the return flags as their raw bits.
+fn serialize_return_flags<S>(
+    flags: &ReturnFlags,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_u32(flags.bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contract_exec_result_serializes_to_json() {
+        let result: ContractExecResult<u128> = ContractResult {
+            gas_consumed: Weight::from_parts(100, 10),
+            gas_required: Weight::from_parts(200, 20),
+            storage_deposit: StorageDeposit::Charge(5),
+            debug_message: b"debug output".to_vec(),
+            result: Ok(ExecReturnValue {
+                flags: ReturnFlags::REVERT,
+                data: vec![0x00, 0x2a],
+            }),
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "gas_consumed": { "ref_time": 100, "proof_size": 10 },
+                "gas_required": { "ref_time": 200, "proof_size": 20 },
+                "storage_deposit": { "Charge": 5 },
+                "debug_message": "debug output",
+                "result": { "Ok": { "flags": 1, "data": "0x002a" } },
+            })
+        );
+    }
+}
@@ -15,23 +15,27 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
-    events::CodeRemoved,
+    events::{
+        find_first_contracts_event,
+        CodeRemoved,
+    },
     submit_extrinsic,
     ContractMessageTranscoder,
     ErrorVariant,
 };
 use crate::{
-    extrinsic_calls::RemoveCode,
+    extrinsic_calls::{
+        check_contracts_pallet,
+        RemoveCode,
+    },
     extrinsic_opts::ExtrinsicOpts,
+    with_ss58_prefix,
 };
 
 use anyhow::Result;
 use ink_env::Environment;
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     blocks::ExtrinsicEvents,
     config::{
         DefaultExtrinsicParams,
@@ -107,9 +111,12 @@ where
         }?;
 
         let url = self.extrinsic_opts.url();
-        let rpc_cli = RpcClient::from_url(&url).await?;
+        let rpc_cli = self.extrinsic_opts.rpc_connection().connect(&url).await?;
         let client = OnlineClient::<C>::from_rpc_client(rpc_cli.clone()).await?;
         let rpc = LegacyRpcMethods::<C>::new(rpc_cli);
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        let transcoder =
+            with_ss58_prefix(transcoder, &client, self.extrinsic_opts.ss58_prefix());
 
         Ok(RemoveExec {
             final_code_hash,
@@ -152,13 +159,15 @@ where
     {
         let code_hash = self.final_code_hash;
 
-        let call = RemoveCode::new(code_hash).build();
+        let call = RemoveCode::new(code_hash).build(self.opts.pallet_name());
 
-        let events =
-            submit_extrinsic(&self.client, &self.rpc, &call, self.opts.signer()).await?;
+        let events = submit_extrinsic(&self.client, &self.rpc, &call, &self.opts).await?;
 
         let code_removed =
-            events.find_first::<CodeRemoved<C::Hash, C::AccountId, E::Balance>>()?;
+            find_first_contracts_event::<
+                CodeRemoved<C::Hash, C::AccountId, E::Balance>,
+                C,
+            >(&events, &self.client.metadata(), self.opts.pallet_name())?;
         Ok(RemoveResult {
             code_removed,
             events,
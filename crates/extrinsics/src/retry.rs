@@ -0,0 +1,306 @@
+// Copyright (C) Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    future::Future,
+    time::Duration,
+};
+
+use jsonrpsee::core::ClientError;
+use subxt::error::RpcError;
+
+/// The delay before the first retry, if not configured otherwise.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Policy for retrying read-only RPC requests which failed due to a transient error,
+/// e.g. a dropped websocket connection.
+///
+/// The delay before each retry doubles with every attempt. Submitting an extrinsic is
+/// never retried, in order to avoid submitting it twice.
+///
+/// Retrying only helps if the connection to the node is re-established in between,
+/// which is why requests are retried over a [`crate::RpcConnection::Reconnecting`]
+/// connection, see [`crate::RpcConnection::for_retries`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    retries: u32,
+    delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying a failed request up to `retries` times, waiting
+    /// `delay` before the first retry.
+    pub fn new(retries: u32, delay: Duration) -> Self {
+        Self { retries, delay }
+    }
+
+    /// Return the maximum number of retries.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Return the delay before the first retry.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Return the delay before the given retry, starting with `0` for the first one.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.delay.saturating_mul(2u32.saturating_pow(retry))
+    }
+
+    /// Runs the request returned by `request`, retrying it while it fails with a
+    /// transient error and retries are left.
+    pub(crate) async fn retry<T, E, F, Fut>(&self, mut request: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: TransientError + std::fmt::Display,
+    {
+        let mut retry = 0;
+        loop {
+            match request().await {
+                Err(err) if retry < self.retries && err.is_transient() => {
+                    let delay = self.backoff(retry);
+                    tracing::debug!(
+                        "RPC request failed: {err}, retrying in {}ms",
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Errors which may succeed when the failed request is retried.
+pub(crate) trait TransientError {
+    /// Returns `true` if the error was caused by the connection to the node rather than
+    /// by the request itself.
+    fn is_transient(&self) -> bool;
+}
+
+impl TransientError for subxt::Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            // the client error also wraps the errors returned by the node for the call
+            // itself, only the ones caused by the connection are transient
+            subxt::Error::Rpc(RpcError::ClientError(err)) => {
+                matches!(
+                    err.downcast_ref::<ClientError>(),
+                    Some(ClientError::Transport(_) | ClientError::RestartNeeded(_))
+                )
+            }
+            subxt::Error::Rpc(
+                RpcError::SubscriptionDropped | RpcError::DisconnectedWillReconnect(_),
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::types::ErrorObject;
+    use std::{
+        cell::Cell,
+        fmt,
+    };
+
+    /// An error returned by the mocked client.
+    #[derive(Debug)]
+    enum MockError {
+        ConnectionDropped,
+        InvalidRequest,
+    }
+
+    impl fmt::Display for MockError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl TransientError for MockError {
+        fn is_transient(&self) -> bool {
+            matches!(self, MockError::ConnectionDropped)
+        }
+    }
+
+    /// A client failing with the given errors before succeeding.
+    struct MockClient {
+        errors: Cell<Vec<MockError>>,
+        requests: Cell<u32>,
+    }
+
+    impl MockClient {
+        fn new(mut errors: Vec<MockError>) -> Self {
+            errors.reverse();
+            Self {
+                errors: Cell::new(errors),
+                requests: Cell::new(0),
+            }
+        }
+
+        async fn request(&self) -> Result<u64, MockError> {
+            self.requests.set(self.requests.get() + 1);
+            let mut errors = self.errors.take();
+            let result = errors.pop().map_or(Ok(42), Err);
+            self.errors.set(errors);
+            result
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_with_every_retry() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100));
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(1600));
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        let policy = RetryPolicy::new(u32::MAX, Duration::from_secs(u64::MAX / 2));
+        assert_eq!(policy.backoff(2), Duration::MAX);
+        assert_eq!(policy.backoff(u32::MAX), Duration::MAX);
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let client = MockClient::new(vec![
+            MockError::ConnectionDropped,
+            MockError::ConnectionDropped,
+        ]);
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        let result = policy.retry(|| client.request()).await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(client.requests.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_are_limited() {
+        let client = MockClient::new(vec![
+            MockError::ConnectionDropped,
+            MockError::ConnectionDropped,
+            MockError::ConnectionDropped,
+        ]);
+        let policy = RetryPolicy::new(2, Duration::from_millis(1));
+
+        let result = policy.retry(|| client.request()).await;
+
+        assert!(matches!(result, Err(MockError::ConnectionDropped)));
+        assert_eq!(client.requests.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let client = MockClient::new(vec![MockError::InvalidRequest]);
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        let result = policy.retry(|| client.request()).await;
+
+        assert!(matches!(result, Err(MockError::InvalidRequest)));
+        assert_eq!(client.requests.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_wait_for_the_backoff() {
+        let client = MockClient::new(vec![
+            MockError::ConnectionDropped,
+            MockError::ConnectionDropped,
+        ]);
+        let policy = RetryPolicy::new(2, Duration::from_millis(20));
+        let start = std::time::Instant::now();
+
+        let result = policy.retry(|| client.request()).await;
+
+        assert!(result.is_ok());
+        // 20ms before the first and 40ms before the second retry
+        assert!(start.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[test]
+    fn dropped_connections_are_transient() {
+        let reconnecting = subxt::Error::Rpc(RpcError::DisconnectedWillReconnect(
+            "connection closed".into(),
+        ));
+        let dropped = subxt::Error::Rpc(RpcError::SubscriptionDropped);
+        let rejected = subxt::Error::Rpc(RpcError::RequestRejected("invalid".into()));
+
+        assert!(reconnecting.is_transient());
+        assert!(dropped.is_transient());
+        assert!(!rejected.is_transient());
+    }
+
+    #[test]
+    fn failed_connections_are_transient() {
+        let transport = subxt::Error::Rpc(RpcError::ClientError(Box::new(
+            ClientError::Transport(anyhow::anyhow!("connection refused")),
+        )));
+        let restart = subxt::Error::Rpc(RpcError::ClientError(Box::new(
+            ClientError::RestartNeeded(std::sync::Arc::new(ClientError::Transport(
+                anyhow::anyhow!("connection closed"),
+            ))),
+        )));
+
+        assert!(transport.is_transient());
+        assert!(restart.is_transient());
+    }
+
+    #[tokio::test]
+    async fn json_rpc_call_errors_are_not_retried() {
+        let requests = Cell::new(0);
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        let result: Result<(), _> = policy
+            .retry(|| {
+                requests.set(requests.get() + 1);
+                async {
+                    Err(subxt::Error::Rpc(RpcError::ClientError(Box::new(
+                        ClientError::Call(ErrorObject::owned(
+                            -32602,
+                            "Invalid params",
+                            None::<()>,
+                        )),
+                    ))))
+                }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(requests.get(), 1);
+    }
+
+    #[test]
+    fn default_policy_does_not_retry() {
+        assert_eq!(RetryPolicy::default().retries(), 0);
+    }
+}
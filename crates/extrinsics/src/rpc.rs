@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::str::FromStr;
+use std::{
+    path::Path,
+    str::FromStr,
+};
 
 use contract_transcode::AccountId32;
 use subxt::{
@@ -32,14 +35,18 @@ use subxt::{
     },
 };
 
-use crate::url_to_string;
+use crate::{
+    url_to_string,
+    RpcConnection,
+};
 use anyhow::{
     anyhow,
     bail,
+    Context,
     Result,
 };
 
-pub struct RawParams(Option<Box<RawValue>>);
+pub struct RawParams(Vec<Box<RawValue>>);
 
 impl RawParams {
     /// Creates a new `RawParams` instance from a slice of string parameters.
@@ -55,32 +62,73 @@ impl RawParams {
             .collect::<Result<Vec<_>, ParseError>>()
             .map_err(|e| anyhow::anyhow!("Method parameters parsing failed: {e}"))?;
 
-        let params = match value_params.is_empty() {
-            true => None,
-            false => {
-                value_params
-                    .iter()
-                    .try_fold(RpcParams::new(), |mut v, e| {
-                        v.push(e)?;
-                        Ok(v)
-                    })
-                    .map_err(|e: subxt::Error| {
-                        anyhow::anyhow!("Building method parameters failed: {e}")
-                    })?
-                    .build()
-            }
-        };
+        let params = value_params
+            .iter()
+            .map(serde_json::value::to_raw_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Building method parameters failed: {e}"))?;
+
+        Ok(Self(params))
+    }
+
+    /// Creates a new `RawParams` instance from a JSON array of parameters.
+    /// Returns an error if `params` is not an array.
+    pub fn from_json(params: &serde_json::Value) -> Result<Self> {
+        let params = params.as_array().ok_or_else(|| {
+            anyhow!("Method parameters must be a JSON array, got: {params}")
+        })?;
+        let params = params
+            .iter()
+            .map(serde_json::value::to_raw_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Building method parameters failed: {e}"))?;
 
         Ok(Self(params))
     }
+
+    /// Creates a new `RawParams` instance from a file containing a JSON array of
+    /// parameters.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read params file {}", path.display()))?;
+        let params: serde_json::Value = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse params file {}", path.display()))?;
+        Self::from_json(&params)
+            .with_context(|| format!("Invalid params file {}", path.display()))
+    }
+
+    /// Appends the parameters of `other` to these parameters.
+    pub fn extend(self, other: RawParams) -> Self {
+        let mut this = self;
+        this.0.extend(other.0);
+        this
+    }
+
+    /// Builds the parameters of the RPC request, `None` if there are no parameters.
+    fn build(self) -> Result<Option<Box<RawValue>>> {
+        if self.0.is_empty() {
+            return Ok(None)
+        }
+        let params = self
+            .0
+            .iter()
+            .try_fold(RpcParams::new(), |mut v, e| {
+                v.push(e)?;
+                Ok(v)
+            })
+            .map_err(|e: subxt::Error| {
+                anyhow::anyhow!("Building method parameters failed: {e}")
+            })?;
+        Ok(params.build())
+    }
 }
 
 pub struct RpcRequest(RpcClient);
 
 impl RpcRequest {
     /// Creates a new `RpcRequest` instance.
-    pub async fn new(url: &url::Url) -> Result<Self> {
-        let rpc = RpcClient::from_url(url_to_string(url)).await?;
+    pub async fn new(url: &url::Url, connection: &RpcConnection) -> Result<Self> {
+        let rpc = connection.connect(&url_to_string(url)).await?;
         Ok(Self(rpc))
     }
 
@@ -100,7 +148,7 @@ impl RpcRequest {
             );
         }
         self.0
-            .request_raw(method, params.0)
+            .request_raw(method, params.build()?)
             .await
             .map_err(|e| anyhow!("Raw RPC call failed: {e}"))
     }
@@ -169,11 +217,16 @@ mod tests {
     fn assert_raw_params_value(input: &[&str], expected: &str) {
         let input = input.iter().map(|e| e.to_string()).collect::<Vec<String>>();
         let raw_params = RawParams::new(&input).expect("Raw param shall be created");
+        assert_raw_params_eq(raw_params, expected);
+    }
+
+    fn assert_raw_params_eq(raw_params: RawParams, expected: &str) {
         let expected = expected
             .chars()
             .filter(|&c| !c.is_whitespace())
             .collect::<String>();
-        assert_eq!(raw_params.0.unwrap().get(), expected);
+        let built = raw_params.build().expect("Raw params shall be built");
+        assert_eq!(built.unwrap().get(), expected);
     }
 
     #[test]
@@ -205,4 +258,45 @@ mod tests {
         5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY, c: \"test\"}"];
         assert_raw_params_value(input, expected);
     }
+
+    #[test]
+    fn params_file_works() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), r#"["0x1234", {"at": null}, [1, 2]]"#).unwrap();
+
+        let inline = RawParams::new(&["\"state_call\"".to_string()]).unwrap();
+        let from_file = RawParams::from_json_file(file.path()).unwrap();
+
+        assert_raw_params_eq(
+            inline.extend(from_file),
+            r#"["state_call", "0x1234", {"at": null}, [1, 2]]"#,
+        );
+    }
+
+    #[test]
+    fn params_file_must_be_an_array() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), r#"{"params": [1, 2]}"#).unwrap();
+
+        let err = RawParams::from_json_file(file.path())
+            .err()
+            .expect("params file must be an array");
+
+        assert_eq!(
+            format!("{:#}", err),
+            format!(
+                "Invalid params file {}: Method parameters must be a JSON array, got: \
+                 {{\"params\":[1,2]}}",
+                file.path().display()
+            )
+        );
+    }
+
+    #[test]
+    fn no_params_are_not_sent() {
+        let raw_params = RawParams::new(&[])
+            .unwrap()
+            .extend(RawParams::from_json(&serde_json::json!([])).unwrap());
+        assert!(raw_params.build().unwrap().is_none());
+    }
 }
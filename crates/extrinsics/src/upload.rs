@@ -15,8 +15,13 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
-    events::CodeStored,
+    events::{
+        find_first_contracts_event,
+        CodeStored,
+    },
+    get_best_block,
     pallet_contracts_primitives::CodeUploadResult,
+    sign_extrinsic_offline,
     state_call,
     submit_extrinsic,
     ErrorVariant,
@@ -24,18 +29,28 @@ use super::{
 };
 use crate::{
     check_env_types,
-    extrinsic_calls::UploadCode,
+    extrinsic_calls::{
+        check_contracts_pallet,
+        UploadCode,
+    },
     extrinsic_opts::ExtrinsicOpts,
+    multisig::{
+        submit_multisig,
+        Multisig,
+        MultisigResult,
+    },
+    with_ss58_prefix,
 };
 use anyhow::Result;
 use contract_transcode::ContractMessageTranscoder;
 use ink_env::Environment;
-use scale::Encode;
+use scale::{
+    Decode,
+    Encode,
+};
+use serde::Serialize;
 use subxt::{
-    backend::{
-        legacy::LegacyRpcMethods,
-        rpc::RpcClient,
-    },
+    backend::legacy::LegacyRpcMethods,
     blocks::ExtrinsicEvents,
     config::{
         DefaultExtrinsicParams,
@@ -44,9 +59,12 @@ use subxt::{
     ext::{
         scale_decode::IntoVisitor,
         scale_encode::EncodeAsType,
+        scale_value::Value,
     },
+    storage::dynamic,
     tx,
     Config,
+    OfflineClient,
     OnlineClient,
 };
 
@@ -76,21 +94,15 @@ where
     /// Returns the `UploadExec` containing the preprocessed data for the upload or
     /// execution.
     pub async fn done(self) -> Result<UploadExec<C, E, Signer>> {
-        let artifacts = self.extrinsic_opts.contract_artifacts()?;
-        let transcoder = artifacts.contract_transcoder()?;
-
-        let artifacts_path = artifacts.artifact_path().to_path_buf();
-        let code = artifacts.code.ok_or_else(|| {
-            anyhow::anyhow!(
-                "Contract code not found from artifact file {}",
-                artifacts_path.display()
-            )
-        })?;
+        let (code, transcoder) = self.code()?;
 
         let url = self.extrinsic_opts.url();
-        let rpc_cli = RpcClient::from_url(&url).await?;
+        let rpc_cli = self.extrinsic_opts.rpc_connection().connect(&url).await?;
         let client = OnlineClient::from_rpc_client(rpc_cli.clone()).await?;
         check_env_types(&client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        let transcoder =
+            with_ss58_prefix(transcoder, &client, self.extrinsic_opts.ss58_prefix());
         let rpc = LegacyRpcMethods::new(rpc_cli);
 
         Ok(UploadExec {
@@ -101,6 +113,40 @@ where
             transcoder,
         })
     }
+
+    /// Signs the code upload without a connection to the node.
+    ///
+    /// The upload is checked against the chain metadata of the given [`OfflineClient`].
+    ///
+    /// Returns the SCALE encoded signed extrinsic, which can be submitted later on with
+    /// [`crate::submit_signed_extrinsic`].
+    pub fn sign_offline(&self, client: &OfflineClient<C>) -> Result<Vec<u8>>
+    where
+        <C::ExtrinsicParams as ExtrinsicParams<C>>::Params:
+            From<<DefaultExtrinsicParams<C> as ExtrinsicParams<C>>::Params>,
+        E::Balance: EncodeAsType,
+    {
+        let (code, transcoder) = self.code()?;
+        check_env_types(client, &transcoder, self.extrinsic_opts.verbosity())?;
+        check_contracts_pallet(&client.metadata(), self.extrinsic_opts.pallet_name())?;
+        let call = upload_code_call(code, &self.extrinsic_opts);
+        Ok(sign_extrinsic_offline(client, &call, &self.extrinsic_opts)?)
+    }
+
+    /// Returns the code to upload along with the transcoder of the contract artifacts.
+    fn code(&self) -> Result<(WasmCode, ContractMessageTranscoder)> {
+        let artifacts = self.extrinsic_opts.contract_artifacts()?;
+        let transcoder = artifacts.contract_transcoder()?;
+
+        let artifacts_path = artifacts.artifact_path().to_path_buf();
+        let code = artifacts.code.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Contract code not found from artifact file {}",
+                artifacts_path.display()
+            )
+        })?;
+        Ok((code, transcoder))
+    }
 }
 
 pub struct UploadExec<C: Config, E: Environment, Signer: Clone> {
@@ -134,7 +180,33 @@ where
             storage_deposit_limit,
             determinism: Determinism::Enforced,
         };
-        state_call(&self.rpc, "ContractsApi_upload_code", call_request).await
+        state_call(
+            &self.rpc,
+            self.opts.rpc_retry_policy(),
+            "ContractsApi_upload_code",
+            call_request,
+        )
+        .await
+    }
+
+    /// Returns whether the code is already stored on-chain.
+    ///
+    /// Uploading code which already exists has no effect, so the submission can be
+    /// skipped in that case. This only reads the chain state.
+    pub async fn code_exists(&self) -> Result<bool> {
+        let best_block = get_best_block(&self.rpc).await?;
+        let pristine_code_address = dynamic(
+            self.opts.pallet_name(),
+            "PristineCode",
+            vec![Value::from_bytes(self.code.code_hash())],
+        );
+        let pristine_code = self
+            .client
+            .storage()
+            .at(best_block)
+            .fetch(&pristine_code_address)
+            .await?;
+        Ok(pristine_code.is_some())
     }
 
     /// Uploads contract code to the blockchain with specified options.
@@ -143,26 +215,54 @@ where
     /// blockchain, utilizing the provided options.
     /// The function handles the necessary interactions with the blockchain's runtime
     /// API to ensure the successful upload of the code.
-    pub async fn upload_code(&self) -> Result<UploadResult<C>, ErrorVariant> {
-        let storage_deposit_limit = self.opts.storage_deposit_limit();
-
-        let call = UploadCode::new(
-            self.code.clone(),
-            storage_deposit_limit,
-            Determinism::Enforced,
-        )
-        .build();
+    ///
+    /// The deposit of the upload is estimated by a dry run before the submission, it
+    /// is zero if the code already existed on-chain.
+    pub async fn upload_code(&self) -> Result<UploadResult<C, E>, ErrorVariant> {
+        let code_hash = C::Hash::decode(&mut &self.code.code_hash()[..])
+            .map_err(anyhow::Error::from)?;
+        let deposit = self
+            .upload_code_rpc()
+            .await?
+            .ok()
+            .map(|result| result.deposit);
+        let call = self.upload_code_call();
 
-        let events =
-            submit_extrinsic(&self.client, &self.rpc, &call, self.opts.signer()).await?;
+        let events = submit_extrinsic(&self.client, &self.rpc, &call, &self.opts).await?;
 
-        let code_stored = events.find_first::<CodeStored<C::Hash>>()?;
+        let code_stored = find_first_contracts_event::<CodeStored<C::Hash>, C>(
+            &events,
+            &self.client.metadata(),
+            self.opts.pallet_name(),
+        )?;
         Ok(UploadResult {
+            code_hash,
             code_stored,
+            deposit,
             events,
         })
     }
 
+    /// Submits the code upload via the given multisig, with the signer as one of its
+    /// signatories.
+    ///
+    /// The code is uploaded from the multisig account with the final approval, see
+    /// [`crate::Multisig`].
+    pub async fn upload_code_multisig(
+        &self,
+        multisig: &Multisig<C::AccountId>,
+    ) -> Result<MultisigResult<C>, ErrorVariant>
+    where
+        C::AccountId: Decode + PartialEq + EncodeAsType,
+    {
+        let call = self.upload_code_call();
+        submit_multisig(&self.client, &self.rpc, &call, multisig, &self.opts).await
+    }
+
+    fn upload_code_call(&self) -> tx::Payload<UploadCode<E::Balance>> {
+        upload_code_call(self.code.clone(), &self.opts)
+    }
+
     /// Returns the extrinsic options.
     pub fn opts(&self) -> &ExtrinsicOpts<C, E, Signer> {
         &self.opts
@@ -184,6 +284,21 @@ where
     }
 }
 
+/// Builds the extrinsic payload uploading the given code.
+fn upload_code_call<C, E, Signer>(
+    code: WasmCode,
+    opts: &ExtrinsicOpts<C, E, Signer>,
+) -> tx::Payload<UploadCode<E::Balance>>
+where
+    C: Config,
+    E: Environment,
+    E::Balance: EncodeAsType,
+    Signer: tx::Signer<C> + Clone,
+{
+    UploadCode::new(code, opts.storage_deposit_limit(), Determinism::Enforced)
+        .build(opts.pallet_name())
+}
+
 /// A struct that encodes RPC parameters required for a call to upload a new code.
 #[derive(Encode)]
 struct CodeUploadRequest<AccountId, Balance> {
@@ -194,11 +309,89 @@ struct CodeUploadRequest<AccountId, Balance> {
 }
 
 /// A struct representing the result of an upload command execution.
-pub struct UploadResult<C: Config> {
+pub struct UploadResult<C: Config, E: Environment> {
+    /// The hash of the uploaded code.
+    pub code_hash: C::Hash,
+    /// Only raised if the code has not already been uploaded.
     pub code_stored: Option<CodeStored<C::Hash>>,
+    /// The deposit estimated by a dry run of the upload, if it succeeded.
+    pub deposit: Option<E::Balance>,
     pub events: ExtrinsicEvents<C>,
 }
 
+impl<C: Config, E: Environment> UploadResult<C, E> {
+    /// Returns whether the code was newly uploaded or already present on-chain.
+    pub fn status(&self) -> UploadStatus {
+        if self.code_stored.is_some() {
+            UploadStatus::Uploaded
+        } else {
+            UploadStatus::AlreadyExists
+        }
+    }
+
+    /// Returns the serializable outcome of the upload, without the events.
+    pub fn outcome(&self) -> UploadOutcome<C::Hash, E::Balance> {
+        match self.status() {
+            UploadStatus::Uploaded => {
+                UploadOutcome {
+                    status: UploadStatus::Uploaded,
+                    code_hash: self.code_hash,
+                    deposit: self.deposit,
+                }
+            }
+            UploadStatus::AlreadyExists => UploadOutcome::already_exists(self.code_hash),
+        }
+    }
+
+    /// Returns the outcome of the upload in json format.
+    pub fn to_json(&self) -> Result<String>
+    where
+        C::Hash: Serialize,
+        E::Balance: Serialize,
+    {
+        self.outcome().to_json()
+    }
+}
+
+/// Whether the uploaded code was stored by the upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadStatus {
+    /// The code was stored on-chain by the upload.
+    Uploaded,
+    /// The code was already present on-chain, nothing was stored.
+    AlreadyExists,
+}
+
+/// The outcome of an upload, see [`UploadResult::outcome`].
+#[derive(Debug, Serialize)]
+pub struct UploadOutcome<Hash, Balance> {
+    pub status: UploadStatus,
+    pub code_hash: Hash,
+    /// The deposit reserved for storing the code, zero if it already existed.
+    ///
+    /// Not present if the deposit of a new upload could not be estimated.
+    pub deposit: Option<Balance>,
+}
+
+impl<Hash, Balance: From<u8>> UploadOutcome<Hash, Balance> {
+    /// The outcome of an upload of code which is already present on-chain.
+    pub fn already_exists(code_hash: Hash) -> Self {
+        Self {
+            status: UploadStatus::AlreadyExists,
+            code_hash,
+            deposit: Some(0u8.into()),
+        }
+    }
+}
+
+impl<Hash: Serialize, Balance: Serialize> UploadOutcome<Hash, Balance> {
+    /// Returns a result in json format
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
 /// Copied from `pallet-contracts` to additionally implement `scale_encode::EncodeAsType`.
 #[allow(dead_code)]
 #[derive(Debug, Encode, EncodeAsType)]
@@ -220,3 +413,43 @@ pub(crate) enum Determinism {
     /// **Never** use this mode for on-chain execution.
     Relaxed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::H256;
+
+    #[test]
+    fn upload_outcome_to_json_distinguishes_already_existing_code() {
+        let uploaded = UploadOutcome {
+            status: UploadStatus::Uploaded,
+            code_hash: H256::repeat_byte(0x11),
+            deposit: Some(1_000u128),
+        };
+        let already_exists =
+            UploadOutcome::<_, u128>::already_exists(H256::repeat_byte(0x11));
+
+        let uploaded: serde_json::Value =
+            serde_json::from_str(&uploaded.to_json().unwrap()).unwrap();
+        let already_exists: serde_json::Value =
+            serde_json::from_str(&already_exists.to_json().unwrap()).unwrap();
+
+        let code_hash = format!("0x{}", "11".repeat(32));
+        assert_eq!(
+            uploaded,
+            serde_json::json!({
+                "status": "uploaded",
+                "code_hash": code_hash,
+                "deposit": 1000,
+            })
+        );
+        assert_eq!(
+            already_exists,
+            serde_json::json!({
+                "status": "already_exists",
+                "code_hash": code_hash,
+                "deposit": 0,
+            })
+        );
+    }
+}
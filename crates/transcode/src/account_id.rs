@@ -99,9 +99,30 @@ impl AccountId32 {
         // substrate prefix (since we have no way to otherwise pick one). It
         // doesn't really matter, since when it's deserialized back in
         // system_accountNextIndex, we ignore this (so long as it's valid).
-        const SUBSTRATE_SS58_PREFIX: u8 = 42;
-        // prefix <= 63 just take up one byte at the start:
-        let mut v = vec![SUBSTRATE_SS58_PREFIX];
+        const SUBSTRATE_SS58_PREFIX: u16 = 42;
+        self.to_ss58check_with_prefix(SUBSTRATE_SS58_PREFIX)
+    }
+
+    // Return the ss58-check string for this key using the given network prefix. Adapted
+    // from `sp_core::crypto`.
+    pub fn to_ss58check_with_prefix(&self, prefix: u16) -> String {
+        // We mask out the upper two bits of the ident - SS58 Prefix currently only
+        // supports 14-bits
+        let ident: u16 = prefix & 0b0011_1111_1111_1111;
+        let mut v = match ident {
+            // prefix <= 63 just take up one byte at the start:
+            0..=63 => vec![ident as u8],
+            // upper six bits of the lower byte(!)
+            64..=16_383 => {
+                let first = ((ident & 0b0000_0000_1111_1100) as u8) >> 2;
+                // lower two bits of the lower byte in the high pos,
+                // lower bits of the upper byte in the low pos
+                let second =
+                    ((ident >> 8) as u8) | ((ident & 0b0000_0000_0000_0011) as u8) << 6;
+                vec![first | 0b01000000, second]
+            }
+            _ => unreachable!("masked out the upper two bits; qed"),
+        };
         // then push the account ID bytes.
         v.extend(self.0);
         // then push a 2 byte checksum of what we have so far.
@@ -243,4 +264,27 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn ss58_with_custom_prefix_is_compatible_with_substrate_impl() {
+        use sp_core::crypto::Ss58AddressFormat;
+
+        // Single byte (Polkadot, Kusama) and two byte (Astar) prefixes.
+        for prefix in [0u16, 2, 5, 4006] {
+            let substrate_account = AccountKeyring::Alice.to_account_id();
+            let local_account = AccountId32(substrate_account.clone().into());
+
+            let substrate_ss58 = substrate_account
+                .to_ss58check_with_version(Ss58AddressFormat::custom(prefix));
+            assert_eq!(
+                substrate_ss58,
+                local_account.to_ss58check_with_prefix(prefix)
+            );
+
+            assert_eq!(
+                AccountId32::from_ss58check(&substrate_ss58).unwrap(),
+                local_account
+            );
+        }
+    }
 }
@@ -0,0 +1,138 @@
+// Copyright 2018-2020 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SCALE encoding of `bitvec::BitVec`, as described by a [`TypeDefBitSequence`].
+//!
+//! A bit sequence is encoded as the compact encoded number of bits, followed by the
+//! little endian encoded store elements (`u8`, `u16`, `u32` or `u64`) holding the bits.
+//! The bit order type (`Lsb0` or `Msb0`) determines whether the first bit of an element
+//! is its least or most significant bit.
+
+use anyhow::Result;
+use scale::{
+    Compact,
+    Decode,
+    Encode,
+    Output,
+};
+use scale_info::{
+    form::PortableForm,
+    PortableRegistry,
+    TypeDef,
+    TypeDefBitSequence,
+    TypeDefPrimitive,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitOrder {
+    Lsb0,
+    Msb0,
+}
+
+/// The layout of a bit sequence type.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSequenceFormat {
+    store_bits: usize,
+    order: BitOrder,
+}
+
+impl BitSequenceFormat {
+    /// Resolves the store and bit order types of the bit sequence.
+    pub fn from_type_def(
+        registry: &PortableRegistry,
+        bit_sequence: &TypeDefBitSequence<PortableForm>,
+    ) -> Result<Self> {
+        let store_id = bit_sequence.bit_store_type.id;
+        let store = registry.resolve(store_id).ok_or_else(|| {
+            anyhow::anyhow!("Failed to resolve bit store type with id '{}'", store_id)
+        })?;
+        let store_bits = match &store.type_def {
+            TypeDef::Primitive(TypeDefPrimitive::U8) => 8,
+            TypeDef::Primitive(TypeDefPrimitive::U16) => 16,
+            TypeDef::Primitive(TypeDefPrimitive::U32) => 32,
+            TypeDef::Primitive(TypeDefPrimitive::U64) => 64,
+            def => {
+                anyhow::bail!("Unsupported bit store type {:?}", def)
+            }
+        };
+
+        let order_id = bit_sequence.bit_order_type.id;
+        let order = registry.resolve(order_id).ok_or_else(|| {
+            anyhow::anyhow!("Failed to resolve bit order type with id '{}'", order_id)
+        })?;
+        let order = match order.path.segments.last().map(|s| s.as_str()) {
+            Some("Lsb0") => BitOrder::Lsb0,
+            Some("Msb0") => BitOrder::Msb0,
+            _ => anyhow::bail!("Unsupported bit order type {}", order.path),
+        };
+
+        Ok(Self { store_bits, order })
+    }
+
+    /// Decodes the bits of the sequence.
+    pub fn decode(&self, input: &mut &[u8]) -> Result<Vec<bool>> {
+        let len = <Compact<u32>>::decode(input)?.0 as usize;
+        let store_bytes = self.store_bits / 8;
+        // check the untrusted length against the input before allocating for it
+        let required_bytes = len.div_ceil(self.store_bits) * store_bytes;
+        if input.len() < required_bytes {
+            anyhow::bail!("Not enough data to decode a bit sequence of {} bits", len)
+        }
+        let mut bits = Vec::with_capacity(len);
+        while bits.len() < len {
+            let (element, rest) = input.split_at(store_bytes);
+            *input = rest;
+            let element = element
+                .iter()
+                .rev()
+                .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+            let remaining = (len - bits.len()).min(self.store_bits);
+            bits.extend((0..remaining).map(|i| element & self.mask(i) != 0));
+        }
+        Ok(bits)
+    }
+
+    /// Encodes the bits of the sequence.
+    pub fn encode<O: Output + ?Sized>(
+        &self,
+        bits: &[bool],
+        output: &mut O,
+    ) -> Result<()> {
+        let len: u32 = bits
+            .len()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Bit sequence exceeds u32::MAX bits"))?;
+        Compact(len).encode_to(output);
+        let store_bytes = self.store_bits / 8;
+        for chunk in bits.chunks(self.store_bits) {
+            let element = chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+                .fold(0u64, |acc, (i, _)| acc | self.mask(i));
+            output.write(&element.to_le_bytes()[..store_bytes]);
+        }
+        Ok(())
+    }
+
+    /// The mask of the bit at the given index within a store element.
+    fn mask(&self, index: usize) -> u64 {
+        match self.order {
+            BitOrder::Lsb0 => 1 << index,
+            BitOrder::Msb0 => 1 << (self.store_bits - 1 - index),
+        }
+    }
+}
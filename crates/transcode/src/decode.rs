@@ -15,6 +15,7 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
+    bit_sequence::BitSequenceFormat,
     env_types::EnvTypesTranscoder,
     scon::{
         Map,
@@ -131,8 +132,12 @@ impl<'a> Decoder<'a> {
             }
             TypeDef::Primitive(primitive) => self.decode_primitive(primitive, input),
             TypeDef::Compact(compact) => self.decode_compact(compact, input),
-            TypeDef::BitSequence(_) => {
-                Err(anyhow::anyhow!("bitvec decoding not yet supported"))
+            TypeDef::BitSequence(bit_sequence) => {
+                let bits = BitSequenceFormat::from_type_def(self.registry, bit_sequence)?
+                    .decode(input)?;
+                Ok(Value::Seq(
+                    bits.into_iter().map(Value::Bool).collect::<Vec<_>>().into(),
+                ))
             }
         }
         .context(format!("Error decoding type {}: {}", id, ty.path))
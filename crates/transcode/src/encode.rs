@@ -15,8 +15,15 @@
 // along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{
+    bit_sequence::BitSequenceFormat,
+    did_you_mean,
     env_types::EnvTypesTranscoder,
-    scon::Value,
+    scon::{
+        Map,
+        Seq,
+        Tuple,
+        Value,
+    },
     CompositeTypeFields,
 };
 use anyhow::Result;
@@ -34,6 +41,7 @@ use scale_info::{
     Field,
     PortableRegistry,
     TypeDef,
+    TypeDefBitSequence,
     TypeDefCompact,
     TypeDefPrimitive,
     TypeDefTuple,
@@ -52,6 +60,7 @@ use std::{
 pub struct Encoder<'a> {
     registry: &'a PortableRegistry,
     env_types: &'a EnvTypesTranscoder,
+    allow_default_fields: bool,
 }
 
 impl<'a> Encoder<'a> {
@@ -62,6 +71,16 @@ impl<'a> Encoder<'a> {
         Self {
             registry,
             env_types,
+            allow_default_fields: false,
+        }
+    }
+
+    /// Allows omitting fields of structs, which are then encoded with the default value
+    /// of their type.
+    pub fn allow_default_fields(self, allow_default_fields: bool) -> Self {
+        Self {
+            allow_default_fields,
+            ..self
         }
     }
 
@@ -98,8 +117,8 @@ impl<'a> Encoder<'a> {
                     self.encode_primitive(primitive, value, output)
                 }
                 TypeDef::Compact(compact) => self.encode_compact(compact, value, output),
-                TypeDef::BitSequence(_) => {
-                    Err(anyhow::anyhow!("bitvec encoding not yet supported"))
+                TypeDef::BitSequence(bit_sequence) => {
+                    self.encode_bit_sequence(bit_sequence, value, output)
                 }
             }?;
         }
@@ -125,11 +144,33 @@ impl<'a> Encoder<'a> {
                     }
                     CompositeTypeFields::NoFields => Ok(()),
                     CompositeTypeFields::Named(named_fields) => {
+                        let field_names: Vec<_> =
+                            named_fields.iter().map(|field| field.name()).collect();
+                        check_unknown_fields(map, &field_names)?;
                         for named_field in named_fields {
                             let field_name = named_field.name();
-                            let value = map.get_by_str(field_name).ok_or_else(|| {
-                                anyhow::anyhow!("Missing a field named `{}`", field_name)
-                            })?;
+                            let default;
+                            let value = match map.get_by_str(field_name) {
+                                Some(value) => value,
+                                None if self.allow_default_fields => {
+                                    default = self
+                                        .default_value(named_field.field().ty.id)
+                                        .map_err(|e| {
+                                            anyhow::anyhow!(
+                                                "Missing a field named `{}`: {}",
+                                                field_name,
+                                                e
+                                            )
+                                        })?;
+                                    &default
+                                }
+                                None => {
+                                    anyhow::bail!(
+                                        "Missing a field named `{}`",
+                                        field_name
+                                    )
+                                }
+                            };
                             self.encode(named_field.field().ty.id, value, output)
                                 .map_err(|e| {
                                     anyhow::anyhow!(
@@ -170,6 +211,96 @@ impl<'a> Encoder<'a> {
         }
     }
 
+    /// Returns the default value of the given type, for a field omitted from a struct.
+    ///
+    /// Only types with a zero value have a default: numbers, booleans, strings,
+    /// sequences, `Option`s and structs, tuples and arrays of those.
+    fn default_value(&self, type_id: u32) -> Result<Value> {
+        let ty = self.registry.resolve(type_id).ok_or_else(|| {
+            anyhow::anyhow!("Failed to resolve type with id '{:?}'", type_id)
+        })?;
+        let no_default = || {
+            anyhow::anyhow!(
+                "the type `{}` has no default value",
+                ty.path
+                    .segments
+                    .last()
+                    .map(String::as_str)
+                    .unwrap_or("unknown")
+            )
+        };
+        if self.env_types.has_encoder(type_id) {
+            return Err(no_default())
+        }
+        let value = match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                let ident = ty.path.segments.last().map(String::as_str);
+                match CompositeTypeFields::from_fields(&composite.fields)? {
+                    CompositeTypeFields::Named(fields) => {
+                        let map = fields
+                            .iter()
+                            .map(|field| {
+                                Ok((
+                                    Value::String(field.name().to_string()),
+                                    self.default_value(field.field().ty.id)?,
+                                ))
+                            })
+                            .collect::<Result<_>>()?;
+                        Value::Map(Map::new(ident, map))
+                    }
+                    CompositeTypeFields::Unnamed(fields) => {
+                        let values = fields
+                            .iter()
+                            .map(|field| self.default_value(field.ty.id))
+                            .collect::<Result<_>>()?;
+                        Value::Tuple(Tuple::new(ident, values))
+                    }
+                    CompositeTypeFields::NoFields => {
+                        Value::Tuple(Tuple::new(ident, Vec::new()))
+                    }
+                }
+            }
+            TypeDef::Variant(variant) if is_option(variant) => {
+                Value::Tuple(Tuple::new(Some("None"), Vec::new()))
+            }
+            TypeDef::Array(array) => {
+                let elem = self.default_value(array.type_param.id)?;
+                Value::Seq(Seq::new(vec![elem; array.len as usize]))
+            }
+            TypeDef::Tuple(tuple) => {
+                let values = tuple
+                    .fields
+                    .iter()
+                    .map(|field| self.default_value(field.id))
+                    .collect::<Result<_>>()?;
+                Value::Tuple(Tuple::new(None, values))
+            }
+            TypeDef::Sequence(_) => Value::Seq(Seq::new(Vec::new())),
+            TypeDef::Compact(_) => Value::UInt(0),
+            TypeDef::Primitive(primitive) => {
+                match primitive {
+                    TypeDefPrimitive::Bool => Value::Bool(false),
+                    TypeDefPrimitive::Str => Value::String(String::new()),
+                    TypeDefPrimitive::U8
+                    | TypeDefPrimitive::U16
+                    | TypeDefPrimitive::U32
+                    | TypeDefPrimitive::U64
+                    | TypeDefPrimitive::U128 => Value::UInt(0),
+                    TypeDefPrimitive::I8
+                    | TypeDefPrimitive::I16
+                    | TypeDefPrimitive::I32
+                    | TypeDefPrimitive::I64
+                    | TypeDefPrimitive::I128 => Value::Int(0),
+                    _ => {
+                        anyhow::bail!("the type `{:?}` has no default value", primitive)
+                    }
+                }
+            }
+            TypeDef::Variant(_) | TypeDef::BitSequence(_) => return Err(no_default()),
+        };
+        Ok(value)
+    }
+
     fn encode_tuple<O: Output + Debug>(
         &self,
         tuple: &TypeDefTuple<PortableForm>,
@@ -202,6 +333,22 @@ impl<'a> Encoder<'a> {
         value: &Value,
         output: &mut O,
     ) -> Result<()> {
+        if let Some((discriminant, fields)) = variant_discriminant(value) {
+            // a plain number is much more likely meant as the value of `Some`
+            if fields.is_none() && is_option(variant_def) {
+                anyhow::bail!(
+                    "Expected `None` or `Some(..)` for an option, found '{}'",
+                    value
+                )
+            }
+            return self.encode_variant_by_discriminant(
+                variant_def,
+                discriminant,
+                fields,
+                output,
+            )
+        }
+
         let variant_ident = match value {
             Value::Map(map) => {
                 map.ident().ok_or_else(|| {
@@ -215,6 +362,12 @@ impl<'a> Encoder<'a> {
             }
             v => Err(anyhow::anyhow!("Invalid enum variant value '{:?}'", v)),
         }?;
+        // `null` is accepted as an alias for `None`, for users used to JSON.
+        let variant_ident = if variant_ident == "null" && is_option(variant_def) {
+            "None"
+        } else {
+            variant_ident.as_str()
+        };
 
         let (index, variant) = variant_def
             .variants
@@ -230,6 +383,47 @@ impl<'a> Encoder<'a> {
         self.encode_composite(&variant.fields, value, output)
     }
 
+    /// Encodes the variant whose index equals the given discriminant, for tooling which
+    /// does not know the variant names.
+    fn encode_variant_by_discriminant<O: Output + Debug>(
+        &self,
+        variant_def: &TypeDefVariant<PortableForm>,
+        discriminant: u128,
+        fields: Option<&Value>,
+        output: &mut O,
+    ) -> Result<()> {
+        let variant = variant_def
+            .variants
+            .iter()
+            .find(|v| u128::from(v.index) == discriminant)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No variant with discriminant {} found, expected one of: {}",
+                    discriminant,
+                    variant_def
+                        .variants
+                        .iter()
+                        .map(|v| format!("{} ({})", v.index, v.name))
+                        .join(", ")
+                )
+            })?;
+        output.push_byte(variant.index);
+
+        match fields {
+            Some(fields) => self.encode_composite(&variant.fields, fields, output),
+            None if variant.fields.is_empty() => Ok(()),
+            None => {
+                Err(anyhow::anyhow!(
+                    "Variant '{}' with discriminant {} has fields, pass them in a tuple \
+                    following the discriminant, e.g. `({}, (..))`",
+                    variant.name,
+                    discriminant,
+                    discriminant
+                ))
+            }
+        }
+    }
+
     fn encode_seq<O: Output + Debug>(
         &self,
         ty: &<PortableForm as Form>::Type,
@@ -261,6 +455,37 @@ impl<'a> Encoder<'a> {
         Ok(())
     }
 
+    fn encode_bit_sequence<O: Output + Debug>(
+        &self,
+        bit_sequence: &TypeDefBitSequence<PortableForm>,
+        value: &Value,
+        output: &mut O,
+    ) -> Result<()> {
+        let bits = match value {
+            Value::Seq(seq) => {
+                seq.elems()
+                    .iter()
+                    .map(|elem| {
+                        match elem {
+                            Value::Bool(bit) => Ok(*bit),
+                            v => {
+                                Err(anyhow::anyhow!("Expected a bool value, found {}", v))
+                            }
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            v => {
+                return Err(anyhow::anyhow!(
+                    "Expected a Seq of bools for a bit sequence, found {:?}",
+                    v
+                ))
+            }
+        };
+        BitSequenceFormat::from_type_def(self.registry, bit_sequence)?
+            .encode(&bits, output)
+    }
+
     fn encode_primitive<O: Output + Debug>(
         &self,
         primitive: &TypeDefPrimitive,
@@ -408,6 +633,63 @@ impl<'a> Encoder<'a> {
     }
 }
 
+/// Returns the discriminant of an enum variant given by number instead of by name,
+/// either as a plain `n` or as `(n, fields)` with its fields in a following tuple or map.
+///
+/// Options only accept the `(n, fields)` form, see [`is_option`].
+fn variant_discriminant(value: &Value) -> Option<(u128, Option<&Value>)> {
+    match value {
+        Value::UInt(discriminant) => Some((*discriminant, None)),
+        Value::Tuple(tuple) if tuple.ident().is_none() => {
+            match tuple.values().collect::<Vec<_>>()[..] {
+                [Value::UInt(discriminant)] => Some((*discriminant, None)),
+                [Value::UInt(discriminant), fields]
+                    if matches!(fields, Value::Tuple(_) | Value::Map(_)) =>
+                {
+                    Some((*discriminant, Some(fields)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` if the variant type has the shape of an `Option`.
+fn is_option(variant_def: &TypeDefVariant<PortableForm>) -> bool {
+    match &variant_def.variants[..] {
+        [none, some] => {
+            none.name == "None"
+                && none.fields.is_empty()
+                && some.name == "Some"
+                && some.fields.len() == 1
+        }
+        _ => false,
+    }
+}
+
+/// Fails for the first key of the map which is not one of the field names of the struct,
+/// suggesting the most similar field name.
+fn check_unknown_fields(map: &Map, field_names: &[&str]) -> Result<()> {
+    let keys = map.iter().filter_map(|(key, _)| {
+        match key {
+            Value::String(key) => Some(key),
+            _ => None,
+        }
+    });
+    for key in keys {
+        if field_names.contains(&key.as_str()) {
+            continue
+        }
+        let help_txt = did_you_mean(key, field_names)
+            .pop()
+            .map(|suggestion| format!("did you mean '{suggestion}'?"))
+            .unwrap_or_else(|| format!("expected one of: {}", field_names.join(", ")));
+        anyhow::bail!("Unknown field '{key}', {help_txt}")
+    }
+    Ok(())
+}
+
 fn uint_from_value<T>(value: &Value, expected: &str) -> Result<T>
 where
     T: TryFrom<u128> + TryFromHex + FromStr,
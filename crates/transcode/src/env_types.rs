@@ -57,6 +57,22 @@ impl EnvTypesTranscoder {
         Self { encoders, decoders }
     }
 
+    /// Registers a custom decoder for the given type id, replacing any existing one.
+    pub fn register_decoder(
+        &mut self,
+        type_id: u32,
+        decoder: Box<dyn CustomTypeDecoder>,
+    ) {
+        if self.decoders.insert(type_id, decoder).is_some() {
+            tracing::debug!("Replaced custom decoder for type `{:?}`", type_id);
+        }
+    }
+
+    /// Returns `true` if the given type id is for a type with custom encoding.
+    pub fn has_encoder(&self, type_id: u32) -> bool {
+        self.encoders.contains_key(&type_id)
+    }
+
     /// If the given type id is for a type with custom encoding, encodes the given value
     /// with the custom encoder and returns `true`. Otherwise returns `false`.
     ///
@@ -127,6 +143,13 @@ impl From<&Path<PortableForm>> for PathKey {
     }
 }
 
+/// Parses a `::` separated type path, e.g. `ink_primitives::types::AccountId`.
+impl From<&str> for PathKey {
+    fn from(path: &str) -> Self {
+        PathKey(path.split("::").map(ToOwned::to_owned).collect())
+    }
+}
+
 pub type TypesByPath = HashMap<PathKey, u32>;
 
 /// Implement this trait to define custom encoding for a type in a `scale-info` type
@@ -145,10 +168,24 @@ pub trait CustomTypeDecoder: Send + Sync {
 ///
 /// Enables an `AccountId` to be input/ouput as an SS58 Encoded literal e.g.
 /// 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY
-#[derive(Clone)]
-pub struct AccountId;
+///
+/// Decoded account ids are rendered with the default Substrate SS58 prefix, unless a
+/// chain specific prefix is given via [`Ss58AccountId::with_ss58_prefix`].
+#[derive(Clone, Default)]
+pub struct Ss58AccountId {
+    ss58_prefix: Option<u16>,
+}
 
-impl CustomTypeEncoder for AccountId {
+impl Ss58AccountId {
+    /// Renders decoded account ids with the given SS58 prefix.
+    pub fn with_ss58_prefix(ss58_prefix: u16) -> Self {
+        Self {
+            ss58_prefix: Some(ss58_prefix),
+        }
+    }
+}
+
+impl CustomTypeEncoder for Ss58AccountId {
     fn encode_value(&self, value: &Value) -> Result<Vec<u8>> {
         let account_id = match value {
             Value::Literal(literal) => {
@@ -187,10 +224,34 @@ impl CustomTypeEncoder for AccountId {
     }
 }
 
-impl CustomTypeDecoder for AccountId {
+impl CustomTypeDecoder for Ss58AccountId {
     fn decode_value(&self, input: &mut &[u8]) -> Result<Value> {
         let account_id = AccountId32::decode(input)?;
-        Ok(Value::Literal(account_id.to_ss58check()))
+        let ss58 = match self.ss58_prefix {
+            Some(prefix) => account_id.to_ss58check_with_prefix(prefix),
+            None => account_id.to_ss58check(),
+        };
+        Ok(Value::Literal(ss58))
+    }
+}
+
+/// Custom encoding/decoding for the Substrate `AccountId` type, always rendering
+/// decoded account ids with the default Substrate SS58 prefix.
+#[deprecated(note = "use `Ss58AccountId` instead")]
+#[derive(Clone)]
+pub struct AccountId;
+
+#[allow(deprecated)]
+impl CustomTypeEncoder for AccountId {
+    fn encode_value(&self, value: &Value) -> Result<Vec<u8>> {
+        Ss58AccountId::default().encode_value(value)
+    }
+}
+
+#[allow(deprecated)]
+impl CustomTypeDecoder for AccountId {
+    fn decode_value(&self, input: &mut &[u8]) -> Result<Value> {
+        Ss58AccountId::default().decode_value(input)
     }
 }
 
@@ -0,0 +1,282 @@
+// Copyright 2018-2020 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Conversion of JSON values into [`Value`]s, guided by the type they are encoded as.
+//!
+//! JSON has no notion of enum variants, tuples or integers wider than 64 bits, so the
+//! conversion follows these conventions:
+//!
+//! - Structs are objects, tuples and tuple structs are arrays.
+//! - Enum variants without fields are strings, e.g. `"None"`. Variants with fields are
+//!   objects with the variant name as the single key, e.g. `{ "Some": 42 }`. `null` is
+//!   accepted for `None`.
+//! - Integers can be given as numbers, or as (hex) strings if they exceed 64 bits.
+//! - Byte sequences and arrays can be given as hex strings, e.g. `"0xdeadbeef"`.
+//! - Bit sequences are arrays of bools.
+
+use super::{
+    did_you_mean,
+    scon::{
+        Hex,
+        Map,
+        Tuple,
+        Value,
+    },
+    CompositeTypeFields,
+};
+use anyhow::{
+    Context,
+    Result,
+};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use scale_info::{
+    form::PortableForm,
+    Field,
+    PortableRegistry,
+    TypeDef,
+    TypeDefPrimitive,
+    TypeDefVariant,
+};
+use serde_json::Value as JsonValue;
+use std::str::FromStr;
+
+/// Converts the JSON value into a [`Value`] which can be encoded as the given type.
+pub fn json_to_value(
+    registry: &PortableRegistry,
+    type_id: u32,
+    json: &JsonValue,
+) -> Result<Value> {
+    let ty = registry.resolve(type_id).ok_or_else(|| {
+        anyhow::anyhow!("Failed to resolve type with id '{:?}'", type_id)
+    })?;
+
+    match &ty.type_def {
+        TypeDef::Composite(composite) => {
+            composite_to_value(registry, &composite.fields, None, json)
+        }
+        TypeDef::Variant(variant) => variant_to_value(registry, variant, json),
+        TypeDef::Array(array) => seq_to_value(registry, array.type_param.id, json),
+        TypeDef::Sequence(sequence) => {
+            seq_to_value(registry, sequence.type_param.id, json)
+        }
+        TypeDef::Tuple(tuple) => {
+            let values = match (tuple.fields.iter().exactly_one(), json) {
+                (Ok(field), json) => return json_to_value(registry, field.id, json),
+                (Err(_), JsonValue::Array(elems))
+                    if elems.len() == tuple.fields.len() =>
+                {
+                    tuple
+                        .fields
+                        .iter()
+                        .zip(elems)
+                        .map(|(field, elem)| json_to_value(registry, field.id, elem))
+                        .collect::<Result<Vec<_>>>()?
+                }
+                _ => {
+                    anyhow::bail!(
+                        "Expected an array of {} elements for a tuple, found {}",
+                        tuple.fields.len(),
+                        json
+                    )
+                }
+            };
+            Ok(Value::Tuple(Tuple::new(None, values)))
+        }
+        TypeDef::Primitive(primitive) => primitive_to_value(primitive, json),
+        TypeDef::Compact(compact) => json_to_value(registry, compact.type_param.id, json),
+        TypeDef::BitSequence(_) => {
+            let JsonValue::Array(bits) = json else {
+                anyhow::bail!(
+                    "Expected an array of bools for a bit sequence, found {}",
+                    json
+                )
+            };
+            let bits = bits
+                .iter()
+                .map(|bit| {
+                    bit.as_bool().map(Value::Bool).ok_or_else(|| {
+                        anyhow::anyhow!("Expected a bool value, found {}", bit)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::Seq(bits.into()))
+        }
+    }
+}
+
+fn composite_to_value(
+    registry: &PortableRegistry,
+    fields: &[Field<PortableForm>],
+    ident: Option<&str>,
+    json: &JsonValue,
+) -> Result<Value> {
+    match CompositeTypeFields::from_fields(fields)? {
+        CompositeTypeFields::NoFields => Ok(Value::Tuple(Tuple::new(ident, Vec::new()))),
+        CompositeTypeFields::Named(named_fields) => {
+            let JsonValue::Object(object) = json else {
+                anyhow::bail!("Expected an object for a struct, found {}", json)
+            };
+            let field_names: Vec<_> = named_fields.iter().map(|f| f.name()).collect();
+            if let Some(unknown) = object
+                .keys()
+                .find(|key| !field_names.contains(&key.as_str()))
+            {
+                let help_txt = did_you_mean(unknown, &field_names)
+                    .pop()
+                    .map(|suggestion| format!("Did you mean '{}'?", suggestion))
+                    .unwrap_or_else(|| {
+                        format!("Should be one of: {}", field_names.join(", "))
+                    });
+                anyhow::bail!("Unknown field `{unknown}`.\n{help_txt}")
+            }
+            let map = named_fields
+                .iter()
+                .map(|field| {
+                    let name = field.name();
+                    let json = object.get(name).ok_or_else(|| {
+                        anyhow::anyhow!("Missing a field named `{}`", name)
+                    })?;
+                    let value = json_to_value(registry, field.field().ty.id, json)
+                        .with_context(|| format!("Error converting field `{}`", name))?;
+                    Ok((Value::String(name.to_string()), value))
+                })
+                .collect::<Result<IndexMap<_, _>>>()?;
+            Ok(Value::Map(Map::new(ident, map)))
+        }
+        CompositeTypeFields::Unnamed(fields) => {
+            let values = match (fields.iter().exactly_one(), json) {
+                (Ok(field), json) => vec![json_to_value(registry, field.ty.id, json)?],
+                (Err(_), JsonValue::Array(elems)) if elems.len() == fields.len() => {
+                    fields
+                        .iter()
+                        .zip(elems)
+                        .map(|(field, elem)| json_to_value(registry, field.ty.id, elem))
+                        .collect::<Result<Vec<_>>>()?
+                }
+                _ => {
+                    anyhow::bail!(
+                        "Expected an array of {} elements for a tuple struct, found {}",
+                        fields.len(),
+                        json
+                    )
+                }
+            };
+            match (ident, values.as_slice()) {
+                // a newtype wrapping a scalar is encoded as its single field, which
+                // also lets custom encoders, e.g. for `AccountId`, handle the value
+                (None, [value]) if !matches!(value, Value::Map(_) | Value::Tuple(_)) => {
+                    Ok(value.clone())
+                }
+                _ => Ok(Value::Tuple(Tuple::new(ident, values))),
+            }
+        }
+    }
+}
+
+fn variant_to_value(
+    registry: &PortableRegistry,
+    variant_def: &TypeDefVariant<PortableForm>,
+    json: &JsonValue,
+) -> Result<Value> {
+    let (name, payload) = match json {
+        JsonValue::Null => ("None", &JsonValue::Null),
+        JsonValue::String(name) => (name.as_str(), &JsonValue::Null),
+        JsonValue::Object(object) if object.len() == 1 => {
+            let (name, payload) = object.iter().next().expect("object has one entry");
+            (name.as_str(), payload)
+        }
+        _ => {
+            anyhow::bail!(
+                "Expected a variant name or an object with the variant name as the \
+                single key for an enum, found {}",
+                json
+            )
+        }
+    };
+
+    let variant = variant_def
+        .variants
+        .iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| {
+            let variants: Vec<_> = variant_def
+                .variants
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect();
+            let help_txt = did_you_mean(name, &variants)
+                .pop()
+                .map(|suggestion| format!("Did you mean '{}'?", suggestion))
+                .unwrap_or_else(|| format!("Should be one of: {}", variants.join(", ")));
+            anyhow::anyhow!("No variant '{}' found.\n{}", name, help_txt)
+        })?;
+
+    composite_to_value(registry, &variant.fields, Some(name), payload)
+        .with_context(|| format!("Error converting variant `{}`", name))
+}
+
+fn seq_to_value(
+    registry: &PortableRegistry,
+    elem_type_id: u32,
+    json: &JsonValue,
+) -> Result<Value> {
+    match json {
+        JsonValue::Array(elems) => {
+            let elems = elems
+                .iter()
+                .map(|elem| json_to_value(registry, elem_type_id, elem))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::Seq(elems.into()))
+        }
+        // hex encoded bytes, or e.g. an SS58 encoded `AccountId` for custom encoders
+        JsonValue::String(s) => string_to_value(s),
+        _ => anyhow::bail!("Expected an array or a hex string, found {}", json),
+    }
+}
+
+fn primitive_to_value(primitive: &TypeDefPrimitive, json: &JsonValue) -> Result<Value> {
+    match (primitive, json) {
+        (TypeDefPrimitive::Bool, JsonValue::Bool(b)) => Ok(Value::Bool(*b)),
+        (TypeDefPrimitive::Str, JsonValue::String(s)) => Ok(Value::String(s.clone())),
+        (TypeDefPrimitive::Bool | TypeDefPrimitive::Str, _) => {
+            anyhow::bail!("Expected a {:?} value, found {}", primitive, json)
+        }
+        (_, JsonValue::Number(number)) => {
+            if let Some(uint) = number.as_u64() {
+                Ok(Value::UInt(uint.into()))
+            } else if let Some(int) = number.as_i64() {
+                Ok(Value::Int(int.into()))
+            } else {
+                anyhow::bail!(
+                    "Expected an integer, found {}. Integers exceeding 64 bits must be \
+                    given as strings",
+                    number
+                )
+            }
+        }
+        (_, JsonValue::String(s)) => string_to_value(s),
+        _ => anyhow::bail!("Expected an integer, found {}", json),
+    }
+}
+
+fn string_to_value(s: &str) -> Result<Value> {
+    if s.starts_with("0x") {
+        Ok(Value::Hex(Hex::from_str(s)?))
+    } else {
+        Ok(Value::String(s.to_string()))
+    }
+}
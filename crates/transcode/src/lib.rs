@@ -99,15 +99,19 @@
 //! ```
 
 mod account_id;
+mod bit_sequence;
 mod decode;
 mod encode;
 pub mod env_types;
+mod json;
+mod registry;
 mod scon;
 mod transcoder;
 mod util;
 
 pub use self::{
     account_id::AccountId32,
+    registry::RegistryDisplay,
     scon::{
         Hex,
         Map,
@@ -127,8 +131,11 @@ use anyhow::{
 pub use ink_metadata;
 use ink_metadata::{
     ConstructorSpec,
+    EventSpec,
     InkProject,
+    MessageParamSpec,
     MessageSpec,
+    Selector,
 };
 use itertools::Itertools;
 use scale::{
@@ -154,13 +161,14 @@ use std::{
 pub struct ContractMessageTranscoder {
     metadata: InkProject,
     transcoder: Transcoder,
+    ss58_prefix: Option<u16>,
 }
 
 /// Find strings from an iterable of `possible_values` similar to a given value `v`
 /// Returns a Vec of all possible values that exceed a similarity threshold
 /// sorted by ascending similarity, most similar comes last
 /// Extracted from https://github.com/clap-rs/clap/blob/v4.3.4/clap_builder/src/parser/features/suggestions.rs#L11-L26
-fn did_you_mean<T, I>(v: &str, possible_values: I) -> Vec<String>
+pub(crate) fn did_you_mean<T, I>(v: &str, possible_values: I) -> Vec<String>
 where
     T: AsRef<str>,
     I: IntoIterator<Item = T>,
@@ -175,15 +183,100 @@ where
 }
 
 impl ContractMessageTranscoder {
+    /// Creates a transcoder for the given contract metadata.
+    ///
+    /// Logs a warning if constructors or messages share a selector, use
+    /// [`Self::try_new`] to treat that as an error.
     pub fn new(metadata: InkProject) -> Self {
+        if let Err(err) = check_selectors(&metadata) {
+            tracing::warn!("{err}");
+        }
         let transcoder = TranscoderBuilder::new(metadata.registry())
-            .register_custom_type_transcoder::<<ink_env::DefaultEnvironment as ink_env::Environment>::AccountId, _>(env_types::AccountId)
+            .register_custom_type_transcoder::<<ink_env::DefaultEnvironment as ink_env::Environment>::AccountId, _>(env_types::Ss58AccountId::default())
             .register_custom_type_decoder::<<ink_env::DefaultEnvironment as ink_env::Environment>::Hash, _>(env_types::Hash)
             .done();
         Self {
             metadata,
             transcoder,
+            ss58_prefix: None,
+        }
+    }
+
+    /// Creates a transcoder for the given contract metadata, returning an error if
+    /// constructors or messages share a selector.
+    ///
+    /// Messages and constructors are decoded by their selector, so with duplicate
+    /// selectors the wrong one could be picked.
+    pub fn try_new(metadata: InkProject) -> Result<Self> {
+        check_selectors(&metadata)?;
+        Ok(Self::new(metadata))
+    }
+
+    /// Renders decoded `AccountId` values with the given SS58 prefix, e.g. the
+    /// `System::SS58Prefix` of the target chain.
+    ///
+    /// Without a prefix the default Substrate prefix is used.
+    pub fn with_ss58_prefix(self, ss58_prefix: u16) -> Self {
+        let mut this = self.register_custom_type_decoder(
+            env_types::PathKey::from_type::<
+                <ink_env::DefaultEnvironment as ink_env::Environment>::AccountId,
+            >(),
+            env_types::Ss58AccountId::with_ss58_prefix(ss58_prefix),
+        );
+        this.ss58_prefix = Some(ss58_prefix);
+        this
+    }
+
+    /// Allows omitting struct fields of the arguments given in SCON, which are then
+    /// encoded with the default value of their type.
+    ///
+    /// See [`Transcoder::set_allow_default_fields`].
+    pub fn with_default_fields(self, allow_default_fields: bool) -> Self {
+        let mut this = self;
+        this.transcoder
+            .set_allow_default_fields(allow_default_fields);
+        this
+    }
+
+    /// Registers a custom decoder for all types with the given path in the contract
+    /// metadata, e.g. `ink_primitives::types::AccountId`, replacing any existing one.
+    ///
+    /// This allows rendering types of a non-default `Environment`. It is the runtime
+    /// equivalent of [`TranscoderBuilder::register_custom_type_decoder`], which takes
+    /// the path from the `TypeInfo` of a Rust type instead. Only structs and enums
+    /// have a path; a `Balance` which is an alias of `u128` can not be customized.
+    pub fn with_custom_type_decoder<D>(self, type_path: &str, decoder: D) -> Self
+    where
+        D: env_types::CustomTypeDecoder + Clone + 'static,
+    {
+        self.register_custom_type_decoder(env_types::PathKey::from(type_path), decoder)
+    }
+
+    fn register_custom_type_decoder<D>(
+        self,
+        path_key: env_types::PathKey,
+        decoder: D,
+    ) -> Self
+    where
+        D: env_types::CustomTypeDecoder + Clone + 'static,
+    {
+        let mut this = self;
+        let type_ids: Vec<_> = this
+            .metadata
+            .registry()
+            .types
+            .iter()
+            .filter(|ty| env_types::PathKey::from(&ty.ty.path) == path_key)
+            .map(|ty| ty.id)
+            .collect();
+        if type_ids.is_empty() {
+            tracing::debug!("No matching type in registry for path {:?}.", path_key);
+        }
+        for type_id in type_ids {
+            this.transcoder
+                .register_custom_type_decoder(type_id, Box::new(decoder.clone()));
         }
+        this
     }
 
     /// Attempt to create a [`ContractMessageTranscoder`] from the metadata file at the
@@ -203,7 +296,7 @@ impl ContractMessageTranscoder {
             path.display()
         ))?;
 
-        Ok(Self::new(ink_metadata))
+        Self::try_new(ink_metadata)
     }
 
     pub fn encode<I, S>(&self, name: &str, args: I) -> Result<Vec<u8>>
@@ -211,14 +304,141 @@ impl ContractMessageTranscoder {
         I: IntoIterator<Item = S>,
         S: AsRef<str> + Debug,
     {
-        let (selector, spec_args) = match (
+        let (selector, spec_args) = self.find_spec(name)?;
+
+        let args: Vec<_> = args.into_iter().collect();
+        if spec_args.len() != args.len() {
+            anyhow::bail!(
+                "Invalid number of input arguments: expected {}, {} provided",
+                spec_args.len(),
+                args.len()
+            )
+        }
+
+        let mut encoded = selector.to_bytes().to_vec();
+        for (spec, arg) in spec_args.iter().zip(args) {
+            let value = scon::parse_value(arg.as_ref())?;
+            self.transcoder.encode(
+                self.metadata.registry(),
+                spec.ty().ty().id,
+                &value,
+                &mut encoded,
+            )?;
+        }
+        Ok(encoded)
+    }
+
+    /// Encodes a constructor or message call with the arguments given as JSON.
+    ///
+    /// The arguments are either an array in the order of the parameters, or an object
+    /// mapping the parameter names to their values. Structs are given as objects,
+    /// tuples as arrays, enum variants as `"Variant"` or `{ "Variant": <fields> }`,
+    /// and integers exceeding 64 bits as strings.
+    pub fn encode_from_json(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+    ) -> Result<Vec<u8>> {
+        let (selector, spec_args) = self.find_spec(name)?;
+
+        let args: Vec<_> = match args {
+            serde_json::Value::Array(args) => {
+                if spec_args.len() != args.len() {
+                    anyhow::bail!(
+                        "Invalid number of input arguments: expected {}, {} provided",
+                        spec_args.len(),
+                        args.len()
+                    )
+                }
+                args.iter().collect()
+            }
+            serde_json::Value::Object(args) => {
+                let labels: Vec<_> =
+                    spec_args.iter().map(|arg| arg.label().as_str()).collect();
+                if let Some(unknown) =
+                    args.keys().find(|key| !labels.contains(&key.as_str()))
+                {
+                    let help_txt = did_you_mean(unknown, &labels)
+                        .pop()
+                        .map(|suggestion| format!("Did you mean '{}'?", suggestion))
+                        .unwrap_or_else(|| {
+                            format!("Should be one of: {}", labels.join(", "))
+                        });
+                    anyhow::bail!(
+                        "No argument with the name '{unknown}' found.\n{help_txt}"
+                    )
+                }
+                labels
+                    .iter()
+                    .map(|label| {
+                        args.get(*label).ok_or_else(|| {
+                            anyhow::anyhow!("Missing argument '{}'", label)
+                        })
+                    })
+                    .collect::<Result<_>>()?
+            }
+            _ => {
+                anyhow::bail!(
+                    "Expected the arguments as a JSON array or object, found {}",
+                    args
+                )
+            }
+        };
+
+        let mut encoded = selector.to_bytes().to_vec();
+        for (spec, arg) in spec_args.iter().zip(args) {
+            let value =
+                json::json_to_value(self.metadata.registry(), spec.ty().ty().id, arg)
+                    .with_context(|| {
+                        format!("Invalid value for argument '{}'", spec.label())
+                    })?;
+            self.transcoder.encode(
+                self.metadata.registry(),
+                spec.ty().ty().id,
+                &value,
+                &mut encoded,
+            )?;
+        }
+        Ok(encoded)
+    }
+
+    /// Returns the parameters of the constructor or message with the given name.
+    pub fn params(&self, name: &str) -> Result<&[MessageParamSpec<PortableForm>]> {
+        self.find_spec(name).map(|(_, params)| params)
+    }
+
+    /// Encodes a single argument as the type of the given parameter, e.g. to validate
+    /// it before encoding the whole call.
+    pub fn encode_arg(
+        &self,
+        param: &MessageParamSpec<PortableForm>,
+        arg: &str,
+    ) -> Result<Vec<u8>> {
+        let value = scon::parse_single_value(arg)?;
+        let mut encoded = Vec::new();
+        self.transcoder.encode(
+            self.metadata.registry(),
+            param.ty().ty().id,
+            &value,
+            &mut encoded,
+        )?;
+        Ok(encoded)
+    }
+
+    /// Returns the selector and parameters of the constructor or message with the given
+    /// name.
+    fn find_spec(
+        &self,
+        name: &str,
+    ) -> Result<(&Selector, &[MessageParamSpec<PortableForm>])> {
+        match (
             self.find_constructor_spec(name),
             self.find_message_spec(name),
         ) {
-            (Some(c), None) => (c.selector(), c.args()),
-            (None, Some(m)) => (m.selector(), m.args()),
+            (Some(c), None) => Ok((c.selector(), c.args())),
+            (None, Some(m)) => Ok((m.selector(), m.args())),
             (Some(_), Some(_)) => {
-                return Err(anyhow::anyhow!(
+                Err(anyhow::anyhow!(
                 "Invalid metadata: both a constructor and message found with name '{}'",
                 name
             ))
@@ -234,32 +454,60 @@ impl ContractMessageTranscoder {
                         format!("Should be one of: {}", possible_values.iter().join(", "))
                     });
 
-                return Err(anyhow::anyhow!(
+                Err(anyhow::anyhow!(
                     "No constructor or message with the name '{name}' found.\n{help_txt}",
                 ))
             }
-        };
-
-        let args: Vec<_> = args.into_iter().collect();
-        if spec_args.len() != args.len() {
-            anyhow::bail!(
-                "Invalid number of input arguments: expected {}, {} provided",
-                spec_args.len(),
-                args.len()
-            )
         }
+    }
 
-        let mut encoded = selector.to_bytes().to_vec();
-        for (spec, arg) in spec_args.iter().zip(args) {
-            let value = scon::parse_value(arg.as_ref())?;
-            self.transcoder.encode(
-                self.metadata.registry(),
-                spec.ty().ty().id,
-                &value,
-                &mut encoded,
-            )?;
+    /// Resolves a type path of the metadata registry, e.g.
+    /// `ink_primitives::types::AccountId`, or the name of a primitive type, e.g. `u128`,
+    /// to the id of the type.
+    pub fn resolve_type_path(&self, path: &str) -> Result<u32> {
+        let types = &self.metadata.registry().types;
+        let type_path = |ty: &scale_info::PortableType| {
+            match &ty.ty.type_def {
+                scale_info::TypeDef::Primitive(primitive) => {
+                    format!("{primitive:?}").to_lowercase()
+                }
+                _ => ty.ty.path.segments.join("::"),
+            }
+        };
+        let matches: Vec<_> = types
+            .iter()
+            .filter(|ty| type_path(ty) == path)
+            .map(|ty| ty.id)
+            .collect();
+        match matches[..] {
+            [type_id] => Ok(type_id),
+            [] => {
+                let paths: Vec<_> = types
+                    .iter()
+                    .map(type_path)
+                    .filter(|path| !path.is_empty())
+                    .unique()
+                    .collect();
+                let help_txt = did_you_mean(path, &paths)
+                    .iter()
+                    .rev()
+                    .take(3)
+                    .map(|suggestion| format!("'{suggestion}'"))
+                    .join(", ");
+                if help_txt.is_empty() {
+                    anyhow::bail!("No type with the path '{path}' found")
+                }
+                anyhow::bail!(
+                    "No type with the path '{path}' found.\nDid you mean {help_txt}?"
+                )
+            }
+            _ => {
+                anyhow::bail!(
+                    "The type path '{path}' is ambiguous, it matches the type ids {}",
+                    matches.iter().join(", ")
+                )
+            }
         }
-        Ok(encoded)
     }
 
     pub fn decode(&self, type_id: u32, input: &mut &[u8]) -> Result<Value> {
@@ -267,10 +515,26 @@ impl ContractMessageTranscoder {
             .decode(self.metadata.registry(), type_id, input)
     }
 
+    /// Decodes the `data` as a value of the type with the given `path`, see
+    /// [`Self::resolve_type_path`]. Fails if not all of the `data` is decoded.
+    pub fn decode_type_path(&self, path: &str, data: &mut &[u8]) -> Result<Value> {
+        let type_id = self.resolve_type_path(path)?;
+        let total_len = data.len();
+        let value = self.decode(type_id, data)?;
+        Self::validate_length(data, total_len, path, &[])?;
+        Ok(value)
+    }
+
     pub fn metadata(&self) -> &InkProject {
         &self.metadata
     }
 
+    /// Returns the SS58 prefix decoded account ids are rendered with, if not the
+    /// default one.
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        self.ss58_prefix
+    }
+
     fn constructors(&self) -> impl Iterator<Item = &ConstructorSpec<PortableForm>> {
         self.metadata.spec().constructors().iter()
     }
@@ -299,6 +563,7 @@ impl ContractMessageTranscoder {
     where
         Hash: AsRef<[u8]>,
     {
+        let total_len = data.len();
         // data is an encoded `Vec<u8>` so is prepended with its length `Compact<u32>`,
         // which we ignore because the structure of the event data is known for
         // decoding.
@@ -316,11 +581,56 @@ impl ContractMessageTranscoder {
                 }
             })
             .ok_or_else(|| {
+                let has_anonymous_events = self
+                    .metadata
+                    .spec()
+                    .events()
+                    .iter()
+                    .any(|event| event.signature_topic().is_none());
+                let hint = if has_anonymous_events {
+                    "\nThe contract has anonymous events without a signature topic, \
+                    these can only be decoded by their index"
+                } else {
+                    ""
+                };
                 anyhow::anyhow!(
-                    "Event with signature topic {} not found in contract metadata",
-                    hex::encode(event_sig_topic)
+                    "Event with signature topic {} not found in contract metadata{}",
+                    hex::encode(event_sig_topic),
+                    hint
                 )
             })?;
+        self.decode_event(event_spec, data, total_len)
+    }
+
+    /// Decodes the event at the given index of the events in the contract metadata.
+    ///
+    /// Anonymous events have no signature topic to identify them, so they can only be
+    /// decoded if the caller knows which event was emitted.
+    pub fn decode_contract_event_by_index(
+        &self,
+        index: usize,
+        data: &mut &[u8],
+    ) -> Result<Value> {
+        let total_len = data.len();
+        // data is an encoded `Vec<u8>`, see `decode_contract_event`
+        let _len = <Compact<u32>>::decode(data)?;
+        let events = self.metadata.spec().events();
+        let event_spec = events.get(index).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Event index {} out of range, the contract metadata contains {} events",
+                index,
+                events.len()
+            )
+        })?;
+        self.decode_event(event_spec, data, total_len)
+    }
+
+    fn decode_event(
+        &self,
+        event_spec: &EventSpec<PortableForm>,
+        data: &mut &[u8],
+        total_len: usize,
+    ) -> Result<Value> {
         tracing::debug!("Decoding contract event '{}'", event_spec.label());
 
         let mut args = Vec::new();
@@ -330,7 +640,7 @@ impl ContractMessageTranscoder {
             args.push((Value::String(name), value));
         }
 
-        Self::validate_length(data, event_spec.label(), &args)?;
+        Self::validate_length(data, total_len, event_spec.label(), &args)?;
 
         let name = event_spec.label().to_string();
         let map = Map::new(Some(&name), args.into_iter().collect());
@@ -339,6 +649,7 @@ impl ContractMessageTranscoder {
     }
 
     pub fn decode_contract_message(&self, data: &mut &[u8]) -> Result<Value> {
+        let total_len = data.len();
         let mut msg_selector = [0u8; 4];
         data.read(&mut msg_selector)?;
         let msg_spec = self
@@ -359,7 +670,7 @@ impl ContractMessageTranscoder {
             args.push((Value::String(name), value));
         }
 
-        Self::validate_length(data, msg_spec.label(), &args)?;
+        Self::validate_length(data, total_len, msg_spec.label(), &args)?;
 
         let name = msg_spec.label().to_string();
         let map = Map::new(Some(&name), args.into_iter().collect());
@@ -368,6 +679,7 @@ impl ContractMessageTranscoder {
     }
 
     pub fn decode_contract_constructor(&self, data: &mut &[u8]) -> Result<Value> {
+        let total_len = data.len();
         let mut msg_selector = [0u8; 4];
         data.read(&mut msg_selector)?;
         let msg_spec = self
@@ -388,7 +700,7 @@ impl ContractMessageTranscoder {
             args.push((Value::String(name), value));
         }
 
-        Self::validate_length(data, msg_spec.label(), &args)?;
+        Self::validate_length(data, total_len, msg_spec.label(), &args)?;
 
         let name = msg_spec.label().to_string();
         let map = Map::new(Some(&name), args.into_iter().collect());
@@ -416,17 +728,27 @@ impl ContractMessageTranscoder {
         self.decode(return_ty.ty().id, data)
     }
 
-    /// Checks if buffer empty, otherwise returns am error
-    fn validate_length(data: &[u8], label: &str, args: &[(Value, Value)]) -> Result<()> {
+    /// Checks if buffer empty, otherwise returns an error with the offset into the
+    /// original input of `total_len` bytes at which decoding stopped.
+    fn validate_length(
+        data: &[u8],
+        total_len: usize,
+        label: &str,
+        args: &[(Value, Value)],
+    ) -> Result<()> {
         if !data.is_empty() {
+            let decoded_len = total_len - data.len();
             let arg_list_string: String =
                 args.iter().fold(format!("`{label}`"), |init, arg| {
                     format!("{}, `{}`", init, arg.0)
                 });
             let encoded_bytes = hex::encode_upper(data);
             return Err(anyhow::anyhow!(
-                "input length was longer than expected by {} byte(s).\nManaged to decode {} but `{}` bytes were left unread",
+                "input length was longer than expected by {} byte(s): decoded {} of {} bytes, stopped at offset {}.\nManaged to decode {} but `{}` bytes were left unread",
                 data.len(),
+                decoded_len,
+                total_len,
+                decoded_len,
                 arg_list_string,
                 encoded_bytes
             ));
@@ -441,12 +763,70 @@ impl TryFrom<contract_metadata::ContractMetadata> for ContractMessageTranscoder
     fn try_from(
         metadata: contract_metadata::ContractMetadata,
     ) -> Result<Self, Self::Error> {
-        Ok(Self::new(serde_json::from_value(
-            serde_json::Value::Object(metadata.abi),
-        )?))
+        Self::try_new(serde_json::from_value(serde_json::Value::Object(
+            metadata.abi,
+        ))?)
     }
 }
 
+/// Returns an error naming the constructors or messages which share a selector.
+fn check_selectors(metadata: &InkProject) -> Result<()> {
+    let constructors = metadata
+        .spec()
+        .constructors()
+        .iter()
+        .map(|c| (c.selector(), c.label()));
+    let messages = metadata
+        .spec()
+        .messages()
+        .iter()
+        .map(|m| (m.selector(), m.label()));
+
+    let duplicates: Vec<_> = duplicate_selectors(constructors)
+        .into_iter()
+        .map(|(selector, labels)| ("constructors", selector, labels))
+        .chain(
+            duplicate_selectors(messages)
+                .into_iter()
+                .map(|(selector, labels)| ("messages", selector, labels)),
+        )
+        .map(|(kind, selector, labels)| {
+            format!(
+                "selector 0x{} is shared by the {kind} {}",
+                hex::encode(selector),
+                labels.iter().map(|label| format!("'{label}'")).join(", ")
+            )
+        })
+        .collect();
+
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Duplicate selectors in contract metadata: {}",
+            duplicates.join("; ")
+        ))
+    }
+}
+
+/// Returns the selectors used by more than one of the given specs, with their labels.
+fn duplicate_selectors<'a>(
+    specs: impl Iterator<Item = (&'a Selector, &'a String)>,
+) -> Vec<(&'a [u8], Vec<&'a String>)> {
+    let mut labels_by_selector: indexmap::IndexMap<&[u8], Vec<&String>> =
+        indexmap::IndexMap::new();
+    for (selector, label) in specs {
+        labels_by_selector
+            .entry(selector.to_bytes())
+            .or_default()
+            .push(label);
+    }
+    labels_by_selector
+        .into_iter()
+        .filter(|(_, labels)| labels.len() > 1)
+        .collect()
+}
+
 #[derive(Debug)]
 pub enum CompositeTypeFields {
     Named(Vec<CompositeTypeNamedField>),
@@ -529,6 +909,15 @@ mod tests {
             from: AccountId,
         }
 
+        #[ink(event, anonymous)]
+        pub struct AnonymousEvent {
+            value: u32,
+        }
+
+        /// A balance of a custom environment.
+        #[ink::scale_derive(Encode, Decode, TypeInfo)]
+        pub struct TokenBalance(u128);
+
         impl Transcode {
             #[ink(constructor)]
             pub fn new(init_value: bool) -> Self {
@@ -580,6 +969,11 @@ mod tests {
             pub fn uint_array_args(&self, arr: [u8; 4]) {
                 let _ = arr;
             }
+
+            #[ink(message)]
+            pub fn token_balance(&self) -> TokenBalance {
+                TokenBalance(self.value.into())
+            }
         }
     }
 
@@ -591,6 +985,102 @@ mod tests {
         unsafe { __ink_generate_metadata() }
     }
 
+    #[test]
+    fn resolve_type_path_works() -> Result<()> {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        let bool_id = transcoder.resolve_type_path("bool")?;
+        assert_eq!(
+            transcoder.decode(bool_id, &mut &true.encode()[..])?,
+            Value::Bool(true)
+        );
+
+        let account_id =
+            transcoder.resolve_type_path("ink_primitives::types::AccountId")?;
+        let decoded = transcoder.decode(account_id, &mut &[0u8; 32][..])?;
+        assert!(matches!(decoded, Value::Literal(_)), "{decoded:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn decode_type_path_rejects_trailing_bytes() -> Result<()> {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        assert_eq!(
+            transcoder.decode_type_path("bool", &mut &true.encode()[..])?,
+            Value::Bool(true)
+        );
+        let err = transcoder
+            .decode_type_path("bool", &mut &[1u8; 32][..])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("input length was longer than expected by 31 byte(s)"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_type_path_suggests_similar_paths() {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        let err = transcoder
+            .resolve_type_path("ink_primitives::AccountId")
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            err.starts_with(
+                "No type with the path 'ink_primitives::AccountId' found.\nDid you mean"
+            ),
+            "{err}"
+        );
+        assert!(err.contains("'ink_primitives::types::AccountId'"), "{err}");
+    }
+
+    #[test]
+    fn registry_display_lists_known_types() {
+        let metadata = generate_metadata();
+        let registry = metadata.registry();
+        let type_id = |path: &str| {
+            registry
+                .types
+                .iter()
+                .find(|ty| ty.ty.path.segments.join("::") == path)
+                .map(|ty| ty.id)
+                .unwrap_or_else(|| panic!("type {path} not found"))
+        };
+        let bool_id = registry
+            .types
+            .iter()
+            .find(|ty| {
+                matches!(
+                    ty.ty.type_def,
+                    scale_info::TypeDef::Primitive(scale_info::TypeDefPrimitive::Bool)
+                )
+            })
+            .map(|ty| ty.id)
+            .unwrap();
+        let account_id = type_id("ink_primitives::types::AccountId");
+
+        let display = RegistryDisplay(registry).to_string();
+
+        assert!(
+            display.contains(&format!("#{bool_id}\n    bool\n")),
+            "{display}"
+        );
+        assert!(
+            display.contains(&format!(
+                "#{account_id} ink_primitives::types::AccountId\n    struct\n        #"
+            )),
+            "{display}"
+        );
+    }
+
     #[test]
     fn encode_single_primitive_arg() -> Result<()> {
         let metadata = generate_metadata();
@@ -677,6 +1167,97 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn encode_from_json_matches_encode() -> Result<()> {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        let from_array = transcoder.encode_from_json(
+            "uint_args",
+            &serde_json::json!([1, 2, 3, 4, "340282366920938463463374607431768211455"]),
+        )?;
+        let from_object = transcoder.encode_from_json(
+            "uint_args",
+            &serde_json::json!({
+                "_u8": 1,
+                "_u16": 2,
+                "_u32": 3,
+                "_u64": 4,
+                "_u128": "340282366920938463463374607431768211455",
+            }),
+        )?;
+        let expected = transcoder.encode(
+            "uint_args",
+            [
+                "1",
+                "2",
+                "3",
+                "4",
+                "340282366920938463463374607431768211455",
+            ],
+        )?;
+        assert_eq!(expected, from_array);
+        assert_eq!(expected, from_object);
+
+        let account_ids = transcoder.encode_from_json(
+            "set_account_ids_vec",
+            &serde_json::json!([[
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+                "0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48",
+            ]]),
+        )?;
+        let expected = transcoder.encode(
+            "set_account_ids_vec",
+            ["[5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY, 5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty]"],
+        )?;
+        assert_eq!(expected, account_ids);
+        Ok(())
+    }
+
+    #[test]
+    fn encode_from_json_mismatching_args() {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        assert_eq!(
+            transcoder
+                .encode_from_json("new", &serde_json::json!([true, false]))
+                .unwrap_err()
+                .to_string(),
+            "Invalid number of input arguments: expected 1, 2 provided"
+        );
+        assert_eq!(
+            transcoder
+                .encode_from_json("new", &serde_json::json!({ "init_valeu": true }))
+                .unwrap_err()
+                .to_string(),
+            "No argument with the name 'init_valeu' found.\nDid you mean 'init_value'?"
+        );
+        assert_eq!(
+            transcoder
+                .encode_from_json("new", &serde_json::json!({}))
+                .unwrap_err()
+                .to_string(),
+            "Missing argument 'init_value'"
+        );
+    }
+
+    #[test]
+    fn encode_arg_validates_single_args() -> Result<()> {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        let params = transcoder.params("uint_args")?;
+        assert_eq!(params.len(), 5);
+        assert_eq!(params[2].label(), "_u32");
+        assert_eq!(params[2].ty().display_name().segments, ["u32"]);
+
+        assert_eq!(transcoder.encode_arg(&params[2], "42")?, 42u32.encode());
+        assert!(transcoder.encode_arg(&params[2], "true").is_err());
+        assert!(transcoder.encode_arg(&params[2], "42 43").is_err());
+        Ok(())
+    }
+
     #[test]
     fn encode_primitive_vec_args() -> Result<()> {
         let metadata = generate_metadata();
@@ -798,6 +1379,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn decode_anonymous_contract_event_by_index() -> Result<()> {
+        let metadata = generate_metadata();
+        let index = metadata
+            .spec()
+            .events()
+            .iter()
+            .position(|event| event.label() == "AnonymousEvent")
+            .expect("anonymous event is in the metadata");
+        let transcoder = ContractMessageTranscoder::new(metadata);
+
+        // encode as a Vec<u8> which has a len prefix.
+        let encoded_bytes = 42u32.encode().encode();
+
+        let err = transcoder
+            .decode_contract_event(&[0u8; 32], &mut &encoded_bytes[..])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("can only be decoded by their index"));
+
+        let decoded =
+            transcoder.decode_contract_event_by_index(index, &mut &encoded_bytes[..])?;
+        assert_eq!(
+            Value::Map(Map::new(
+                Some("AnonymousEvent"),
+                vec![(Value::String("value".into()), Value::UInt(42))]
+                    .into_iter()
+                    .collect()
+            )),
+            decoded
+        );
+
+        assert!(transcoder
+            .decode_contract_event_by_index(usize::MAX, &mut &encoded_bytes[..])
+            .is_err());
+        Ok(())
+    }
+
     #[test]
     fn decode_hash_as_hex_encoded_string() -> Result<()> {
         let metadata = generate_metadata();
@@ -836,6 +1456,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_account_id_with_ss58_prefix() -> Result<()> {
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata).with_ss58_prefix(0);
+
+        // Alice
+        let mut account_id = [0u8; 32];
+        hex::decode_to_slice(
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d",
+            &mut account_id,
+        )?;
+        let signature_topic: <DefaultEnvironment as Environment>::Hash =
+            <transcode::Event1 as ink::env::Event>::SIGNATURE_TOPIC
+                .unwrap()
+                .into();
+        let encoded = ([0u32; 8], account_id).encode();
+        let encoded_bytes = encoded.encode();
+        let decoded = transcoder
+            .decode_contract_event(&signature_topic, &mut &encoded_bytes[..])?;
+
+        let Value::Map(ref map) = decoded else {
+            return Err(anyhow::anyhow!(
+                "Expected a Value::Map for the decoded event"
+            ))
+        };
+        assert_eq!(
+            map[&Value::String("from".into())],
+            Value::Literal("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_with_custom_type_decoder() -> Result<()> {
+        /// Formats a balance with 12 decimals.
+        #[derive(Clone)]
+        struct BalanceFormatter;
+
+        impl env_types::CustomTypeDecoder for BalanceFormatter {
+            fn decode_value(&self, input: &mut &[u8]) -> Result<Value> {
+                let balance = u128::decode(input)?;
+                Ok(Value::Literal(format!(
+                    "{}.{:012} UNIT",
+                    balance / 1_000_000_000_000,
+                    balance % 1_000_000_000_000
+                )))
+            }
+        }
+
+        let metadata = generate_metadata();
+        let transcoder = ContractMessageTranscoder::new(metadata)
+            .with_custom_type_decoder(
+                "contract_transcode::tests::transcode::TokenBalance",
+                BalanceFormatter,
+            );
+
+        let encoded = Result::<u128, ()>::Ok(1_500_000_000_000).encode();
+        let decoded =
+            transcoder.decode_message_return("token_balance", &mut &encoded[..])?;
+
+        assert_eq!(
+            Value::Tuple(Tuple::new(
+                Some("Ok"),
+                vec![Value::Literal("1.500000000000 UNIT".into())]
+            )),
+            decoded
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_selectors_are_rejected() {
+        assert!(ContractMessageTranscoder::try_new(generate_metadata()).is_ok());
+
+        // give `get` the selector of `flip`
+        let mut json = serde_json::to_value(generate_metadata()).unwrap();
+        let messages = json["spec"]["messages"].as_array_mut().unwrap();
+        let index_of = |messages: &[serde_json::Value], label: &str| {
+            messages
+                .iter()
+                .position(|m| m["label"] == label)
+                .expect("message exists")
+        };
+        let flip = index_of(messages, "flip");
+        let get = index_of(messages, "get");
+        messages[get]["selector"] = messages[flip]["selector"].clone();
+        let selector = messages[flip]["selector"].as_str().unwrap().to_string();
+        let metadata: InkProject = serde_json::from_value(json).unwrap();
+
+        let err = ContractMessageTranscoder::try_new(metadata)
+            .err()
+            .expect("duplicate selectors are an error");
+        assert_eq!(
+            format!(
+                "Duplicate selectors in contract metadata: selector {selector} is shared \
+                by the messages 'flip', 'get'"
+            ),
+            err.to_string()
+        );
+    }
+
     #[test]
     fn decode_contract_message() -> Result<()> {
         let metadata = generate_metadata();
@@ -849,7 +1570,7 @@ mod tests {
 
     #[test]
     #[should_panic(
-        expected = "input length was longer than expected by 1 byte(s).\nManaged to decode `flip` but `00` bytes were left unread"
+        expected = "input length was longer than expected by 1 byte(s): decoded 4 of 5 bytes, stopped at offset 4.\nManaged to decode `flip` but `00` bytes were left unread"
     )]
     fn fail_decode_input_with_extra_bytes() {
         let metadata = generate_metadata();
@@ -863,7 +1584,7 @@ mod tests {
 
     #[test]
     #[should_panic(
-        expected = "input length was longer than expected by 2 byte(s).\nManaged to decode `Event1`, `name`, `from` but `0C10` bytes were left unread"
+        expected = "input length was longer than expected by 2 byte(s): decoded 66 of 68 bytes, stopped at offset 66.\nManaged to decode `Event1`, `name`, `from` but `0C10` bytes were left unread"
     )]
     fn fail_decode_contract_event_with_extra_bytes() {
         let metadata = generate_metadata();
@@ -0,0 +1,119 @@
+// Copyright 2018-2022 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use itertools::Itertools;
+use scale_info::{
+    form::PortableForm,
+    Field,
+    PortableRegistry,
+    TypeDef,
+};
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+/// Displays every type of a registry with its id, path and definition, for inspecting
+/// how the types of a contract are resolved.
+pub struct RegistryDisplay<'a>(pub &'a PortableRegistry);
+
+impl Display for RegistryDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for ty in &self.0.types {
+            write!(f, "#{}", ty.id)?;
+            if !ty.ty.path.segments.is_empty() {
+                write!(f, " {}", ty.ty.path.segments.join("::"))?;
+            }
+            if !ty.ty.type_params.is_empty() {
+                let params = ty.ty.type_params.iter().map(|param| {
+                    match param.ty {
+                        Some(param_ty) => format!("{} = #{}", param.name, param_ty.id),
+                        None => param.name.clone(),
+                    }
+                });
+                write!(f, "<{}>", params.format(", "))?;
+            }
+            writeln!(f)?;
+            write_type_def(f, &ty.ty.type_def)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_type_def(
+    f: &mut Formatter<'_>,
+    type_def: &TypeDef<PortableForm>,
+) -> fmt::Result {
+    match type_def {
+        TypeDef::Composite(composite) => {
+            writeln!(f, "    struct")?;
+            write_fields(f, &composite.fields, 2)
+        }
+        TypeDef::Variant(variant) => {
+            writeln!(f, "    enum")?;
+            for var in &variant.variants {
+                writeln!(f, "        {}: {}", var.index, var.name)?;
+                write_fields(f, &var.fields, 3)?;
+            }
+            Ok(())
+        }
+        TypeDef::Sequence(sequence) => {
+            writeln!(f, "    sequence of #{}", sequence.type_param.id)
+        }
+        TypeDef::Array(array) => {
+            writeln!(f, "    array of {} #{}", array.len, array.type_param.id)
+        }
+        TypeDef::Tuple(tuple) => {
+            let fields = tuple.fields.iter().map(|field| format!("#{}", field.id));
+            writeln!(f, "    tuple ({})", fields.format(", "))
+        }
+        TypeDef::Primitive(primitive) => {
+            writeln!(f, "    {}", format!("{primitive:?}").to_lowercase())
+        }
+        TypeDef::Compact(compact) => {
+            writeln!(f, "    compact #{}", compact.type_param.id)
+        }
+        TypeDef::BitSequence(bit_sequence) => {
+            writeln!(
+                f,
+                "    bit sequence, store #{}, order #{}",
+                bit_sequence.bit_store_type.id, bit_sequence.bit_order_type.id
+            )
+        }
+    }
+}
+
+/// Writes the fields one per line, indented by the given depth.
+fn write_fields(
+    f: &mut Formatter<'_>,
+    fields: &[Field<PortableForm>],
+    depth: usize,
+) -> fmt::Result {
+    let indent = "    ".repeat(depth);
+    for field in fields {
+        write!(f, "{indent}")?;
+        if let Some(name) = &field.name {
+            write!(f, "{name}: ")?;
+        }
+        write!(f, "#{}", field.ty.id)?;
+        if let Some(type_name) = &field.type_name {
+            write!(f, " ({type_name})")?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
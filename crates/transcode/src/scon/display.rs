@@ -29,37 +29,96 @@ use std::fmt::{
     Result,
 };
 
-/// Wraps Value for custom Debug impl to provide pretty-printed Display
-struct DisplayValue<'a>(&'a Value);
+/// Wraps Value for custom Debug impl to provide pretty-printed Display.
+///
+/// Byte sequences longer than the optional maximum number of bytes are truncated.
+struct DisplayValue<'a>(&'a Value, Option<usize>);
 
 impl<'a> Debug for DisplayValue<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let max_len = self.1;
         match &self.0 {
             Value::Bool(boolean) => <bool as Debug>::fmt(boolean, f),
             Value::Char(character) => <char as Debug>::fmt(character, f),
             Value::UInt(uint) => <u128 as Display>::fmt(uint, f),
             Value::Int(integer) => <i128 as Display>::fmt(integer, f),
-            Value::Map(map) => <DisplayMap as Debug>::fmt(&DisplayMap(map), f),
-            Value::Tuple(tuple) => <DisplayTuple as Debug>::fmt(&DisplayTuple(tuple), f),
+            Value::Map(map) => <DisplayMap as Debug>::fmt(&DisplayMap(map, max_len), f),
+            Value::Tuple(tuple) => {
+                <DisplayTuple as Debug>::fmt(&DisplayTuple(tuple, max_len), f)
+            }
             Value::String(string) => <String as Display>::fmt(string, f),
-            Value::Seq(seq) => <DisplaySeq as Debug>::fmt(&DisplaySeq(seq), f),
-            Value::Hex(hex) => <Hex as Debug>::fmt(hex, f),
+            Value::Seq(seq) => <DisplaySeq as Debug>::fmt(&DisplaySeq(seq, max_len), f),
+            Value::Hex(hex) => {
+                match max_len {
+                    Some(max_len) if hex.bytes.len() > max_len => {
+                        let (head, tail) = truncated(&hex.bytes, max_len);
+                        write!(
+                            f,
+                            "0x{}..{} ({} bytes)",
+                            hex::encode(head),
+                            hex::encode(tail),
+                            hex.bytes.len()
+                        )
+                    }
+                    _ => <Hex as Debug>::fmt(hex, f),
+                }
+            }
             Value::Literal(literal) => <String as Display>::fmt(literal, f),
             Value::Unit => write!(f, "()"),
         }
     }
 }
 
+/// Displays the value on a single line, or with the alternate flag `{:#}` with nested
+/// maps, tuples and sequences indented over multiple lines.
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             Value::String(string) => <String as Display>::fmt(string, f),
-            value => <DisplayValue as Debug>::fmt(&DisplayValue(value), f),
+            value => <DisplayValue as Debug>::fmt(&DisplayValue(value, None), f),
+        }
+    }
+}
+
+impl Value {
+    /// Returns a wrapper displaying the value like [`Display`], but with byte sequences
+    /// longer than `max_len` bytes truncated to their first and last bytes.
+    ///
+    /// A byte sequence is either a [`Hex`] value or a sequence of which all elements
+    /// are integers fitting into a byte, e.g. a decoded `Vec<u8>`. The element type of
+    /// a sequence is not known, so its length is given in elements instead of bytes.
+    pub fn display_truncated(&self, max_len: usize) -> impl Display + '_ {
+        TruncatedValue(self, max_len)
+    }
+}
+
+struct TruncatedValue<'a>(&'a Value, usize);
+
+impl<'a> Display for TruncatedValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.0 {
+            Value::String(string) => <String as Display>::fmt(string, f),
+            value => <DisplayValue as Debug>::fmt(&DisplayValue(value, Some(self.1)), f),
         }
     }
 }
 
-struct DisplayMap<'a>(&'a Map);
+/// Splits off the first and last bytes of a sequence, `max_len` in total.
+fn truncated<T>(elems: &[T], max_len: usize) -> (&[T], &[T]) {
+    let head = max_len - max_len / 2;
+    (&elems[..head], &elems[elems.len() - max_len / 2..])
+}
+
+/// Placeholder for the elements left out of a truncated sequence.
+struct Ellipsis;
+
+impl Debug for Ellipsis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "..")
+    }
+}
+
+struct DisplayMap<'a>(&'a Map, Option<usize>);
 
 impl<'a> Debug for DisplayMap<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
@@ -67,14 +126,14 @@ impl<'a> Debug for DisplayMap<'a> {
             Some(ref name) => {
                 let mut builder = f.debug_struct(name);
                 for (name, value) in self.0.map.iter() {
-                    builder.field(&format!("{name}"), &DisplayValue(value));
+                    builder.field(&format!("{name}"), &DisplayValue(value, self.1));
                 }
                 builder.finish()
             }
             None => {
                 let mut builder = f.debug_map();
                 for (name, value) in self.0.map.iter() {
-                    builder.entry(name, &DisplayValue(value));
+                    builder.entry(name, &DisplayValue(value, self.1));
                 }
                 builder.finish()
             }
@@ -82,28 +141,47 @@ impl<'a> Debug for DisplayMap<'a> {
     }
 }
 
-struct DisplayTuple<'a>(&'a Tuple);
+struct DisplayTuple<'a>(&'a Tuple, Option<usize>);
 
 impl<'a> Debug for DisplayTuple<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let name = self.0.ident.as_ref().map_or("", |s| s.as_str());
         let mut builder = f.debug_tuple(name);
         for value in self.0.values.iter() {
-            builder.field(&DisplayValue(value));
+            builder.field(&DisplayValue(value, self.1));
         }
         builder.finish()
     }
 }
 
-struct DisplaySeq<'a>(&'a Seq);
+struct DisplaySeq<'a>(&'a Seq, Option<usize>);
 
 impl<'a> Debug for DisplaySeq<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let mut builder = f.debug_list();
-        for elem in &self.0.elems {
-            builder.entry(&DisplayValue(elem));
+        let elems = &self.0.elems;
+        let is_byte_sized = || {
+            elems
+                .iter()
+                .all(|elem| matches!(elem, Value::UInt(uint) if *uint <= u8::MAX.into()))
+        };
+        match self.1 {
+            Some(max_len) if elems.len() > max_len && is_byte_sized() => {
+                let (head, tail) = truncated(elems, max_len);
+                let mut builder = f.debug_list();
+                builder.entries(head.iter().map(|elem| DisplayValue(elem, None)));
+                builder.entry(&Ellipsis);
+                builder.entries(tail.iter().map(|elem| DisplayValue(elem, None)));
+                builder.finish()?;
+                write!(f, " ({} elements)", elems.len())
+            }
+            _ => {
+                let mut builder = f.debug_list();
+                for elem in elems {
+                    builder.entry(&DisplayValue(elem, self.1));
+                }
+                builder.finish()
+            }
         }
-        builder.finish()
     }
 }
 
@@ -142,4 +220,82 @@ mod tests {
             "alternate indented (pretty)"
         );
     }
+
+    fn nested_value() -> Value {
+        Value::Map(Map::new(
+            Some("Account"),
+            vec![
+                (
+                    Value::String("balance".into()),
+                    Value::Tuple(Tuple::new(
+                        Some("Some"),
+                        vec![Value::Seq(Seq::new(vec![Value::UInt(1), Value::UInt(2)]))],
+                    )),
+                ),
+                (
+                    Value::String("owner".into()),
+                    Value::Hex("0x0102".parse().unwrap()),
+                ),
+                (Value::String("frozen".into()), Value::Unit),
+            ]
+            .into_iter()
+            .collect(),
+        ))
+    }
+
+    #[test]
+    fn display_nested_compact() {
+        assert_eq!(
+            format!("{}", nested_value()),
+            "Account { balance: Some([1, 2]), owner: 0x0102, frozen: () }"
+        );
+    }
+
+    #[test]
+    fn display_truncated_bytes_at_boundary() {
+        let bytes = |len: u8| {
+            let hex = hex::encode((0..len).collect::<Vec<_>>());
+            Value::Hex(hex.parse().unwrap())
+        };
+        let seq = |len: u8| {
+            Value::Seq(Seq::new((0..len).map(|b| Value::UInt(b.into())).collect()))
+        };
+
+        assert_eq!(bytes(4).display_truncated(4).to_string(), "0x00010203");
+        assert_eq!(
+            bytes(5).display_truncated(4).to_string(),
+            "0x0001..0304 (5 bytes)"
+        );
+        assert_eq!(
+            bytes(5).to_string(),
+            "0x0001020304",
+            "only truncated if opted in"
+        );
+
+        assert_eq!(seq(3).display_truncated(3).to_string(), "[0, 1, 2]");
+        assert_eq!(
+            seq(4).display_truncated(3).to_string(),
+            "[0, 1, .., 3] (4 elements)"
+        );
+        let not_bytes = Value::Seq(Seq::new(vec![Value::UInt(256); 4]));
+        assert_eq!(
+            not_bytes.display_truncated(3).to_string(),
+            "[256, 256, 256, 256]"
+        );
+    }
+
+    #[test]
+    fn display_nested_pretty() {
+        let expected = r#"Account {
+    balance: Some(
+        [
+            1,
+            2,
+        ],
+    ),
+    owner: 0x0102,
+    frozen: (),
+}"#;
+        assert_eq!(format!("{:#}", nested_value()), expected);
+    }
 }
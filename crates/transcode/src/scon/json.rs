@@ -0,0 +1,177 @@
+// Copyright 2018-2020 Use Ink (UK) Ltd.
+// This file is part of cargo-contract.
+//
+// cargo-contract is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cargo-contract is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cargo-contract.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{
+    Map,
+    Tuple,
+    Value,
+};
+use serde_json::{
+    Map as JsonMap,
+    Value as JsonValue,
+};
+
+impl Value {
+    /// Converts the value into JSON.
+    ///
+    /// Unlike the `Serialize` implementation this is lossless for hex values and keeps
+    /// the identifiers of enum variants and structs:
+    ///
+    /// - Hex values become `0x` prefixed strings.
+    /// - Integers become numbers, or strings if they exceed 64 bits.
+    /// - Maps and tuples without identifier become objects and arrays.
+    /// - Maps and tuples with an identifier are wrapped in an object with the identifier
+    ///   as the single key, e.g. `{ "Some": 42 }`. Without fields they become the
+    ///   identifier string, e.g. `"None"`.
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Value::Bool(boolean) => JsonValue::Bool(*boolean),
+            Value::Char(character) => JsonValue::String(character.to_string()),
+            Value::UInt(uint) => {
+                match u64::try_from(*uint) {
+                    Ok(uint) => uint.into(),
+                    Err(_) => JsonValue::String(uint.to_string()),
+                }
+            }
+            Value::Int(int) => {
+                match i64::try_from(*int) {
+                    Ok(int) => int.into(),
+                    Err(_) => JsonValue::String(int.to_string()),
+                }
+            }
+            Value::Map(map) => map_to_json(map),
+            Value::Tuple(tuple) => tuple_to_json(tuple),
+            Value::String(string) => JsonValue::String(string.clone()),
+            Value::Seq(seq) => {
+                JsonValue::Array(seq.elems().iter().map(Value::to_json).collect())
+            }
+            Value::Hex(hex) => JsonValue::String(format!("0x{}", hex.as_str())),
+            Value::Literal(literal) => JsonValue::String(literal.clone()),
+            Value::Unit => JsonValue::Null,
+        }
+    }
+}
+
+fn map_to_json(map: &Map) -> JsonValue {
+    let fields = map
+        .iter()
+        .map(|(key, value)| {
+            let key = match key {
+                Value::String(key) => key.clone(),
+                key => key.to_string(),
+            };
+            (key, value.to_json())
+        })
+        .collect::<JsonMap<_, _>>();
+    with_ident(map.ident(), JsonValue::Object(fields))
+}
+
+fn tuple_to_json(tuple: &Tuple) -> JsonValue {
+    let mut values: Vec<_> = tuple.values().map(Value::to_json).collect();
+    match (tuple.ident(), values.len()) {
+        (Some(ident), 0) => JsonValue::String(ident),
+        (Some(ident), 1) => {
+            with_ident(Some(ident), values.pop().expect("tuple has one value"))
+        }
+        (ident, _) => with_ident(ident, JsonValue::Array(values)),
+    }
+}
+
+fn with_ident(ident: Option<String>, json: JsonValue) -> JsonValue {
+    match ident {
+        Some(ident) => JsonValue::Object(JsonMap::from_iter([(ident, json)])),
+        None => json,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scon::Hex;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn map_as_json() {
+        let map = Value::Map(Map::new(
+            None,
+            vec![
+                (Value::String("a".into()), Value::UInt(1)),
+                (Value::String("b".into()), Value::Int(-1)),
+                (Value::UInt(2), Value::Bool(true)),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+        assert_eq!(json!({ "a": 1, "b": -1, "2": true }), map.to_json());
+
+        let map = Value::Map(Map::new(
+            Some("Transfer"),
+            vec![(Value::String("value".into()), Value::UInt(u128::MAX))]
+                .into_iter()
+                .collect(),
+        ));
+        assert_eq!(
+            json!({ "Transfer": { "value": "340282366920938463463374607431768211455" } }),
+            map.to_json()
+        );
+    }
+
+    #[test]
+    fn tuple_as_json() {
+        let tuple = Value::Tuple(Tuple::new(
+            None,
+            vec![Value::UInt(1), Value::String("a".into())],
+        ));
+        assert_eq!(json!([1, "a"]), tuple.to_json());
+
+        let some = Value::Tuple(Tuple::new(Some("Some"), vec![Value::UInt(1)]));
+        assert_eq!(json!({ "Some": 1 }), some.to_json());
+
+        let none = Value::Tuple(Tuple::new(Some("None"), vec![]));
+        assert_eq!(json!("None"), none.to_json());
+
+        let pair = Value::Tuple(Tuple::new(
+            Some("Pair"),
+            vec![Value::Bool(true), Value::Unit],
+        ));
+        assert_eq!(json!({ "Pair": [true, null] }), pair.to_json());
+    }
+
+    #[test]
+    fn seq_as_json() {
+        let seq = Value::Seq(
+            vec![
+                Value::Literal("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into()),
+                Value::Seq(vec![Value::UInt(1), Value::UInt(2)].into()),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            json!(["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", [1, 2]]),
+            seq.to_json()
+        );
+    }
+
+    #[test]
+    fn hex_as_json() {
+        let hex = Value::Hex(Hex::from_str("0xdeadbeef").unwrap());
+        assert_eq!(json!("0xdeadbeef"), hex.to_json());
+
+        let empty = Value::Hex(Hex::from_str("0x").unwrap());
+        assert_eq!(json!("0x"), empty.to_json());
+    }
+}
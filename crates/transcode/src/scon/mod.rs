@@ -17,6 +17,7 @@
 //! SCALE Object Notation (SCON)
 
 mod display;
+mod json;
 mod parse;
 
 use indexmap::IndexMap;
@@ -44,7 +45,10 @@ use serde::{
     Serialize,
 };
 
-pub use self::parse::parse_value;
+pub use self::parse::{
+    parse_single_value,
+    parse_value,
+};
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum Value {
@@ -62,6 +62,19 @@ pub fn parse_value(input: &str) -> anyhow::Result<Value> {
     Ok(value)
 }
 
+/// Attempt to parse a single SCON value, failing if any input is left after it.
+pub fn parse_single_value(input: &str) -> anyhow::Result<Value> {
+    let (rest, value) = scon_value(input)
+        .map_err(|err| anyhow::anyhow!("Error parsing Value: {}", err))?;
+    if !rest.is_empty() {
+        anyhow::bail!(
+            "Error parsing Value: unexpected input '{}' after the value",
+            rest
+        )
+    }
+    Ok(value)
+}
+
 fn scon_value(input: &str) -> IResult<&str, Value, ErrorTree<&str>> {
     ws(alt((
         scon_unit,
@@ -143,20 +156,50 @@ fn rust_ident(input: &str) -> IResult<&str, &str, ErrorTree<&str>> {
 }
 
 /// Parse a signed or unsigned integer literal, supports optional Rust style underscore
-/// separators.
+/// separators and a decimal exponent, e.g. `1_000e9`.
 fn scon_integer(input: &str) -> IResult<&str, Value, ErrorTree<&str>> {
-    let sign = alt((char('+'), char('-')));
-    pair(sign.opt(), separated_list0(char('_'), digit1))
-        .map_res(|(sign, parts)| {
-            let digits = parts.join("");
-            if let Some(sign) = sign {
-                let s = format!("{sign}{digits}");
-                s.parse::<i128>().map(Value::Int)
-            } else {
-                digits.parse::<u128>().map(Value::UInt)
+    #[derive(Debug)]
+    struct IntegerError(String);
+    impl std::error::Error for IntegerError {}
+
+    impl std::fmt::Display for IntegerError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "Invalid integer literal: {}", self.0)
+        }
+    }
+
+    let sign = alt((char::<&str, ErrorTree<&str>>('+'), char('-')));
+    let exponent = digit1.preceded_by(alt((char('e'), char('E'))));
+    tuple((
+        sign.opt(),
+        separated_list0(char('_'), digit1),
+        exponent.opt(),
+    ))
+    .map_res(|(sign, parts, exponent)| {
+        let digits = parts.join("");
+        let overflow = || IntegerError("overflows 128 bits".to_string());
+        let scale = match exponent {
+            Some(exponent) => {
+                let exponent = exponent.parse::<u32>().map_err(|_| overflow())?;
+                10u128.checked_pow(exponent).ok_or_else(overflow)?
             }
-        })
-        .parse(input)
+            None => 1,
+        };
+        if let Some(sign) = sign {
+            let s = format!("{sign}{digits}");
+            let int = s.parse::<i128>().map_err(|e| IntegerError(e.to_string()))?;
+            let scale = i128::try_from(scale).map_err(|_| overflow())?;
+            int.checked_mul(scale).map(Value::Int).ok_or_else(overflow)
+        } else {
+            let uint = digits
+                .parse::<u128>()
+                .map_err(|e| IntegerError(e.to_string()))?;
+            uint.checked_mul(scale)
+                .map(Value::UInt)
+                .ok_or_else(overflow)
+        }
+    })
+    .parse(input)
 }
 
 fn scon_unit(input: &str) -> IResult<&str, Value, ErrorTree<&str>> {
@@ -313,11 +356,27 @@ mod tests {
             ("", Value::UInt(340282366920938463463374607431768211455))
         );
 
+        // exponents
+        assert_eq!(scon_integer("1e6").unwrap(), ("", Value::UInt(1_000_000)));
+        assert_eq!(scon_integer("1E6").unwrap(), ("", Value::UInt(1_000_000)));
+        assert_eq!(
+            scon_integer("1_000e9").unwrap(),
+            ("", Value::UInt(1_000_000_000_000))
+        );
+        assert_eq!(scon_integer("-5e3").unwrap(), ("", Value::Int(-5_000)));
+        assert_scon_value("1_000", Value::UInt(1_000));
+        assert_scon_value("1e12", Value::UInt(1_000_000_000_000));
+
         // too many digits
         assert_matches!(
             scon_integer("3402823669209384634633746074317682114550"),
             Err(nom::Err::Error(_))
         );
+        // exponent overflows
+        assert_matches!(scon_integer("1e40"), Err(nom::Err::Error(_)));
+        assert_matches!(scon_integer("4e38"), Err(nom::Err::Error(_)));
+        assert_matches!(scon_integer("-1e39"), Err(nom::Err::Error(_)));
+        assert!(parse_value("1e40").is_err());
         assert_matches!(scon_integer("abc123"), Err(nom::Err::Error(_)));
     }
 
@@ -44,11 +44,24 @@ use std::{
 /// Decode SCALE encoded input into `Value` objects.
 pub struct Transcoder {
     env_types: EnvTypesTranscoder,
+    allow_default_fields: bool,
 }
 
 impl Transcoder {
     pub fn new(env_types: EnvTypesTranscoder) -> Self {
-        Self { env_types }
+        Self {
+            env_types,
+            allow_default_fields: false,
+        }
+    }
+
+    /// Allows omitting fields of structs when encoding, which are then encoded with the
+    /// default value of their type, e.g. `0` for numbers.
+    ///
+    /// Encoding fails if an omitted field has a type without a default value, e.g. an
+    /// `AccountId`.
+    pub fn set_allow_default_fields(&mut self, allow_default_fields: bool) {
+        self.allow_default_fields = allow_default_fields;
     }
 
     pub fn encode<O>(
@@ -61,7 +74,8 @@ impl Transcoder {
     where
         O: Output + Debug,
     {
-        let encoder = Encoder::new(registry, &self.env_types);
+        let encoder = Encoder::new(registry, &self.env_types)
+            .allow_default_fields(self.allow_default_fields);
         encoder.encode(type_id, value, output)
     }
 
@@ -74,6 +88,15 @@ impl Transcoder {
         let decoder = Decoder::new(registry, &self.env_types);
         decoder.decode(type_id, input)
     }
+
+    /// Registers a custom decoder for the given type id, replacing any existing one.
+    pub fn register_custom_type_decoder(
+        &mut self,
+        type_id: u32,
+        decoder: Box<dyn CustomTypeDecoder>,
+    ) {
+        self.env_types.register_decoder(type_id, decoder)
+    }
 }
 
 /// Construct a [`Transcoder`], allows registering custom transcoders for certain types.
@@ -98,7 +121,22 @@ impl TranscoderBuilder {
     }
 
     pub fn with_default_custom_type_transcoders(self) -> Self {
-        self.register_custom_type_transcoder::<AccountId32, _>(env_types::AccountId)
+        self.with_custom_type_transcoders(env_types::Ss58AccountId::default())
+    }
+
+    /// Registers the default custom type transcoders, rendering decoded account ids with
+    /// the given SS58 prefix.
+    pub fn with_default_custom_type_transcoders_for_ss58_prefix(
+        self,
+        ss58_prefix: u16,
+    ) -> Self {
+        self.with_custom_type_transcoders(env_types::Ss58AccountId::with_ss58_prefix(
+            ss58_prefix,
+        ))
+    }
+
+    fn with_custom_type_transcoders(self, account_id: env_types::Ss58AccountId) -> Self {
+        self.register_custom_type_transcoder::<AccountId32, _>(account_id)
             .register_custom_type_decoder::<primitive_types::H256, _>(env_types::Hash)
     }
 
@@ -140,6 +178,16 @@ impl TranscoderBuilder {
         this
     }
 
+    /// Registers a custom decoder for the type `T`.
+    ///
+    /// Custom decoders are keyed by the path of a type, e.g.
+    /// `ink_primitives::types::AccountId`: the path of `T` is taken from its `TypeInfo`
+    /// and looked up in the registry. If no type in the registry has that path the
+    /// decoder is not used. Since types are matched by their path, only structs and
+    /// enums can have custom decoders, not primitives or aliases of primitives.
+    ///
+    /// See [`crate::ContractMessageTranscoder::with_custom_type_decoder`] for
+    /// registering decoders by path at runtime.
     pub fn register_custom_type_decoder<T, U>(self, encoder: U) -> Self
     where
         T: TypeInfo + 'static,
@@ -627,6 +675,63 @@ mod tests {
         )
     }
 
+    #[test]
+    fn encode_enum_variant_by_discriminant() -> Result<()> {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        enum E {
+            A(u32, String),
+            B { a: bool },
+            C,
+        }
+
+        let (registry, ty) = registry_with_type::<E>()?;
+        let transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+        let encode = |input: &str| -> Result<Vec<u8>> {
+            let mut output = Vec::new();
+            transcoder.encode(&registry, ty, &scon::parse_value(input)?, &mut output)?;
+            Ok(output)
+        };
+
+        assert_eq!(encode("2")?, encode("C")?);
+        assert_eq!(encode("(2)")?, encode("C")?);
+        assert_eq!(encode(r#"(0, (1, "2"))"#)?, encode(r#"A(1, "2")"#)?);
+        assert_eq!(encode("(1, { a: true })")?, encode("B { a: true }")?);
+
+        assert_eq!(
+            encode("3").unwrap_err().to_string(),
+            "No variant with discriminant 3 found, expected one of: 0 (A), 1 (B), 2 (C)"
+        );
+        assert!(encode("0")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Variant 'A' with discriminant 0 has fields"));
+        Ok(())
+    }
+
+    #[test]
+    fn encode_option_rejects_plain_discriminant() -> Result<()> {
+        let (registry, ty) = registry_with_type::<Option<u32>>()?;
+        let transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+        let encode = |input: &str| -> Result<Vec<u8>> {
+            let mut output = Vec::new();
+            transcoder.encode(&registry, ty, &scon::parse_value(input)?, &mut output)?;
+            Ok(output)
+        };
+
+        assert_eq!(
+            encode("0").unwrap_err().to_string(),
+            "Expected `None` or `Some(..)` for an option, found '0'"
+        );
+        assert!(encode("(1)").is_err());
+        assert_eq!(encode("(1, (5))")?, encode("Some(5)")?);
+        Ok(())
+    }
+
     #[test]
     fn transcode_enum_variant_map() -> Result<()> {
         #[derive(TypeInfo)]
@@ -681,9 +786,188 @@ mod tests {
         transcode_roundtrip::<Option<u32>>(
             r#"None"#,
             Value::Tuple(Tuple::new(Some("None"), Vec::new())),
+        )?;
+
+        transcode_roundtrip::<Option<u32>>(
+            r#"null"#,
+            Value::Tuple(Tuple::new(Some("None"), Vec::new())),
         )
     }
 
+    #[test]
+    fn transcode_option_account_id() -> Result<()> {
+        transcode_roundtrip::<Option<AccountId32>>(
+            r#"Some(5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY)"#,
+            Value::Tuple(Tuple::new(
+                Some("Some"),
+                vec![Value::Literal(
+                    "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into(),
+                )],
+            )),
+        )?;
+
+        transcode_roundtrip::<Option<AccountId32>>(
+            r#"None"#,
+            Value::Tuple(Tuple::new(Some("None"), Vec::new())),
+        )
+    }
+
+    #[test]
+    fn transcode_nested_option() -> Result<()> {
+        let some = |value| Value::Tuple(Tuple::new(Some("Some"), vec![value]));
+        let none = || Value::Tuple(Tuple::new(Some("None"), Vec::new()));
+
+        transcode_roundtrip::<Option<Option<u32>>>(
+            r#"Some(Some(5))"#,
+            some(some(Value::UInt(5))),
+        )?;
+        transcode_roundtrip::<Option<Option<u32>>>(r#"Some(None)"#, some(none()))?;
+        transcode_roundtrip::<Option<Option<u32>>>(r#"Some(null)"#, some(none()))?;
+        transcode_roundtrip::<Option<Option<u32>>>(r#"None"#, none())
+    }
+
+    #[test]
+    fn null_is_only_an_alias_for_none_of_options() -> Result<()> {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        enum E {
+            None,
+            Other(u32),
+        }
+
+        let (registry, ty) = registry_with_type::<E>()?;
+        let transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+        let value = scon::parse_value("null")?;
+
+        let result = transcoder.encode(&registry, ty, &value, &mut Vec::new());
+
+        assert_eq!(result.unwrap_err().to_string(), "No variant 'null' found");
+        Ok(())
+    }
+
+    fn encode_with_default_fields<T>(input: &str) -> Result<Vec<u8>>
+    where
+        T: scale_info::TypeInfo + 'static,
+    {
+        let (registry, ty) = registry_with_type::<T>()?;
+        let mut transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+        transcoder.set_allow_default_fields(true);
+
+        let value = scon::parse_value(input)?;
+        let mut output = Vec::new();
+        transcoder.encode(&registry, ty, &value, &mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn encode_struct_with_default_fields() -> Result<()> {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct Inner {
+            a: u32,
+            b: Vec<u8>,
+        }
+
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct S {
+            a: u32,
+            b: Option<i8>,
+            c: (bool, String),
+            d: [u16; 2],
+            inner: Inner,
+        }
+
+        let encoded =
+            encode_with_default_fields::<S>(r#"S { a: 1, inner: Inner { a: 2 } }"#)?;
+
+        let expected = (
+            1u32,
+            None::<i8>,
+            (false, String::new()),
+            [0u16; 2],
+            (2u32, Vec::<u8>::new()),
+        )
+            .encode();
+        assert_eq!(expected, encoded);
+        Ok(())
+    }
+
+    #[test]
+    fn encode_struct_with_missing_fields_without_defaults_fails() -> Result<()> {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        enum E {
+            A,
+            B,
+        }
+
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct S {
+            a: u32,
+            owner: AccountId32,
+            e: E,
+        }
+
+        let (registry, ty) = registry_with_type::<S>()?;
+        let transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+        let value = scon::parse_value(
+            r#"S { owner: 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY, e: A }"#,
+        )?;
+        let result = transcoder.encode(&registry, ty, &value, &mut Vec::new());
+        assert_eq!(result.unwrap_err().to_string(), "Missing a field named `a`");
+
+        let result = encode_with_default_fields::<S>(r#"S { e: A }"#);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing a field named `owner`: the type `AccountId32` has no default value"
+        );
+
+        let result = encode_with_default_fields::<S>(
+            r#"S { owner: 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY }"#,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing a field named `e`: the type `E` has no default value"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encode_struct_with_misspelled_field_suggests_field_name() -> Result<()> {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct S {
+            name: String,
+            value: u32,
+        }
+
+        let (registry, ty) = registry_with_type::<S>()?;
+        let transcoder = TranscoderBuilder::new(&registry).done();
+
+        let value = scon::parse_value(r#"S { nmae: "ink", value: 1 }"#)?;
+        let result = transcoder.encode(&registry, ty, &value, &mut Vec::new());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown field 'nmae', did you mean 'name'?"
+        );
+
+        let value = scon::parse_value(r#"S { name: "ink", value: 1, zzz: 2 }"#)?;
+        let result = transcoder.encode(&registry, ty, &value, &mut Vec::new());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown field 'zzz', expected one of: name, value"
+        );
+        Ok(())
+    }
+
     #[test]
     fn transcode_account_id_custom_ss58_encoding() -> Result<()> {
         type AccountId = AccountId32;
@@ -847,4 +1131,105 @@ mod tests {
             )),
         )
     }
+
+    /// Bit order types with the paths of the `bitvec` crate.
+    macro_rules! bit_order {
+        ($name:ident) => {
+            struct $name;
+
+            impl TypeInfo for $name {
+                type Identity = Self;
+
+                fn type_info() -> scale_info::Type {
+                    scale_info::Type::builder()
+                        .path(scale_info::Path::new(stringify!($name), "bitvec::order"))
+                        .composite(scale_info::build::Fields::unit())
+                }
+            }
+        };
+    }
+    bit_order!(Lsb0);
+    bit_order!(Msb0);
+
+    /// A `BitVec<Store, Order>` as described by its metadata.
+    struct BitVec<Store, Order>(std::marker::PhantomData<(Store, Order)>);
+
+    impl<Store, Order> TypeInfo for BitVec<Store, Order>
+    where
+        Store: TypeInfo + 'static,
+        Order: TypeInfo + 'static,
+    {
+        type Identity = Self;
+
+        fn type_info() -> scale_info::Type {
+            scale_info::TypeDefBitSequence::new::<Store, Order>().into()
+        }
+    }
+
+    fn transcode_bits_roundtrip<T>(expected_encoded: &[u8]) -> Result<()>
+    where
+        T: TypeInfo + 'static,
+    {
+        let (registry, ty) = registry_with_type::<T>()?;
+        let transcoder = TranscoderBuilder::new(&registry)
+            .with_default_custom_type_transcoders()
+            .done();
+
+        let bits = [true, false, true, true, false, false, false, false, true];
+        let value = scon::parse_value(
+            "[true, false, true, true, false, false, false, false, true]",
+        )?;
+
+        let mut output = Vec::new();
+        transcoder.encode(&registry, ty, &value, &mut output)?;
+        assert_eq!(expected_encoded, &output[..], "encoding");
+
+        let decoded = transcoder.decode(&registry, ty, &mut &output[..])?;
+        let expected =
+            Value::Seq(bits.into_iter().map(Value::Bool).collect::<Vec<_>>().into());
+        assert_eq!(expected, decoded, "decoding");
+        Ok(())
+    }
+
+    #[test]
+    fn transcode_bit_sequence_lsb0() -> Result<()> {
+        // compact encoded length of 9 bits, followed by the store elements
+        transcode_bits_roundtrip::<BitVec<u8, Lsb0>>(&[0x24, 0b0000_1101, 0b0000_0001])?;
+        transcode_bits_roundtrip::<BitVec<u16, Lsb0>>(&[0x24, 0b0000_1101, 0b0000_0001])
+    }
+
+    #[test]
+    fn transcode_bit_sequence_msb0() -> Result<()> {
+        transcode_bits_roundtrip::<BitVec<u8, Msb0>>(&[0x24, 0b1011_0000, 0b1000_0000])?;
+        transcode_bits_roundtrip::<BitVec<u16, Msb0>>(&[0x24, 0b1000_0000, 0b1011_0000])
+    }
+
+    #[test]
+    fn decode_bit_sequence_with_huge_length_fails() -> Result<()> {
+        let (registry, ty) = registry_with_type::<BitVec<u8, Lsb0>>()?;
+        let transcoder = TranscoderBuilder::new(&registry).done();
+
+        // compact encoded length of `u32::MAX` bits without any store elements
+        let input = scale::Compact(u32::MAX).encode();
+        let result = transcoder.decode(&registry, ty, &mut &input[..]);
+        assert!(format!("{:?}", result.unwrap_err()).contains(&format!(
+            "Not enough data to decode a bit sequence of {} bits",
+            u32::MAX
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn transcode_empty_bit_sequence() -> Result<()> {
+        let (registry, ty) = registry_with_type::<BitVec<u32, Lsb0>>()?;
+        let transcoder = TranscoderBuilder::new(&registry).done();
+
+        let mut output = Vec::new();
+        transcoder.encode(&registry, ty, &scon::parse_value("[]")?, &mut output)?;
+        assert_eq!(vec![0x00], output);
+
+        let decoded = transcoder.decode(&registry, ty, &mut &output[..])?;
+        assert_eq!(Value::Seq(Vec::<Value>::new().into()), decoded);
+        Ok(())
+    }
 }